@@ -0,0 +1,6 @@
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    // Generates the `ProofStream` service, see src/grpc.rs
+    #[cfg(feature = "grpc")]
+    tonic_build::compile_protos("proto/relayer.proto")?;
+    Ok(())
+}
@@ -0,0 +1,138 @@
+use anyhow::Result;
+use serde::Serialize;
+use std::sync::Arc;
+
+use crate::db::HealthCheckData;
+use crate::domain::Domain;
+use crate::freshness::StalenessTier;
+use crate::metrics::Metrics;
+
+/// A self-describing alert condition. `expr` is the PromQL expression over the
+/// precomputed gauge, so Alertmanager rules can be generated from `/alerts` as-is.
+#[derive(Debug, Serialize)]
+pub struct AlertCondition {
+    pub name: &'static str,
+    pub domain: String,
+    pub severity: &'static str,
+    pub firing: bool,
+    pub metric: &'static str,
+    pub expr: String,
+    pub summary: String,
+}
+
+fn unhealthy_age(domain: &Domain, recorded_at: chrono::DateTime<chrono::Utc>) -> bool {
+    StalenessTier::of(&domain.config.staleness, chrono::Utc::now() - recorded_at)
+        == StalenessTier::Unhealthy
+}
+
+/// Evaluates every alert condition for every domain, updating the matching gauges on the way
+pub async fn evaluate_alerts(
+    domains: &[Arc<Domain>],
+    metrics: &Metrics,
+) -> Result<Vec<AlertCondition>> {
+    let mut alerts = Vec::new();
+    for domain in domains {
+        let health = domain.read(|db| db.get_latest_health_check()).await?;
+        alerts.extend(evaluate_domain_alerts(domain, health, metrics));
+    }
+    Ok(alerts)
+}
+
+fn evaluate_domain_alerts(
+    domain: &Domain,
+    health: Option<HealthCheckData>,
+    metrics: &Metrics,
+) -> Vec<AlertCondition> {
+    let name = domain.name();
+
+    let progress = domain.freshness.progress();
+    let (unhealthy, unhealthy_summary) = match health {
+        Some(health) if progress.is_some_and(|progress| progress.degraded) => (
+            true,
+            format!(
+                "Height {} hasn't advanced for {} expected epochs",
+                health.current_height,
+                progress.map_or(0, |progress| progress.stalled_epochs)
+            ),
+        ),
+        Some(health) if progress.is_some() => (
+            false,
+            format!("Height {} is advancing", health.current_height),
+        ),
+        // A degraded domain is flagged on `/health` but isn't unhealthy yet
+        Some(health) if !unhealthy_age(domain, health.timestamp) => (
+            false,
+            format!("Latest proof at height {} is fresh", health.current_height),
+        ),
+        Some(health) => (
+            true,
+            format!(
+                "No new proof since {} (height {})",
+                health.timestamp.to_rfc3339(),
+                health.current_height
+            ),
+        ),
+        None => (true, "No health check data recorded yet".to_string()),
+    };
+
+    let pending_rollback = domain.rollback.pending();
+    let rollback_summary = match &pending_rollback {
+        Some(candidate) => format!(
+            "Rollback to height {} (high-water mark {}) awaiting approval",
+            candidate.height, candidate.high_water_mark
+        ),
+        None => "No rollback awaiting approval".to_string(),
+    };
+
+    let conflict = domain.conflicts.current();
+    let conflict_summary = match &conflict {
+        Some(conflict) => format!(
+            "Prover served root {} for height {}, but {} is stored",
+            conflict.served_root, conflict.height, conflict.stored_root
+        ),
+        None => "No root conflicts with the proof history".to_string(),
+    };
+
+    metrics
+        .domain_unhealthy
+        .with_label_values(&[name])
+        .set(unhealthy as i64);
+    metrics
+        .rollback_pending
+        .with_label_values(&[name])
+        .set(pending_rollback.is_some() as i64);
+    metrics
+        .root_conflict
+        .with_label_values(&[name])
+        .set(conflict.is_some() as i64);
+
+    vec![
+        AlertCondition {
+            name: "RelayerDomainUnhealthy",
+            domain: name.to_string(),
+            severity: "critical",
+            firing: unhealthy,
+            metric: "relayer_domain_unhealthy",
+            expr: format!("relayer_domain_unhealthy{{domain=\"{}\"}} == 1", name),
+            summary: unhealthy_summary,
+        },
+        AlertCondition {
+            name: "RelayerRollbackPending",
+            domain: name.to_string(),
+            severity: "warning",
+            firing: pending_rollback.is_some(),
+            metric: "relayer_rollback_pending",
+            expr: format!("relayer_rollback_pending{{domain=\"{}\"}} == 1", name),
+            summary: rollback_summary,
+        },
+        AlertCondition {
+            name: "RelayerRootConflict",
+            domain: name.to_string(),
+            severity: "critical",
+            firing: conflict.is_some(),
+            metric: "relayer_root_conflict",
+            expr: format!("relayer_root_conflict{{domain=\"{}\"}} == 1", name),
+            summary: conflict_summary,
+        },
+    ]
+}
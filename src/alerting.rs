@@ -0,0 +1,470 @@
+use chrono::Utc;
+use serde_json::json;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tracing::{info, warn};
+
+use crate::config::{AlertingConfig, WebhookConfig, WebhookKind};
+use crate::crosscheck::RootDivergence;
+use crate::db::{AlertRecord, AlertState};
+use crate::domain::Domain;
+use crate::equivocation::RootConflict;
+use crate::errors::RelayerError;
+use crate::registry::ResponseClass;
+use crate::relayer::http_client;
+use crate::writer::DbWriter;
+
+/// Posts alert messages to the configured Slack and Discord webhooks
+pub struct Alerter {
+    webhooks: Vec<WebhookConfig>,
+    client: reqwest::Client,
+}
+
+impl Alerter {
+    /// Returns `None` when no webhooks are configured
+    pub fn new(config: &AlertingConfig) -> anyhow::Result<Option<Self>> {
+        if config.webhooks.is_empty() {
+            return Ok(None);
+        }
+        info!("📣 Sending alerts to {} webhooks", config.webhooks.len());
+
+        let client = http_client(None, Some(Duration::from_secs(10)))?;
+        Ok(Some(Alerter {
+            webhooks: config.webhooks.clone(),
+            client,
+        }))
+    }
+
+    /// Delivers a message to every webhook in the background, so a slow chat service
+    /// never holds up the caller
+    pub fn notify(self: &Arc<Self>, message: String) {
+        let alerter = self.clone();
+        tokio::spawn(async move {
+            for (index, webhook) in alerter.webhooks.iter().enumerate() {
+                let body = match webhook.kind {
+                    WebhookKind::Slack => json!({ "text": message }),
+                    WebhookKind::Discord => json!({ "content": message }),
+                };
+                // Webhook URLs embed their credentials, so only the index is logged
+                match alerter.client.post(&webhook.url).json(&body).send().await {
+                    Ok(response) if response.status().is_success() => {}
+                    Ok(response) => warn!(
+                        "⚠️  {} webhook #{} rejected alert with status {}",
+                        webhook.kind.as_str(),
+                        index,
+                        response.status()
+                    ),
+                    Err(e) => warn!(
+                        "⚠️  Failed to deliver alert to {} webhook #{}{}",
+                        webhook.kind.as_str(),
+                        index,
+                        if e.is_timeout() { " (timed out)" } else { "" }
+                    ),
+                }
+            }
+        });
+    }
+}
+
+fn notify(domain: &str, alerter: Option<&Arc<Alerter>>, message: String) {
+    if let Some(alerter) = alerter {
+        info!("📣 [{}] Sending alert: {}", domain, message);
+        alerter.notify(message);
+    }
+}
+
+/// Recent alerts checked for ones a previous run left open
+const OPEN_ALERT_SCAN: usize = 100;
+
+/// One domain's alert state. Each condition alerts once when it starts and once when
+/// it clears, rather than on every cycle, plus a reminder every `repeat_after` while it
+/// is firing and unacknowledged. Without webhooks every method is a no-op.
+pub struct DomainAlerts {
+    domain: String,
+    alerter: Option<Arc<Alerter>>,
+    /// Records fired alerts in the domain's storage, see `with_history`
+    history: Option<DbWriter>,
+    /// Alert id of each condition currently firing, updated as the writes are applied
+    open: Arc<Mutex<HashMap<&'static str, u64>>>,
+    send_failure_threshold: u32,
+    stale_after: Duration,
+    repeat_after: Option<Duration>,
+    consecutive_send_failures: AtomicU32,
+    send_failing: AtomicBool,
+    vk_rejected: AtomicBool,
+    decode_failing: AtomicBool,
+    regressed: AtomicBool,
+    conflicting: AtomicBool,
+    diverging: AtomicBool,
+    last_new_proof: Mutex<Instant>,
+    stale: AtomicBool,
+}
+
+impl DomainAlerts {
+    pub fn new(domain: &str, alerter: Option<Arc<Alerter>>, config: &AlertingConfig) -> Self {
+        DomainAlerts {
+            domain: domain.to_string(),
+            alerter,
+            history: None,
+            open: Arc::new(Mutex::new(HashMap::new())),
+            send_failure_threshold: config.send_failure_threshold.max(1),
+            stale_after: Duration::from_secs(config.stale_after_minutes * 60),
+            repeat_after: (config.repeat_after_minutes > 0)
+                .then(|| Duration::from_secs(config.repeat_after_minutes * 60)),
+            consecutive_send_failures: AtomicU32::new(0),
+            send_failing: AtomicBool::new(false),
+            vk_rejected: AtomicBool::new(false),
+            decode_failing: AtomicBool::new(false),
+            regressed: AtomicBool::new(false),
+            conflicting: AtomicBool::new(false),
+            diverging: AtomicBool::new(false),
+            last_new_proof: Mutex::new(Instant::now()),
+            stale: AtomicBool::new(false),
+        }
+    }
+
+    /// Records fired alerts in the domain's `alerts` table, so they can be acknowledged.
+    /// Alerts a previous run left open are resolved, since their conditions are
+    /// re-evaluated from scratch.
+    pub fn with_history(mut self, writer: DbWriter) -> Self {
+        let domain = self.domain.clone();
+        writer.submit("close alerts left open", move |db| {
+            match db.get_alerts(OPEN_ALERT_SCAN) {
+                Ok(alerts) => {
+                    let now = Utc::now();
+                    for mut alert in alerts
+                        .into_iter()
+                        .filter(|alert| alert.state != AlertState::Resolved)
+                    {
+                        alert.state = AlertState::Resolved;
+                        alert.resolved_at = Some(now);
+                        if let Err(e) = db.update_alert(&alert) {
+                            warn!(
+                                "⚠️  [{}] Failed to close alert #{}: {}",
+                                domain, alert.id, e
+                            );
+                        }
+                    }
+                }
+                Err(e) => warn!("⚠️  [{}] Failed to load alert history: {}", domain, e),
+            }
+            Ok(())
+        });
+        self.history = Some(writer);
+        self
+    }
+
+    fn notify(&self, message: String) {
+        notify(&self.domain, self.alerter.as_ref(), message);
+    }
+
+    /// Sends the alert for a condition that just started and records it as firing
+    fn fire(&self, condition: &'static str, message: String) {
+        if self.alerter.is_none() {
+            return;
+        }
+        self.notify(message.clone());
+
+        let Some(writer) = &self.history else {
+            return;
+        };
+        let now = Utc::now();
+        let alert = AlertRecord {
+            id: 0,
+            condition: condition.to_string(),
+            message,
+            state: AlertState::Firing,
+            fired_at: now,
+            notifications: 1,
+            last_notified_at: now,
+            acknowledged_at: None,
+            acknowledged_by: None,
+            resolved_at: None,
+        };
+        let (open, domain) = (self.open.clone(), self.domain.clone());
+        writer.submit("record alert", move |db| {
+            match db.insert_alert(&alert) {
+                Ok(id) => {
+                    open.lock().unwrap().insert(condition, id);
+                }
+                Err(e) => warn!("⚠️  [{}] Failed to record alert: {}", domain, e),
+            }
+            Ok(())
+        });
+    }
+
+    /// Sends the all-clear for a condition and marks its alert resolved
+    fn resolve(&self, condition: &'static str, message: String) {
+        if self.alerter.is_none() {
+            return;
+        }
+        self.notify(message);
+
+        let Some(writer) = &self.history else {
+            return;
+        };
+        let (open, domain) = (self.open.clone(), self.domain.clone());
+        writer.submit("resolve alert", move |db| {
+            // Looked up once applied, after the write that recorded the alert
+            let Some(id) = open.lock().unwrap().remove(condition) else {
+                return Ok(());
+            };
+            let result = db.get_alert(id).and_then(|alert| match alert {
+                Some(mut alert) => {
+                    alert.state = AlertState::Resolved;
+                    alert.resolved_at = Some(Utc::now());
+                    db.update_alert(&alert).map(|_| ())
+                }
+                None => Ok(()),
+            });
+            if let Err(e) = result {
+                warn!("⚠️  [{}] Failed to resolve alert #{}: {}", domain, id, e);
+            }
+            Ok(())
+        });
+    }
+
+    /// Re-sends firing alerts nobody has acknowledged within `repeat_after`
+    fn repeat_unacknowledged(&self) {
+        let (Some(writer), Some(repeat_after)) = (&self.history, self.repeat_after) else {
+            return;
+        };
+        if self.open.lock().unwrap().is_empty() {
+            return;
+        }
+        let (open, domain, alerter) =
+            (self.open.clone(), self.domain.clone(), self.alerter.clone());
+        writer.submit("repeat unacknowledged alerts", move |db| {
+            let open: Vec<u64> = open.lock().unwrap().values().copied().collect();
+            for id in open {
+                let mut alert = match db.get_alert(id) {
+                    Ok(Some(alert)) if alert.state == AlertState::Firing => alert,
+                    Ok(_) => continue,
+                    Err(e) => {
+                        warn!("⚠️  [{}] Failed to load alert #{}: {}", domain, id, e);
+                        continue;
+                    }
+                };
+                let due = alert.last_notified_at
+                    + chrono::Duration::from_std(repeat_after).unwrap_or(chrono::Duration::MAX);
+                if Utc::now() < due {
+                    continue;
+                }
+
+                notify(
+                    &domain,
+                    alerter.as_ref(),
+                    format!(
+                        "🔁 Unacknowledged alert #{} (since {}): {}",
+                        alert.id,
+                        alert.fired_at.to_rfc3339(),
+                        alert.message
+                    ),
+                );
+                alert.notifications += 1;
+                alert.last_notified_at = Utc::now();
+                if let Err(e) = db.update_alert(&alert) {
+                    warn!("⚠️  [{}] Failed to update alert #{}: {}", domain, id, e);
+                }
+            }
+            Ok(())
+        });
+    }
+
+    /// A registry refusing the configured VK alerts straight away, since every later
+    /// proof will be refused too
+    pub fn record_send_failure(&self, error: &anyhow::Error) {
+        if RelayerError::of(error)
+            .and_then(RelayerError::rejection)
+            .is_some_and(|rejection| rejection.class == ResponseClass::InvalidVk)
+            && !self.vk_rejected.swap(true, Ordering::SeqCst)
+        {
+            self.fire(
+                "registry_invalid_vk",
+                format!(
+                    "🚨 [{}] Registry rejects proofs for the configured VK: {}",
+                    self.domain, error
+                ),
+            );
+        }
+        let failures = self
+            .consecutive_send_failures
+            .fetch_add(1, Ordering::SeqCst)
+            + 1;
+        if failures >= self.send_failure_threshold
+            && !self.send_failing.swap(true, Ordering::SeqCst)
+        {
+            self.fire(
+                "send_failure",
+                format!(
+                    "🚨 [{}] Registry submission failed {} times in a row: {}",
+                    self.domain, failures, error
+                ),
+            );
+        }
+    }
+
+    pub fn record_send_success(&self) {
+        self.consecutive_send_failures.store(0, Ordering::SeqCst);
+        if self.vk_rejected.swap(false, Ordering::SeqCst) {
+            self.resolve(
+                "registry_invalid_vk",
+                format!(
+                    "✅ [{}] Registry accepts the configured VK again",
+                    self.domain
+                ),
+            );
+        }
+        if self.send_failing.swap(false, Ordering::SeqCst) {
+            self.resolve(
+                "send_failure",
+                format!(
+                    "✅ [{}] Registry submissions are succeeding again",
+                    self.domain
+                ),
+            );
+        }
+    }
+
+    /// A proof's public values could not be decoded (including a panic in the decoder)
+    pub fn record_decode_failure(&self, error: &anyhow::Error) {
+        if !self.decode_failing.swap(true, Ordering::SeqCst) {
+            self.fire("decode_failure", format!("🚨 [{}] {}", self.domain, error));
+        }
+    }
+
+    /// The prover served a height further below the last accepted one than the reorg
+    /// tolerance allows
+    pub fn record_height_regression(&self, height: u64, last_accepted: u64) {
+        if !self.regressed.swap(true, Ordering::SeqCst) {
+            self.fire(
+                "height_regression",
+                format!(
+                    "🚨 [{}] Prover went back to height {} from last accepted height {}, refusing its proofs",
+                    self.domain, height, last_accepted
+                ),
+            );
+        }
+    }
+
+    /// The domain's unresolved root conflict, if any. It alerts when one is flagged and
+    /// clears once an operator resolves it, see `ConflictTracker`.
+    pub fn record_root_conflict(&self, conflict: Option<&RootConflict>) {
+        match conflict {
+            Some(conflict) if !self.conflicting.swap(true, Ordering::SeqCst) => self.fire(
+                "root_conflict",
+                format!(
+                    "🚨 CRITICAL [{}] Prover served root {} for height {}, but {} is stored. The prover is equivocating or the chain reorganised; resolve with POST /admin/conflict/resolve",
+                    self.domain, conflict.served_root, conflict.height, conflict.stored_root
+                ),
+            ),
+            None if self.conflicting.swap(false, Ordering::SeqCst) => self.resolve(
+                "root_conflict",
+                format!("✅ [{}] Root conflict resolved by an operator", self.domain),
+            ),
+            _ => {}
+        }
+    }
+
+    /// Outcome of a root cross-check: the divergence if the chain disagreed
+    pub fn record_root_check(&self, divergence: Option<&RootDivergence>) {
+        match divergence {
+            Some(divergence) if !self.diverging.swap(true, Ordering::SeqCst) => self.fire(
+                "root_divergence",
+                format!("🚨 [{}] Prover fault: {}", self.domain, divergence),
+            ),
+            None if self.diverging.swap(false, Ordering::SeqCst) => self.resolve(
+                "root_divergence",
+                format!("✅ [{}] Proof roots match the chain again", self.domain),
+            ),
+            _ => {}
+        }
+    }
+
+    /// A proof decoded successfully; `new` if its height was above the last one seen
+    pub fn record_decoded(&self, height: u64, new: bool) {
+        if self.decode_failing.swap(false, Ordering::SeqCst) {
+            self.resolve(
+                "decode_failure",
+                format!(
+                    "✅ [{}] Proofs are decoding again (height {})",
+                    self.domain, height
+                ),
+            );
+        }
+        if new {
+            if self.regressed.swap(false, Ordering::SeqCst) {
+                self.resolve(
+                    "height_regression",
+                    format!(
+                        "✅ [{}] Prover is serving new heights again (height {})",
+                        self.domain, height
+                    ),
+                );
+            }
+            self.reset_staleness();
+            if self.stale.swap(false, Ordering::SeqCst) {
+                self.resolve(
+                    "stale",
+                    format!(
+                        "✅ [{}] New proof observed at height {}",
+                        self.domain, height
+                    ),
+                );
+            }
+        }
+    }
+
+    fn reset_staleness(&self) {
+        *self.last_new_proof.lock().unwrap() = Instant::now();
+    }
+
+    fn check_stale(&self) {
+        let elapsed = self.last_new_proof.lock().unwrap().elapsed();
+        if elapsed >= self.stale_after && !self.stale.swap(true, Ordering::SeqCst) {
+            self.fire(
+                "stale",
+                format!(
+                    "🚨 [{}] No new proof observed for {} minutes",
+                    self.domain,
+                    elapsed.as_secs() / 60
+                ),
+            );
+        }
+    }
+}
+
+/// Fires the staleness alert even when the loop itself is stuck, and repeats
+/// unacknowledged alerts. Time spent paused by an operator doesn't count as stale.
+pub async fn run_staleness_check(domain: Arc<Domain>, alerts: Arc<DomainAlerts>) {
+    loop {
+        tokio::time::sleep(Duration::from_secs(30)).await;
+        if domain.control.is_stopped() {
+            return;
+        }
+        if domain.control.is_paused() {
+            alerts.reset_staleness();
+        } else {
+            alerts.check_stale();
+        }
+        alerts.repeat_unacknowledged();
+    }
+}
+
+/// Creates a domain's webhook alert state, starting its staleness check when alerting is enabled
+pub fn spawn_alerts(
+    domain: &Arc<Domain>,
+    alerter: Option<Arc<Alerter>>,
+    config: &AlertingConfig,
+) -> Arc<DomainAlerts> {
+    let enabled = alerter.is_some();
+    let alerts = Arc::new(
+        DomainAlerts::new(domain.name(), alerter, config).with_history(domain.writer.clone()),
+    );
+    if enabled {
+        tokio::spawn(run_staleness_check(domain.clone(), alerts.clone()));
+    }
+    alerts
+}
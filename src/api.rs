@@ -1,95 +1,2737 @@
-use crate::config::API_PORT;
-use crate::db::Database;
+use crate::alert_rules::evaluate_alerts;
+use crate::broadcast::{Broadcaster, Subscription};
+use crate::candidate::CandidateReport;
+use crate::config::{
+    API_PORT, ApiLimits, Config, DomainConfig, EndpointRedaction, TlsConfig,
+    WS_CLIENT_QUEUE_CAPACITY, WS_MAX_DROPPED_EVENTS, WS_PING_INTERVAL_SECS, WS_PONG_TIMEOUT_SECS,
+};
+use crate::db::{AlertState, CutoverRecord, DecodeFailure, RelayConfirmation};
+use crate::domain::{Domain, DomainLifecycle, DomainSet, RegistryTarget};
+use crate::equivocation::RootConflict;
+use crate::errors::{PollOutcomes, SubsystemErrors};
+use crate::freshness::{Progress, StalenessTier};
+use crate::gaps::GapSnapshot;
+use crate::identity::IdentityKey;
+use crate::jobs::{JobScheduler, backup_stamp, write_backup};
+use crate::metrics::Metrics;
+use crate::proof_id::ProofId;
+use crate::prover::EndpointHealth;
+use crate::relayer::{decode_public_values, get_proof_at};
+use crate::scheduler::SchedulerSnapshot;
+use crate::timeseries::{METRICS, backfill_history, parse_window};
+use crate::verifier::verify_wrapper_proof;
+use crate::vk::VkObservation;
 use axum::{
     Router,
-    extract::State,
-    http::StatusCode,
-    response::{IntoResponse, Json},
-    routing::get,
+    body::Body,
+    extract::{
+        Path, Query, Request, State,
+        ws::{Message, WebSocket, WebSocketUpgrade},
+    },
+    http::{
+        HeaderMap, HeaderValue, StatusCode,
+        header::{AUTHORIZATION, CONTENT_DISPOSITION, CONTENT_TYPE},
+    },
+    middleware::{self, Next},
+    response::{
+        IntoResponse, Json,
+        sse::{Event, KeepAlive, Sse},
+    },
+    routing::{delete, get, post},
 };
-use serde::Serialize;
+use axum_server::tls_rustls::RustlsConfig;
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD as BASE64;
+use futures::StreamExt;
+use serde::{Deserialize, Serialize};
 use std::sync::Arc;
+use std::time::Duration;
+use tokio_util::io::ReaderStream;
+use tower::limit::GlobalConcurrencyLimitLayer;
+use tower_http::{
+    limit::RequestBodyLimitLayer,
+    request_id::{MakeRequestUuid, PropagateRequestIdLayer, SetRequestIdLayer},
+    timeout::TimeoutLayer,
+    trace::TraceLayer,
+};
 
 #[derive(Serialize)]
 pub struct HealthCheckResponse {
+    /// Sequence number of the latest proof in the history
+    pub seq: Option<u64>,
     pub current_height: u64,
     pub current_root: String, // hex encoded
     pub timestamp: String,
+    /// `healthy`, `unhealthy`, `degraded`, `no_data`, or `conflict` while a root conflict
+    /// is unresolved
+    pub status: String,
+    /// The unresolved root conflict behind a `conflict` status
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub conflict: Option<RootConflict>,
+    /// The most recent proof whose public values failed to decode, if any ever did
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_decode_failure: Option<DecodeFailure>,
+    /// Height progression across polls, when the domain's freshness is judged by it
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub progress: Option<Progress>,
+    /// Individual checks, with `?checks=true`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub checks: Option<Vec<SubCheck>>,
+    /// Worst status among `checks`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub overall: Option<String>,
+}
+
+/// One dependency or condition behind a domain's health
+#[derive(Serialize)]
+pub struct SubCheck {
+    /// `database`, `prover`, `registry`, `proof_age` or (relayer mode) `last_relay`
+    pub name: &'static str,
+    /// `healthy`, `degraded`, `unhealthy` or `unknown`
+    pub status: &'static str,
+    /// How long the probe took, for checks that make one
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub latency_ms: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+}
+
+/// Probes of the prover and registry give up after this long
+const SUB_CHECK_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// `/health` options
+#[derive(Deserialize)]
+pub struct HealthQuery {
+    pub domain: Option<String>,
+    /// Also probe the domain's dependencies, see `SubCheck`
+    #[serde(default)]
+    pub checks: bool,
+}
+
+/// Health of one domain plus the attestation stream it is reading from
+#[derive(Serialize)]
+pub struct DomainHealthResponse {
+    pub domain: String,
+    #[serde(flatten)]
+    pub health: HealthCheckResponse,
+    pub vk: String,
+    /// Prover that served the latest successful fetch
+    pub prover_endpoint: Option<String>,
+    pub registry_endpoint: String,
+}
+
+#[derive(Serialize)]
+pub struct DomainStatus {
+    pub domain: String,
+    pub mode: String,
+    pub paused: bool,
+    pub vk_mismatch: bool,
+    /// Payloads in the dead-letter queue, see `/admin/failed`
+    pub failed_submissions: usize,
+    /// Height of the latest proof in the history
+    pub latest_height: Option<u64>,
+    pub candidate_prover: Option<CandidateReport>,
+    /// Last failure of the prover client, registry, database and verifier
+    pub errors: SubsystemErrors,
+    /// Polls of the prover by outcome. `no_new_proof` polls are normal between epochs
+    /// and don't count as prover errors.
+    pub polls: PollOutcomes,
+}
+
+#[derive(Serialize)]
+pub struct StatusResponse {
+    /// Conditions an operator should act on, such as a VK mismatch
+    pub warnings: Vec<String>,
+    pub domains: Vec<DomainStatus>,
+}
+
+#[derive(Serialize)]
+pub struct DomainVk {
+    pub domain: String,
+    pub configured_vk: String,
+    pub configured_fingerprint: String,
+    /// VK read from the most recent proof, if any has been seen yet
+    pub latest_proof: Option<VkObservation>,
+    pub mismatch: bool,
+}
+
+#[derive(Serialize)]
+pub struct DomainProvers {
+    pub domain: String,
+    pub endpoints: Vec<EndpointHealth>,
+}
+
+#[derive(Serialize)]
+pub struct VerifyCurrentResponse {
+    pub domain: String,
     pub status: String,
+    pub height: Option<u64>,
+    pub proof_system: Option<String>,
+    pub vk: String,
+    pub error: Option<String>,
+}
+
+/// Decoded wrapper circuit outputs of the most recent proof in the history
+#[derive(Serialize)]
+pub struct PublicValuesResponse {
+    pub seq: u64,
+    pub domain: String,
+    pub mode: String,
+    pub height: u64,
+    pub root: String, // hex encoded
+    /// Raw borsh-encoded outputs, for fields not decoded above
+    pub public_values: String, // hex encoded
+    pub timestamp: String,
+}
+
+/// The most recent proof in the history, which in relayer mode is the latest one the
+/// registry accepted
+#[derive(Serialize)]
+pub struct LatestSubmissionResponse {
+    pub seq: u64,
+    pub domain: String,
+    pub height: u64,
+    pub root: String, // hex encoded
+    pub proof_id: ProofId,
+    pub timestamp: String,
+    /// Registries' confirmations of this height, when a status endpoint is polled
+    pub confirmations: Vec<RelayConfirmation>,
+}
+
+/// A proof fetched from the domain's provers by height, as served by `/provers/proofs/{height}`
+#[derive(Serialize)]
+pub struct ProverProofResponse {
+    pub domain: String,
+    pub height: u64,
+    pub root: String, // hex encoded
+    pub proof_id: ProofId,
+    pub public_values: String, // hex encoded
+    pub proof: String,         // hex encoded
+}
+
+#[cfg(all(feature = "relayer", not(feature = "health-check")))]
+#[derive(Serialize)]
+pub struct FailedRetryResponse {
+    pub domain: String,
+    pub id: u64,
+    pub height: u64,
+}
+
+/// A domain added or removed through `/admin/domains`
+#[derive(Serialize)]
+pub struct DomainChangeResponse {
+    pub domain: String,
+    /// Whether the domain is now served
+    pub active: bool,
+}
+
+#[derive(Serialize)]
+pub struct RelayControlResponse {
+    pub domain: String,
+    pub paused: bool,
+}
+
+/// Pushed to WebSocket clients whenever a new proof is accepted
+#[derive(Serialize, Deserialize, Clone)]
+pub struct ProofEvent {
+    /// Per-domain sequence number, for resuming with `?since_seq=`
+    pub seq: u64,
+    pub domain: String,
+    pub height: u64,
+    pub root: String, // hex encoded
+    /// `None` only when read from a relayer that predates proof ids
+    #[serde(default)]
+    pub proof_id: Option<ProofId>,
+    pub timestamp: String,
+}
+
+/// Relay lifecycle events, streamed to `/ws` clients that subscribe to them by name
+#[derive(Serialize, Clone)]
+#[serde(tag = "event")]
+pub enum StreamEvent {
+    /// A new proof passed its checks: recorded in health check mode, queued for
+    /// submission in relayer mode
+    #[serde(rename = "proof.accepted")]
+    ProofAccepted {
+        domain: String,
+        height: u64,
+        root: String, // hex encoded
+        proof_id: ProofId,
+    },
+    /// The primary sink accepted a proof
+    #[serde(rename = "proof.relayed")]
+    ProofRelayed(ProofEvent),
+    /// Submitting a proof failed after its retries
+    #[serde(rename = "relay.failed")]
+    RelayFailed {
+        domain: String,
+        height: u64,
+        error: String,
+    },
+}
+
+impl StreamEvent {
+    pub const NAMES: [&'static str; 3] = ["proof.accepted", "proof.relayed", "relay.failed"];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            StreamEvent::ProofAccepted { .. } => "proof.accepted",
+            StreamEvent::ProofRelayed(_) => "proof.relayed",
+            StreamEvent::RelayFailed { .. } => "relay.failed",
+        }
+    }
+
+    fn domain(&self) -> &str {
+        match self {
+            StreamEvent::ProofAccepted { domain, .. } | StreamEvent::RelayFailed { domain, .. } => {
+                domain
+            }
+            StreamEvent::ProofRelayed(event) => &event.domain,
+        }
+    }
+}
+
+/// The broadcasters behind `/ws` and `/events`
+#[derive(Clone)]
+pub struct EventStreams {
+    /// Newly recorded proofs, with the sequence numbers consumers checkpoint on
+    pub proofs: Arc<Broadcaster<ProofEvent>>,
+    pub lifecycle: Arc<Broadcaster<StreamEvent>>,
+}
+
+impl EventStreams {
+    pub fn new(capacity: usize, max_dropped: u64) -> Self {
+        EventStreams {
+            proofs: Arc::new(Broadcaster::new(capacity, max_dropped)),
+            lifecycle: Arc::new(Broadcaster::new(capacity, max_dropped)),
+        }
+    }
+}
+
+impl Default for EventStreams {
+    fn default() -> Self {
+        EventStreams::new(WS_CLIENT_QUEUE_CAPACITY, WS_MAX_DROPPED_EVENTS)
+    }
+}
+
+/// Sent by a `/ws` client to change which lifecycle events it receives, e.g.
+/// `{"subscribe": ["proof.relayed", "relay.failed"]}`
+#[derive(Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum WsRequest {
+    Subscribe(Vec<String>),
+    Unsubscribe(Vec<String>),
+}
+
+/// Selects a domain on routes that act on a single one; defaults to the first configured domain
+#[derive(Deserialize)]
+pub struct DomainQuery {
+    pub domain: Option<String>,
+}
+
+/// Proofs recorded after a consumer's checkpoint, or a page of the history by height
+#[derive(Deserialize)]
+pub struct SinceSeqQuery {
+    pub domain: Option<String>,
+    pub since_seq: Option<u64>,
+    pub limit: Option<usize>,
+    /// Instead of a checkpoint, return this many of the highest proofs
+    pub latest: Option<usize>,
+    /// Any of these pages through the history by height instead (see `ProofHistoryEntry`)
+    pub from_height: Option<u64>,
+    pub to_height: Option<u64>,
+    pub offset: Option<usize>,
+    /// `/ws` only: comma-separated lifecycle events to subscribe to on connect
+    pub events: Option<String>,
+}
+
+/// Encoding of `/proofs/{height}/raw`
+#[derive(Debug, Clone, Copy, Default, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RawProofFormat {
+    /// The stored proof bytes as `application/octet-stream`
+    #[default]
+    Binary,
+    /// The same bytes hex encoded, as `text/plain`
+    Hex,
+}
+
+#[derive(Deserialize)]
+pub struct RawProofQuery {
+    pub domain: Option<String>,
+    #[serde(default)]
+    pub format: RawProofFormat,
+}
+
+/// One `/proofs` entry when paging by height
+#[derive(Serialize)]
+pub struct ProofHistoryEntry {
+    pub seq: u64,
+    pub height: u64,
+    pub root: String, // hex encoded
+    pub timestamp: String,
+    /// Length of the serialized proof in bytes
+    pub proof_size: usize,
+}
+
+/// Most proofs returned by one `/proofs` request or replayed on one `/ws` connection
+const MAX_PROOFS_PER_REQUEST: usize = 1000;
+
+/// `/alerts/history` page size
+#[derive(Deserialize)]
+pub struct AlertHistoryQuery {
+    pub domain: Option<String>,
+    /// Defaults to 100, at most 1000
+    pub limit: Option<usize>,
+}
+
+/// `/relays` options
+#[derive(Deserialize)]
+pub struct RelaysQuery {
+    pub domain: Option<String>,
+    /// Only attempts for this height
+    pub height: Option<u64>,
+    /// Defaults to 100, at most 1000
+    pub limit: Option<usize>,
+}
+
+/// `/stats/timeseries` options
+#[derive(Deserialize)]
+pub struct TimeseriesQuery {
+    pub domain: Option<String>,
+    /// One of `timeseries::METRICS`
+    pub metric: String,
+    /// e.g. `30m`, `24h` or `7d`; defaults to `24h`
+    pub window: Option<String>,
+}
+
+/// Samples of one metric over the requested window, oldest first
+#[derive(Serialize)]
+pub struct TimeseriesResponse {
+    pub domain: String,
+    pub metric: String,
+    pub window_secs: i64,
+    pub interval_secs: u64,
+    pub samples: Vec<TimeseriesPoint>,
+}
+
+#[derive(Serialize)]
+pub struct TimeseriesPoint {
+    pub sampled_at: String,
+    pub value: f64,
+}
+
+/// Who is acknowledging an alert
+#[derive(Deserialize)]
+pub struct AckQuery {
+    pub domain: Option<String>,
+    pub by: Option<String>,
+}
+
+impl ProofEvent {
+    pub fn from_record(domain: &str, record: &crate::db::ProofRecord) -> Self {
+        ProofEvent {
+            seq: record.seq,
+            domain: domain.to_string(),
+            height: record.height,
+            root: hex::encode(&record.root),
+            proof_id: Some(record.proof_id.clone()),
+            timestamp: record.timestamp.to_rfc3339(),
+        }
+    }
 }
 
 pub struct AppState {
-    pub db: Arc<Database>,
+    pub config: Arc<Config>,
+    pub domains: Arc<DomainSet>,
+    /// Backs `/admin/domains`; only the relayer can add and remove domains at runtime
+    pub lifecycle: Option<Arc<dyn DomainLifecycle>>,
+    pub events: EventStreams,
+    pub metrics: Arc<Metrics>,
+    /// Maintenance jobs, for `/jobs`
+    pub jobs: Arc<JobScheduler>,
+    /// Bearer token required by the relay control routes; they are disabled when unset
+    pub admin_token: Option<String>,
+    /// Signs health responses when response signing is configured
+    pub identity: Option<Arc<IdentityKey>>,
 }
 
-pub fn create_api_server(db: Arc<Database>) -> Router {
-    let state = Arc::new(AppState { db });
+impl AppState {
+    fn domain(&self, name: Option<&str>) -> Option<Arc<Domain>> {
+        match name {
+            Some(name) => self.domains.get(name),
+            None => self.domains.first(),
+        }
+    }
+
+    /// Like `domain`, but an omitted name selects every domain
+    fn domains_or_all(&self, name: Option<&str>) -> Option<Vec<Arc<Domain>>> {
+        match name {
+            Some(name) => self.domain(Some(name)).map(|domain| vec![domain]),
+            None => Some(self.domains.all()),
+        }
+    }
 
-    Router::new()
+    fn authorize(&self, headers: &HeaderMap) -> Result<(), axum::response::Response> {
+        let Some(expected) = self.admin_token.as_deref() else {
+            return Err(
+                (StatusCode::FORBIDDEN, "Admin API disabled, set ADMIN_TOKEN").into_response(),
+            );
+        };
+
+        let provided = headers
+            .get(AUTHORIZATION)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.strip_prefix("Bearer "))
+            .unwrap_or_default();
+
+        if constant_time_eq(provided.as_bytes(), expected.as_bytes()) {
+            Ok(())
+        } else {
+            tracing::warn!("Rejected admin request with missing or invalid token");
+            Err(StatusCode::UNAUTHORIZED.into_response())
+        }
+    }
+}
+
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+fn unknown_domain(name: Option<&str>) -> axum::response::Response {
+    (
+        StatusCode::NOT_FOUND,
+        format!("Unknown domain {}", name.unwrap_or_default()),
+    )
+        .into_response()
+}
+
+pub fn create_api_server(state: AppState, limits: &ApiLimits) -> Router {
+    let state = Arc::new(state);
+
+    // Attestations consumers may want to verify end to end
+    let signed = Router::new()
         .route("/health", get(get_health_check))
+        .route("/health/{domain}", get(get_domain_health_check))
+        .route("/submissions/latest", get(get_latest_submission))
+        .route_layer(middleware::from_fn_with_state(state.clone(), sign_response));
+
+    Router::new()
+        .merge(signed)
+        .route("/identity", get(get_identity))
+        .route("/metrics", get(get_metrics))
+        .route("/alerts", get(get_alerts))
+        .route("/alerts/history", get(get_alert_history))
+        .route("/alerts/{id}/ack", post(acknowledge_alert))
+        .route("/provers", get(get_provers))
+        .route("/provers/proofs/{height}", get(get_prover_proof))
+        .route("/vk", get(get_vk))
+        .route("/public-values/latest", get(get_latest_public_values))
+        .route("/proofs", get(get_proofs))
+        .route("/proofs/{height}/raw", get(get_raw_proof))
+        .route("/status", get(get_status))
+        .route("/stats/scheduler", get(get_scheduler_stats))
+        .route("/stats/gaps", get(get_gap_stats))
+        .route("/stats/timeseries", get(get_timeseries))
+        .route("/jobs", get(get_jobs))
+        .route("/relays", get(get_relays))
+        .route("/relays/confirmations", get(get_relay_confirmations))
+        .route("/ws", get(ws_feed))
+        .route("/events", get(sse_feed))
+        .route("/admin/verify-current", post(verify_current))
+        .route("/admin/rollback", get(get_pending_rollback))
+        .route("/admin/rollback/approve", post(approve_rollback))
+        .route("/admin/conflict/resolve", post(resolve_conflict))
+        .route("/admin/history/backfill", post(backfill_metrics_history))
+        .route("/admin/backup", post(backup_databases))
+        .route("/admin/pause", post(pause_relaying))
+        .route("/admin/resume", post(resume_relaying))
+        .route("/admin/relay-now", post(relay_now))
+        .route("/admin/domains", post(add_domain))
+        .route("/admin/domains/{name}", delete(remove_domain))
+        .route("/admin/registry/cutover", post(cut_over_registry))
+        .route("/admin/registry/cutovers", get(get_cutovers))
+        .route("/admin/failed", get(get_failed_submissions))
+        .route("/admin/failed/{id}/retry", post(retry_failed_submission))
         .route("/", get(root))
+        .layer(RequestBodyLimitLayer::new(limits.max_body_bytes))
+        .layer(TimeoutLayer::new(Duration::from_secs(
+            limits.request_timeout_secs,
+        )))
+        .layer(GlobalConcurrencyLimitLayer::new(
+            limits.max_concurrent_requests,
+        ))
+        // Tag every request with an ID (honouring one sent by the client) and log within its span
+        .layer(PropagateRequestIdLayer::x_request_id())
+        .layer(TraceLayer::new_for_http().make_span_with(
+            |request: &axum::http::Request<axum::body::Body>| {
+                let request_id = request
+                    .headers()
+                    .get("x-request-id")
+                    .and_then(|value| value.to_str().ok())
+                    .unwrap_or_default();
+                tracing::info_span!(
+                    "request",
+                    request_id = %request_id,
+                    method = %request.method(),
+                    path = %request.uri().path(),
+                )
+            },
+        ))
+        .layer(SetRequestIdLayer::x_request_id(MakeRequestUuid))
         .with_state(state)
 }
 
+/// Header carrying the base64 ed25519 signature over the exact response body
+const SIGNATURE_HEADER: &str = "x-relayer-signature";
+/// Header naming the hex public key that made the signature
+const SIGNATURE_KEY_HEADER: &str = "x-relayer-key";
+
+/// Adds a detached signature over the response body. Consumers verify the body bytes as
+/// received, so nothing needs to be re-serialized on their side.
+async fn sign_response(
+    State(state): State<Arc<AppState>>,
+    request: Request,
+    next: Next,
+) -> axum::response::Response {
+    let response = next.run(request).await;
+    let Some(identity) = &state.identity else {
+        return response;
+    };
+
+    let (mut parts, body) = response.into_parts();
+    let body = match axum::body::to_bytes(body, usize::MAX).await {
+        Ok(body) => body,
+        Err(e) => {
+            tracing::error!("Failed to buffer response for signing: {}", e);
+            return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+        }
+    };
+    let signature = BASE64.encode(identity.sign(&body));
+    if let (Ok(signature), Ok(key)) = (
+        HeaderValue::from_str(&signature),
+        HeaderValue::from_str(&identity.public_key()),
+    ) {
+        parts.headers.insert(SIGNATURE_HEADER, signature);
+        parts.headers.insert(SIGNATURE_KEY_HEADER, key);
+    }
+    axum::response::Response::from_parts(parts, Body::from(body))
+}
+
+#[derive(Serialize)]
+pub struct IdentityResponse {
+    /// Hex ed25519 public key signing `/health` and `/submissions/latest` responses;
+    /// `None` when signing is off
+    pub public_key: Option<String>,
+}
+
+async fn get_identity(State(state): State<Arc<AppState>>) -> impl IntoResponse {
+    Json(IdentityResponse {
+        public_key: state
+            .identity
+            .as_ref()
+            .map(|identity| identity.public_key()),
+    })
+}
+
 async fn root() -> &'static str {
-    "Helios Proof Relayer API\nUse /health to get latest health check data"
+    "Helios Proof Relayer API\nUse /health to get latest health check data\nUse /ws to stream new proofs\nUse /status for relayer status\nUse /metrics and /alerts for monitoring"
+}
+
+async fn ws_feed(
+    ws: WebSocketUpgrade,
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<SinceSeqQuery>,
+) -> impl IntoResponse {
+    let subscribed = match query.events.as_deref() {
+        Some(names) => match resolve_event_names(names.split(',').map(str::trim)) {
+            Ok(names) => Some(names),
+            Err(message) => return (StatusCode::BAD_REQUEST, message).into_response(),
+        },
+        None => None,
+    };
+
+    // Subscribe before reading the backlog so nothing falls in between
+    let subscription = WsSubscription {
+        proofs: state.events.proofs.subscribe(),
+        lifecycle: state.events.lifecycle.subscribe(),
+        domain: query.domain.clone(),
+        subscribed,
+    };
+    let backlog = match load_backlog(&state, &query).await {
+        Ok(backlog) => backlog,
+        Err(response) => return response,
+    };
+
+    tracing::info!(
+        "New WebSocket client connected ({} total, replaying {} proofs)",
+        state.events.proofs.subscriber_count(),
+        backlog.len()
+    );
+    ws.on_upgrade(move |socket| stream_events(socket, subscription, backlog))
+}
+
+/// Maps requested lifecycle event names to `StreamEvent::NAMES`, rejecting unknown ones
+fn resolve_event_names<'a>(
+    names: impl IntoIterator<Item = &'a str>,
+) -> Result<std::collections::BTreeSet<&'static str>, String> {
+    names
+        .into_iter()
+        .filter(|name| !name.is_empty())
+        .map(|name| {
+            StreamEvent::NAMES
+                .into_iter()
+                .find(|known| *known == name)
+                .ok_or_else(|| {
+                    format!(
+                        "Unknown event {}, expected one of {}",
+                        name,
+                        StreamEvent::NAMES.join(", ")
+                    )
+                })
+        })
+        .collect()
+}
+
+/// Proofs recorded after `since_seq`, replayed to a new `/ws` or `/events` subscriber
+async fn load_backlog(
+    state: &AppState,
+    query: &SinceSeqQuery,
+) -> Result<Vec<ProofEvent>, axum::response::Response> {
+    let mut backlog = Vec::new();
+    let Some(since_seq) = query.since_seq else {
+        return Ok(backlog);
+    };
+
+    let domains: Vec<Arc<Domain>> = match query.domain.as_deref() {
+        Some(name) => match state.domain(Some(name)) {
+            Some(domain) => vec![domain],
+            None => return Err(unknown_domain(Some(name))),
+        },
+        None => state.domains.all(),
+    };
+    for domain in domains {
+        match domain
+            .read(move |db| db.get_proofs_since_seq(since_seq, MAX_PROOFS_PER_REQUEST))
+            .await
+        {
+            Ok(records) => backlog.extend(
+                records
+                    .iter()
+                    .map(|record| ProofEvent::from_record(domain.name(), record)),
+            ),
+            Err(e) => {
+                tracing::error!("Failed to load proofs since seq {}: {}", since_seq, e);
+                return Err(StatusCode::INTERNAL_SERVER_ERROR.into_response());
+            }
+        }
+    }
+    Ok(backlog)
+}
+
+/// Server-Sent Events stream of newly recorded proofs, one `proof` event each with the
+/// `ProofEvent` as JSON and its sequence number as the event id. Takes the same `domain`
+/// filter and `since_seq` replay as `/ws`.
+async fn sse_feed(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<SinceSeqQuery>,
+) -> impl IntoResponse {
+    let subscription = state.events.proofs.subscribe();
+    if let Some(name) = query.domain.as_deref()
+        && state.domain(Some(name)).is_none()
+    {
+        return unknown_domain(Some(name));
+    }
+    let backlog = match load_backlog(&state, &query).await {
+        Ok(backlog) => backlog,
+        Err(response) => return response,
+    };
+    tracing::info!(
+        "New SSE client connected ({} total, replaying {} proofs)",
+        state.events.proofs.subscriber_count(),
+        backlog.len()
+    );
+
+    // Highest sequence number replayed per domain; live events at or below it are duplicates
+    let mut replayed: std::collections::HashMap<String, u64> = std::collections::HashMap::new();
+    for event in &backlog {
+        let seq = replayed.entry(event.domain.clone()).or_default();
+        *seq = (*seq).max(event.seq);
+    }
+    let domain = query.domain;
+    let live = futures::stream::unfold(subscription, |mut subscription| async move {
+        subscription.recv().await.map(|event| (event, subscription))
+    })
+    .filter(move |event| {
+        let wanted = domain.as_ref().is_none_or(|name| *name == event.domain)
+            && replayed
+                .get(&event.domain)
+                .is_none_or(|&seq| event.seq > seq);
+        std::future::ready(wanted)
+    });
+
+    let stream = futures::stream::iter(backlog).chain(live).map(|event| {
+        Event::default()
+            .event("proof")
+            .id(event.seq.to_string())
+            .json_data(&event)
+    });
+    Sse::new(stream)
+        .keep_alive(KeepAlive::default())
+        .into_response()
+}
+
+/// One `/ws` connection's queues, plus what it asked for
+struct WsSubscription {
+    proofs: Subscription<ProofEvent>,
+    lifecycle: Subscription<StreamEvent>,
+    domain: Option<String>,
+    /// Lifecycle events the client subscribed to; until it does, it receives the plain
+    /// proof stream instead
+    subscribed: Option<std::collections::BTreeSet<&'static str>>,
+}
+
+async fn stream_events(
+    mut socket: WebSocket,
+    mut subscription: WsSubscription,
+    backlog: Vec<ProofEvent>,
+) {
+    // Highest sequence number replayed per domain; live events at or below it are duplicates
+    let mut replayed: std::collections::HashMap<String, u64> = std::collections::HashMap::new();
+    if subscription.subscribed.is_none() {
+        for event in backlog {
+            let seq = replayed.entry(event.domain.clone()).or_default();
+            *seq = (*seq).max(event.seq);
+            if send_json(&mut socket, &event).await.is_err() {
+                tracing::info!("WebSocket client disconnected");
+                return;
+            }
+        }
+    }
+
+    let mut keepalive = tokio::time::interval(Duration::from_secs(WS_PING_INTERVAL_SECS));
+    keepalive.tick().await;
+    let mut last_seen = tokio::time::Instant::now();
+
+    // Both queues are drained whichever the client reads, so the unused one never
+    // counts it as too slow
+    loop {
+        tokio::select! {
+            event = subscription.proofs.recv() => {
+                let Some(event) = event else {
+                    tracing::warn!("Disconnecting WebSocket client that fell too far behind");
+                    let _ = socket.send(Message::Close(None)).await;
+                    break;
+                };
+                if subscription.subscribed.is_some()
+                    || replayed.get(&event.domain).is_some_and(|&seq| event.seq <= seq)
+                {
+                    continue;
+                }
+                if send_json(&mut socket, &event).await.is_err() {
+                    break;
+                }
+            }
+            event = subscription.lifecycle.recv() => {
+                let Some(event) = event else {
+                    tracing::warn!("Disconnecting WebSocket client that fell too far behind");
+                    let _ = socket.send(Message::Close(None)).await;
+                    break;
+                };
+                let wanted = subscription
+                    .subscribed
+                    .as_ref()
+                    .is_some_and(|names| names.contains(event.name()))
+                    && subscription
+                        .domain
+                        .as_ref()
+                        .is_none_or(|name| name == event.domain());
+                if wanted && send_json(&mut socket, &event).await.is_err() {
+                    break;
+                }
+            }
+            _ = keepalive.tick() => {
+                if last_seen.elapsed() > Duration::from_secs(WS_PONG_TIMEOUT_SECS) {
+                    tracing::warn!("Disconnecting WebSocket client that stopped answering pings");
+                    let _ = socket.send(Message::Close(None)).await;
+                    break;
+                }
+                if socket.send(Message::Ping(Default::default())).await.is_err() {
+                    break;
+                }
+            }
+            message = socket.recv() => {
+                match message {
+                    Some(Ok(Message::Close(_))) | Some(Err(_)) | None => break,
+                    Some(Ok(message)) => {
+                        last_seen = tokio::time::Instant::now();
+                        if let Message::Text(text) = message {
+                            let reply = handle_ws_request(&mut subscription, &text);
+                            if send_json(&mut socket, &reply).await.is_err() {
+                                break;
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+    tracing::info!("WebSocket client disconnected");
+}
+
+/// Applies a subscription change, answering with the events now subscribed to or an error
+fn handle_ws_request(subscription: &mut WsSubscription, text: &str) -> serde_json::Value {
+    let request = match serde_json::from_str::<WsRequest>(text) {
+        Ok(request) => request,
+        Err(e) => return serde_json::json!({ "error": format!("Invalid request: {}", e) }),
+    };
+    let (names, subscribe) = match &request {
+        WsRequest::Subscribe(names) => (names, true),
+        WsRequest::Unsubscribe(names) => (names, false),
+    };
+    let names = match resolve_event_names(names.iter().map(String::as_str)) {
+        Ok(names) => names,
+        Err(message) => return serde_json::json!({ "error": message }),
+    };
+
+    let subscribed = subscription.subscribed.get_or_insert_default();
+    if subscribe {
+        subscribed.extend(names);
+    } else {
+        subscribed.retain(|name| !names.contains(name));
+    }
+    serde_json::json!({ "subscribed": subscribed })
+}
+
+/// Sends one message; only a closed socket is an error, unserializable messages are skipped
+async fn send_json<T: Serialize>(socket: &mut WebSocket, message: &T) -> Result<(), axum::Error> {
+    let text = match serde_json::to_string(message) {
+        Ok(text) => text,
+        Err(e) => {
+            tracing::error!("Failed to serialize WebSocket message: {}", e);
+            return Ok(());
+        }
+    };
+    socket.send(Message::Text(text.into())).await
+}
+
+async fn get_proofs(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<SinceSeqQuery>,
+) -> impl IntoResponse {
+    let Some(domain) = state.domain(query.domain.as_deref()) else {
+        return unknown_domain(query.domain.as_deref());
+    };
+
+    let limit = query
+        .limit
+        .unwrap_or(MAX_PROOFS_PER_REQUEST)
+        .min(MAX_PROOFS_PER_REQUEST);
+
+    if query.from_height.is_some() || query.to_height.is_some() || query.offset.is_some() {
+        let (from, to, offset) = (
+            query.from_height.unwrap_or(0),
+            query.to_height.unwrap_or(u64::MAX),
+            query.offset.unwrap_or(0),
+        );
+        let page = domain
+            .read(move |db| db.get_proofs_page(from, to, limit, offset))
+            .await;
+        return match page {
+            Ok(records) => {
+                let entries: Vec<ProofHistoryEntry> = records
+                    .iter()
+                    .map(|record| ProofHistoryEntry {
+                        seq: record.seq,
+                        height: record.height,
+                        root: hex::encode(&record.root),
+                        timestamp: record.timestamp.to_rfc3339(),
+                        proof_size: record.proof.len(),
+                    })
+                    .collect();
+                (StatusCode::OK, Json(entries)).into_response()
+            }
+            Err(e) => {
+                tracing::error!("Failed to load proof history: {}", e);
+                StatusCode::INTERNAL_SERVER_ERROR.into_response()
+            }
+        };
+    }
+
+    let (latest, since_seq) = (query.latest, query.since_seq);
+    let records = domain
+        .read(move |db| match (latest, since_seq) {
+            (Some(latest), None) => db.get_latest_proofs(latest.min(MAX_PROOFS_PER_REQUEST)),
+            (_, since_seq) => db.get_proofs_since_seq(since_seq.unwrap_or(0), limit),
+        })
+        .await;
+    match records {
+        Ok(records) => {
+            let proofs: Vec<ProofEvent> = records
+                .iter()
+                .map(|record| ProofEvent::from_record(domain.name(), record))
+                .collect();
+            (StatusCode::OK, Json(proofs)).into_response()
+        }
+        Err(e) => {
+            tracing::error!("Failed to load proof history: {}", e);
+            StatusCode::INTERNAL_SERVER_ERROR.into_response()
+        }
+    }
 }
 
-async fn get_health_check(State(state): State<Arc<AppState>>) -> impl IntoResponse {
+async fn get_health_check(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<HealthQuery>,
+) -> impl IntoResponse {
     tracing::info!("Received request for latest health check data");
 
-    match state.db.get_latest_health_check() {
-        Ok(Some(health_data)) => {
-            let now = chrono::Utc::now();
-            let threshold = now - chrono::Duration::minutes(30);
-            let status = if health_data.timestamp > threshold {
-                "healthy"
-            } else {
-                "unhealthy"
+    let Some(domain) = state.domain(query.domain.as_deref()) else {
+        return unknown_domain(query.domain.as_deref());
+    };
+
+    match latest_health(&domain).await {
+        Ok((status_code, mut response)) => {
+            if query.checks {
+                add_sub_checks(&domain, &mut response).await;
+            }
+            (status_code, Json(response)).into_response()
+        }
+        Err(e) => {
+            tracing::error!("Failed to get health check data: {}", e);
+            StatusCode::INTERNAL_SERVER_ERROR.into_response()
+        }
+    }
+}
+
+async fn get_domain_health_check(
+    State(state): State<Arc<AppState>>,
+    Path(name): Path<String>,
+    Query(query): Query<HealthQuery>,
+) -> impl IntoResponse {
+    tracing::info!("Received request for latest health check data of {}", name);
+
+    let Some(domain) = state.domain(Some(&name)) else {
+        return unknown_domain(Some(&name));
+    };
+
+    let redaction = state.config.endpoint_redaction;
+    match latest_health(&domain).await {
+        Ok((status_code, mut health)) => {
+            if query.checks {
+                add_sub_checks(&domain, &mut health).await;
+            }
+            let response = DomainHealthResponse {
+                domain: domain.name().to_string(),
+                health,
+                vk: domain.config.vk.clone(),
+                prover_endpoint: domain
+                    .provers
+                    .active_endpoint()
+                    .map(|endpoint| redact_endpoint(endpoint, redaction)),
+                registry_endpoint: redact_endpoint(&domain.registry_endpoint(), redaction),
+            };
+            (status_code, Json(response)).into_response()
+        }
+        Err(e) => {
+            tracing::error!("Failed to get health check data: {}", e);
+            StatusCode::INTERNAL_SERVER_ERROR.into_response()
+        }
+    }
+}
+
+async fn latest_health(domain: &Domain) -> anyhow::Result<(StatusCode, HealthCheckResponse)> {
+    let (seq, last_decode_failure, health_data) = domain
+        .read(|db| {
+            Ok((
+                db.get_latest_proof()?.map(|record| record.seq),
+                db.get_decode_failures(1)?.pop(),
+                db.get_latest_health_check()?,
+            ))
+        })
+        .await?;
+    let conflict = domain.conflicts.current();
+
+    match health_data {
+        Some(health_data) => {
+            let progress = domain.freshness.progress();
+            let status = match progress {
+                _ if conflict.is_some() => "conflict",
+                Some(progress) if progress.degraded => "degraded",
+                Some(_) => "healthy",
+                None => StalenessTier::of(
+                    &domain.config.staleness,
+                    chrono::Utc::now() - health_data.timestamp,
+                )
+                .as_str(),
             };
 
             let response = HealthCheckResponse {
+                seq,
                 current_height: health_data.current_height,
                 current_root: hex::encode(&health_data.current_root),
                 timestamp: health_data.timestamp.to_rfc3339(),
                 status: status.to_string(),
+                conflict,
+                last_decode_failure,
+                progress,
+                checks: None,
+                overall: None,
             };
             tracing::info!(
                 "Returning health check data: height={}, status={}",
                 health_data.current_height,
                 status
             );
-            (StatusCode::OK, Json(response)).into_response()
+            Ok((StatusCode::OK, response))
         }
-        Ok(None) => {
+        None => {
             let response = HealthCheckResponse {
+                seq,
                 current_height: 0,
                 current_root: "".to_string(),
                 timestamp: chrono::Utc::now().to_rfc3339(),
                 status: "no_data".to_string(),
+                conflict,
+                last_decode_failure,
+                progress: domain.freshness.progress(),
+                checks: None,
+                overall: None,
             };
             tracing::info!("No health check data available");
-            (StatusCode::NOT_FOUND, Json(response)).into_response()
+            Ok((StatusCode::NOT_FOUND, response))
+        }
+    }
+}
+
+/// Runs every sub-check, the network probes concurrently, and rolls them up into `overall`
+async fn add_sub_checks(domain: &Domain, response: &mut HealthCheckResponse) {
+    let prover = domain
+        .provers
+        .active_endpoint()
+        .or(domain.config.prover_endpoints.first().map(String::as_str))
+        .map(str::to_string);
+    let (prover, registry) = tokio::join!(
+        probe_endpoint(domain, "prover", prover),
+        probe_endpoint(domain, "registry", Some(domain.registry_endpoint())),
+    );
+
+    let mut checks = vec![check_database(domain).await, prover, registry];
+    let latest = domain.read(|db| db.get_latest_proof()).await.ok().flatten();
+    checks.push(check_proof_age(domain, latest.as_ref()));
+    #[cfg(all(feature = "relayer", not(feature = "health-check")))]
+    checks.push(check_last_relay(domain, latest.as_ref()));
+
+    let rank = |status: &str| match status {
+        "unhealthy" => 2,
+        "degraded" => 1,
+        _ => 0,
+    };
+    let overall = checks
+        .iter()
+        .map(|check| check.status)
+        .max_by_key(|status| rank(status))
+        .unwrap_or("healthy");
+    response.overall = Some(
+        if overall == "unknown" {
+            "healthy"
+        } else {
+            overall
         }
+        .to_string(),
+    );
+    response.checks = Some(checks);
+}
+
+async fn check_database(domain: &Domain) -> SubCheck {
+    let started = std::time::Instant::now();
+    let result = domain.read(|db| db.get_latest_health_check()).await;
+    let latency_ms = Some(started.elapsed().as_millis() as u64);
+    match result {
+        Ok(_) => SubCheck {
+            name: "database",
+            status: "healthy",
+            latency_ms,
+            detail: None,
+        },
+        Err(e) => SubCheck {
+            name: "database",
+            status: "unhealthy",
+            latency_ms,
+            detail: Some(e.to_string()),
+        },
+    }
+}
+
+/// Probes the endpoint with the proxy, headers and timeouts configured for it, within
+/// `SUB_CHECK_TIMEOUT`. 2xx and 3xx answers are healthy, 4xx degraded (reachable, but
+/// e.g. refusing the relayer's credentials) and 5xx unhealthy. The endpoint itself is
+/// left out of the result since it may carry credentials.
+async fn probe_endpoint(domain: &Domain, name: &'static str, endpoint: Option<String>) -> SubCheck {
+    let Some(endpoint) = endpoint else {
+        return SubCheck {
+            name,
+            status: "unknown",
+            latency_ms: None,
+            detail: Some("No endpoint configured".to_string()),
+        };
+    };
+    let http = domain.config.http_for(&endpoint);
+    let timeout = http
+        .timeouts
+        .deadline()
+        .map_or(SUB_CHECK_TIMEOUT, |deadline| {
+            deadline.min(SUB_CHECK_TIMEOUT)
+        });
+    let client = match crate::relayer::endpoint_client(&http) {
+        Ok(client) => client,
         Err(e) => {
-            tracing::error!("Failed to get health check data: {}", e);
+            return SubCheck {
+                name,
+                status: "unknown",
+                latency_ms: None,
+                detail: Some(e.to_string()),
+            };
+        }
+    };
+
+    let started = std::time::Instant::now();
+    let result = client.get(&endpoint).timeout(timeout).send().await;
+    let latency_ms = Some(started.elapsed().as_millis() as u64);
+    match result {
+        Ok(response) => {
+            let status = response.status();
+            SubCheck {
+                name,
+                status: if status.is_success() || status.is_redirection() {
+                    "healthy"
+                } else if status.is_client_error() {
+                    "degraded"
+                } else {
+                    "unhealthy"
+                },
+                latency_ms,
+                detail: (!status.is_success() && !status.is_redirection())
+                    .then(|| format!("HTTP {}", status.as_u16())),
+            }
+        }
+        Err(e) => SubCheck {
+            name,
+            status: "unhealthy",
+            latency_ms,
+            detail: Some(if e.is_timeout() {
+                format!("No response within {:?}", timeout)
+            } else {
+                "Unreachable".to_string()
+            }),
+        },
+    }
+}
+
+/// Age of the newest proof in the history, against the domain's `staleness` thresholds
+fn check_proof_age(domain: &Domain, latest: Option<&crate::db::ProofRecord>) -> SubCheck {
+    let Some(latest) = latest else {
+        return SubCheck {
+            name: "proof_age",
+            status: "unknown",
+            latency_ms: None,
+            detail: Some("No proof recorded yet".to_string()),
+        };
+    };
+    let age = chrono::Utc::now() - latest.timestamp;
+    SubCheck {
+        name: "proof_age",
+        status: StalenessTier::of(&domain.config.staleness, age).as_str(),
+        latency_ms: None,
+        detail: Some(format!(
+            "Height {} recorded {}s ago",
+            latest.height,
+            age.num_seconds()
+        )),
+    }
+}
+
+/// Degraded when the registry has failed since the last proof it accepted
+#[cfg(all(feature = "relayer", not(feature = "health-check")))]
+fn check_last_relay(domain: &Domain, latest: Option<&crate::db::ProofRecord>) -> SubCheck {
+    let failed_at = domain.errors.snapshot().registry.and_then(|error| {
+        chrono::DateTime::parse_from_rfc3339(&error.at)
+            .ok()
+            .map(|at| at.with_timezone(&chrono::Utc))
+    });
+    let (status, detail) = match (latest, failed_at) {
+        (None, None) => ("unknown", "Nothing relayed yet".to_string()),
+        (None, Some(failed_at)) => (
+            "unhealthy",
+            format!(
+                "Nothing relayed yet, last failure at {}",
+                failed_at.to_rfc3339()
+            ),
+        ),
+        (Some(latest), Some(failed_at)) if failed_at > latest.timestamp => (
+            "degraded",
+            format!(
+                "Submissions failing since height {} was relayed at {}",
+                latest.height,
+                latest.timestamp.to_rfc3339()
+            ),
+        ),
+        (Some(latest), _) => (
+            "healthy",
+            format!(
+                "Height {} relayed at {}",
+                latest.height,
+                latest.timestamp.to_rfc3339()
+            ),
+        ),
+    };
+    SubCheck {
+        name: "last_relay",
+        status,
+        latency_ms: None,
+        detail: Some(detail),
+    }
+}
+
+/// Hides endpoint details that may carry secrets before they are shown to API consumers
+fn redact_endpoint(endpoint: &str, redaction: EndpointRedaction) -> String {
+    match redaction {
+        EndpointRedaction::None => endpoint.to_string(),
+        EndpointRedaction::Full => "<redacted>".to_string(),
+        EndpointRedaction::Credentials => match reqwest::Url::parse(endpoint) {
+            Ok(mut url) => {
+                let _ = url.set_username("");
+                let _ = url.set_password(None);
+                url.set_query(None);
+                url.to_string()
+            }
+            Err(_) => "<redacted>".to_string(),
+        },
+    }
+}
+
+async fn get_metrics(State(state): State<Arc<AppState>>) -> impl IntoResponse {
+    // Refresh the alert-state gauges so every scrape sees current values
+    if let Err(e) = evaluate_alerts(&state.domains.all(), &state.metrics).await {
+        tracing::error!("Failed to evaluate alert conditions: {}", e);
+    }
+    for domain in state.domains.all() {
+        state
+            .metrics
+            .db_write_queue_depth
+            .with_label_values(&[domain.name()])
+            .set(domain.writer.depth() as i64);
+
+        let scheduler = domain.scheduler.snapshot();
+        let metrics = &state.metrics;
+        metrics
+            .scheduler_last_drift
+            .with_label_values(&[domain.name()])
+            .set(scheduler.last_drift_ms as i64);
+        metrics
+            .scheduler_accumulated_drift
+            .with_label_values(&[domain.name()])
+            .set(scheduler.accumulated_drift_ms as i64);
+        metrics
+            .scheduler_longest_stall
+            .with_label_values(&[domain.name()])
+            .set(scheduler.longest_stall_ms as i64);
+
+        let pruning = domain.pruning.snapshot();
+        if let Some(last_run_at) = pruning.last_run_at {
+            metrics
+                .proof_history_size
+                .with_label_values(&[domain.name()])
+                .set(pruning.proofs_kept as i64);
+            metrics
+                .last_prune_timestamp
+                .with_label_values(&[domain.name()])
+                .set(last_run_at.timestamp());
+        }
+    }
+
+    match state.metrics.render() {
+        Ok(body) => (StatusCode::OK, body).into_response(),
+        Err(e) => {
+            tracing::error!("Failed to render metrics: {}", e);
             StatusCode::INTERNAL_SERVER_ERROR.into_response()
         }
     }
 }
 
-pub async fn start_api_server(router: Router) -> Result<(), Box<dyn std::error::Error>> {
-    // Get server port from environment or use default from config
-    let port = std::env::var("API_PORT").unwrap_or_else(|_| API_PORT.to_string());
-    let addr = format!("0.0.0.0:{}", port);
+async fn get_alerts(State(state): State<Arc<AppState>>) -> impl IntoResponse {
+    match evaluate_alerts(&state.domains.all(), &state.metrics).await {
+        Ok(alerts) => (StatusCode::OK, Json(alerts)).into_response(),
+        Err(e) => {
+            tracing::error!("Failed to evaluate alert conditions: {}", e);
+            StatusCode::INTERNAL_SERVER_ERROR.into_response()
+        }
+    }
+}
 
-    // Parse the address properly
-    let socket_addr: std::net::SocketAddr = addr.parse()?;
+/// Serves a recorded proof as stored, so integrators don't have to ask the prover again
+async fn get_raw_proof(
+    State(state): State<Arc<AppState>>,
+    Path(height): Path<u64>,
+    Query(query): Query<RawProofQuery>,
+) -> impl IntoResponse {
+    let Some(domain) = state.domain(query.domain.as_deref()) else {
+        return unknown_domain(query.domain.as_deref());
+    };
 
-    let listener = tokio::net::TcpListener::bind(socket_addr).await?;
-    tracing::info!("API server listening on http://{}", addr);
-    tracing::info!("🌐 Server is externally reachable on port {}", port);
+    let record = match domain.read(move |db| db.get_proof_by_height(height)).await {
+        Ok(Some(record)) => record,
+        Ok(None) => {
+            return (
+                StatusCode::NOT_FOUND,
+                format!("No proof recorded at height {}", height),
+            )
+                .into_response();
+        }
+        Err(e) => {
+            tracing::error!("Failed to load proof at height {}: {}", height, e);
+            return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+        }
+    };
 
-    axum::serve(listener, router).await?;
-    Ok(())
+    let (content_type, extension, body) = match query.format {
+        RawProofFormat::Binary => ("application/octet-stream", "bin", record.proof),
+        RawProofFormat::Hex => ("text/plain", "hex", hex::encode(record.proof).into_bytes()),
+    };
+    (
+        StatusCode::OK,
+        [
+            (CONTENT_TYPE, content_type.to_string()),
+            (
+                CONTENT_DISPOSITION,
+                format!(
+                    "attachment; filename=\"{}-{}.{}\"",
+                    domain.name(),
+                    height,
+                    extension
+                ),
+            ),
+        ],
+        body,
+    )
+        .into_response()
+}
+
+/// Cycle timing of one domain's loop
+#[derive(Serialize)]
+pub struct DomainSchedulerStats {
+    pub domain: String,
+    #[serde(flatten)]
+    pub stats: SchedulerSnapshot,
+    /// Average seconds between new proofs, with adaptive polling
+    pub observed_proof_interval_secs: Option<f64>,
+}
+
+async fn get_scheduler_stats(State(state): State<Arc<AppState>>) -> impl IntoResponse {
+    let stats: Vec<DomainSchedulerStats> = state
+        .domains
+        .all()
+        .iter()
+        .map(|domain| DomainSchedulerStats {
+            domain: domain.name().to_string(),
+            stats: domain.scheduler.snapshot(),
+            observed_proof_interval_secs: domain.polling.observed_cadence(),
+        })
+        .collect();
+    Json(stats)
+}
+
+async fn get_jobs(State(state): State<Arc<AppState>>) -> impl IntoResponse {
+    Json(state.jobs.snapshot())
+}
+
+/// Heights one domain skipped between relayed proofs
+#[derive(Serialize)]
+pub struct DomainGapStats {
+    pub domain: String,
+    #[serde(flatten)]
+    pub gaps: GapSnapshot,
+}
+
+async fn get_gap_stats(State(state): State<Arc<AppState>>) -> impl IntoResponse {
+    let stats: Vec<DomainGapStats> = state
+        .domains
+        .all()
+        .iter()
+        .map(|domain| DomainGapStats {
+            domain: domain.name().to_string(),
+            gaps: domain.gaps.snapshot(),
+        })
+        .collect();
+    Json(stats)
+}
+
+/// Registry submission attempts and responses, newest first
+async fn get_relays(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<RelaysQuery>,
+) -> impl IntoResponse {
+    let Some(domain) = state.domain(query.domain.as_deref()) else {
+        return unknown_domain(query.domain.as_deref());
+    };
+
+    let limit = query.limit.unwrap_or(100).min(1000);
+    let height = query.height;
+    match domain
+        .read(move |db| db.get_relay_attempts(limit, height))
+        .await
+    {
+        Ok(mut attempts) => {
+            for attempt in &mut attempts {
+                attempt.endpoint =
+                    redact_endpoint(&attempt.endpoint, state.config.endpoint_redaction);
+            }
+            (StatusCode::OK, Json(attempts)).into_response()
+        }
+        Err(e) => {
+            tracing::error!("Failed to load relay attempts: {}", e);
+            StatusCode::INTERNAL_SERVER_ERROR.into_response()
+        }
+    }
+}
+
+/// Whether asynchronous registries confirmed each submission, most recently updated first
+async fn get_relay_confirmations(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<RelaysQuery>,
+) -> impl IntoResponse {
+    let Some(domain) = state.domain(query.domain.as_deref()) else {
+        return unknown_domain(query.domain.as_deref());
+    };
+
+    let limit = query.limit.unwrap_or(100).min(1000);
+    let height = query.height;
+    match domain
+        .read(move |db| db.get_relay_confirmations(limit, height))
+        .await
+    {
+        Ok(mut confirmations) => {
+            for confirmation in &mut confirmations {
+                confirmation.endpoint =
+                    redact_endpoint(&confirmation.endpoint, state.config.endpoint_redaction);
+            }
+            (StatusCode::OK, Json(confirmations)).into_response()
+        }
+        Err(e) => {
+            tracing::error!("Failed to load relay confirmations: {}", e);
+            StatusCode::INTERNAL_SERVER_ERROR.into_response()
+        }
+    }
+}
+
+async fn get_timeseries(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<TimeseriesQuery>,
+) -> impl IntoResponse {
+    let Some(history) = &state.config.metrics_history else {
+        return (StatusCode::NOT_FOUND, "Metrics history is disabled").into_response();
+    };
+    let Some(domain) = state.domain(query.domain.as_deref()) else {
+        return unknown_domain(query.domain.as_deref());
+    };
+    if !METRICS.contains(&query.metric.as_str()) {
+        return (
+            StatusCode::BAD_REQUEST,
+            format!(
+                "Unknown metric {}, expected one of {}",
+                query.metric,
+                METRICS.join(", ")
+            ),
+        )
+            .into_response();
+    }
+    let window = match parse_window(query.window.as_deref().unwrap_or("24h")) {
+        Ok(window) => window,
+        Err(e) => return (StatusCode::BAD_REQUEST, e.to_string()).into_response(),
+    };
+
+    let since = chrono::Utc::now() - window;
+    let metric = query.metric.clone();
+    match domain
+        .read(move |db| db.get_metric_samples(&metric, since))
+        .await
+    {
+        Ok(samples) => Json(TimeseriesResponse {
+            domain: domain.name().to_string(),
+            metric: query.metric,
+            window_secs: window.num_seconds(),
+            interval_secs: history.interval_secs,
+            samples: samples
+                .into_iter()
+                .map(|sample| TimeseriesPoint {
+                    sampled_at: sample.sampled_at.to_rfc3339(),
+                    value: sample.value,
+                })
+                .collect(),
+        })
+        .into_response(),
+        Err(e) => {
+            tracing::error!("Failed to load metric samples: {}", e);
+            StatusCode::INTERNAL_SERVER_ERROR.into_response()
+        }
+    }
+}
+
+async fn get_alert_history(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<AlertHistoryQuery>,
+) -> impl IntoResponse {
+    let Some(domain) = state.domain(query.domain.as_deref()) else {
+        return unknown_domain(query.domain.as_deref());
+    };
+
+    let limit = query.limit.unwrap_or(100).min(1000);
+    match domain.read(move |db| db.get_alerts(limit)).await {
+        Ok(alerts) => (StatusCode::OK, Json(alerts)).into_response(),
+        Err(e) => {
+            tracing::error!("Failed to load alert history: {}", e);
+            StatusCode::INTERNAL_SERVER_ERROR.into_response()
+        }
+    }
+}
+
+/// Marks a firing alert acknowledged, which stops its repeat notifications. Acknowledging
+/// twice keeps the first acknowledgement; resolved alerts can't be acknowledged.
+async fn acknowledge_alert(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<u64>,
+    Query(query): Query<AckQuery>,
+    headers: HeaderMap,
+) -> impl IntoResponse {
+    if let Err(response) = state.authorize(&headers) {
+        return response;
+    }
+    let Some(domain) = state.domain(query.domain.as_deref()) else {
+        return unknown_domain(query.domain.as_deref());
+    };
+
+    let mut alert = match domain.read(move |db| db.get_alert(id)).await {
+        Ok(Some(alert)) => alert,
+        Ok(None) => return (StatusCode::NOT_FOUND, format!("No alert #{}", id)).into_response(),
+        Err(e) => {
+            tracing::error!("Failed to load alert #{}: {}", id, e);
+            return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+        }
+    };
+    match alert.state {
+        AlertState::Resolved => {
+            return (
+                StatusCode::CONFLICT,
+                format!("Alert #{} is already resolved", id),
+            )
+                .into_response();
+        }
+        AlertState::Acknowledged => return (StatusCode::OK, Json(alert)).into_response(),
+        AlertState::Firing => {}
+    }
+
+    alert.state = AlertState::Acknowledged;
+    alert.acknowledged_at = Some(chrono::Utc::now());
+    alert.acknowledged_by = query.by;
+    let update = alert.clone();
+    let result = domain
+        .writer
+        .write("acknowledge alert", move |db| {
+            db.update_alert(&update).map(|_| ())
+        })
+        .await;
+    match result {
+        Ok(()) => {
+            tracing::info!(
+                "🔕 [{}] Alert #{} acknowledged by {}",
+                domain.name(),
+                id,
+                alert.acknowledged_by.as_deref().unwrap_or("operator")
+            );
+            (StatusCode::OK, Json(alert)).into_response()
+        }
+        Err(e) => {
+            tracing::error!("Failed to acknowledge alert #{}: {}", id, e);
+            StatusCode::INTERNAL_SERVER_ERROR.into_response()
+        }
+    }
+}
+
+async fn get_provers(State(state): State<Arc<AppState>>) -> impl IntoResponse {
+    let provers: Vec<DomainProvers> = state
+        .domains
+        .all()
+        .iter()
+        .map(|domain| DomainProvers {
+            domain: domain.name().to_string(),
+            endpoints: domain.provers.snapshot(),
+        })
+        .collect();
+    Json(provers)
+}
+
+/// Asks the domain's provers for the proof at one height. Nothing is verified or
+/// recorded; this is for looking at heights the history doesn't have.
+async fn get_prover_proof(
+    State(state): State<Arc<AppState>>,
+    Path(height): Path<u64>,
+    Query(query): Query<DomainQuery>,
+) -> impl IntoResponse {
+    let Some(domain) = state.domain(query.domain.as_deref()) else {
+        return unknown_domain(query.domain.as_deref());
+    };
+    if !domain.provers.serves_heights() {
+        return (
+            StatusCode::NOT_IMPLEMENTED,
+            "The domain's provers can't serve proofs by height",
+        )
+            .into_response();
+    }
+
+    let proof = match get_proof_at(&domain.provers, height).await {
+        Ok(Some(proof)) => proof,
+        Ok(None) => {
+            return (
+                StatusCode::NOT_FOUND,
+                format!("Prover has no proof at height {}", height),
+            )
+                .into_response();
+        }
+        Err(e) => {
+            tracing::error!("Failed to fetch proof at height {}: {}", height, e);
+            return (StatusCode::BAD_GATEWAY, e.to_string()).into_response();
+        }
+    };
+
+    let public_values = proof.public_values.to_vec();
+    match decode_public_values(domain.config.mode, &public_values) {
+        Ok((decoded, root)) => {
+            let proof = proof.bytes();
+            let response = ProverProofResponse {
+                domain: domain.name().to_string(),
+                height: decoded,
+                root: hex::encode(root),
+                proof_id: ProofId::of(domain.config.proof_id_algorithm, &proof),
+                public_values: hex::encode(&public_values),
+                proof: hex::encode(&proof),
+            };
+            (StatusCode::OK, Json(response)).into_response()
+        }
+        Err(e) => (
+            StatusCode::BAD_GATEWAY,
+            format!("Prover's proof at height {} doesn't decode: {}", height, e),
+        )
+            .into_response(),
+    }
+}
+
+async fn get_latest_submission(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<DomainQuery>,
+) -> impl IntoResponse {
+    let Some(domain) = state.domain(query.domain.as_deref()) else {
+        return unknown_domain(query.domain.as_deref());
+    };
+
+    let latest = domain
+        .read(|db| {
+            let Some(record) = db.get_latest_proof()? else {
+                return Ok(None);
+            };
+            let confirmations = db.get_relay_confirmations(1000, Some(record.height))?;
+            Ok(Some((record, confirmations)))
+        })
+        .await;
+    match latest {
+        Ok(Some((record, mut confirmations))) => {
+            for confirmation in &mut confirmations {
+                confirmation.endpoint =
+                    redact_endpoint(&confirmation.endpoint, state.config.endpoint_redaction);
+            }
+            let response = LatestSubmissionResponse {
+                seq: record.seq,
+                domain: domain.name().to_string(),
+                height: record.height,
+                root: hex::encode(&record.root),
+                proof_id: record.proof_id,
+                timestamp: record.timestamp.to_rfc3339(),
+                confirmations,
+            };
+            (StatusCode::OK, Json(response)).into_response()
+        }
+        Ok(None) => (StatusCode::NOT_FOUND, "No proof recorded yet").into_response(),
+        Err(e) => {
+            tracing::error!("Failed to load the latest submission: {}", e);
+            StatusCode::INTERNAL_SERVER_ERROR.into_response()
+        }
+    }
+}
+
+async fn get_latest_public_values(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<DomainQuery>,
+) -> impl IntoResponse {
+    let Some(domain) = state.domain(query.domain.as_deref()) else {
+        return unknown_domain(query.domain.as_deref());
+    };
+
+    let record = match domain.read(|db| db.get_latest_proof()).await {
+        Ok(Some(record)) => record,
+        Ok(None) => return (StatusCode::NOT_FOUND, "No proof recorded yet").into_response(),
+        Err(e) => {
+            tracing::error!("Failed to load proof history: {}", e);
+            return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+        }
+    };
+
+    match decode_public_values(domain.config.mode, &record.public_values) {
+        Ok((height, root)) => {
+            let response = PublicValuesResponse {
+                seq: record.seq,
+                domain: domain.name().to_string(),
+                mode: domain.config.mode.as_str().to_string(),
+                height,
+                root: hex::encode(root),
+                public_values: hex::encode(&record.public_values),
+                timestamp: record.timestamp.to_rfc3339(),
+            };
+            (StatusCode::OK, Json(response)).into_response()
+        }
+        Err(e) => {
+            tracing::error!(
+                "Failed to decode public values of proof at height {}: {}",
+                record.height,
+                e
+            );
+            (
+                StatusCode::UNPROCESSABLE_ENTITY,
+                format!(
+                    "Stored public values don't decode as {} outputs: {}",
+                    domain.config.mode.as_str(),
+                    e
+                ),
+            )
+                .into_response()
+        }
+    }
+}
+
+async fn get_vk(State(state): State<Arc<AppState>>) -> impl IntoResponse {
+    let vks: Vec<DomainVk> = state
+        .domains
+        .all()
+        .iter()
+        .map(|domain| DomainVk {
+            domain: domain.name().to_string(),
+            configured_vk: domain.vk_monitor.configured().to_string(),
+            configured_fingerprint: domain.vk_monitor.configured_fingerprint().to_string(),
+            latest_proof: domain.vk_monitor.last_observation(),
+            mismatch: domain.vk_monitor.is_mismatched(),
+        })
+        .collect();
+    Json(vks)
+}
+
+async fn get_status(State(state): State<Arc<AppState>>) -> impl IntoResponse {
+    let mut domains = Vec::new();
+    let mut warnings = Vec::new();
+
+    for domain in state.domains.all() {
+        // The proof history is written in both modes, unlike the health check table
+        let latest_height = match domain.read(|db| db.get_latest_proof()).await {
+            Ok(latest) => latest.map(|record| record.height),
+            Err(e) => {
+                tracing::error!("Failed to load proof history: {}", e);
+                return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+            }
+        };
+
+        let vk_mismatch = domain.vk_monitor.is_mismatched();
+        if let Some(observation) = domain.vk_monitor.last_observation().filter(|_| vk_mismatch) {
+            warnings.push(format!(
+                "VK MISMATCH on {}: latest proof was produced for fingerprint {}, configured fingerprint is {}",
+                domain.name(),
+                observation.fingerprint,
+                domain.vk_monitor.configured_fingerprint()
+            ));
+        }
+
+        let failed_submissions = match domain.read(|db| db.get_failed_submissions()).await {
+            Ok(failed) => failed.len(),
+            Err(e) => {
+                tracing::error!("Failed to load dead-lettered payloads: {}", e);
+                return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+            }
+        };
+        if failed_submissions > 0 {
+            warnings.push(format!(
+                "{} payloads for {} failed every submission attempt, see /admin/failed",
+                failed_submissions,
+                domain.name()
+            ));
+        }
+
+        domains.push(DomainStatus {
+            domain: domain.name().to_string(),
+            mode: domain.config.mode.as_str().to_string(),
+            paused: domain.control.is_paused(),
+            vk_mismatch,
+            failed_submissions,
+            latest_height,
+            candidate_prover: domain
+                .candidate
+                .as_ref()
+                .map(|candidate| candidate.report()),
+            errors: domain.errors.snapshot(),
+            polls: domain.errors.polls(),
+        });
+    }
+
+    (StatusCode::OK, Json(StatusResponse { warnings, domains })).into_response()
+}
+
+async fn verify_current(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<DomainQuery>,
+    headers: HeaderMap,
+) -> impl IntoResponse {
+    if let Err(response) = state.authorize(&headers) {
+        return response;
+    }
+    tracing::info!("Received request to re-verify the stored previous proof");
+
+    let Some(domain) = state.domain(query.domain.as_deref()) else {
+        return unknown_domain(query.domain.as_deref());
+    };
+    let name = domain.name().to_string();
+    let vk = domain.config.vk.clone();
+
+    let previous_proof = match domain.read(|db| db.get_previous_proof()).await {
+        Ok(Some(previous_proof)) => previous_proof,
+        Ok(None) => {
+            let response = VerifyCurrentResponse {
+                domain: name.clone(),
+                status: "no_proof".to_string(),
+                height: None,
+                proof_system: None,
+                vk: vk.clone(),
+                error: None,
+            };
+            return (StatusCode::NOT_FOUND, Json(response)).into_response();
+        }
+        Err(e) => {
+            tracing::error!("Failed to load previous proof: {}", e);
+            return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+        }
+    };
+
+    // The previous proof only holds the proof bytes; the public values come from the history
+    let record = match domain.read(|db| db.get_latest_proof()).await {
+        Ok(Some(record)) if hex::encode(&record.proof) == previous_proof.proof_data => record,
+        Ok(_) => {
+            let response = VerifyCurrentResponse {
+                domain: name.clone(),
+                status: "invalid".to_string(),
+                height: None,
+                proof_system: None,
+                vk: vk.clone(),
+                error: Some("Stored proof has no matching entry in the proof history".to_string()),
+            };
+            return (StatusCode::CONFLICT, Json(response)).into_response();
+        }
+        Err(e) => {
+            tracing::error!("Failed to load proof history: {}", e);
+            return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+        }
+    };
+
+    let height = record.height;
+    let result = {
+        let vk = vk.clone();
+        tokio::task::spawn_blocking(move || {
+            verify_wrapper_proof(&record.proof, &record.public_values, &vk)
+        })
+        .await
+    };
+
+    let (status_code, response) = match result {
+        Ok(Ok(proof_system)) => {
+            tracing::info!("✅ Stored proof at height {} verified", height);
+            (
+                StatusCode::OK,
+                VerifyCurrentResponse {
+                    domain: name.clone(),
+                    status: "valid".to_string(),
+                    height: Some(height),
+                    proof_system: Some(proof_system.as_str().to_string()),
+                    vk: vk.clone(),
+                    error: None,
+                },
+            )
+        }
+        Ok(Err(e)) => {
+            tracing::error!(
+                "❌ Stored proof at height {} failed verification: {}",
+                height,
+                e
+            );
+            (
+                StatusCode::OK,
+                VerifyCurrentResponse {
+                    domain: name.clone(),
+                    status: "invalid".to_string(),
+                    height: Some(height),
+                    proof_system: None,
+                    vk: vk.clone(),
+                    error: Some(e.to_string()),
+                },
+            )
+        }
+        Err(e) => {
+            tracing::error!("Verification task failed: {}", e);
+            return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+        }
+    };
+
+    (status_code, Json(response)).into_response()
+}
+
+async fn get_pending_rollback(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<DomainQuery>,
+    headers: HeaderMap,
+) -> impl IntoResponse {
+    if let Err(response) = state.authorize(&headers) {
+        return response;
+    }
+    let Some(domain) = state.domain(query.domain.as_deref()) else {
+        return unknown_domain(query.domain.as_deref());
+    };
+
+    match domain.rollback.pending() {
+        Some(candidate) => (StatusCode::OK, Json(candidate)).into_response(),
+        None => StatusCode::NO_CONTENT.into_response(),
+    }
+}
+
+async fn approve_rollback(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<DomainQuery>,
+    headers: HeaderMap,
+) -> impl IntoResponse {
+    if let Err(response) = state.authorize(&headers) {
+        return response;
+    }
+    let Some(domain) = state.domain(query.domain.as_deref()) else {
+        return unknown_domain(query.domain.as_deref());
+    };
+
+    match domain.rollback.approve() {
+        Some(candidate) => {
+            tracing::warn!(
+                "[{}] Rollback to height {} (root {}) approved by operator",
+                domain.name(),
+                candidate.height,
+                candidate.root
+            );
+            (StatusCode::OK, Json(candidate)).into_response()
+        }
+        None => (StatusCode::NOT_FOUND, "No rollback awaiting approval").into_response(),
+    }
+}
+
+async fn resolve_conflict(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<DomainQuery>,
+    headers: HeaderMap,
+) -> impl IntoResponse {
+    if let Err(response) = state.authorize(&headers) {
+        return response;
+    }
+    let Some(domain) = state.domain(query.domain.as_deref()) else {
+        return unknown_domain(query.domain.as_deref());
+    };
+
+    match domain.conflicts.resolve() {
+        Some(conflict) => {
+            tracing::warn!(
+                "[{}] Root conflict at height {} (served {}, stored {}) resolved by operator",
+                domain.name(),
+                conflict.height,
+                conflict.served_root,
+                conflict.stored_root
+            );
+            (StatusCode::OK, Json(conflict)).into_response()
+        }
+        None => (StatusCode::NOT_FOUND, "No unresolved root conflict").into_response(),
+    }
+}
+
+async fn backfill_metrics_history(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<DomainQuery>,
+    headers: HeaderMap,
+) -> impl IntoResponse {
+    if let Err(response) = state.authorize(&headers) {
+        return response;
+    }
+    let Some(history) = &state.config.metrics_history else {
+        return (StatusCode::NOT_FOUND, "Metrics history is disabled").into_response();
+    };
+    let Some(domains) = state.domains_or_all(query.domain.as_deref()) else {
+        return unknown_domain(query.domain.as_deref());
+    };
+
+    let mut backfills = Vec::new();
+    for domain in &domains {
+        match backfill_history(domain, history.retention_hours).await {
+            Ok(backfill) => backfills.push(backfill),
+            Err(e) => {
+                tracing::error!(
+                    "[{}] Failed to backfill metrics history: {}",
+                    domain.name(),
+                    e
+                );
+                return (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response();
+            }
+        }
+    }
+    (StatusCode::OK, Json(backfills)).into_response()
+}
+
+#[derive(Deserialize)]
+struct BackupQuery {
+    domain: Option<String>,
+    /// Send the backup in the response instead of writing it to `backup_dir`
+    #[serde(default)]
+    download: bool,
+}
+
+/// Temporary directory of a downloaded backup, removed when dropped
+struct BackupDir(std::path::PathBuf);
+
+impl Drop for BackupDir {
+    fn drop(&mut self) {
+        let dir = std::mem::take(&mut self.0);
+        let remove = move || {
+            if let Err(e) = std::fs::remove_dir_all(&dir)
+                && e.kind() != std::io::ErrorKind::NotFound
+            {
+                tracing::warn!("⚠️  Failed to remove {}: {}", dir.display(), e);
+            }
+        };
+        match tokio::runtime::Handle::try_current() {
+            Ok(runtime) => drop(runtime.spawn_blocking(remove)),
+            Err(_) => remove(),
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct BackupResponse {
+    domain: String,
+    path: String,
+}
+
+/// Writes a consistent copy of each domain's SQLite database (or only `?domain=`'s) to
+/// `backup_dir`, or with `?download=true` sends one domain's as the response
+async fn backup_databases(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<BackupQuery>,
+    headers: HeaderMap,
+) -> impl IntoResponse {
+    if let Err(response) = state.authorize(&headers) {
+        return response;
+    }
+    let stamp = backup_stamp();
+
+    if query.download {
+        let Some(domain) = state.domain(query.domain.as_deref()) else {
+            return unknown_domain(query.domain.as_deref());
+        };
+        let dir = BackupDir(
+            std::env::temp_dir().join(format!("relayer-backup-{:016x}", rand::random::<u64>())),
+        );
+        let backup = match write_backup(&domain, &dir.0, &stamp).await {
+            Ok(path) => tokio::fs::File::open(&path)
+                .await
+                .map_err(anyhow::Error::from),
+            Err(e) => Err(e),
+        };
+        return match backup {
+            Ok(file) => (
+                StatusCode::OK,
+                [
+                    (CONTENT_TYPE, "application/vnd.sqlite3".to_string()),
+                    (
+                        CONTENT_DISPOSITION,
+                        format!("attachment; filename=\"{}_{}.db\"", domain.name(), stamp),
+                    ),
+                ],
+                // The stream owns the directory, so it goes once the body is sent or dropped
+                Body::from_stream(ReaderStream::new(file).map(move |chunk| {
+                    let _ = &dir;
+                    chunk
+                })),
+            )
+                .into_response(),
+            Err(e) => {
+                tracing::error!("[{}] Failed to back up the database: {}", domain.name(), e);
+                (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response()
+            }
+        };
+    }
+
+    let Some(dir) = &state.config.backup_dir else {
+        return (
+            StatusCode::NOT_FOUND,
+            "Backups to disk are disabled, set backup_dir or pass ?download=true",
+        )
+            .into_response();
+    };
+    let Some(domains) = state.domains_or_all(query.domain.as_deref()) else {
+        return unknown_domain(query.domain.as_deref());
+    };
+    let mut backups = Vec::new();
+    for domain in &domains {
+        match write_backup(domain, dir, &stamp).await {
+            Ok(path) => backups.push(BackupResponse {
+                domain: domain.name().to_string(),
+                path: path.display().to_string(),
+            }),
+            Err(e) => {
+                tracing::error!("[{}] Failed to back up the database: {}", domain.name(), e);
+                return (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response();
+            }
+        }
+    }
+    (StatusCode::OK, Json(backups)).into_response()
+}
+
+fn control_state(domains: &[Arc<Domain>]) -> Vec<RelayControlResponse> {
+    domains
+        .iter()
+        .map(|domain| RelayControlResponse {
+            domain: domain.name().to_string(),
+            paused: domain.control.is_paused(),
+        })
+        .collect()
+}
+
+async fn pause_relaying(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<DomainQuery>,
+    headers: HeaderMap,
+) -> impl IntoResponse {
+    if let Err(response) = state.authorize(&headers) {
+        return response;
+    }
+    let Some(domains) = state.domains_or_all(query.domain.as_deref()) else {
+        return unknown_domain(query.domain.as_deref());
+    };
+
+    for domain in &domains {
+        if domain.control.pause() {
+            tracing::warn!("⏸️  [{}] Relaying paused by operator", domain.name());
+        }
+    }
+    (StatusCode::OK, Json(control_state(&domains))).into_response()
+}
+
+async fn resume_relaying(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<DomainQuery>,
+    headers: HeaderMap,
+) -> impl IntoResponse {
+    if let Err(response) = state.authorize(&headers) {
+        return response;
+    }
+    let Some(domains) = state.domains_or_all(query.domain.as_deref()) else {
+        return unknown_domain(query.domain.as_deref());
+    };
+
+    for domain in &domains {
+        if domain.control.resume() {
+            tracing::info!("▶️  [{}] Relaying resumed by operator", domain.name());
+        }
+    }
+    (StatusCode::OK, Json(control_state(&domains))).into_response()
+}
+
+async fn relay_now(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<DomainQuery>,
+    headers: HeaderMap,
+) -> impl IntoResponse {
+    if let Err(response) = state.authorize(&headers) {
+        return response;
+    }
+    let Some(domains) = state.domains_or_all(query.domain.as_deref()) else {
+        return unknown_domain(query.domain.as_deref());
+    };
+
+    if let Some(paused) = domains.iter().find(|domain| domain.control.is_paused()) {
+        return (
+            StatusCode::CONFLICT,
+            format!("Relaying is paused for {}", paused.name()),
+        )
+            .into_response();
+    }
+
+    for domain in &domains {
+        tracing::info!("⚡ [{}] Manual relay requested by operator", domain.name());
+        domain.control.relay_now();
+    }
+    (StatusCode::ACCEPTED, Json(control_state(&domains))).into_response()
+}
+
+/// Starts relaying a new domain without restarting the others. The body is a domain
+/// entry as in the config file; domains added here are not written back to it.
+async fn add_domain(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+    Json(config): Json<DomainConfig>,
+) -> impl IntoResponse {
+    if let Err(response) = state.authorize(&headers) {
+        return response;
+    }
+    let Some(lifecycle) = state.lifecycle.clone() else {
+        return (
+            StatusCode::NOT_IMPLEMENTED,
+            "Domains can only be added at runtime in relayer mode",
+        )
+            .into_response();
+    };
+    if state.domains.get(&config.name).is_some() {
+        return (
+            StatusCode::CONFLICT,
+            format!("Domain {} already exists", config.name),
+        )
+            .into_response();
+    }
+
+    // Opening storage may block, e.g. on Postgres migrations
+    let name = config.name.clone();
+    match tokio::task::spawn_blocking(move || lifecycle.add(config)).await {
+        Ok(Ok(domain)) => {
+            tracing::info!("[{}] Domain added by operator", domain.name());
+            (
+                StatusCode::CREATED,
+                Json(DomainChangeResponse {
+                    domain: domain.name().to_string(),
+                    active: true,
+                }),
+            )
+                .into_response()
+        }
+        Ok(Err(e)) => {
+            tracing::warn!("Rejected new domain {}: {}", name, e);
+            (
+                StatusCode::BAD_REQUEST,
+                format!("Failed to add domain {}: {}", name, e),
+            )
+                .into_response()
+        }
+        Err(e) => {
+            tracing::error!("Adding domain {} panicked: {}", name, e);
+            StatusCode::INTERNAL_SERVER_ERROR.into_response()
+        }
+    }
+}
+
+/// Stops relaying a domain and stops serving it; its stored data is kept
+async fn remove_domain(
+    State(state): State<Arc<AppState>>,
+    Path(name): Path<String>,
+    headers: HeaderMap,
+) -> impl IntoResponse {
+    if let Err(response) = state.authorize(&headers) {
+        return response;
+    }
+    let Some(lifecycle) = &state.lifecycle else {
+        return (
+            StatusCode::NOT_IMPLEMENTED,
+            "Domains can only be removed at runtime in relayer mode",
+        )
+            .into_response();
+    };
+
+    if !lifecycle.remove(&name) {
+        return unknown_domain(Some(&name));
+    }
+    tracing::info!("[{}] Domain removed by operator", name);
+    (
+        StatusCode::OK,
+        Json(DomainChangeResponse {
+            domain: name,
+            active: false,
+        }),
+    )
+        .into_response()
+}
+
+fn redact_cutover(mut cutover: CutoverRecord, redaction: EndpointRedaction) -> CutoverRecord {
+    cutover.from_endpoint = redact_endpoint(&cutover.from_endpoint, redaction);
+    cutover.to_endpoint = redact_endpoint(&cutover.to_endpoint, redaction);
+    cutover.validate_endpoint = cutover
+        .validate_endpoint
+        .map(|endpoint| redact_endpoint(&endpoint, redaction));
+    cutover
+}
+
+/// Moves a domain's primary registry to the endpoint in the body, answering once the
+/// relay loop has drained the queue to the old registry, replayed the latest proof to
+/// the new one and switched
+async fn cut_over_registry(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<DomainQuery>,
+    headers: HeaderMap,
+    Json(target): Json<RegistryTarget>,
+) -> impl IntoResponse {
+    if let Err(response) = state.authorize(&headers) {
+        return response;
+    }
+    let Some(lifecycle) = &state.lifecycle else {
+        return (
+            StatusCode::NOT_IMPLEMENTED,
+            "Registries can only be cut over in relayer mode",
+        )
+            .into_response();
+    };
+    let Some(domain) = state.domain(query.domain.as_deref()) else {
+        return unknown_domain(query.domain.as_deref());
+    };
+
+    let outcome = match lifecycle.cut_over(domain.name(), target) {
+        Ok(outcome) => outcome,
+        Err(e) => {
+            tracing::warn!("[{}] Rejected registry cutover: {}", domain.name(), e);
+            return (StatusCode::BAD_REQUEST, e.to_string()).into_response();
+        }
+    };
+    tracing::info!("[{}] Registry cutover requested by operator", domain.name());
+
+    // Answer before the request times out; the cutover carries on regardless
+    let wait = Duration::from_secs(state.config.api_limits.request_timeout_secs.max(2) - 1);
+    match tokio::time::timeout(wait, outcome).await {
+        Ok(Ok(Ok(cutover))) => (
+            StatusCode::OK,
+            Json(redact_cutover(cutover, state.config.endpoint_redaction)),
+        )
+            .into_response(),
+        Ok(Ok(Err(e))) => (
+            StatusCode::BAD_GATEWAY,
+            format!("Cutover abandoned, the old registry is still in use: {}", e),
+        )
+            .into_response(),
+        Ok(Err(_)) => (
+            StatusCode::SERVICE_UNAVAILABLE,
+            "The relay loop stopped before running the cutover",
+        )
+            .into_response(),
+        Err(_) => (
+            StatusCode::ACCEPTED,
+            "The cutover is still running, see /admin/registry/cutovers",
+        )
+            .into_response(),
+    }
+}
+
+/// The domain's registry cutovers, newest first
+async fn get_cutovers(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<DomainQuery>,
+    headers: HeaderMap,
+) -> impl IntoResponse {
+    if let Err(response) = state.authorize(&headers) {
+        return response;
+    }
+    let Some(domain) = state.domain(query.domain.as_deref()) else {
+        return unknown_domain(query.domain.as_deref());
+    };
+
+    match domain.read(|db| db.get_cutovers(100)).await {
+        Ok(cutovers) => {
+            let cutovers: Vec<CutoverRecord> = cutovers
+                .into_iter()
+                .map(|cutover| redact_cutover(cutover, state.config.endpoint_redaction))
+                .collect();
+            (StatusCode::OK, Json(cutovers)).into_response()
+        }
+        Err(e) => {
+            tracing::error!("Failed to load registry cutovers: {}", e);
+            StatusCode::INTERNAL_SERVER_ERROR.into_response()
+        }
+    }
+}
+
+async fn get_failed_submissions(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<DomainQuery>,
+    headers: HeaderMap,
+) -> impl IntoResponse {
+    if let Err(response) = state.authorize(&headers) {
+        return response;
+    }
+    let Some(domain) = state.domain(query.domain.as_deref()) else {
+        return unknown_domain(query.domain.as_deref());
+    };
+
+    match domain.read(|db| db.get_failed_submissions()).await {
+        Ok(failed) => (StatusCode::OK, Json(failed)).into_response(),
+        Err(e) => {
+            tracing::error!("Failed to load dead-lettered payloads: {}", e);
+            StatusCode::INTERNAL_SERVER_ERROR.into_response()
+        }
+    }
+}
+
+async fn retry_failed_submission(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<u64>,
+    Query(query): Query<DomainQuery>,
+    headers: HeaderMap,
+) -> impl IntoResponse {
+    if let Err(response) = state.authorize(&headers) {
+        return response;
+    }
+    let Some(domain) = state.domain(query.domain.as_deref()) else {
+        return unknown_domain(query.domain.as_deref());
+    };
+
+    #[cfg(any(feature = "health-check", not(feature = "relayer")))]
+    {
+        let _ = (domain, id);
+        (
+            StatusCode::CONFLICT,
+            "Submissions are only made in relayer mode",
+        )
+            .into_response()
+    }
+
+    #[cfg(all(feature = "relayer", not(feature = "health-check")))]
+    {
+        tracing::info!(
+            "🔁 [{}] Resubmission of dead-lettered payload #{} requested by operator",
+            domain.name(),
+            id
+        );
+        match crate::relayer::retry_failed_submission(
+            &domain,
+            id,
+            &state.config.registry_retry,
+            state.config.inline_history_retention(),
+            &state.events,
+        )
+        .await
+        {
+            Ok(Some(record)) => (
+                StatusCode::OK,
+                Json(FailedRetryResponse {
+                    domain: domain.name().to_string(),
+                    id,
+                    height: record.height,
+                }),
+            )
+                .into_response(),
+            Ok(None) => (
+                StatusCode::NOT_FOUND,
+                format!("No failed submission #{}", id),
+            )
+                .into_response(),
+            Err(e) => {
+                tracing::error!(
+                    "[{}] Resubmission of payload #{} failed: {}",
+                    domain.name(),
+                    id,
+                    e
+                );
+                (
+                    StatusCode::BAD_GATEWAY,
+                    format!("Resubmission failed: {}", e),
+                )
+                    .into_response()
+            }
+        }
+    }
+}
+
+pub async fn start_api_server(
+    router: Router,
+    tls: Option<&TlsConfig>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    // Get server port from environment or use default from config
+    let port = std::env::var("API_PORT").unwrap_or_else(|_| API_PORT.to_string());
+    let addr = format!("0.0.0.0:{}", port);
+
+    // Parse the address properly
+    let socket_addr: std::net::SocketAddr = addr.parse()?;
+
+    let Some(tls) = tls else {
+        let listener = tokio::net::TcpListener::bind(socket_addr).await?;
+        tracing::info!("API server listening on http://{}", addr);
+        tracing::info!("🌐 Server is externally reachable on port {}", port);
+
+        axum::serve(listener, router).await?;
+        return Ok(());
+    };
+
+    // Other dependencies pull in rustls too, so the provider can't be picked automatically
+    let _ = rustls::crypto::aws_lc_rs::default_provider().install_default();
+    let rustls_config = RustlsConfig::from_pem_file(&tls.cert_path, &tls.key_path).await?;
+    tokio::spawn(reload_certificate(rustls_config.clone(), tls.clone()));
+    tracing::info!("API server listening on https://{}", addr);
+    tracing::info!("🌐 Server is externally reachable on port {}", port);
+
+    axum_server::bind_rustls(socket_addr, rustls_config)
+        .serve(router.into_make_service())
+        .await?;
+    Ok(())
+}
+
+/// Swaps in the certificate and key whenever either file changes, so a renewed certificate
+/// is picked up without a restart. A pair that fails to load (e.g. the certificate was
+/// written before its key) is retried on the next check while the old one stays in use.
+async fn reload_certificate(rustls_config: RustlsConfig, tls: TlsConfig) {
+    let modified = |tls: &TlsConfig| {
+        [&tls.cert_path, &tls.key_path].map(|path| {
+            std::fs::metadata(path)
+                .and_then(|metadata| metadata.modified())
+                .ok()
+        })
+    };
+    let mut loaded = modified(&tls);
+    let mut checks = tokio::time::interval(Duration::from_secs(tls.reload_interval_secs.max(1)));
+    checks.tick().await;
+
+    loop {
+        checks.tick().await;
+        let current = modified(&tls);
+        if current == loaded {
+            continue;
+        }
+        match rustls_config
+            .reload_from_pem_file(&tls.cert_path, &tls.key_path)
+            .await
+        {
+            Ok(()) => {
+                tracing::info!("🔐 Reloaded TLS certificate from {}", tls.cert_path);
+                loaded = current;
+            }
+            Err(e) => tracing::warn!(
+                "⚠️  Failed to reload TLS certificate, keeping the current one: {}",
+                e
+            ),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::StorageConfig;
+    use anyhow::Result;
+
+    fn state(admin_token: Option<&str>) -> Result<AppState> {
+        let config = Arc::new(Config::default());
+        Ok(AppState {
+            domains: Arc::new(DomainSet::default()),
+            lifecycle: None,
+            events: EventStreams::default(),
+            metrics: Arc::new(Metrics::new(&config.metrics)?),
+            jobs: Arc::new(JobScheduler::new(&config)?),
+            admin_token: admin_token.map(str::to_string),
+            identity: None,
+            config,
+        })
+    }
+
+    fn bearer(token: &str) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            AUTHORIZATION,
+            HeaderValue::from_str(&format!("Bearer {}", token)).unwrap(),
+        );
+        headers
+    }
+
+    fn status(result: Result<(), axum::response::Response>) -> Option<StatusCode> {
+        result.err().map(|response| response.status())
+    }
+
+    #[test]
+    fn test_authorize() -> Result<()> {
+        let state = state(Some("secret"))?;
+        assert_eq!(status(state.authorize(&bearer("secret"))), None);
+        assert_eq!(
+            status(state.authorize(&HeaderMap::new())),
+            Some(StatusCode::UNAUTHORIZED)
+        );
+        assert_eq!(
+            status(state.authorize(&bearer("wrong"))),
+            Some(StatusCode::UNAUTHORIZED)
+        );
+        // A prefix of the token isn't enough
+        assert_eq!(
+            status(state.authorize(&bearer("secre"))),
+            Some(StatusCode::UNAUTHORIZED)
+        );
+
+        // Without ADMIN_TOKEN the routes are off, whatever is sent
+        let disabled = state(None)?;
+        assert_eq!(
+            status(disabled.authorize(&bearer("secret"))),
+            Some(StatusCode::FORBIDDEN)
+        );
+        assert_eq!(
+            status(disabled.authorize(&HeaderMap::new())),
+            Some(StatusCode::FORBIDDEN)
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_probe_endpoint() -> Result<()> {
+        async fn gateway(headers: HeaderMap) -> StatusCode {
+            match headers.get(AUTHORIZATION) {
+                Some(value) if value == "Bearer token" => StatusCode::OK,
+                _ => StatusCode::UNAUTHORIZED,
+            }
+        }
+        let router = Router::new()
+            .route("/gateway", get(gateway))
+            .route("/down", get(|| async { StatusCode::SERVICE_UNAVAILABLE }));
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await?;
+        let base = format!("http://{}", listener.local_addr()?);
+        tokio::spawn(async move {
+            let _ = axum::serve(listener, router).await;
+        });
+
+        let gateway = format!("{}/gateway", base);
+        let mut config = DomainConfig {
+            name: "probe".to_string(),
+            ..DomainConfig::default()
+        };
+        let domain = Domain::open(config.clone(), &StorageConfig::Memory, "relayer")?;
+        let check = probe_endpoint(&domain, "prover", Some(gateway.clone())).await;
+        assert_eq!(
+            (check.status, check.detail.as_deref()),
+            ("degraded", Some("HTTP 401"))
+        );
+        let check = probe_endpoint(&domain, "registry", Some(format!("{}/down", base))).await;
+        assert_eq!(
+            (check.status, check.detail.as_deref()),
+            ("unhealthy", Some("HTTP 503"))
+        );
+
+        // The endpoint's configured headers are sent with the probe
+        config.http_headers.insert(
+            gateway.clone(),
+            [("Authorization".to_string(), "Bearer token".to_string())].into(),
+        );
+        let domain = Domain::open(config, &StorageConfig::Memory, "relayer")?;
+        let check = probe_endpoint(&domain, "prover", Some(gateway)).await;
+        assert_eq!((check.status, check.detail), ("healthy", None));
+        Ok(())
+    }
 }
@@ -0,0 +1,112 @@
+use anyhow::{Result, anyhow};
+use hmac::{Hmac, Mac};
+use sha2::{Digest, Sha256};
+
+/// Credentials AWS requests are signed with, also accepted by S3-compatible stores
+#[derive(Debug, Clone)]
+pub struct AwsCredentials {
+    pub access_key_id: String,
+    pub secret_access_key: String,
+    pub session_token: Option<String>,
+}
+
+impl AwsCredentials {
+    /// The given keys, or else `AWS_ACCESS_KEY_ID` / `AWS_SECRET_ACCESS_KEY`, with the
+    /// session token from `AWS_SESSION_TOKEN`. `what` names the user in errors.
+    pub fn resolve(
+        access_key_id: Option<&str>,
+        secret_access_key: Option<&str>,
+        what: &str,
+    ) -> Result<Self> {
+        let from_env = |name: &str| std::env::var(name).ok();
+        Ok(AwsCredentials {
+            access_key_id: access_key_id
+                .map(str::to_string)
+                .or_else(|| from_env("AWS_ACCESS_KEY_ID"))
+                .ok_or_else(|| anyhow!("{} has no access key", what))?,
+            secret_access_key: secret_access_key
+                .map(str::to_string)
+                .or_else(|| from_env("AWS_SECRET_ACCESS_KEY"))
+                .ok_or_else(|| anyhow!("{} has no secret key", what))?,
+            session_token: from_env("AWS_SESSION_TOKEN"),
+        })
+    }
+}
+
+/// Signs a request to `url` with AWS Signature Version 4. `headers` are the request's own
+/// headers to sign, with lower-case names. Returns every header to send, `authorization`
+/// included, except `host`, which the HTTP client sets itself.
+pub fn sign_v4(
+    method: &str,
+    url: &reqwest::Url,
+    service: &str,
+    region: &str,
+    mut headers: Vec<(&'static str, String)>,
+    body: &[u8],
+    credentials: &AwsCredentials,
+) -> Vec<(&'static str, String)> {
+    let host = match url.port() {
+        Some(port) => format!("{}:{}", url.host_str().unwrap_or_default(), port),
+        None => url.host_str().unwrap_or_default().to_string(),
+    };
+    let content_hash = hex::encode(Sha256::digest(body));
+    let now = chrono::Utc::now();
+    let amz_date = now.format("%Y%m%dT%H%M%SZ").to_string();
+    let date = now.format("%Y%m%d").to_string();
+
+    headers.push(("host", host));
+    headers.push(("x-amz-content-sha256", content_hash.clone()));
+    headers.push(("x-amz-date", amz_date.clone()));
+    if let Some(token) = &credentials.session_token {
+        headers.push(("x-amz-security-token", token.clone()));
+    }
+    headers.sort();
+    let signed_headers = headers
+        .iter()
+        .map(|(name, _)| *name)
+        .collect::<Vec<_>>()
+        .join(";");
+    let canonical_headers: String = headers
+        .iter()
+        .map(|(name, value)| format!("{}:{}\n", name, value.trim()))
+        .collect();
+
+    let canonical_request = format!(
+        "{}\n{}\n{}\n{}\n{}\n{}",
+        method,
+        url.path(),
+        url.query().unwrap_or_default(),
+        canonical_headers,
+        signed_headers,
+        content_hash
+    );
+    let scope = format!("{}/{}/{}/aws4_request", date, region, service);
+    let string_to_sign = format!(
+        "AWS4-HMAC-SHA256\n{}\n{}\n{}",
+        amz_date,
+        scope,
+        hex::encode(Sha256::digest(canonical_request.as_bytes()))
+    );
+
+    let mut key = format!("AWS4{}", credentials.secret_access_key).into_bytes();
+    for part in [date.as_str(), region, service, "aws4_request"] {
+        key = hmac_sha256(&key, part.as_bytes());
+    }
+    let signature = hex::encode(hmac_sha256(&key, string_to_sign.as_bytes()));
+
+    headers.retain(|(name, _)| *name != "host");
+    headers.push((
+        "authorization",
+        format!(
+            "AWS4-HMAC-SHA256 Credential={}/{}, SignedHeaders={}, Signature={}",
+            credentials.access_key_id, scope, signed_headers, signature
+        ),
+    ));
+    headers
+}
+
+fn hmac_sha256(key: &[u8], data: &[u8]) -> Vec<u8> {
+    let mut mac = Hmac::<Sha256>::new_from_slice(key).expect("HMAC accepts keys of any length");
+    mac.update(data);
+    mac.finalize().into_bytes().to_vec()
+}
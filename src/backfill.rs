@@ -0,0 +1,51 @@
+use anyhow::Result;
+use futures::stream::{self, Stream, StreamExt};
+use std::ops::Range;
+use std::sync::Arc;
+
+use crate::domain::Domain;
+use crate::errors::Subsystem;
+use crate::relayer::{get_proof_at, verified_payload};
+
+/// Fetches and verifies the proofs at `heights`, up to `concurrency` at a time, and
+/// yields their payloads in height order. Each height runs as its own task, so those in
+/// flight keep going while the caller submits an earlier one. Heights the prover has no
+/// proof for yield `None`.
+pub fn fetch_range(
+    domain: Arc<Domain>,
+    heights: Range<u64>,
+    concurrency: usize,
+) -> impl Stream<Item = (u64, Result<Option<serde_json::Value>>)> {
+    stream::iter(heights)
+        .map(move |height| {
+            let domain = domain.clone();
+            async move {
+                let fetched = tokio::spawn(async move { fetch_one(&domain, height).await })
+                    .await
+                    .map_err(anyhow::Error::from)
+                    .and_then(|fetched| fetched);
+                (height, fetched)
+            }
+        })
+        .buffered(concurrency.max(1))
+}
+
+async fn fetch_one(domain: &Domain, height: u64) -> Result<Option<serde_json::Value>> {
+    let proof = get_proof_at(&domain.provers, height)
+        .await
+        .inspect_err(|e| domain.errors.record(Subsystem::Prover, e))?;
+    let Some(proof) = proof else {
+        return Ok(None);
+    };
+
+    verified_payload(
+        proof,
+        &domain.vk_monitor,
+        &domain.errors,
+        domain.envelope.as_deref(),
+        domain.config.proof_id_algorithm,
+        &domain.latency,
+    )
+    .await
+    .map(Some)
+}
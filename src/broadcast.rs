@@ -0,0 +1,140 @@
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+use tokio::sync::Notify;
+
+/// Fans events out to many subscribers, each with its own bounded queue.
+///
+/// Publishing never blocks: a full queue drops its oldest event, and a subscriber
+/// that loses more than `max_dropped` events without catching up is disconnected.
+pub struct Broadcaster<T> {
+    clients: Mutex<Vec<Arc<ClientQueue<T>>>>,
+    capacity: usize,
+    max_dropped: u64,
+}
+
+struct ClientQueue<T> {
+    state: Mutex<QueueState<T>>,
+    notify: Notify,
+}
+
+struct QueueState<T> {
+    events: VecDeque<T>,
+    dropped: u64,
+    closed: bool,
+}
+
+pub struct Subscription<T> {
+    queue: Arc<ClientQueue<T>>,
+}
+
+impl<T: Clone> Broadcaster<T> {
+    pub fn new(capacity: usize, max_dropped: u64) -> Self {
+        Broadcaster {
+            clients: Mutex::new(Vec::new()),
+            capacity: capacity.max(1),
+            max_dropped,
+        }
+    }
+
+    pub fn subscribe(&self) -> Subscription<T> {
+        let queue = Arc::new(ClientQueue {
+            state: Mutex::new(QueueState {
+                events: VecDeque::with_capacity(self.capacity),
+                dropped: 0,
+                closed: false,
+            }),
+            notify: Notify::new(),
+        });
+        self.clients.lock().unwrap().push(queue.clone());
+        Subscription { queue }
+    }
+
+    pub fn publish(&self, event: T) {
+        let mut clients = self.clients.lock().unwrap();
+
+        clients.retain(|client| {
+            let mut state = client.state.lock().unwrap();
+            if state.closed {
+                return false;
+            }
+
+            if state.events.len() >= self.capacity {
+                state.events.pop_front();
+                state.dropped += 1;
+
+                if state.dropped > self.max_dropped {
+                    // Chronically slow client, cut it loose
+                    state.closed = true;
+                    state.events.clear();
+                    drop(state);
+                    client.notify.notify_one();
+                    return false;
+                }
+            }
+
+            state.events.push_back(event.clone());
+            drop(state);
+            client.notify.notify_one();
+            true
+        });
+    }
+
+    pub fn subscriber_count(&self) -> usize {
+        self.clients.lock().unwrap().len()
+    }
+}
+
+impl<T> Subscription<T> {
+    /// Waits for the next event. Returns `None` once the subscriber has been
+    /// disconnected for falling too far behind.
+    pub async fn recv(&mut self) -> Option<T> {
+        loop {
+            {
+                let mut state = self.queue.state.lock().unwrap();
+                if let Some(event) = state.events.pop_front() {
+                    if state.events.is_empty() {
+                        // Caught up, forgive earlier drops
+                        state.dropped = 0;
+                    }
+                    return Some(event);
+                }
+                if state.closed {
+                    return None;
+                }
+            }
+            self.queue.notify.notified().await;
+        }
+    }
+}
+
+impl<T> Drop for Subscription<T> {
+    fn drop(&mut self) {
+        // The broadcaster removes closed queues on its next publish
+        self.queue.state.lock().unwrap().closed = true;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_drop_oldest_and_disconnect() {
+        let broadcaster = Broadcaster::new(2, 3);
+        let mut subscription = broadcaster.subscribe();
+
+        // Queue holds the two most recent events
+        for i in 0..3 {
+            broadcaster.publish(i);
+        }
+        assert_eq!(subscription.recv().await, Some(1));
+        assert_eq!(subscription.recv().await, Some(2));
+
+        // Losing more than three events without catching up disconnects the client
+        for i in 0..6 {
+            broadcaster.publish(i);
+        }
+        assert_eq!(broadcaster.subscriber_count(), 0);
+        assert_eq!(subscription.recv().await, None);
+    }
+}
@@ -0,0 +1,182 @@
+use anyhow::Result;
+use serde::Serialize;
+use sp1_sdk::SP1ProofWithPublicValues;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::time::sleep;
+use tracing::{info, warn};
+
+use crate::config::MODE;
+use crate::domain::Domain;
+use crate::relayer::{decode_public_values, get_proof};
+use crate::source;
+use crate::verifier::verify_wrapper_proof;
+
+/// How a candidate prover has compared against the current provers so far
+#[derive(Debug, Clone, Serialize)]
+pub struct CandidateReport {
+    pub endpoint: String,
+    pub comparisons: u64,
+    pub matches: u64,
+    pub height_mismatches: u64,
+    pub root_mismatches: u64,
+    pub verification_mismatches: u64,
+    pub candidate_errors: u64,
+    /// Fraction of comparisons in which both provers agreed on height, root and validity
+    pub compatibility_score: Option<f64>,
+    pub last_comparison: Option<String>,
+    pub last_mismatch: Option<String>,
+}
+
+struct Observation {
+    height: u64,
+    root: [u8; 32],
+    valid: bool,
+}
+
+pub struct CandidateTracker {
+    report: Mutex<CandidateReport>,
+}
+
+impl CandidateTracker {
+    pub fn new(endpoint: String) -> Self {
+        CandidateTracker {
+            report: Mutex::new(CandidateReport {
+                endpoint,
+                comparisons: 0,
+                matches: 0,
+                height_mismatches: 0,
+                root_mismatches: 0,
+                verification_mismatches: 0,
+                candidate_errors: 0,
+                compatibility_score: None,
+                last_comparison: None,
+                last_mismatch: None,
+            }),
+        }
+    }
+
+    pub fn endpoint(&self) -> String {
+        self.report.lock().unwrap().endpoint.clone()
+    }
+
+    pub fn report(&self) -> CandidateReport {
+        self.report.lock().unwrap().clone()
+    }
+
+    fn compare(&self, current: &Observation, candidate: Result<Observation>) {
+        let mut report = self.report.lock().unwrap();
+        report.comparisons += 1;
+        report.last_comparison = Some(chrono::Utc::now().to_rfc3339());
+
+        let mismatch = match candidate {
+            Err(e) => {
+                report.candidate_errors += 1;
+                Some(format!("Candidate failed: {}", e))
+            }
+            Ok(candidate) if candidate.height != current.height => {
+                report.height_mismatches += 1;
+                Some(format!(
+                    "Height {} from candidate, {} from current",
+                    candidate.height, current.height
+                ))
+            }
+            Ok(candidate) if candidate.root != current.root => {
+                report.root_mismatches += 1;
+                Some(format!(
+                    "Root {} from candidate, {} from current at height {}",
+                    hex::encode(candidate.root),
+                    hex::encode(current.root),
+                    current.height
+                ))
+            }
+            Ok(candidate) if candidate.valid != current.valid => {
+                report.verification_mismatches += 1;
+                Some(format!(
+                    "Candidate proof valid: {}, current proof valid: {} at height {}",
+                    candidate.valid, current.valid, current.height
+                ))
+            }
+            Ok(_) => {
+                report.matches += 1;
+                None
+            }
+        };
+
+        report.compatibility_score = Some(report.matches as f64 / report.comparisons as f64);
+
+        match mismatch {
+            Some(mismatch) => {
+                warn!("⚠️  Candidate prover mismatch: {}", mismatch);
+                report.last_mismatch = Some(mismatch);
+            }
+            None => info!(
+                "✅ Candidate prover agrees at height {} (score {:.2})",
+                current.height,
+                report.compatibility_score.unwrap_or_default()
+            ),
+        }
+    }
+}
+
+async fn observe(proof: SP1ProofWithPublicValues, mode: MODE, vk: &str) -> Result<Observation> {
+    let (height, root) = decode_public_values(mode, proof.public_values.as_slice())?;
+    let proof_bytes = proof.bytes();
+    let public_values = proof.public_values.to_vec();
+    let vk = vk.to_string();
+    let valid = tokio::task::spawn_blocking(move || {
+        verify_wrapper_proof(&proof_bytes, &public_values, &vk).is_ok()
+    })
+    .await?;
+
+    Ok(Observation {
+        height,
+        root,
+        valid,
+    })
+}
+
+/// Periodically fetches from both the current provers and the candidate and scores how often they agree
+pub async fn run_candidate_comparison(
+    domain: Arc<Domain>,
+    tracker: Arc<CandidateTracker>,
+    interval: Duration,
+) {
+    let endpoint = tracker.endpoint();
+    let mode = domain.config.mode;
+    let vk = domain.config.vk.clone();
+    info!(
+        "🧪 [{}] Comparing candidate prover {} against current provers",
+        domain.name(),
+        endpoint
+    );
+
+    loop {
+        let current = match get_proof(&domain.provers).await {
+            Ok(proof) => observe(proof, mode, &vk).await,
+            Err(e) => Err(e),
+        };
+
+        match current {
+            Ok(current) => {
+                let source = source::build(
+                    domain.config.proof_source,
+                    &endpoint,
+                    domain.config.http_for(&endpoint),
+                );
+                let candidate = match source.fetch().await {
+                    Ok(proof) => observe(proof, mode, &vk).await,
+                    Err(e) => Err(e),
+                };
+                tracker.compare(&current, candidate);
+            }
+            Err(e) => warn!(
+                "⚠️  [{}] Skipping candidate comparison, current provers unavailable: {}",
+                domain.name(),
+                e
+            ),
+        }
+
+        sleep(interval).await;
+    }
+}
@@ -0,0 +1,732 @@
+use anyhow::{Result, anyhow};
+use clap::{Parser, Subcommand, ValueEnum};
+use serde::{Deserialize, Serialize};
+
+use crate::api::ProofEvent;
+use crate::config::{Config, MODE};
+use crate::domain::open_domain_storage;
+
+/// Runs the relayer service, or inspects its state with a subcommand
+#[derive(Parser)]
+#[command(name = "relayer")]
+pub struct Cli {
+    #[command(subcommand)]
+    pub command: Option<Command>,
+    /// Fetch, decode, verify and record proofs as usual, but submit nothing. State goes to
+    /// a separate SQLite file per domain, `relayer_dry_run_<domain>.db`.
+    #[cfg(all(feature = "relayer", not(feature = "health-check")))]
+    #[arg(long, global = true)]
+    pub dry_run: bool,
+    /// Run a single fetch, verify and relay cycle per domain, then exit with its outcome:
+    /// 0 when relayed or up to date, 2 when a proof was refused, 3 when submission failed
+    #[cfg(all(feature = "relayer", not(feature = "health-check")))]
+    #[arg(long)]
+    pub once: bool,
+}
+
+#[derive(Subcommand)]
+pub enum Command {
+    /// Show the most recently recorded proofs
+    History {
+        /// Proofs to show per domain
+        #[arg(long, default_value_t = 20)]
+        limit: usize,
+        #[arg(long, value_enum, default_value_t = OutputFormat::Table)]
+        format: OutputFormat,
+        /// Only show this domain
+        #[arg(long)]
+        domain: Option<String>,
+        /// Query a running relayer's API (e.g. http://host:8080) instead of the local database
+        #[arg(long)]
+        api: Option<String>,
+    },
+    /// Show each domain's latest height and queued submissions
+    Status {
+        #[arg(long, value_enum, default_value_t = OutputFormat::Table)]
+        format: OutputFormat,
+        /// Query a running relayer's API instead of the local database
+        #[arg(long)]
+        api: Option<String>,
+    },
+    /// Decode, verify and submit a single proof, printing the registry's response. Nothing
+    /// is recorded in the database.
+    #[cfg(all(feature = "relayer", not(feature = "health-check")))]
+    RelayOnce {
+        /// Read the proof from stdin (hex, JSON or bincode) instead of the domain's provers
+        #[arg(long)]
+        stdin: bool,
+        /// Replay the prover's proof at this height instead of its latest one
+        #[arg(long, conflicts_with = "stdin")]
+        height: Option<u64>,
+        /// Domain whose mode, VK and registry to use; defaults to the first one
+        #[arg(long)]
+        domain: Option<String>,
+    },
+    /// Resubmit a proof from the domain's database, or from a file, to its registry,
+    /// printing the registry's response. Nothing is recorded in the database.
+    #[cfg(all(feature = "relayer", not(feature = "health-check")))]
+    Submit {
+        /// Height of the recorded proof to submit
+        #[arg(long, required_unless_present = "file")]
+        height: Option<u64>,
+        /// File holding the proof (hex, JSON or bincode), or `-` for stdin
+        #[arg(long, conflicts_with = "height")]
+        file: Option<std::path::PathBuf>,
+        /// Domain whose database, mode, VK and registry to use; defaults to the first one
+        #[arg(long)]
+        domain: Option<String>,
+    },
+    /// Replace a domain's SQLite database with a backup, e.g. one written by
+    /// `POST /admin/backup`. Stop the relayer first.
+    Restore {
+        /// Backup file to restore
+        #[arg(long)]
+        from: std::path::PathBuf,
+        /// Domain whose database to replace; defaults to the first one
+        #[arg(long)]
+        domain: Option<String>,
+        /// Replace an existing database, keeping it as `<file>.pre-restore`
+        #[arg(long)]
+        force: bool,
+    },
+    /// Decode a proof's public values offline and print its height and root as JSON
+    Decode {
+        /// File holding the proof (hex, JSON or bincode), or `-` for stdin
+        #[arg(long)]
+        file: std::path::PathBuf,
+        /// Circuit whose outputs to decode; defaults to the first domain's mode
+        #[arg(long, value_enum)]
+        mode: Option<MODE>,
+        /// The file holds bare public values, hex or binary, rather than a whole proof
+        #[arg(long)]
+        public_values: bool,
+    },
+    /// Verify a proof locally against a program vkey hash and print the result as JSON.
+    /// Exits non-zero if the proof is invalid.
+    Verify {
+        /// File holding the proof (hex, JSON or bincode), or `-` for stdin
+        #[arg(long)]
+        file: std::path::PathBuf,
+        /// Program vkey hash to verify against; defaults to the first domain's
+        #[arg(long)]
+        vk: Option<String>,
+        /// Circuit whose outputs to decode; defaults to the first domain's mode
+        #[arg(long, value_enum)]
+        mode: Option<MODE>,
+    },
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+pub enum OutputFormat {
+    Table,
+    Json,
+}
+
+/// One domain's state as shown by `relayer status`. Fields only one source can
+/// provide are `None` for the other: the pause state exists only in a running process.
+#[derive(Serialize, Deserialize)]
+struct DomainSummary {
+    domain: String,
+    mode: String,
+    latest_height: Option<u64>,
+    #[serde(default)]
+    paused: Option<bool>,
+    #[serde(default)]
+    failed_submissions: usize,
+}
+
+#[derive(Deserialize)]
+struct RemoteStatus {
+    domains: Vec<DomainSummary>,
+}
+
+/// A proof's decoded outputs as printed by `relayer decode`
+#[derive(Serialize)]
+struct DecodedOutputs {
+    mode: &'static str,
+    height: u64,
+    root: String, // hex encoded
+    /// Only committed to by extended Tendermint circuits
+    #[serde(skip_serializing_if = "Option::is_none")]
+    chain_id: Option<String>,
+    public_values: String, // hex encoded
+}
+
+/// The outcome of `relayer verify`. A proof that verifies but doesn't decode for the
+/// mode has `decoded: null` and a `decode_error`.
+#[derive(Serialize)]
+struct VerifyReport {
+    valid: bool,
+    vk: String,
+    /// `groth16` or `plonk` when valid
+    #[serde(skip_serializing_if = "Option::is_none")]
+    proof_system: Option<&'static str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+    proof_bytes: usize,
+    decoded: Option<DecodedOutputs>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    decode_error: Option<String>,
+}
+
+pub async fn run(command: Command, config: &Config) -> Result<()> {
+    match command {
+        Command::History {
+            limit,
+            format,
+            domain,
+            api,
+        } => {
+            let proofs = match api {
+                Some(api) => remote_history(&api, domain.as_deref(), limit).await?,
+                None => local_history(config, domain.as_deref(), limit)?,
+            };
+            match format {
+                OutputFormat::Json => println!("{}", serde_json::to_string_pretty(&proofs)?),
+                OutputFormat::Table => print_table(
+                    &["DOMAIN", "SEQ", "HEIGHT", "ROOT", "PROOF ID", "TIMESTAMP"],
+                    proofs
+                        .iter()
+                        .map(|proof| {
+                            vec![
+                                proof.domain.clone(),
+                                proof.seq.to_string(),
+                                proof.height.to_string(),
+                                proof.root.clone(),
+                                proof
+                                    .proof_id
+                                    .as_ref()
+                                    .map_or_else(|| "-".to_string(), |id| id.short().to_string()),
+                                proof.timestamp.clone(),
+                            ]
+                        })
+                        .collect(),
+                ),
+            }
+        }
+        Command::Status { format, api } => {
+            let domains = match api {
+                Some(api) => remote_status(&api).await?.domains,
+                None => local_status(config)?,
+            };
+            match format {
+                OutputFormat::Json => println!("{}", serde_json::to_string_pretty(&domains)?),
+                OutputFormat::Table => print_table(
+                    &["DOMAIN", "MODE", "LATEST HEIGHT", "PAUSED", "FAILED"],
+                    domains
+                        .iter()
+                        .map(|domain| {
+                            vec![
+                                domain.domain.clone(),
+                                domain.mode.clone(),
+                                optional(domain.latest_height),
+                                optional(domain.paused),
+                                domain.failed_submissions.to_string(),
+                            ]
+                        })
+                        .collect(),
+                ),
+            }
+        }
+        #[cfg(all(feature = "relayer", not(feature = "health-check")))]
+        Command::RelayOnce {
+            stdin,
+            height,
+            domain,
+        } => relay_once(config, domain.as_deref(), stdin, height).await?,
+        #[cfg(all(feature = "relayer", not(feature = "health-check")))]
+        Command::Submit {
+            height,
+            file,
+            domain,
+        } => submit(config, domain.as_deref(), height, file.as_deref()).await?,
+        Command::Restore {
+            from,
+            domain,
+            force,
+        } => restore(config, &from, domain.as_deref(), force)?,
+        Command::Decode {
+            file,
+            mode,
+            public_values,
+        } => {
+            let mode = match mode {
+                Some(mode) => mode,
+                None => first_domain(config, "--mode")?.mode,
+            };
+            let input = read_input(&file)?;
+            let public_values = if public_values {
+                match std::str::from_utf8(&input).map(|text| text.trim().trim_start_matches("0x")) {
+                    Ok(hex)
+                        if !hex.is_empty() && hex.bytes().all(|byte| byte.is_ascii_hexdigit()) =>
+                    {
+                        hex::decode(hex)?
+                    }
+                    _ => input,
+                }
+            } else {
+                crate::source::parse_any(&input)?.public_values.to_vec()
+            };
+            let decoded = decode(mode, &public_values)?;
+            println!("{}", serde_json::to_string_pretty(&decoded)?);
+        }
+        Command::Verify { file, vk, mode } => {
+            let vk = match vk {
+                Some(vk) => vk,
+                None => first_domain(config, "--vk")?.vk.clone(),
+            };
+            let mode = match mode {
+                Some(mode) => mode,
+                None => first_domain(config, "--mode")?.mode,
+            };
+            let proof = crate::source::parse_any(&read_input(&file)?)?;
+            let public_values = proof.public_values.to_vec();
+            let proof_bytes = proof.bytes();
+            let verified = crate::verifier::verify_wrapper_proof(&proof_bytes, &public_values, &vk);
+            let decoded = decode(mode, &public_values);
+            let report = VerifyReport {
+                valid: verified.is_ok(),
+                proof_system: verified.as_ref().ok().map(|system| system.as_str()),
+                error: verified.as_ref().err().map(|e| e.to_string()),
+                vk,
+                proof_bytes: proof_bytes.len(),
+                decode_error: decoded.as_ref().err().map(|e| e.to_string()),
+                decoded: decoded.ok(),
+            };
+            println!("{}", serde_json::to_string_pretty(&report)?);
+            if !report.valid {
+                return Err(anyhow!("Proof failed verification"));
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Progress goes to stderr and the registry's response to stdout, for shell pipelines
+#[cfg(all(feature = "relayer", not(feature = "health-check")))]
+async fn relay_once(
+    config: &Config,
+    domain: Option<&str>,
+    stdin: bool,
+    at: Option<u64>,
+) -> Result<()> {
+    use crate::prover::ProverPool;
+    use crate::relayer::{get_proof, get_proof_at};
+    use crate::source::parse_any;
+    use std::io::Read;
+
+    let domain_config = selected_domains(config, domain)?
+        .into_iter()
+        .next()
+        .ok_or_else(|| anyhow!("No domains configured"))?;
+
+    let proof = if stdin {
+        let mut input = Vec::new();
+        std::io::stdin().read_to_end(&mut input)?;
+        parse_any(&input)?
+    } else {
+        let provers = ProverPool::new(
+            domain_config.prover_endpoints.clone(),
+            domain_config.prover_selection,
+        )
+        .with_http(&domain_config)
+        .with_source(domain_config.proof_source);
+        provers.detect_api_versions().await;
+        match at {
+            Some(at) => get_proof_at(&provers, at)
+                .await?
+                .ok_or_else(|| anyhow!("Prover has no proof at height {}", at))?,
+            None => get_proof(&provers).await?,
+        }
+    };
+
+    if let Some(envelope) = &domain_config.envelope {
+        crate::envelope::EnvelopeCheck::new(domain_config.mode, envelope)?.check(&proof)?;
+    }
+    submit_proof(
+        config,
+        domain_config,
+        &proof.bytes(),
+        &proof.public_values.to_vec(),
+        at,
+    )
+    .await
+}
+
+/// Submits a proof recorded at `height`, or read from `file`
+#[cfg(all(feature = "relayer", not(feature = "health-check")))]
+async fn submit(
+    config: &Config,
+    domain: Option<&str>,
+    height: Option<u64>,
+    file: Option<&std::path::Path>,
+) -> Result<()> {
+    let domain_config = selected_domains(config, domain)?
+        .into_iter()
+        .next()
+        .ok_or_else(|| anyhow!("No domains configured"))?;
+
+    if let Some(file) = file {
+        let proof = crate::source::parse_any(&read_input(file)?)?;
+        if let Some(envelope) = &domain_config.envelope {
+            crate::envelope::EnvelopeCheck::new(domain_config.mode, envelope)?.check(&proof)?;
+        }
+        return submit_proof(
+            config,
+            domain_config,
+            &proof.bytes(),
+            &proof.public_values.to_vec(),
+            None,
+        )
+        .await;
+    }
+
+    let height = height.ok_or_else(|| anyhow!("Pass --height or --file"))?;
+    let db = local_storage(config, domain_config)?;
+    let record = db.get_proof_by_height(height)?.ok_or_else(|| {
+        anyhow!(
+            "Domain {} has no recorded proof at height {}",
+            domain_config.name,
+            height
+        )
+    })?;
+    eprintln!(
+        "Read the proof recorded at height {} on {}",
+        height,
+        record.timestamp.to_rfc3339()
+    );
+    submit_proof(
+        config,
+        domain_config,
+        &record.proof,
+        &record.public_values,
+        Some(height),
+    )
+    .await
+}
+
+/// Decodes and verifies on-chain proof bytes for the domain, then submits them to its
+/// primary sink. Fails if `expected_height` is given and the proof is at another one.
+#[cfg(all(feature = "relayer", not(feature = "health-check")))]
+async fn submit_proof(
+    config: &Config,
+    domain_config: &crate::config::DomainConfig,
+    proof_bytes: &[u8],
+    public_values: &[u8],
+    expected_height: Option<u64>,
+) -> Result<()> {
+    use crate::encryption::PayloadEncryptor;
+    use crate::proof_id::ProofId;
+    use crate::relayer::{
+        build_payload, check_chain_id, decode_public_values, endpoint_client, post_payload,
+        with_metadata,
+    };
+    use crate::sink::{ProofSink, primary_sink};
+    use crate::verifier::verify_wrapper_proof;
+
+    let (height, root) = decode_public_values(domain_config.mode, public_values)?;
+    check_chain_id(
+        domain_config.mode,
+        domain_config.chain_id.as_deref(),
+        public_values,
+    )?;
+    eprintln!("Decoded height {} with root {}", height, hex::encode(root));
+    if let Some(expected) = expected_height
+        && expected != height
+    {
+        return Err(anyhow!(
+            "Proof is at height {} rather than the requested {}",
+            height,
+            expected
+        ));
+    }
+
+    let proof_system = verify_wrapper_proof(proof_bytes, public_values, &domain_config.vk)?;
+    eprintln!(
+        "Verified against {} ({})",
+        domain_config.vk,
+        proof_system.as_str()
+    );
+    let proof_id = ProofId::of(domain_config.proof_id_algorithm, proof_bytes);
+    eprintln!("Proof id {}", proof_id);
+    let payload = with_metadata(
+        build_payload(proof_bytes, public_values, &domain_config.vk, &proof_id),
+        domain_config,
+        height,
+        &root,
+        chrono::Utc::now(),
+    );
+
+    if domain_config.dry_run {
+        eprintln!(
+            "Dry run, not submitting to {}",
+            primary_sink(domain_config, &config.registry_retry, None, None)?.describe()
+        );
+        println!("{}", payload);
+        return Ok(());
+    }
+
+    // Another primary target has no response body to show
+    if domain_config.primary_sink.is_some() {
+        let once = crate::config::RetryPolicy {
+            max_attempts: 1,
+            ..config.registry_retry.clone()
+        };
+        let sink = primary_sink(domain_config, &once, None, None)?;
+        sink.submit(&payload, height).await?;
+        eprintln!("Accepted by {}", sink.describe());
+        sink.settle().await;
+        return Ok(());
+    }
+
+    let endpoint = &domain_config.registry_endpoint;
+    let payload = match domain_config.payload_encryption.get(endpoint) {
+        Some(encryption) => PayloadEncryptor::new(encryption)?.encrypt(&payload)?,
+        None => payload,
+    };
+    let client = endpoint_client(&domain_config.http_for(endpoint))?;
+    let response = post_payload(
+        &client,
+        endpoint,
+        &payload,
+        &domain_config.wire_for(endpoint),
+    )
+    .await?;
+    let class = crate::registry::ResponseClass::of(&response);
+    eprintln!(
+        "Registry responded with status {} ({})",
+        response.status,
+        class.as_str()
+    );
+    println!("{}", response.body);
+    match class {
+        crate::registry::ResponseClass::Accepted | crate::registry::ResponseClass::Duplicate => {
+            Ok(())
+        }
+        _ => Err(anyhow!(
+            "Registry rejected the proof with status {}",
+            response.status
+        )),
+    }
+}
+
+fn restore(
+    config: &Config,
+    from: &std::path::Path,
+    domain: Option<&str>,
+    force: bool,
+) -> Result<()> {
+    use crate::db::Database;
+
+    let crate::config::StorageConfig::Sqlite(options) = &config.storage else {
+        return Err(anyhow!("Only sqlite storage can be restored from a backup"));
+    };
+    let domain_config = selected_domains(config, domain)?
+        .into_iter()
+        .next()
+        .ok_or_else(|| anyhow!("No domains configured"))?;
+    let target = crate::domain::sqlite_path(domain_config, &config.db_prefix());
+    let target_path = std::path::Path::new(&target);
+
+    if target_path.exists() {
+        let kept = format!("{}.pre-restore", target);
+        if !force {
+            return Err(anyhow!(
+                "{} already exists; pass --force to replace it, keeping it as {}",
+                target,
+                kept
+            ));
+        }
+        // Its WAL files go with it, so the kept copy stays complete
+        for suffix in ["", "-wal", "-shm"] {
+            let file = format!("{}{}", target, suffix);
+            if std::path::Path::new(&file).exists() {
+                std::fs::rename(&file, format!("{}{}", kept, suffix))?;
+            }
+        }
+        eprintln!("Kept the previous database as {}", kept);
+    }
+
+    Database::restore(from, target_path)?;
+    // Opening it applies any migrations the backup predates
+    let db = Database::open_with(&target, options)?;
+    let latest = crate::db::Storage::get_latest_proof(&db)?;
+    eprintln!(
+        "Restored {} to {} for {}, latest proof at height {}",
+        from.display(),
+        target,
+        domain_config.name,
+        optional(latest.map(|record| record.height))
+    );
+    Ok(())
+}
+
+/// The domain `--<flag>` defaults from
+fn first_domain<'a>(config: &'a Config, flag: &str) -> Result<&'a crate::config::DomainConfig> {
+    config
+        .domains
+        .first()
+        .ok_or_else(|| anyhow!("No domains configured; pass {}", flag))
+}
+
+/// The contents of `file`, or of stdin for `-`
+fn read_input(file: &std::path::Path) -> Result<Vec<u8>> {
+    if file.as_os_str() == "-" {
+        let mut input = Vec::new();
+        std::io::Read::read_to_end(&mut std::io::stdin(), &mut input)?;
+        return Ok(input);
+    }
+    std::fs::read(file).map_err(|e| anyhow!("Failed to read {}: {}", file.display(), e))
+}
+
+fn decode(mode: MODE, public_values: &[u8]) -> Result<DecodedOutputs> {
+    use crate::relayer::{decode_public_values, decode_tendermint_chain_id};
+
+    let (height, root) = decode_public_values(mode, public_values)?;
+    let chain_id = match mode {
+        MODE::TENDERMINT => decode_tendermint_chain_id(public_values)?,
+        MODE::HELIOS => None,
+    };
+    Ok(DecodedOutputs {
+        mode: mode.as_str(),
+        height,
+        root: hex::encode(root),
+        chain_id,
+        public_values: hex::encode(public_values),
+    })
+}
+
+/// The domain's own storage; in-memory storage only exists inside the running relayer
+fn local_storage(
+    config: &Config,
+    domain_config: &crate::config::DomainConfig,
+) -> Result<std::sync::Arc<dyn crate::db::Storage>> {
+    if matches!(config.storage, crate::config::StorageConfig::Memory) && !domain_config.dry_run {
+        return Err(anyhow!(
+            "Storage is in memory, so only the running relayer has its state; query it with --api"
+        ));
+    }
+    open_domain_storage(domain_config, &config.storage, &config.db_prefix())
+}
+
+fn local_history(config: &Config, domain: Option<&str>, limit: usize) -> Result<Vec<ProofEvent>> {
+    let mut proofs = Vec::new();
+    for domain_config in selected_domains(config, domain)? {
+        let db = local_storage(config, domain_config)?;
+        proofs.extend(
+            db.get_latest_proofs(limit)?
+                .iter()
+                .map(|record| ProofEvent::from_record(&domain_config.name, record)),
+        );
+    }
+    Ok(proofs)
+}
+
+async fn remote_history(api: &str, domain: Option<&str>, limit: usize) -> Result<Vec<ProofEvent>> {
+    let names = match domain {
+        Some(domain) => vec![domain.to_string()],
+        None => remote_status(api)
+            .await?
+            .domains
+            .into_iter()
+            .map(|domain| domain.domain)
+            .collect(),
+    };
+
+    let client = reqwest::Client::new();
+    let mut proofs = Vec::new();
+    for name in names {
+        let url = format!("{}/proofs", api.trim_end_matches('/'));
+        let response = client
+            .get(&url)
+            .query(&[("domain", name.as_str()), ("latest", &limit.to_string())])
+            .send()
+            .await?;
+        if !response.status().is_success() {
+            return Err(anyhow!(
+                "{} responded with status {}",
+                url,
+                response.status()
+            ));
+        }
+        proofs.extend(response.json::<Vec<ProofEvent>>().await?);
+    }
+    Ok(proofs)
+}
+
+fn local_status(config: &Config) -> Result<Vec<DomainSummary>> {
+    let mut domains = Vec::new();
+    for domain_config in &config.domains {
+        let db = local_storage(config, domain_config)?;
+        let latest_height = match db.get_latest_health_check()? {
+            Some(health) => Some(health.current_height),
+            None => db.get_latest_proof()?.map(|record| record.height),
+        };
+        domains.push(DomainSummary {
+            domain: domain_config.name.clone(),
+            mode: domain_config.mode.as_str().to_string(),
+            latest_height,
+            paused: None,
+            failed_submissions: db.get_failed_submissions()?.len(),
+        });
+    }
+    Ok(domains)
+}
+
+async fn remote_status(api: &str) -> Result<RemoteStatus> {
+    let url = format!("{}/status", api.trim_end_matches('/'));
+    let response = reqwest::get(&url).await?;
+    if !response.status().is_success() {
+        return Err(anyhow!(
+            "{} responded with status {}",
+            url,
+            response.status()
+        ));
+    }
+    Ok(response.json().await?)
+}
+
+fn selected_domains<'a>(
+    config: &'a Config,
+    domain: Option<&str>,
+) -> Result<Vec<&'a crate::config::DomainConfig>> {
+    match domain {
+        Some(name) => config
+            .domains
+            .iter()
+            .find(|domain| domain.name == name)
+            .map(|domain| vec![domain])
+            .ok_or_else(|| anyhow!("Unknown domain {}", name)),
+        None => Ok(config.domains.iter().collect()),
+    }
+}
+
+fn optional<T: ToString>(value: Option<T>) -> String {
+    value.map_or_else(|| "-".to_string(), |value| value.to_string())
+}
+
+/// Prints rows under a header, padding every column to its widest cell
+fn print_table(headers: &[&str], rows: Vec<Vec<String>>) {
+    let mut widths: Vec<usize> = headers.iter().map(|header| header.len()).collect();
+    for row in &rows {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.chars().count());
+        }
+    }
+
+    let line = |cells: Vec<&str>| {
+        cells
+            .iter()
+            .zip(&widths)
+            .map(|(cell, width)| format!("{:<width$}", cell, width = width))
+            .collect::<Vec<_>>()
+            .join("  ")
+            .trim_end()
+            .to_string()
+    };
+    println!("{}", line(headers.to_vec()));
+    for row in &rows {
+        println!("{}", line(row.iter().map(String::as_str).collect()));
+    }
+}
@@ -0,0 +1,127 @@
+use anyhow::{Result, anyhow};
+use borsh::{BorshDeserialize, BorshSerialize};
+
+use crate::config::{PayloadCodec, PayloadCompression};
+
+/// A registry payload in binary form, for the `borsh` and `protobuf` codecs. Binary
+/// fields are raw bytes instead of hex, which halves the size of large proofs.
+#[derive(Clone, PartialEq, prost::Message, BorshSerialize, BorshDeserialize)]
+pub struct WirePayload {
+    #[prost(bytes = "vec", tag = "1")]
+    pub proof: Vec<u8>,
+    #[prost(bytes = "vec", tag = "2")]
+    pub public_values: Vec<u8>,
+    #[prost(bytes = "vec", tag = "3")]
+    pub vk: Vec<u8>,
+    #[prost(string, tag = "4")]
+    pub proof_id: String,
+    #[prost(string, optional, tag = "5")]
+    pub relayer_id: Option<String>,
+    #[prost(uint64, optional, tag = "6")]
+    pub signed_at: Option<u64>,
+    #[prost(bytes = "vec", optional, tag = "7")]
+    pub signature: Option<Vec<u8>>,
+    /// The JSON `metadata` object, when the domain adds one
+    #[prost(string, optional, tag = "8")]
+    pub metadata: Option<String>,
+}
+
+impl WirePayload {
+    /// Reads a JSON payload as built by `build_payload`, signed or not, with or without
+    /// metadata
+    pub fn from_json(payload: &serde_json::Value) -> Result<Self> {
+        let bytes = |name: &str| -> Result<Vec<u8>> {
+            let encoded = payload[name]
+                .as_str()
+                .ok_or_else(|| anyhow!("Payload has no {} field", name))?;
+            Ok(hex::decode(encoded.trim_start_matches("0x"))?)
+        };
+        Ok(WirePayload {
+            proof: bytes("proof")?,
+            public_values: bytes("public_values")?,
+            vk: bytes("vk")?,
+            proof_id: payload["proof_id"]
+                .as_str()
+                .ok_or_else(|| anyhow!("Payload has no proof_id field"))?
+                .to_string(),
+            relayer_id: payload["relayer_id"].as_str().map(str::to_string),
+            signed_at: payload["signed_at"].as_u64(),
+            signature: match payload.get("signature") {
+                Some(_) => Some(bytes("signature")?),
+                None => None,
+            },
+            metadata: payload.get("metadata").map(|metadata| metadata.to_string()),
+        })
+    }
+}
+
+/// The payload's body in `codec`, with the content type to send it as
+pub fn encode(codec: PayloadCodec, payload: &serde_json::Value) -> Result<(Vec<u8>, &'static str)> {
+    let body = match codec {
+        PayloadCodec::Json => serde_json::to_vec(payload)?,
+        PayloadCodec::Borsh => borsh::to_vec(&WirePayload::from_json(payload)?)?,
+        PayloadCodec::Protobuf => prost::Message::encode_to_vec(&WirePayload::from_json(payload)?),
+    };
+    Ok((body, codec.content_type()))
+}
+
+/// The zstd-compressed body, or `None` when it is too small to be worth compressing
+pub fn compress(body: &[u8], compression: &PayloadCompression) -> Result<Option<Vec<u8>>> {
+    if body.len() < compression.min_bytes {
+        return Ok(None);
+    }
+    Ok(Some(zstd::bulk::compress(body, compression.level)?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_binary_codecs_round_trip() -> Result<()> {
+        let payload = json!({
+            "proof": hex::encode([1u8; 16]),
+            "public_values": hex::encode([2u8; 8]),
+            "vk": format!("0x{}", hex::encode([3u8; 32])),
+            "proof_id": "abc",
+            "signature": hex::encode([4u8; 64]),
+            "relayer_id": "relayer-1",
+            "signed_at": 1700000000u64,
+        });
+        let expected = WirePayload::from_json(&payload)?;
+        assert_eq!(expected.vk, vec![3u8; 32]);
+        assert_eq!(expected.metadata, None);
+
+        let (body, content_type) = encode(PayloadCodec::Borsh, &payload)?;
+        assert_eq!(content_type, "application/x-borsh");
+        assert_eq!(WirePayload::try_from_slice(&body)?, expected);
+
+        let (body, content_type) = encode(PayloadCodec::Protobuf, &payload)?;
+        assert_eq!(content_type, "application/x-protobuf");
+        assert_eq!(
+            <WirePayload as prost::Message>::decode(body.as_slice())?,
+            expected
+        );
+
+        // Hex doubles every byte
+        let (json, _) = encode(PayloadCodec::Json, &payload)?;
+        assert!(body.len() < json.len());
+        Ok(())
+    }
+
+    #[test]
+    fn test_compression_threshold() -> Result<()> {
+        let compression = PayloadCompression {
+            level: 3,
+            min_bytes: 1024,
+        };
+        assert_eq!(compress(&[7u8; 1023], &compression)?, None);
+
+        let body = hex::encode([7u8; 4096]).into_bytes();
+        let compressed = compress(&body, &compression)?.unwrap();
+        assert!(compressed.len() < body.len());
+        assert_eq!(zstd::bulk::decompress(&compressed, body.len())?, body);
+        Ok(())
+    }
+}
@@ -1,3 +1,9 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::time::Duration;
+
+use crate::errors::RelayerError;
+
 pub const LIGHT_CLIENT_PROVER_ENDPOINT: &str = "http://165.1.70.239:7778/";
 #[allow(unused)]
 pub const LIGHT_CLIENT_VK: &str =
@@ -9,10 +15,1595 @@ pub const REGISTRY_ENDPOINT: &str =
 
 pub const API_PORT: &str = "17400";
 
+/// Name of the domain relayed when the config file doesn't list any `domains`
+pub const DOMAIN_NAME: &str = "ethereum-alpha";
+
+/// A health check older than this is reported as unhealthy, unless the domain's
+/// `staleness` says otherwise
+pub const HEALTH_STALENESS_THRESHOLD_MINUTES: i64 = 30;
+
 pub const LIGHT_CLIENT_MODE: MODE = MODE::HELIOS;
 
+/// Prefix of the per-domain SQLite database files for the mode this build runs in
+#[cfg(all(feature = "relayer", not(feature = "health-check")))]
+pub const DB_PREFIX: &str = "relayer";
+#[cfg(any(feature = "health-check", not(feature = "relayer")))]
+pub const DB_PREFIX: &str = "health_check";
+
 #[allow(unused)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, clap::ValueEnum)]
+#[serde(rename_all = "lowercase")]
+#[value(rename_all = "lowercase")]
 pub enum MODE {
     HELIOS,
     TENDERMINT,
 }
+
+impl MODE {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            MODE::HELIOS => "helios",
+            MODE::TENDERMINT => "tendermint",
+        }
+    }
+}
+
+/// Maximum number of events buffered per WebSocket client before the oldest is dropped.
+pub const WS_CLIENT_QUEUE_CAPACITY: usize = 64;
+
+/// A WebSocket client that loses this many events without catching up is disconnected.
+pub const WS_MAX_DROPPED_EVENTS: u64 = 256;
+
+/// How often WebSocket clients are pinged.
+pub const WS_PING_INTERVAL_SECS: u64 = 30;
+
+/// A WebSocket client that sends nothing, not even a pong, for this long is disconnected.
+pub const WS_PONG_TIMEOUT_SECS: u64 = 90;
+
+/// Number of proofs kept in the history table unless overridden in the config file.
+pub const DEFAULT_PROOF_HISTORY_RETENTION: u64 = 1000;
+
+/// Process exit code when a fail-fast limit is reached, distinct from crashes (1)
+pub const FAIL_FAST_EXIT_CODE: i32 = 3;
+
+/// What to do when a proof arrives below the highest recorded height with a root
+/// that differs from the one stored for that height. Only Tendermint chains can roll
+/// back; in Helios mode such proofs are always rejected.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RollbackPolicy {
+    #[default]
+    Reject,
+    AcceptWithAlert,
+    AcceptAfterAdminApproval,
+}
+
+/// How a domain's health is judged. `wall_clock` compares the latest proof's timestamp
+/// with the staleness threshold; `height_progression` ignores timestamps and marks the
+/// domain degraded once the height hasn't advanced for `max_stalled_epochs` expected
+/// epochs of `polls_per_epoch` polls each, for hosts whose clocks can't be trusted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(tag = "mode", rename_all = "snake_case")]
+pub enum FreshnessConfig {
+    #[default]
+    WallClock,
+    HeightProgression {
+        #[serde(default = "default_max_stalled_epochs")]
+        max_stalled_epochs: u64,
+        #[serde(default = "default_polls_per_epoch")]
+        polls_per_epoch: u64,
+    },
+}
+
+/// Age of the latest proof at which a wall-clock domain turns `degraded` and then
+/// `unhealthy`. Either threshold can be set directly, or derived from how often the
+/// chain is expected to produce a new proof, e.g. 384 seconds for a Helios epoch.
+#[derive(Debug, Clone, Copy, PartialEq, Default, Deserialize)]
+#[serde(default)]
+pub struct StalenessConfig {
+    /// No `degraded` tier unless this or `expected_interval_secs` is set
+    pub degraded_after_secs: Option<u64>,
+    /// `HEALTH_STALENESS_THRESHOLD_MINUTES` unless this or `expected_interval_secs` is set
+    pub unhealthy_after_secs: Option<u64>,
+    pub expected_interval_secs: Option<u64>,
+    /// Expected intervals without a new proof before `degraded`; defaults to 2
+    pub degraded_after_intervals: Option<f64>,
+    /// Expected intervals without a new proof before `unhealthy`; defaults to 5
+    pub unhealthy_after_intervals: Option<f64>,
+}
+
+impl StalenessConfig {
+    /// The `degraded` and `unhealthy` thresholds, explicit ones taking precedence over
+    /// those derived from the expected interval
+    pub fn thresholds(&self) -> (Option<chrono::Duration>, chrono::Duration) {
+        let derived = |intervals: f64| {
+            self.expected_interval_secs.map(|secs| {
+                chrono::Duration::milliseconds((secs as f64 * intervals * 1000.0) as i64)
+            })
+        };
+        let explicit = |secs: u64| chrono::Duration::seconds(secs as i64);
+
+        let degraded = self
+            .degraded_after_secs
+            .map(explicit)
+            .or_else(|| derived(self.degraded_after_intervals.unwrap_or(2.0)));
+        let unhealthy = self
+            .unhealthy_after_secs
+            .map(explicit)
+            .or_else(|| derived(self.unhealthy_after_intervals.unwrap_or(5.0)))
+            .unwrap_or_else(|| chrono::Duration::minutes(HEALTH_STALENESS_THRESHOLD_MINUTES));
+        (degraded, unhealthy)
+    }
+}
+
+fn default_max_stalled_epochs() -> u64 {
+    3
+}
+
+fn default_polls_per_epoch() -> u64 {
+    1
+}
+
+/// Order in which prover endpoints are tried. `priority` always starts with the
+/// first endpoint; `round_robin` rotates the starting endpoint on every fetch.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ProverSelection {
+    #[default]
+    Priority,
+    RoundRobin,
+}
+
+/// How a domain's prover endpoints serve proofs. `auto` asks each prover for its API
+/// version at startup and picks the matching codec. `hex_http` is a GET returning the
+/// hex-encoded proof JSON (the legacy lightwave prover format), `json_http` a GET returning
+/// the JSON itself, `binary_http` the bincode-encoded proof, and `batched_http` a JSON
+/// array of hex-encoded proofs, newest last. `file` treats each endpoint as a local path
+/// holding the proof JSON or its hex encoding.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ProofSourceKind {
+    #[default]
+    Auto,
+    HexHttp,
+    JsonHttp,
+    BinaryHttp,
+    BatchedHttp,
+    File,
+}
+
+/// Retry schedule for registry submissions. Delays grow exponentially from
+/// `base_delay_ms`, are capped at `max_delay_ms`, and are then reduced by a random
+/// fraction of up to `jitter` (0.0 - 1.0) so replicas don't retry in lockstep.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct RetryPolicy {
+    pub max_attempts: u32,
+    pub base_delay_ms: u64,
+    pub max_delay_ms: u64,
+    pub jitter: f64,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        RetryPolicy {
+            max_attempts: 5,
+            base_delay_ms: 1_000,
+            max_delay_ms: 30_000,
+            jitter: 0.5,
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Delay to wait after the given (1-based) failed attempt
+    pub fn delay_for(&self, attempt: u32) -> Duration {
+        let exponential = self
+            .base_delay_ms
+            .saturating_mul(1u64 << attempt.saturating_sub(1).min(32));
+        let capped = exponential.min(self.max_delay_ms) as f64;
+        let jitter = self.jitter.clamp(0.0, 1.0) * rand::random::<f64>();
+        Duration::from_millis((capped * (1.0 - jitter)) as u64)
+    }
+}
+
+/// Exits the process instead of retrying forever. Both limits are off by default;
+/// either one being reached for any domain stops the process.
+#[derive(Debug, Clone, Copy, Default, Deserialize)]
+#[serde(default)]
+pub struct FailFastPolicy {
+    pub max_consecutive_failures: Option<u32>,
+    pub max_minutes_without_success: Option<u64>,
+}
+
+impl FailFastPolicy {
+    pub fn is_enabled(&self) -> bool {
+        self.max_consecutive_failures.is_some() || self.max_minutes_without_success.is_some()
+    }
+}
+
+/// Chat service a webhook posts to, which decides the message format
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WebhookKind {
+    Slack,
+    Discord,
+}
+
+impl WebhookKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            WebhookKind::Slack => "slack",
+            WebhookKind::Discord => "discord",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct WebhookConfig {
+    pub url: String,
+    pub kind: WebhookKind,
+}
+
+/// Webhook notifications for conditions on-call should act on. Nothing is sent unless
+/// at least one webhook is configured.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct AlertingConfig {
+    pub webhooks: Vec<WebhookConfig>,
+    /// Consecutive failed registry submissions before alerting
+    pub send_failure_threshold: u32,
+    /// Alert once no new proof has been observed for this long
+    pub stale_after_minutes: u64,
+    /// Repeat a firing alert this often until it is acknowledged or resolves; 0 never repeats
+    pub repeat_after_minutes: u64,
+}
+
+impl Default for AlertingConfig {
+    fn default() -> Self {
+        AlertingConfig {
+            webhooks: Vec::new(),
+            send_failure_threshold: 3,
+            stale_after_minutes: HEALTH_STALENESS_THRESHOLD_MINUTES as u64,
+            repeat_after_minutes: 60,
+        }
+    }
+}
+
+/// RPC of the chain a domain proves, queried for its head alongside the proof
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct ReferenceChainConfig {
+    /// CometBFT RPC in Tendermint mode, beacon API in Helios mode
+    pub rpc_url: String,
+    /// Query the head on every Nth cycle only, to bound the added latency and load
+    pub every_n_cycles: u64,
+    /// Query the head while the proof is fetched rather than after it
+    pub parallel: bool,
+    pub timeout_secs: u64,
+}
+
+impl Default for ReferenceChainConfig {
+    fn default() -> Self {
+        ReferenceChainConfig {
+            rpc_url: String::new(),
+            every_n_cycles: 1,
+            parallel: true,
+            timeout_secs: 10,
+        }
+    }
+}
+
+/// Which root of a slot's beacon block a Helios domain's proofs commit to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HeliosRoot {
+    /// The execution payload's state root
+    #[default]
+    ExecutionStateRoot,
+    /// The beacon block header's state root
+    BeaconStateRoot,
+    /// The beacon block header's own root
+    BeaconBlockRoot,
+}
+
+/// Which hash of a CometBFT block a Tendermint domain's proofs commit to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TendermintRoot {
+    /// The header's app hash
+    #[default]
+    AppHash,
+    /// The block hash
+    BlockHash,
+}
+
+/// Compares every new proof's root with the one the chain reports for its height before
+/// the proof is relayed or recorded, see `RootCrossCheck`
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct RootCheckConfig {
+    /// Beacon API in Helios mode, CometBFT RPC in Tendermint mode
+    pub rpc_url: String,
+    /// Helios only
+    pub helios_root: HeliosRoot,
+    /// Tendermint only
+    pub tendermint_root: TendermintRoot,
+    /// Refuse proofs whose root can't be checked, e.g. while the RPC is down; by default
+    /// they are let through unchecked
+    pub required: bool,
+    pub timeout_secs: u64,
+}
+
+impl Default for RootCheckConfig {
+    fn default() -> Self {
+        RootCheckConfig {
+            rpc_url: String::new(),
+            helios_root: HeliosRoot::default(),
+            tendermint_root: TendermintRoot::default(),
+            required: false,
+            timeout_secs: 10,
+        }
+    }
+}
+
+/// Structural checks on every fetched proof before it is decoded, see `EnvelopeCheck`.
+/// Wrapper proof variant and public values layout are always checked once enabled.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct EnvelopeConfig {
+    /// `groth16` or `plonk`; either is accepted when unset
+    pub proof_system: Option<crate::verifier::ProofSystem>,
+    /// Inclusive range of the proof's `sp1_version`, e.g. `"v5.0.0"`
+    pub min_sp1_version: Option<String>,
+    pub max_sp1_version: Option<String>,
+}
+
+/// Periodic samples of key metrics kept in each domain's storage, for deployments
+/// without Prometheus to chart from `/stats/timeseries`
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct MetricsHistoryConfig {
+    pub interval_secs: u64,
+    /// Samples older than this are deleted
+    pub retention_hours: u64,
+}
+
+impl Default for MetricsHistoryConfig {
+    fn default() -> Self {
+        MetricsHistoryConfig {
+            interval_secs: 300,
+            retention_hours: 168,
+        }
+    }
+}
+
+/// Which proofs the history keeps, applied in the background instead of after every
+/// insert. A proof is kept when any rule keeps it, and the latest proof always is, e.g.
+/// `{ "keep_last": 100, "keep_days": 7, "keep_every": 10000 }`.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct HistoryPruningConfig {
+    /// Keeps the N highest proofs
+    pub keep_last: Option<u64>,
+    /// Keeps proofs recorded in the last M days
+    pub keep_days: Option<u64>,
+    /// Keeps every proof whose height is a multiple of K, as checkpoints
+    pub keep_every: Option<u64>,
+    /// How often the `prune_history` job runs when it isn't listed in `jobs`
+    pub interval_secs: u64,
+}
+
+impl Default for HistoryPruningConfig {
+    fn default() -> Self {
+        HistoryPruningConfig {
+            keep_last: None,
+            keep_days: None,
+            keep_every: None,
+            interval_secs: 3600,
+        }
+    }
+}
+
+/// A maintenance job run by the embedded scheduler, e.g.
+/// `{ "job": "backup", "schedule": "0 3 * * *", "dir": "/var/backups/relayer" }`.
+/// `schedule` is a five-field cron expression in UTC, `@hourly`, `@daily`, `@weekly` or
+/// `@every <window>` such as `@every 30s`, see `jobs::Schedule`.
+#[derive(Debug, Clone, Deserialize)]
+pub struct JobConfig {
+    /// Shown on `/jobs`; defaults to the job kind, so only needed to run a kind twice
+    #[serde(default)]
+    pub name: Option<String>,
+    pub schedule: String,
+    #[serde(flatten)]
+    pub job: JobKind,
+}
+
+impl JobConfig {
+    pub fn name(&self) -> &str {
+        self.name.as_deref().unwrap_or(self.job.as_str())
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "job", rename_all = "snake_case")]
+pub enum JobKind {
+    /// Trims each domain's proof history by `history_pruning`, or else to
+    /// `proof_history_retention`
+    PruneHistory,
+    /// Copies each domain's SQLite database into `dir`, keeping the newest `keep` copies
+    Backup {
+        dir: std::path::PathBuf,
+        #[serde(default = "default_backup_keep")]
+        keep: usize,
+    },
+    /// Verifies the newest `proofs` proofs of each domain's history again, against its VK
+    Reverify {
+        #[serde(default = "default_reverify_proofs")]
+        proofs: usize,
+    },
+    /// Logs a summary of every domain and posts it to the alerting webhooks
+    Report,
+    /// Samples the metrics history; added every `metrics_history.interval_secs` unless
+    /// configured explicitly
+    MetricsSnapshot,
+}
+
+impl JobKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            JobKind::PruneHistory => "prune_history",
+            JobKind::Backup { .. } => "backup",
+            JobKind::Reverify { .. } => "reverify",
+            JobKind::Report => "report",
+            JobKind::MetricsSnapshot => "metrics_snapshot",
+        }
+    }
+}
+
+fn default_backup_keep() -> usize {
+    7
+}
+
+fn default_reverify_proofs() -> usize {
+    10
+}
+
+/// Bucket boundaries, in seconds, of the latency histograms at `/metrics`. Each list must
+/// be strictly increasing; an implicit `+Inf` bucket follows the last boundary.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct MetricsConfig {
+    /// Fetching the latest proof, across every prover endpoint tried
+    pub prover_latency_buckets: Vec<f64>,
+    /// Submitting to the primary target, including retries
+    pub registry_latency_buckets: Vec<f64>,
+    /// Verifying a fetched proof locally
+    pub verification_latency_buckets: Vec<f64>,
+}
+
+impl Default for MetricsConfig {
+    fn default() -> Self {
+        MetricsConfig {
+            prover_latency_buckets: vec![0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0, 30.0],
+            registry_latency_buckets: vec![0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0, 30.0, 60.0],
+            verification_latency_buckets: vec![0.1, 0.25, 0.5, 1.0, 2.0, 5.0, 10.0, 30.0],
+        }
+    }
+}
+
+impl MetricsConfig {
+    fn validate(&self) -> anyhow::Result<()> {
+        for (name, buckets) in [
+            ("prover_latency_buckets", &self.prover_latency_buckets),
+            ("registry_latency_buckets", &self.registry_latency_buckets),
+            (
+                "verification_latency_buckets",
+                &self.verification_latency_buckets,
+            ),
+        ] {
+            if buckets.is_empty() || buckets.windows(2).any(|pair| pair[0] >= pair[1]) {
+                return Err(anyhow::anyhow!(
+                    "metrics.{} must be a non-empty, strictly increasing list",
+                    name
+                ));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// How often a domain's loop polls its provers, see `PollSchedule`
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct PollingConfig {
+    pub relay_interval_secs: u64,
+    pub health_check_interval_secs: u64,
+    /// Each wait is randomly lengthened or shortened by up to this fraction of it, e.g.
+    /// `0.1` for ±10%, so relayers sharing a prover don't poll it in lockstep
+    pub jitter: f64,
+    /// Wait after a cycle that found a new proof, to pick up any queued right behind it;
+    /// the usual interval when unset
+    pub catch_up_interval_secs: Option<u64>,
+    /// Polls just before the next proof is expected instead of at a fixed interval, once
+    /// the prover's cadence has been observed
+    pub adaptive: Option<AdaptivePollingConfig>,
+}
+
+impl Default for PollingConfig {
+    fn default() -> Self {
+        PollingConfig {
+            relay_interval_secs: 30,
+            health_check_interval_secs: 120,
+            jitter: 0.0,
+            catch_up_interval_secs: None,
+            adaptive: None,
+        }
+    }
+}
+
+/// Scheduling polls from the moving average of the interval between new proofs
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct AdaptivePollingConfig {
+    /// Weight of the latest interval in the average, between 0 and 1
+    pub smoothing: f64,
+    /// How long before the expected proof to poll
+    pub lead_secs: u64,
+    /// Interval once the expected proof is overdue, until it arrives
+    pub behind_interval_secs: u64,
+    pub min_interval_secs: u64,
+    pub max_interval_secs: u64,
+}
+
+impl Default for AdaptivePollingConfig {
+    fn default() -> Self {
+        AdaptivePollingConfig {
+            smoothing: 0.3,
+            lead_secs: 5,
+            behind_interval_secs: 5,
+            min_interval_secs: 1,
+            max_interval_secs: 600,
+        }
+    }
+}
+
+/// Catching up on heights skipped between two polls, see `DomainConfig::backfill`
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct BackfillConfig {
+    /// Heights fetched and verified at the same time; they are still submitted in order
+    pub concurrency: usize,
+    /// Longest gap filled in one go; older heights in a larger gap are skipped
+    pub max_heights: u64,
+}
+
+impl Default for BackfillConfig {
+    fn default() -> Self {
+        BackfillConfig {
+            concurrency: 4,
+            max_heights: 256,
+        }
+    }
+}
+
+/// Where the summary logged on Ctrl-C or SIGTERM is also posted, as JSON
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct ShutdownReportConfig {
+    pub webhook_url: Option<String>,
+    pub timeout_secs: u64,
+}
+
+impl Default for ShutdownReportConfig {
+    fn default() -> Self {
+        ShutdownReportConfig {
+            webhook_url: None,
+            timeout_secs: 5,
+        }
+    }
+}
+
+/// Where the ed25519 key that signs API responses comes from
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct ResponseSigningConfig {
+    /// File holding the hex-encoded 32-byte secret key; `RELAYER_IDENTITY_KEY` when unset
+    pub key_path: Option<String>,
+}
+
+/// Relayer only: signs each registry payload so the registry can authenticate which
+/// relayer submitted it
+#[derive(Debug, Clone, Deserialize)]
+pub struct PayloadSigningConfig {
+    /// Sent as the payload's `relayer_id`, and covered by the signature
+    pub relayer_id: String,
+    /// File holding the hex-encoded 32-byte secret key; `RELAYER_IDENTITY_KEY` when unset
+    #[serde(default)]
+    pub key_path: Option<String>,
+    /// Signs with a key held in AWS KMS instead, so it never leaves the HSM
+    #[serde(default)]
+    pub kms: Option<KmsSigningConfig>,
+}
+
+/// Relayer only: adds a `metadata` object to each registry payload, for registries that
+/// take one. Off by default, since registries expecting the legacy shape may refuse it.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct PayloadMetadataConfig {
+    /// Identifies this relayer instance; the `HOSTNAME` environment variable when unset
+    pub instance_id: Option<String>,
+}
+
+/// An ed25519 (`ECC_NIST_EDWARDS25519`) signing key in AWS KMS
+#[derive(Debug, Clone, Deserialize)]
+pub struct KmsSigningConfig {
+    /// Key id, ARN or alias
+    pub key_id: String,
+    pub region: String,
+    /// Base URL of the KMS API, e.g. a VPC endpoint; `https://kms.<region>.amazonaws.com`
+    /// when unset
+    #[serde(default)]
+    pub endpoint: Option<String>,
+    /// `AWS_ACCESS_KEY_ID` when unset
+    #[serde(default)]
+    pub access_key_id: Option<String>,
+    /// `AWS_SECRET_ACCESS_KEY` when unset
+    #[serde(default)]
+    pub secret_access_key: Option<String>,
+}
+
+/// Guards for the public-facing API server
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct ApiLimits {
+    /// Largest request body accepted on any route
+    pub max_body_bytes: usize,
+    /// Requests taking longer than this are answered with 408
+    pub request_timeout_secs: u64,
+    /// Requests beyond this many in flight wait for a free slot
+    pub max_concurrent_requests: usize,
+}
+
+impl Default for ApiLimits {
+    fn default() -> Self {
+        ApiLimits {
+            max_body_bytes: 64 * 1024,
+            request_timeout_secs: 30,
+            max_concurrent_requests: 256,
+        }
+    }
+}
+
+/// PEM certificate chain and private key for serving the API over HTTPS
+#[derive(Debug, Clone, Deserialize)]
+pub struct TlsConfig {
+    pub cert_path: String,
+    pub key_path: String,
+    /// How often the files are checked for changes, e.g. a renewed certificate
+    #[serde(default = "default_tls_reload_interval_secs")]
+    pub reload_interval_secs: u64,
+}
+
+fn default_tls_reload_interval_secs() -> u64 {
+    30
+}
+
+/// gRPC proof stream for Valence coprocessor clients, see `proto/relayer.proto`. Only
+/// served by builds with the `grpc` feature.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct GrpcConfig {
+    pub listen_addr: String,
+    /// Proofs queued per stream before the relayer waits for the client to read them
+    pub stream_buffer: usize,
+}
+
+impl Default for GrpcConfig {
+    fn default() -> Self {
+        GrpcConfig {
+            listen_addr: "0.0.0.0:17401".to_string(),
+            stream_buffer: 16,
+        }
+    }
+}
+
+/// How prover and registry endpoints are shown in API responses. `credentials`
+/// strips user info and query strings, which is where tokens usually live.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EndpointRedaction {
+    None,
+    #[default]
+    Credentials,
+    Full,
+}
+
+/// Optional append-only journal of registry submission attempts
+#[derive(Debug, Clone, Deserialize)]
+pub struct JournalConfig {
+    pub path: String,
+    /// The active file is rotated once it would grow past this size
+    #[serde(default = "default_journal_max_bytes")]
+    pub max_bytes: u64,
+    /// Rotated files kept alongside the active one
+    #[serde(default = "default_journal_max_files")]
+    pub max_files: u32,
+}
+
+fn default_journal_max_bytes() -> u64 {
+    100 * 1024 * 1024
+}
+
+fn default_journal_max_files() -> u32 {
+    10
+}
+
+/// Where domain state is persisted. SQLite keeps one file per domain on local disk;
+/// Postgres and Redis are shared between replicas, namespacing state by domain. `memory`
+/// keeps each domain in an in-memory SQLite database that is lost on exit, for tests and
+/// ephemeral deployments.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "backend", rename_all = "snake_case")]
+pub enum StorageConfig {
+    Sqlite(SqliteOptions),
+    Memory,
+    Postgres {
+        url: String,
+        #[serde(default = "default_postgres_max_connections")]
+        max_connections: u32,
+    },
+    Redis {
+        url: String,
+        #[serde(default = "default_redis_key_prefix")]
+        key_prefix: String,
+    },
+}
+
+impl Default for StorageConfig {
+    fn default() -> Self {
+        StorageConfig::Sqlite(SqliteOptions::default())
+    }
+}
+
+/// How each domain's SQLite file is opened
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct SqliteOptions {
+    /// Add tables and columns missing from an older file (e.g. a restored backup) at
+    /// startup; when off, opening it fails naming them instead
+    pub repair_schema: bool,
+    pub journal_mode: SqliteJournalMode,
+    pub synchronous: SqliteSynchronous,
+    /// How long a statement waits for another connection's lock, e.g. the CLI's or a
+    /// backup's, before failing with SQLITE_BUSY
+    pub busy_timeout_ms: u64,
+}
+
+impl Default for SqliteOptions {
+    fn default() -> Self {
+        SqliteOptions {
+            repair_schema: true,
+            journal_mode: SqliteJournalMode::default(),
+            synchronous: SqliteSynchronous::default(),
+            busy_timeout_ms: 5000,
+        }
+    }
+}
+
+/// SQLite's journal mode. `wal` lets readers run alongside a writer instead of
+/// waiting for it; `delete` is SQLite's own default, for filesystems without shared
+/// memory such as some network mounts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SqliteJournalMode {
+    #[default]
+    Wal,
+    Delete,
+    Truncate,
+}
+
+impl SqliteJournalMode {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SqliteJournalMode::Wal => "wal",
+            SqliteJournalMode::Delete => "delete",
+            SqliteJournalMode::Truncate => "truncate",
+        }
+    }
+}
+
+/// When SQLite waits for writes to reach the disk. `full` survives power loss with every
+/// committed write; `normal` in WAL mode only risks the last writes before a power loss,
+/// not corruption, and commits much faster; `off` leaves durability to the OS.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SqliteSynchronous {
+    Off,
+    Normal,
+    #[default]
+    Full,
+    Extra,
+}
+
+impl SqliteSynchronous {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SqliteSynchronous::Off => "off",
+            SqliteSynchronous::Normal => "normal",
+            SqliteSynchronous::Full => "full",
+            SqliteSynchronous::Extra => "extra",
+        }
+    }
+}
+
+fn default_postgres_max_connections() -> u32 {
+    5
+}
+
+fn default_redis_key_prefix() -> String {
+    "relayer".to_string()
+}
+
+/// Target for a domain's relayed proofs. Listed in `sinks`, they receive the same payloads
+/// as the primary target and their failures don't affect relaying.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum SinkConfig {
+    /// Another registry, sent payloads with the same retry policy
+    Registry {
+        endpoint: String,
+        /// The registry only keeps the latest proof, see `DomainConfig::registry_latest_only`
+        #[serde(default)]
+        latest_only: bool,
+        /// See `DomainConfig::registry_validate_endpoint`
+        #[serde(default)]
+        validate_endpoint: Option<String>,
+    },
+    /// Archives payloads as `<dir>/<domain>/<height>.json`
+    File { dir: String },
+    /// Archives payloads as `<prefix><domain>/<height>.json` in an S3 bucket. Credentials
+    /// default to `AWS_ACCESS_KEY_ID` / `AWS_SECRET_ACCESS_KEY` (and `AWS_SESSION_TOKEN`).
+    S3 {
+        bucket: String,
+        region: String,
+        #[serde(default)]
+        prefix: String,
+        /// Base URL of an S3-compatible store, addressed path-style
+        #[serde(default)]
+        endpoint: Option<String>,
+        #[serde(default)]
+        access_key_id: Option<String>,
+        #[serde(default)]
+        secret_access_key: Option<String>,
+    },
+    /// Calls `function` on a verifier contract with the proof and public values as
+    /// `bytes` arguments, waiting for `confirmations` blocks. The signing key defaults to
+    /// `EVM_PRIVATE_KEY`.
+    Evm {
+        rpc_url: String,
+        contract: String,
+        #[serde(default)]
+        latest_only: bool,
+        #[serde(default)]
+        private_key: Option<String>,
+        #[serde(default = "default_evm_function")]
+        function: String,
+        #[serde(default = "default_evm_confirmations")]
+        confirmations: u64,
+        #[serde(default = "default_evm_receipt_timeout_secs")]
+        receipt_timeout_secs: u64,
+        /// Applied to the estimated gas limit
+        #[serde(default = "default_evm_gas_multiplier")]
+        gas_multiplier: f64,
+    },
+    /// Executes `{"<execute_msg>": payload}` on a CosmWasm contract, broadcasting through
+    /// the chain's REST (LCD) endpoint and waiting up to `confirm_timeout_secs` for the
+    /// transaction to be included. The signing key defaults to `COSMOS_MNEMONIC`.
+    Cosmwasm {
+        lcd_url: String,
+        chain_id: String,
+        contract: String,
+        #[serde(default)]
+        latest_only: bool,
+        #[serde(default = "default_cosmwasm_account_prefix")]
+        account_prefix: String,
+        #[serde(default)]
+        key: Option<CosmosKey>,
+        #[serde(default = "default_cosmos_derivation_path")]
+        derivation_path: String,
+        #[serde(default)]
+        fee: CosmosFee,
+        #[serde(default = "default_cosmwasm_execute_msg")]
+        execute_msg: String,
+        #[serde(default = "default_cosmwasm_confirm_timeout_secs")]
+        confirm_timeout_secs: u64,
+    },
+}
+
+/// Where a CosmWasm sink's BIP-39 mnemonic comes from
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CosmosKey {
+    Mnemonic(String),
+    /// An entry in the OS keyring (macOS Keychain, Secret Service, Windows Credential
+    /// Manager) holding the mnemonic
+    Keyring {
+        service: String,
+        user: String,
+    },
+}
+
+/// Fee paid by every CosmWasm submission
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct CosmosFee {
+    pub amount: u128,
+    pub denom: String,
+    pub gas_limit: u64,
+}
+
+impl Default for CosmosFee {
+    fn default() -> Self {
+        CosmosFee {
+            amount: 5000,
+            denom: "untrn".to_string(),
+            gas_limit: 2_000_000,
+        }
+    }
+}
+
+fn default_cosmwasm_account_prefix() -> String {
+    "neutron".to_string()
+}
+
+fn default_cosmos_derivation_path() -> String {
+    "m/44'/118'/0'/0/0".to_string()
+}
+
+/// Status endpoint of a registry that processes submissions asynchronously
+#[derive(Debug, Clone, Deserialize)]
+pub struct RegistryConfirmation {
+    /// Polled as `GET <status_endpoint>?height=<height>&root=<hex root>`
+    pub status_endpoint: String,
+    #[serde(default = "default_confirmation_interval_secs")]
+    pub interval_secs: u64,
+    /// How long a submission may go unconfirmed before it is submitted again
+    #[serde(default = "default_confirmation_timeout_secs")]
+    pub timeout_secs: u64,
+    /// Submissions made again after a failed or missing confirmation, before the proof
+    /// counts as failed
+    #[serde(default = "default_confirmation_resubmissions")]
+    pub max_resubmissions: u32,
+}
+
+fn default_confirmation_interval_secs() -> u64 {
+    10
+}
+
+fn default_confirmation_timeout_secs() -> u64 {
+    300
+}
+
+fn default_confirmation_resubmissions() -> u32 {
+    2
+}
+
+fn default_cosmwasm_execute_msg() -> String {
+    "submit_proof".to_string()
+}
+
+fn default_cosmwasm_confirm_timeout_secs() -> u64 {
+    60
+}
+
+fn default_evm_function() -> String {
+    "submitProof(bytes,bytes)".to_string()
+}
+
+fn default_evm_confirmations() -> u64 {
+    1
+}
+
+fn default_evm_receipt_timeout_secs() -> u64 {
+    120
+}
+
+fn default_evm_gas_multiplier() -> f64 {
+    1.2
+}
+
+/// How payloads are encoded on the wire to a registry
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PayloadCodec {
+    /// JSON with hex-encoded proof and public values
+    #[default]
+    Json,
+    /// Borsh encoding of `WirePayload`
+    Borsh,
+    /// Protobuf `RegistryPayload`, see proto/registry.proto
+    Protobuf,
+}
+
+impl PayloadCodec {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PayloadCodec::Json => "json",
+            PayloadCodec::Borsh => "borsh",
+            PayloadCodec::Protobuf => "protobuf",
+        }
+    }
+
+    pub fn content_type(&self) -> &'static str {
+        match self {
+            PayloadCodec::Json => "application/json",
+            PayloadCodec::Borsh => "application/x-borsh",
+            PayloadCodec::Protobuf => "application/x-protobuf",
+        }
+    }
+}
+
+/// zstd compression of payloads to a registry, sent as `Content-Encoding: zstd`
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(default)]
+pub struct PayloadCompression {
+    /// zstd level, 1 (fastest) to 22
+    pub level: i32,
+    /// Bodies smaller than this are sent uncompressed
+    pub min_bytes: usize,
+}
+
+impl Default for PayloadCompression {
+    fn default() -> Self {
+        PayloadCompression {
+            level: 3,
+            min_bytes: 16 * 1024,
+        }
+    }
+}
+
+/// How payloads are put on the wire to one registry
+#[derive(Debug, Clone, Copy, Default)]
+pub struct PayloadWire {
+    pub codec: PayloadCodec,
+    pub compression: Option<PayloadCompression>,
+}
+
+/// Public-key encryption required by a registry on top of TLS
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "scheme", rename_all = "snake_case")]
+pub enum PayloadEncryption {
+    /// age to an X25519 recipient (`age1…`)
+    Age { recipient: String },
+    /// libsodium sealed box (`crypto_box_seal`) to a hex or base64 X25519 public key
+    SealedBox { public_key: String },
+}
+
+/// SOCKS5 proxy, e.g. a bastion, used to reach one outbound endpoint
+#[derive(Debug, Clone, Deserialize)]
+pub struct Socks5Proxy {
+    /// `host:port` of the proxy
+    pub address: String,
+    #[serde(default)]
+    pub username: Option<String>,
+    #[serde(default)]
+    pub password: Option<String>,
+    /// Resolve endpoint host names on the proxy rather than locally
+    #[serde(default = "default_remote_dns")]
+    pub remote_dns: bool,
+}
+
+fn default_remote_dns() -> bool {
+    true
+}
+
+/// Timeouts for requests to one outbound endpoint. `deadline_secs` bounds a whole request,
+/// body included; `read_timeout_secs` only how long a prover's response may stall, so a
+/// large proof on a slow link can take as long as it needs while it keeps arriving.
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(default)]
+pub struct HttpTimeouts {
+    pub connect_timeout_secs: u64,
+    pub read_timeout_secs: u64,
+    pub deadline_secs: Option<u64>,
+}
+
+impl Default for HttpTimeouts {
+    fn default() -> Self {
+        HttpTimeouts {
+            connect_timeout_secs: 10,
+            read_timeout_secs: 30,
+            deadline_secs: None,
+        }
+    }
+}
+
+impl HttpTimeouts {
+    pub fn connect_timeout(&self) -> Duration {
+        Duration::from_secs(self.connect_timeout_secs)
+    }
+
+    pub fn read_timeout(&self) -> Duration {
+        Duration::from_secs(self.read_timeout_secs)
+    }
+
+    pub fn deadline(&self) -> Option<Duration> {
+        self.deadline_secs.map(Duration::from_secs)
+    }
+}
+
+/// Everything configured for reaching one outbound endpoint
+#[derive(Debug, Clone, Default)]
+pub struct EndpointHttp {
+    pub proxy: Option<Socks5Proxy>,
+    pub timeouts: HttpTimeouts,
+    /// Sent with every request to the endpoint, sorted by name
+    pub headers: Vec<(String, String)>,
+}
+
+/// One chain relayed by this process, with its own provers, registry and VK
+#[derive(Debug, Clone, Deserialize)]
+pub struct DomainConfig {
+    /// Unique name, used for metric labels, API lookups and the database file name
+    pub name: String,
+    pub mode: MODE,
+    /// Prover endpoints to fetch proofs from, falling over to the next one on errors
+    pub prover_endpoints: Vec<String>,
+    #[serde(default)]
+    pub prover_selection: ProverSelection,
+    /// Fetch from every prover endpoint each cycle and only take a proof when at least
+    /// this many agree on its height and root; off unless set
+    #[serde(default)]
+    pub prover_quorum: Option<usize>,
+    /// Applies to the candidate prover too
+    #[serde(default)]
+    pub proof_source: ProofSourceKind,
+    /// Prover being evaluated for cutover; compared against the current provers but never relayed
+    #[serde(default)]
+    pub candidate_prover: Option<String>,
+    pub registry_endpoint: String,
+    /// Relayer only: `registry_endpoint` only keeps the latest proof, so queued payloads
+    /// below a newer queued one are dropped instead of submitted
+    #[serde(default)]
+    pub registry_latest_only: bool,
+    /// Validate-only endpoint of the registry. The first payload after startup is posted
+    /// here before `registry_endpoint`, and isn't submitted if it is refused.
+    #[serde(default)]
+    pub registry_validate_endpoint: Option<String>,
+    /// Relayer only: status endpoints keyed by registry URL, for registries that process
+    /// submissions asynchronously; a submission to one only counts once it is confirmed
+    #[serde(default)]
+    pub registry_confirmations: HashMap<String, RegistryConfirmation>,
+    pub vk: String,
+    /// Only consulted in Tendermint mode
+    #[serde(default)]
+    pub rollback_policy: RollbackPolicy,
+    /// Heights a proof may fall below the last accepted one, e.g. after a reorg, before
+    /// it is treated as a misbehaving prover: refused whatever the rollback policy,
+    /// logged and alerted. The relayer never submits lower heights either way.
+    #[serde(default)]
+    pub reorg_tolerance: u64,
+    /// Tendermint only: proofs whose outputs name a different chain are rejected
+    #[serde(default)]
+    pub chain_id: Option<String>,
+    #[serde(default)]
+    pub freshness: FreshnessConfig,
+    /// Wall-clock thresholds for `/health`, see `StalenessConfig`
+    #[serde(default)]
+    pub staleness: StalenessConfig,
+    /// Relayer only: submits to this sink instead of `registry_endpoint`, e.g. an EVM contract
+    #[serde(default)]
+    pub primary_sink: Option<SinkConfig>,
+    /// Relayer only: where to deliver proofs besides the primary target
+    #[serde(default)]
+    pub sinks: Vec<SinkConfig>,
+    /// SOCKS5 proxies keyed by endpoint URL (prover, candidate or registry); endpoints
+    /// not listed are reached directly
+    #[serde(default)]
+    pub socks5_proxies: HashMap<String, Socks5Proxy>,
+    /// Timeouts keyed by endpoint URL (prover, candidate or registry); endpoints not listed
+    /// use the `HttpTimeouts` defaults
+    #[serde(default)]
+    pub http_timeouts: HashMap<String, HttpTimeouts>,
+    /// Static headers keyed by endpoint URL (prover, candidate or registry), e.g. an
+    /// `Authorization` bearer token for a gateway in front of the prover
+    #[serde(default)]
+    pub http_headers: HashMap<String, HashMap<String, String>>,
+    /// Relayer only: encryption keyed by registry URL (`registry_endpoint` or a `registry`
+    /// sink's); registries not listed receive the plain payload
+    #[serde(default)]
+    pub payload_encryption: HashMap<String, PayloadEncryption>,
+    /// Relayer only: wire encoding keyed by registry URL; registries not listed get JSON
+    #[serde(default)]
+    pub payload_codecs: HashMap<String, PayloadCodec>,
+    /// Relayer only: zstd compression keyed by registry URL; registries not listed get
+    /// uncompressed payloads
+    #[serde(default)]
+    pub payload_compression: HashMap<String, PayloadCompression>,
+    /// Relayer only: signs payloads to registries, before any encryption
+    #[serde(default)]
+    pub payload_signing: Option<PayloadSigningConfig>,
+    #[serde(default)]
+    pub payload_metadata: Option<PayloadMetadataConfig>,
+    /// Chain the proofs attest to; proofs above its head are rejected
+    #[serde(default)]
+    pub reference_chain: Option<ReferenceChainConfig>,
+    /// Where the roots of new proofs are cross-checked; off unless set
+    #[serde(default)]
+    pub root_check: Option<RootCheckConfig>,
+    /// Off unless set
+    #[serde(default)]
+    pub envelope: Option<EnvelopeConfig>,
+    /// Relayer only: when a new proof is more than one height above the last relayed
+    /// one, fetch the heights in between from the prover and submit them first. Off
+    /// unless set; only provers serving proofs by height support it.
+    #[serde(default)]
+    pub backfill: Option<BackfillConfig>,
+    #[serde(default)]
+    pub polling: PollingConfig,
+    /// Hash behind the ids stored and shown for this domain's proofs, see `ProofId`
+    #[serde(default)]
+    pub proof_id_algorithm: crate::proof_id::HashAlgorithm,
+    /// Relayer only: fetch, verify and record proofs without submitting them anywhere, in
+    /// a SQLite file of the domain's own; set on every domain by `--dry-run`
+    #[serde(default)]
+    pub dry_run: bool,
+    /// SQLite file for this domain, instead of `<data_dir>/<mode>_<name>.db`; a relative
+    /// path is taken from `data_dir`
+    #[serde(default)]
+    pub db_path: Option<String>,
+}
+
+impl DomainConfig {
+    /// How requests to `endpoint` are made, from the settings keyed by its URL
+    pub fn http_for(&self, endpoint: &str) -> EndpointHttp {
+        let mut headers: Vec<(String, String)> = self
+            .http_headers
+            .get(endpoint)
+            .map(|headers| headers.clone().into_iter().collect())
+            .unwrap_or_default();
+        headers.sort();
+        EndpointHttp {
+            proxy: self.socks5_proxies.get(endpoint).cloned(),
+            timeouts: self
+                .http_timeouts
+                .get(endpoint)
+                .copied()
+                .unwrap_or_default(),
+            headers,
+        }
+    }
+
+    /// How payloads to the registry at `endpoint` are encoded and compressed
+    pub fn wire_for(&self, endpoint: &str) -> PayloadWire {
+        PayloadWire {
+            codec: self
+                .payload_codecs
+                .get(endpoint)
+                .copied()
+                .unwrap_or_default(),
+            compression: self.payload_compression.get(endpoint).copied(),
+        }
+    }
+}
+
+impl Default for DomainConfig {
+    fn default() -> Self {
+        DomainConfig {
+            name: DOMAIN_NAME.to_string(),
+            mode: LIGHT_CLIENT_MODE,
+            prover_endpoints: vec![LIGHT_CLIENT_PROVER_ENDPOINT.to_string()],
+            prover_selection: ProverSelection::Priority,
+            prover_quorum: None,
+            proof_source: ProofSourceKind::Auto,
+            candidate_prover: None,
+            registry_endpoint: REGISTRY_ENDPOINT.to_string(),
+            registry_latest_only: false,
+            registry_validate_endpoint: None,
+            registry_confirmations: HashMap::new(),
+            vk: LIGHT_CLIENT_VK.to_string(),
+            rollback_policy: RollbackPolicy::Reject,
+            reorg_tolerance: 0,
+            chain_id: None,
+            freshness: FreshnessConfig::WallClock,
+            staleness: StalenessConfig::default(),
+            primary_sink: None,
+            sinks: Vec::new(),
+            socks5_proxies: HashMap::new(),
+            http_timeouts: HashMap::new(),
+            http_headers: HashMap::new(),
+            payload_encryption: HashMap::new(),
+            payload_codecs: HashMap::new(),
+            payload_compression: HashMap::new(),
+            payload_signing: None,
+            payload_metadata: None,
+            reference_chain: None,
+            root_check: None,
+            envelope: None,
+            backfill: None,
+            polling: PollingConfig::default(),
+            proof_id_algorithm: Default::default(),
+            dry_run: false,
+            db_path: None,
+        }
+    }
+}
+
+/// Runtime settings read from the JSON file named by the `RELAYER_CONFIG` environment
+/// variable. Missing fields (or a missing file) fall back to the defaults in this module.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct Config {
+    /// Chains to relay, each with an independent loop and database
+    pub domains: Vec<DomainConfig>,
+    pub storage: StorageConfig,
+    /// Directory for the SQLite databases, created if missing; the working directory
+    /// when unset
+    pub data_dir: Option<String>,
+    /// Directory `POST /admin/backup` writes to; without it, backups can only be
+    /// downloaded
+    pub backup_dir: Option<std::path::PathBuf>,
+    /// Keep only the most recent N proofs in the history table; `null` keeps everything.
+    /// Ignored when `history_pruning` is set.
+    pub proof_history_retention: Option<u64>,
+    /// Prunes the history in the background by policy instead of after every insert
+    pub history_pruning: Option<HistoryPruningConfig>,
+    pub registry_retry: RetryPolicy,
+    pub fail_fast: FailFastPolicy,
+    /// Disabled unless set
+    pub submission_journal: Option<JournalConfig>,
+    /// Disabled unless set
+    pub metrics_history: Option<MetricsHistoryConfig>,
+    pub metrics: MetricsConfig,
+    /// Maintenance jobs, listed at `/jobs`
+    pub jobs: Vec<JobConfig>,
+    pub api_limits: ApiLimits,
+    /// Serves the API over HTTPS when set
+    pub api_tls: Option<TlsConfig>,
+    /// Disabled unless set
+    pub grpc: Option<GrpcConfig>,
+    pub endpoint_redaction: EndpointRedaction,
+    pub alerting: AlertingConfig,
+    /// Signs `/health` responses with the relayer's identity key when set
+    pub response_signing: Option<ResponseSigningConfig>,
+    pub shutdown_report: ShutdownReportConfig,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            domains: vec![DomainConfig::default()],
+            storage: StorageConfig::default(),
+            data_dir: None,
+            backup_dir: None,
+            proof_history_retention: Some(DEFAULT_PROOF_HISTORY_RETENTION),
+            history_pruning: None,
+            registry_retry: RetryPolicy::default(),
+            fail_fast: FailFastPolicy::default(),
+            submission_journal: None,
+            metrics_history: None,
+            metrics: MetricsConfig::default(),
+            jobs: Vec::new(),
+            api_limits: ApiLimits::default(),
+            api_tls: None,
+            grpc: None,
+            endpoint_redaction: EndpointRedaction::default(),
+            alerting: AlertingConfig::default(),
+            response_signing: None,
+            shutdown_report: ShutdownReportConfig::default(),
+        }
+    }
+}
+
+impl Config {
+    /// Turns every domain into a dry run, for `--dry-run`
+    pub fn into_dry_run(mut self) -> Self {
+        for domain in &mut self.domains {
+            domain.dry_run = true;
+        }
+        self
+    }
+
+    /// How many proofs to keep when pruning after every insert, `None` when that is off
+    /// because the history is kept unpruned or pruned in the background by
+    /// `history_pruning`
+    pub fn inline_history_retention(&self) -> Option<u64> {
+        match self.history_pruning {
+            Some(_) => None,
+            None => self.proof_history_retention,
+        }
+    }
+
+    /// Path SQLite files start with, completed by each domain as `<prefix>_<name>.db`:
+    /// `DB_PREFIX`, inside `data_dir` if set
+    pub fn db_prefix(&self) -> String {
+        match &self.data_dir {
+            Some(dir) => std::path::Path::new(dir)
+                .join(DB_PREFIX)
+                .to_string_lossy()
+                .into_owned(),
+            None => DB_PREFIX.to_string(),
+        }
+    }
+
+    pub fn load() -> anyhow::Result<Self> {
+        let config: Config = match std::env::var("RELAYER_CONFIG") {
+            Ok(path) => {
+                let contents = std::fs::read_to_string(&path)
+                    .map_err(|e| anyhow::anyhow!("Failed to read config file {}: {}", path, e))?;
+                serde_json::from_str(&contents)?
+            }
+            Err(_) => Config::default(),
+        };
+        config
+            .validate()
+            .map_err(|e| RelayerError::Config(e.to_string()))?;
+        Ok(config)
+    }
+
+    fn validate(&self) -> anyhow::Result<()> {
+        if self.domains.is_empty() {
+            return Err(anyhow::anyhow!("At least one domain must be configured"));
+        }
+
+        // Dry runs keep SQLite files whatever the storage
+        if let Some(data_dir) = &self.data_dir
+            && !matches!(self.storage, StorageConfig::Sqlite(_))
+            && !self.domains.iter().any(|domain| domain.dry_run)
+        {
+            return Err(anyhow::anyhow!(
+                "data_dir {} only applies to sqlite storage",
+                data_dir
+            ));
+        }
+
+        let mut names = std::collections::HashSet::new();
+        let mut db_paths = std::collections::HashSet::new();
+        for domain in &self.domains {
+            if !names.insert(domain.name.as_str()) {
+                return Err(anyhow::anyhow!("Duplicate domain name {}", domain.name));
+            }
+            if let Some(db_path) = &domain.db_path {
+                if !matches!(self.storage, StorageConfig::Sqlite(_)) {
+                    return Err(anyhow::anyhow!(
+                        "Domain {} sets db_path, which only applies to sqlite storage",
+                        domain.name
+                    ));
+                }
+                if !db_paths.insert(db_path.as_str()) {
+                    return Err(anyhow::anyhow!(
+                        "Domain {} shares db_path {} with another domain",
+                        domain.name,
+                        db_path
+                    ));
+                }
+            }
+            if domain.prover_endpoints.is_empty() {
+                return Err(anyhow::anyhow!(
+                    "Domain {} has no prover endpoints",
+                    domain.name
+                ));
+            }
+            if let Some(quorum) = domain.prover_quorum
+                && (quorum == 0 || quorum > domain.prover_endpoints.len())
+            {
+                return Err(anyhow::anyhow!(
+                    "Domain {} has a prover_quorum of {}, expected 1 to {} (its prover endpoints)",
+                    domain.name,
+                    quorum,
+                    domain.prover_endpoints.len()
+                ));
+            }
+            if domain.chain_id.is_some() && domain.mode != MODE::TENDERMINT {
+                return Err(anyhow::anyhow!(
+                    "Domain {} sets chain_id, which only applies to Tendermint domains",
+                    domain.name
+                ));
+            }
+            for (endpoint, confirmation) in &domain.registry_confirmations {
+                if confirmation.interval_secs == 0
+                    || confirmation.timeout_secs < confirmation.interval_secs
+                {
+                    return Err(anyhow::anyhow!(
+                        "Domain {} polls {} for confirmations every {}s with a {}s timeout; the interval must be positive and within the timeout",
+                        domain.name,
+                        endpoint,
+                        confirmation.interval_secs,
+                        confirmation.timeout_secs
+                    ));
+                }
+            }
+            for (endpoint, codec) in &domain.payload_codecs {
+                if *codec != PayloadCodec::Json && domain.payload_encryption.contains_key(endpoint)
+                {
+                    return Err(anyhow::anyhow!(
+                        "Domain {} encrypts payloads to {}, which only works with the json codec",
+                        domain.name,
+                        endpoint
+                    ));
+                }
+            }
+            for (endpoint, compression) in &domain.payload_compression {
+                if !(1..=22).contains(&compression.level) {
+                    return Err(anyhow::anyhow!(
+                        "Domain {} compresses payloads to {} at level {}, zstd levels are 1 to 22",
+                        domain.name,
+                        endpoint,
+                        compression.level
+                    ));
+                }
+                // Ciphertext doesn't compress
+                if domain.payload_encryption.contains_key(endpoint) {
+                    return Err(anyhow::anyhow!(
+                        "Domain {} both encrypts and compresses payloads to {}",
+                        domain.name,
+                        endpoint
+                    ));
+                }
+            }
+            if let Some(signing) = &domain.payload_signing
+                && signing.kms.is_some()
+                && signing.key_path.is_some()
+            {
+                return Err(anyhow::anyhow!(
+                    "Domain {} sets both a key_path and a KMS key for payload signing",
+                    domain.name
+                ));
+            }
+            for (endpoint, headers) in &domain.http_headers {
+                for (name, value) in headers {
+                    if reqwest::header::HeaderName::from_bytes(name.as_bytes()).is_err()
+                        || reqwest::header::HeaderValue::from_str(value).is_err()
+                    {
+                        return Err(anyhow::anyhow!(
+                            "Domain {} has an invalid header {} for {}",
+                            domain.name,
+                            name,
+                            endpoint
+                        ));
+                    }
+                }
+            }
+            for (endpoint, timeouts) in &domain.http_timeouts {
+                if timeouts.connect_timeout_secs == 0
+                    || timeouts.read_timeout_secs == 0
+                    || timeouts.deadline_secs == Some(0)
+                {
+                    return Err(anyhow::anyhow!(
+                        "Domain {} has a zero timeout for {}",
+                        domain.name,
+                        endpoint
+                    ));
+                }
+            }
+        }
+
+        if let Some(pruning) = &self.history_pruning {
+            if pruning.keep_last.is_none()
+                && pruning.keep_days.is_none()
+                && pruning.keep_every.is_none()
+            {
+                return Err(anyhow::anyhow!(
+                    "history_pruning needs at least one of keep_last, keep_days and keep_every"
+                ));
+            }
+            if pruning.keep_every == Some(0) {
+                return Err(anyhow::anyhow!(
+                    "history_pruning.keep_every must be above 0"
+                ));
+            }
+        }
+
+        let mut job_names = std::collections::HashSet::new();
+        for job in &self.jobs {
+            if !job_names.insert(job.name()) {
+                return Err(anyhow::anyhow!("Duplicate job name {}", job.name()));
+            }
+            crate::jobs::Schedule::parse(&job.schedule)
+                .map_err(|e| anyhow::anyhow!("Job {}: {}", job.name(), e))?;
+        }
+
+        self.metrics.validate()
+    }
+}
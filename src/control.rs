@@ -0,0 +1,68 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+use tokio::sync::Notify;
+
+/// Operator controls for one domain's relay loop, driven from the admin API
+pub struct RelayControl {
+    paused: AtomicBool,
+    stopped: AtomicBool,
+    wake: Notify,
+}
+
+impl Default for RelayControl {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl RelayControl {
+    pub fn new() -> Self {
+        RelayControl {
+            paused: AtomicBool::new(false),
+            stopped: AtomicBool::new(false),
+            wake: Notify::new(),
+        }
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused.load(Ordering::SeqCst)
+    }
+
+    /// Stops submissions until `resume` is called. Returns false if already paused.
+    pub fn pause(&self) -> bool {
+        !self.paused.swap(true, Ordering::SeqCst)
+    }
+
+    /// Resumes submissions and runs a cycle straight away. Returns false if not paused.
+    pub fn resume(&self) -> bool {
+        let was_paused = self.paused.swap(false, Ordering::SeqCst);
+        if was_paused {
+            self.wake.notify_one();
+        }
+        was_paused
+    }
+
+    /// Whether the domain was removed, so its loops should exit
+    pub fn is_stopped(&self) -> bool {
+        self.stopped.load(Ordering::SeqCst)
+    }
+
+    /// Asks the domain's loops to exit, cutting the relay loop's current wait short
+    pub fn stop(&self) {
+        self.stopped.store(true, Ordering::SeqCst);
+        self.wake.notify_one();
+    }
+
+    /// Cuts the current wait short so the loop fetches and sends immediately
+    pub fn relay_now(&self) {
+        self.wake.notify_one();
+    }
+
+    /// Sleeps for `interval`, returning early when woken by `resume` or `relay_now`
+    pub async fn wait(&self, interval: Duration) {
+        tokio::select! {
+            _ = tokio::time::sleep(interval) => {}
+            _ = self.wake.notified() => {}
+        }
+    }
+}
@@ -0,0 +1,281 @@
+use anyhow::{Result, anyhow};
+use async_trait::async_trait;
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD as BASE64;
+use cosmrs::cosmwasm::MsgExecuteContract;
+use cosmrs::crypto::secp256k1::SigningKey;
+use cosmrs::tendermint::chain;
+use cosmrs::tx::{self, Fee, Msg, SignDoc, SignerInfo};
+use cosmrs::{AccountId, Coin};
+use serde_json::json;
+use std::time::Duration;
+use tokio::sync::Mutex;
+use tracing::{info, warn};
+
+use crate::config::{CosmosFee, CosmosKey};
+use crate::sink::ProofSink;
+
+/// Submits proofs to a CosmWasm contract, e.g. on Neutron, as a `MsgExecuteContract`
+/// whose message is `{"<execute_msg>": {"proof", "public_values", "vk"}}`
+pub struct CosmwasmSink {
+    lcd_url: String,
+    chain_id: chain::Id,
+    contract: AccountId,
+    sender: AccountId,
+    signing_key: SigningKey,
+    fee: CosmosFee,
+    execute_msg: String,
+    confirm_timeout: Duration,
+    latest_only: bool,
+    client: reqwest::Client,
+    /// Account number and next sequence, or `None` to query them. Holding the lock for
+    /// the whole submission keeps transactions from this sink strictly ordered.
+    account: Mutex<Option<(u64, u64)>>,
+}
+
+/// Settings for `CosmwasmSink::new`, see `SinkConfig::Cosmwasm`
+pub struct CosmwasmSinkConfig<'a> {
+    pub lcd_url: &'a str,
+    pub chain_id: &'a str,
+    pub contract: &'a str,
+    pub account_prefix: &'a str,
+    pub mnemonic: &'a str,
+    pub derivation_path: &'a str,
+    pub fee: CosmosFee,
+    pub execute_msg: &'a str,
+    pub confirm_timeout_secs: u64,
+    pub latest_only: bool,
+    pub client: reqwest::Client,
+}
+
+impl CosmwasmSink {
+    pub fn new(config: CosmwasmSinkConfig<'_>) -> Result<Self> {
+        let mnemonic = bip32::Mnemonic::new(config.mnemonic.trim(), Default::default())
+            .map_err(|_| anyhow!("Invalid CosmWasm sink mnemonic"))?;
+        let path = config
+            .derivation_path
+            .parse()
+            .map_err(|e| anyhow!("Invalid derivation path {}: {}", config.derivation_path, e))?;
+        let signing_key = SigningKey::derive_from_path(mnemonic.to_seed(""), &path)
+            .map_err(|e| anyhow!("Failed to derive CosmWasm sink key: {}", e))?;
+        let sender = signing_key
+            .public_key()
+            .account_id(config.account_prefix)
+            .map_err(|e| anyhow!("Invalid account prefix {}: {}", config.account_prefix, e))?;
+        let contract = config
+            .contract
+            .parse()
+            .map_err(|e| anyhow!("Invalid contract address {}: {}", config.contract, e))?;
+        let chain_id = config
+            .chain_id
+            .parse()
+            .map_err(|e| anyhow!("Invalid chain id {}: {}", config.chain_id, e))?;
+
+        Ok(CosmwasmSink {
+            lcd_url: config.lcd_url.trim_end_matches('/').to_string(),
+            chain_id,
+            contract,
+            sender,
+            signing_key,
+            fee: config.fee,
+            execute_msg: config.execute_msg.to_string(),
+            confirm_timeout: Duration::from_secs(config.confirm_timeout_secs),
+            latest_only: config.latest_only,
+            client: config.client,
+            account: Mutex::new(None),
+        })
+    }
+
+    /// Account number and current sequence of the sender
+    async fn query_account(&self) -> Result<(u64, u64)> {
+        let url = format!(
+            "{}/cosmos/auth/v1beta1/accounts/{}",
+            self.lcd_url, self.sender
+        );
+        let response: serde_json::Value = self
+            .client
+            .get(&url)
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+
+        let field = |name: &str| -> Result<u64> {
+            response["account"][name]
+                .as_str()
+                .and_then(|value| value.parse().ok())
+                .ok_or_else(|| anyhow!("Account {} has no {}", self.sender, name))
+        };
+        Ok((field("account_number")?, field("sequence")?))
+    }
+
+    fn sign(
+        &self,
+        payload: &serde_json::Value,
+        account_number: u64,
+        sequence: u64,
+    ) -> Result<Vec<u8>> {
+        let report = |e: cosmrs::ErrorReport| anyhow!("Failed to build transaction: {}", e);
+
+        let message = MsgExecuteContract {
+            sender: self.sender.clone(),
+            contract: self.contract.clone(),
+            msg: serde_json::to_vec(&json!({ self.execute_msg.as_str(): payload }))?,
+            funds: Vec::new(),
+        }
+        .to_any()
+        .map_err(report)?;
+        let body = tx::Body::new(vec![message], "", 0u32);
+
+        let fee = Coin {
+            denom: self
+                .fee
+                .denom
+                .parse()
+                .map_err(|e| anyhow!("Invalid fee denom {}: {}", self.fee.denom, e))?,
+            amount: self.fee.amount,
+        };
+        let auth_info = SignerInfo::single_direct(Some(self.signing_key.public_key()), sequence)
+            .auth_info(Fee::from_amount_and_gas(fee, self.fee.gas_limit));
+
+        let sign_doc =
+            SignDoc::new(&body, &auth_info, &self.chain_id, account_number).map_err(report)?;
+        sign_doc
+            .sign(&self.signing_key)
+            .and_then(|raw| raw.to_bytes())
+            .map_err(report)
+    }
+
+    async fn broadcast(&self, tx_bytes: &[u8]) -> Result<String> {
+        let response: serde_json::Value = self
+            .client
+            .post(format!("{}/cosmos/tx/v1beta1/txs", self.lcd_url))
+            .json(&json!({
+                "tx_bytes": BASE64.encode(tx_bytes),
+                "mode": "BROADCAST_MODE_SYNC",
+            }))
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+
+        let tx_response = &response["tx_response"];
+        let code = tx_response["code"].as_u64().unwrap_or_default();
+        if code != 0 {
+            return Err(anyhow!(
+                "Transaction rejected with code {}: {}",
+                code,
+                tx_response["raw_log"].as_str().unwrap_or_default()
+            ));
+        }
+        tx_response["txhash"]
+            .as_str()
+            .map(str::to_string)
+            .ok_or_else(|| anyhow!("Broadcast response has no transaction hash"))
+    }
+
+    /// Waits for the transaction to be included, returning its block height
+    async fn confirm(&self, tx_hash: &str) -> Result<u64> {
+        let url = format!("{}/cosmos/tx/v1beta1/txs/{}", self.lcd_url, tx_hash);
+        let deadline = tokio::time::Instant::now() + self.confirm_timeout;
+
+        while tokio::time::Instant::now() < deadline {
+            tokio::time::sleep(Duration::from_secs(2)).await;
+            let response = match self.client.get(&url).send().await {
+                Ok(response) if response.status().is_success() => response,
+                // Not indexed yet, or a transient error
+                _ => continue,
+            };
+
+            let response: serde_json::Value = response.json().await?;
+            let tx_response = &response["tx_response"];
+            let code = tx_response["code"].as_u64().unwrap_or_default();
+            if code != 0 {
+                return Err(anyhow!(
+                    "Transaction {} failed with code {}: {}",
+                    tx_hash,
+                    code,
+                    tx_response["raw_log"].as_str().unwrap_or_default()
+                ));
+            }
+            return Ok(tx_response["height"]
+                .as_str()
+                .and_then(|height| height.parse().ok())
+                .unwrap_or_default());
+        }
+
+        Err(anyhow!(
+            "Transaction {} was not included within {:?}",
+            tx_hash,
+            self.confirm_timeout
+        ))
+    }
+}
+
+#[async_trait]
+impl ProofSink for CosmwasmSink {
+    fn describe(&self) -> String {
+        format!("CosmWasm contract {} on {}", self.contract, self.chain_id)
+    }
+
+    fn latest_only(&self) -> bool {
+        self.latest_only
+    }
+
+    async fn submit(&self, payload: &serde_json::Value, height: u64) -> Result<()> {
+        let mut account = self.account.lock().await;
+        let (account_number, sequence) = match *account {
+            Some(account) => account,
+            None => self.query_account().await?,
+        };
+
+        let tx_bytes = self.sign(payload, account_number, sequence)?;
+        let tx_hash = match self.broadcast(&tx_bytes).await {
+            Ok(tx_hash) => tx_hash,
+            Err(e) => {
+                // Most likely a sequence mismatch, so query it before the next one
+                *account = None;
+                return Err(e);
+            }
+        };
+        *account = Some((account_number, sequence + 1));
+        info!(
+            "🌌 Sent proof at height {} in transaction {} (sequence {})",
+            height, tx_hash, sequence
+        );
+
+        match self.confirm(&tx_hash).await {
+            Ok(block) => {
+                info!("✅ Proof at height {} included in block {}", height, block);
+                Ok(())
+            }
+            Err(e) => {
+                // A failed execution still consumed the sequence, a dropped one didn't
+                warn!("⚠️  {}", e);
+                *account = None;
+                Err(e)
+            }
+        }
+    }
+}
+
+/// Reads the mnemonic from the configured key source, or `COSMOS_MNEMONIC`
+pub fn load_mnemonic(key: Option<&CosmosKey>, contract: &str) -> Result<String> {
+    match key {
+        Some(CosmosKey::Mnemonic(mnemonic)) => Ok(mnemonic.clone()),
+        Some(CosmosKey::Keyring { service, user }) => keyring::Entry::new(service, user)
+            .and_then(|entry| entry.get_password())
+            .map_err(|e| {
+                anyhow!(
+                    "Failed to read {}/{} from the keyring: {}",
+                    service,
+                    user,
+                    e
+                )
+            }),
+        None => std::env::var("COSMOS_MNEMONIC")
+            .map_err(|_| anyhow!("CosmWasm sink for {} has no mnemonic", contract)),
+    }
+}
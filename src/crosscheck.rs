@@ -0,0 +1,174 @@
+use anyhow::{Result, anyhow};
+use std::fmt;
+use std::time::Duration;
+use tracing::{info, warn};
+
+use crate::config::{HeliosRoot, MODE, RootCheckConfig, TendermintRoot};
+use crate::relayer::http_client;
+
+/// A proof's decoded root differs from the one the chain reports for its height, so the
+/// prover is at fault
+#[derive(Debug, Clone)]
+pub struct RootDivergence {
+    pub height: u64,
+    pub expected: String, // hex encoded
+    pub decoded: String,  // hex encoded
+}
+
+impl fmt::Display for RootDivergence {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Proof root {} at height {} differs from {} reported by the chain",
+            self.decoded, self.height, self.expected
+        )
+    }
+}
+
+impl std::error::Error for RootDivergence {}
+
+/// Looks up the root a proof should carry on the chain itself: in Helios mode, the
+/// configured root of the beacon block at the proof's slot; in Tendermint mode, the
+/// configured hash of the CometBFT block at the proof's height
+pub struct RootCrossCheck {
+    mode: MODE,
+    rpc_url: String,
+    helios_root: HeliosRoot,
+    tendermint_root: TendermintRoot,
+    required: bool,
+    client: reqwest::Client,
+}
+
+impl RootCrossCheck {
+    pub fn new(mode: MODE, config: &RootCheckConfig) -> Result<Self> {
+        Ok(RootCrossCheck {
+            mode,
+            rpc_url: config.rpc_url.trim_end_matches('/').to_string(),
+            helios_root: config.helios_root,
+            tendermint_root: config.tendermint_root,
+            required: config.required,
+            client: http_client(None, Some(Duration::from_secs(config.timeout_secs)))?,
+        })
+    }
+
+    /// The chain's root at `height`, or `None` when there is no block there, e.g. a
+    /// missed slot or a height the CometBFT node has pruned or not reached
+    pub async fn expected_root(&self, height: u64) -> Result<Option<Vec<u8>>> {
+        let (path, pointer) = match self.mode {
+            MODE::HELIOS => match self.helios_root {
+                HeliosRoot::ExecutionStateRoot => (
+                    format!("/eth/v2/beacon/blocks/{}", height),
+                    "/data/message/body/execution_payload/state_root",
+                ),
+                HeliosRoot::BeaconStateRoot => (
+                    format!("/eth/v1/beacon/headers/{}", height),
+                    "/data/header/message/state_root",
+                ),
+                HeliosRoot::BeaconBlockRoot => {
+                    (format!("/eth/v1/beacon/headers/{}", height), "/data/root")
+                }
+            },
+            MODE::TENDERMINT => (
+                format!("/block?height={}", height),
+                match self.tendermint_root {
+                    TendermintRoot::AppHash => "/result/block/header/app_hash",
+                    TendermintRoot::BlockHash => "/result/block_id/hash",
+                },
+            ),
+        };
+
+        let response = self
+            .client
+            .get(format!("{}{}", self.rpc_url, path))
+            .send()
+            .await?;
+        if response.status() == reqwest::StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
+        // CometBFT answers a height it doesn't have with a JSON-RPC error, with HTTP 500
+        // on recent versions
+        let response: serde_json::Value = match self.mode {
+            MODE::TENDERMINT => response.json().await?,
+            MODE::HELIOS => response.error_for_status()?.json().await?,
+        };
+        if let Some(error) = response.get("error") {
+            let data = error
+                .get("data")
+                .and_then(|data| data.as_str())
+                .unwrap_or_default();
+            if data.contains("height") {
+                return Ok(None);
+            }
+            return Err(anyhow!("{} failed: {}", path, error));
+        }
+        let root = response
+            .pointer(pointer)
+            .and_then(|value| value.as_str())
+            .ok_or_else(|| anyhow!("{} response has no {}", path, pointer))?;
+        // Beacon APIs prefix their roots with 0x, CometBFT's are bare upper-case hex
+        Ok(Some(hex::decode(root.trim_start_matches("0x"))?))
+    }
+
+    /// Fails with a `RootDivergence` when the chain reports a different root. A root that
+    /// can't be looked up only fails the check when checks are required.
+    pub async fn check(&self, height: u64, root: &[u8]) -> Result<()> {
+        let expected = match self.expected_root(height).await {
+            Ok(Some(expected)) => expected,
+            Ok(None) if self.required => {
+                return Err(anyhow!("The chain has no block at height {}", height));
+            }
+            Err(e) if self.required => {
+                return Err(anyhow!(
+                    "Failed to look up the root at height {}: {}",
+                    height,
+                    e
+                ));
+            }
+            Ok(None) => {
+                warn!(
+                    "⚠️  The chain has no block at height {}, letting it through unchecked",
+                    height
+                );
+                return Ok(());
+            }
+            Err(e) => {
+                warn!(
+                    "⚠️  Failed to look up the root at height {}, letting it through unchecked: {}",
+                    height, e
+                );
+                return Ok(());
+            }
+        };
+        compare(height, &expected, root)?;
+        info!("🔎 Root at height {} matches the chain", height);
+        Ok(())
+    }
+}
+
+fn compare(height: u64, expected: &[u8], decoded: &[u8]) -> Result<()> {
+    if expected == decoded {
+        return Ok(());
+    }
+    Err(RootDivergence {
+        height,
+        expected: hex::encode(expected),
+        decoded: hex::encode(decoded),
+    }
+    .into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_divergence_is_typed() {
+        assert!(compare(7, &[1; 32], &[1; 32]).is_ok());
+
+        let error = compare(7, &[1; 32], &[2; 32]).unwrap_err();
+        let divergence = error.downcast_ref::<RootDivergence>().unwrap();
+        assert_eq!(divergence.height, 7);
+        assert_eq!(divergence.expected, hex::encode([1; 32]));
+        assert_eq!(divergence.decoded, hex::encode([2; 32]));
+    }
+}
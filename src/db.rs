@@ -1,8 +1,13 @@
 use anyhow::Result;
 use chrono::{DateTime, Utc};
-use rusqlite::{Connection, params};
+use rusqlite::{Connection, OpenFlags, Row, params};
 use serde::{Deserialize, Serialize};
-use std::sync::Mutex;
+use std::path::Path;
+use std::sync::{Mutex, MutexGuard};
+use tracing::{info, warn};
+
+use crate::config::SqliteOptions;
+use crate::proof_id::{HashAlgorithm, ProofId};
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct HealthCheckData {
@@ -17,23 +22,739 @@ pub struct PreviousProof {
     pub timestamp: DateTime<Utc>,
 }
 
+/// One entry in the append-only proof history, keyed by height
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProofRecord {
+    /// Monotonic sequence number assigned by the storage on insert, never reused even
+    /// when proofs are deleted; ignored when inserting
+    pub seq: u64,
+    pub height: u64,
+    pub proof: Vec<u8>,
+    pub public_values: Vec<u8>,
+    pub root: Vec<u8>,
+    /// Canonical id of `proof`, hashed with the domain's `proof_id_algorithm`
+    pub proof_id: ProofId,
+    pub timestamp: DateTime<Utc>,
+}
+
+/// A payload persisted before submission, so it survives a crash before the registry accepts it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PendingSubmission {
+    pub height: u64,
+    /// The JSON payload exactly as it will be sent
+    pub payload: String,
+    pub created_at: DateTime<Utc>,
+}
+
+/// A payload the registry kept rejecting after every retry, held for an operator to
+/// inspect and resubmit
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FailedSubmission {
+    /// Assigned by the storage; ignored when recording
+    pub id: u64,
+    pub height: u64,
+    pub payload: String,
+    /// The last error seen
+    pub error: String,
+    /// Submission attempts made so far, across retries of the same payload
+    pub attempts: u32,
+    pub failed_at: DateTime<Utc>,
+}
+
+/// Where a fired alert stands in the on-call workflow
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AlertState {
+    Firing,
+    /// Still firing, but someone is on it, so it isn't repeated
+    Acknowledged,
+    Resolved,
+}
+
+impl AlertState {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AlertState::Firing => "firing",
+            AlertState::Acknowledged => "acknowledged",
+            AlertState::Resolved => "resolved",
+        }
+    }
+
+    pub fn parse(value: &str) -> Result<Self> {
+        match value {
+            "firing" => Ok(AlertState::Firing),
+            "acknowledged" => Ok(AlertState::Acknowledged),
+            "resolved" => Ok(AlertState::Resolved),
+            other => Err(anyhow::anyhow!("Unknown alert state {}", other)),
+        }
+    }
+}
+
+/// An alert sent to the webhooks, with what happened to it since
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AlertRecord {
+    /// Assigned by the storage; ignored when inserting
+    pub id: u64,
+    /// The condition that fired, e.g. `send_failure`
+    pub condition: String,
+    pub message: String,
+    pub state: AlertState,
+    pub fired_at: DateTime<Utc>,
+    /// Notifications sent, counting repeats
+    pub notifications: u32,
+    pub last_notified_at: DateTime<Utc>,
+    pub acknowledged_at: Option<DateTime<Utc>>,
+    pub acknowledged_by: Option<String>,
+    pub resolved_at: Option<DateTime<Utc>>,
+}
+
+/// One POST of a payload to a registry and what the registry answered
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RelayAttempt {
+    /// Assigned by the storage; ignored when inserting
+    pub id: u64,
+    pub height: u64,
+    pub endpoint: String,
+    /// 1 for the first try, counting up through retries
+    pub attempt: u32,
+    /// SHA-256 of the payload as posted (after any encryption)
+    pub payload_hash: String,
+    /// `None` when no response arrived
+    pub status: Option<u16>,
+    /// The response body, or the error when no response arrived
+    pub response: String,
+    pub latency_ms: u64,
+    pub attempted_at: DateTime<Utc>,
+}
+
+/// Where a submission stands with a registry that confirms asynchronously
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ConfirmationState {
+    /// Submitted, the registry hasn't confirmed it yet
+    Pending,
+    Confirmed,
+    /// The registry failed it or never confirmed it, through every resubmission
+    Failed,
+}
+
+impl ConfirmationState {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ConfirmationState::Pending => "pending",
+            ConfirmationState::Confirmed => "confirmed",
+            ConfirmationState::Failed => "failed",
+        }
+    }
+
+    pub fn parse(value: &str) -> Result<Self> {
+        match value {
+            "pending" => Ok(ConfirmationState::Pending),
+            "confirmed" => Ok(ConfirmationState::Confirmed),
+            "failed" => Ok(ConfirmationState::Failed),
+            other => Err(anyhow::anyhow!("Unknown confirmation state {}", other)),
+        }
+    }
+}
+
+/// A submission to a registry with `registry_confirmations`, and whether the registry
+/// has confirmed it. There is one per endpoint and height.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RelayConfirmation {
+    pub height: u64,
+    pub endpoint: String,
+    /// Hex encoded root the registry must confirm
+    pub root: String,
+    pub state: ConfirmationState,
+    /// 1 for the first submission, counting up through resubmissions
+    pub submissions: u32,
+    /// Why the latest submission wasn't confirmed
+    pub error: Option<String>,
+    pub submitted_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// A move of a domain's primary registry to another endpoint, see `/admin/registry/cutover`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CutoverRecord {
+    /// Assigned by the storage; ignored when inserting
+    pub id: u64,
+    pub from_endpoint: String,
+    pub to_endpoint: String,
+    /// Settings of the new registry, as `registry_latest_only` and
+    /// `registry_validate_endpoint` are for the configured one
+    pub latest_only: bool,
+    pub validate_endpoint: Option<String>,
+    /// Queued submissions sent to the old registry before switching
+    pub drained: u64,
+    /// Height of the latest proof, replayed to the new registry; `None` if there was none
+    pub replayed_height: Option<u64>,
+    pub cut_over_at: DateTime<Utc>,
+}
+
+/// Decode failures kept per domain, oldest dropped first
+pub const DECODE_FAILURES_KEPT: u64 = 100;
+
+/// A fetched proof whose public values or payload couldn't be decoded
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DecodeFailure {
+    /// Assigned by the storage; ignored when inserting
+    pub id: u64,
+    pub error: String,
+    /// Endpoint that served the proof, when known
+    pub prover: Option<String>,
+    /// Hex encoded, as far as they could be read
+    pub public_values: String,
+    pub failed_at: DateTime<Utc>,
+}
+
+impl DecodeFailure {
+    pub fn new(error: &anyhow::Error, prover: Option<&str>, public_values: &[u8]) -> Self {
+        DecodeFailure {
+            id: 0,
+            error: error.to_string(),
+            prover: prover.map(str::to_string),
+            public_values: hex::encode(public_values),
+            failed_at: Utc::now(),
+        }
+    }
+}
+
+/// One periodic reading of a metric, see `timeseries::METRICS`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MetricSample {
+    pub metric: String,
+    pub value: f64,
+    pub sampled_at: DateTime<Utc>,
+}
+
+/// Persistence used by the relay and health-check loops, one instance per domain
+pub trait Storage: Send + Sync {
+    fn update_health_check(&self, data: &HealthCheckData) -> Result<()>;
+
+    fn get_latest_health_check(&self) -> Result<Option<HealthCheckData>>;
+
+    fn update_previous_proof(&self, proof: &PreviousProof) -> Result<()>;
+
+    fn get_previous_proof(&self) -> Result<Option<PreviousProof>>;
+
+    /// Appends a proof to the history and returns its sequence number. Returns `None`
+    /// if a proof for that height was already recorded, in which case the existing
+    /// entry is left untouched.
+    fn insert_proof(&self, record: &ProofRecord) -> Result<Option<u64>>;
+
+    fn get_proof_by_height(&self, height: u64) -> Result<Option<ProofRecord>>;
+
+    fn get_latest_proof(&self) -> Result<Option<ProofRecord>>;
+
+    /// Returns all proofs with `from <= height <= to`, oldest first
+    fn get_proofs_in_range(&self, from: u64, to: u64) -> Result<Vec<ProofRecord>>;
+
+    /// Returns one page of the proofs with `from <= height <= to`, oldest first, skipping
+    /// the first `offset`
+    fn get_proofs_page(
+        &self,
+        from: u64,
+        to: u64,
+        limit: usize,
+        offset: usize,
+    ) -> Result<Vec<ProofRecord>>;
+
+    /// Returns the `limit` highest proofs, oldest first
+    fn get_latest_proofs(&self, limit: usize) -> Result<Vec<ProofRecord>>;
+
+    /// Returns up to `limit` proofs with a sequence number above `since`, in sequence order
+    fn get_proofs_since_seq(&self, since: u64, limit: usize) -> Result<Vec<ProofRecord>>;
+
+    /// Drops every proof at or above `height`, used when the chain rolls back
+    fn delete_proofs_from_height(&self, height: u64) -> Result<usize>;
+
+    /// Deletes everything but the `keep` highest proofs, returning how many were removed
+    fn prune_proof_history(&self, keep: u64) -> Result<usize>;
+
+    /// Returns the height and record time of every stored proof, lowest height first,
+    /// without reading the proofs themselves
+    fn get_proof_timestamps(&self) -> Result<Vec<(u64, DateTime<Utc>)>>;
+
+    /// Deletes the proofs at `heights`, returning how many were removed
+    fn delete_proofs(&self, heights: &[u64]) -> Result<usize>;
+
+    /// Queues a payload for submission. A payload already queued for the same height is kept.
+    fn enqueue_submission(&self, submission: &PendingSubmission) -> Result<()>;
+
+    /// Returns the queued payloads, lowest height first
+    fn get_pending_submissions(&self) -> Result<Vec<PendingSubmission>>;
+
+    /// Removes queued payloads at or below `height`, once it has been submitted
+    fn remove_pending_submissions_through(&self, height: u64) -> Result<usize>;
+
+    /// Dead-letters a payload whose submission failed every retry and returns its id. A
+    /// payload already dead-lettered for the same height keeps its id; its error and
+    /// failure time are replaced and the attempts added to its count.
+    fn record_failed_submission(&self, failed: &FailedSubmission) -> Result<u64>;
+
+    /// Returns the dead-lettered payloads, oldest first
+    fn get_failed_submissions(&self) -> Result<Vec<FailedSubmission>>;
+
+    fn get_failed_submission(&self, id: u64) -> Result<Option<FailedSubmission>>;
+
+    /// Removes a dead-lettered payload, returning whether it existed
+    fn remove_failed_submission(&self, id: u64) -> Result<bool>;
+
+    /// Records a fired alert and returns its id
+    fn insert_alert(&self, alert: &AlertRecord) -> Result<u64>;
+
+    /// Overwrites the alert with `alert.id`, returning whether it existed
+    fn update_alert(&self, alert: &AlertRecord) -> Result<bool>;
+
+    fn get_alert(&self, id: u64) -> Result<Option<AlertRecord>>;
+
+    /// Returns the `limit` most recently fired alerts, newest first
+    fn get_alerts(&self, limit: usize) -> Result<Vec<AlertRecord>>;
+
+    /// Records a registry submission attempt and returns its id
+    fn insert_relay_attempt(&self, attempt: &RelayAttempt) -> Result<u64>;
+
+    /// Returns the `limit` most recent attempts, newest first, optionally for one height only
+    fn get_relay_attempts(&self, limit: usize, height: Option<u64>) -> Result<Vec<RelayAttempt>>;
+
+    /// Records where a submission stands, replacing the entry for its endpoint and height
+    fn record_relay_confirmation(&self, confirmation: &RelayConfirmation) -> Result<()>;
+
+    /// Returns the `limit` most recently updated confirmations, newest first, optionally
+    /// for one height only
+    fn get_relay_confirmations(
+        &self,
+        limit: usize,
+        height: Option<u64>,
+    ) -> Result<Vec<RelayConfirmation>>;
+
+    /// Returns the id assigned to the cutover
+    fn insert_cutover(&self, cutover: &CutoverRecord) -> Result<u64>;
+
+    /// Most recent cutovers first
+    fn get_cutovers(&self, limit: usize) -> Result<Vec<CutoverRecord>>;
+
+    /// Returns the id assigned to the failure. Only the newest `DECODE_FAILURES_KEPT`
+    /// are kept.
+    fn record_decode_failure(&self, failure: &DecodeFailure) -> Result<u64>;
+
+    /// Most recent failures first
+    fn get_decode_failures(&self, limit: usize) -> Result<Vec<DecodeFailure>>;
+
+    /// Appends sampled metric values
+    fn insert_metric_samples(&self, samples: &[MetricSample]) -> Result<()>;
+
+    /// Returns the samples of `metric` taken at or after `since`, oldest first
+    fn get_metric_samples(&self, metric: &str, since: DateTime<Utc>) -> Result<Vec<MetricSample>>;
+
+    /// Deletes samples taken before `before`, returning how many were removed
+    fn prune_metric_samples(&self, before: DateTime<Utc>) -> Result<usize>;
+
+    /// Writes a consistent copy of the whole database to `path`, which must not exist.
+    /// Only SQLite supports it; server-backed storage is backed up with its own tools.
+    fn backup(&self, path: &Path) -> Result<()>;
+
+    /// Clears the latest-value tables. The proof history, submission queue,
+    /// dead-letter queue, alert history, relay attempts and confirmations, decode
+    /// failures and metric samples are kept across restarts.
+    fn clear_all_tables(&self) -> Result<()>;
+}
+
+/// SQLite storage, the default backend
 pub struct Database {
     conn: Mutex<Connection>,
+    /// Read-only connection in WAL mode, so reads see the last commit instead of queuing
+    /// behind a write on `conn`
+    reader: Option<Mutex<Connection>>,
+}
+
+/// Every table's columns in the baseline schema, with the definition that adds one
+/// missing from a file written before versioned migrations, e.g. a restored backup.
+/// `None` marks columns that can't be added to an existing table (keys) or have no
+/// sensible value for existing rows.
+const SCHEMA: &[(&str, &[(&str, Option<&str>)])] = &[
+    (
+        "health_check",
+        &[
+            ("id", None),
+            ("current_height", None),
+            ("current_root", None),
+            ("timestamp", None),
+        ],
+    ),
+    (
+        "previous_proof",
+        &[("id", None), ("proof_data", None), ("timestamp", None)],
+    ),
+    (
+        "proofs",
+        &[
+            ("height", None),
+            ("proof", None),
+            ("public_values", None),
+            ("root", None),
+            ("timestamp", None),
+            // Numbered in height order by `init_tables` rather than added empty
+            ("seq", Some("INTEGER")),
+            // Read back as the default algorithm's id while empty, see `stored_proof_id`
+            ("proof_id", Some("TEXT")),
+        ],
+    ),
+    (
+        "pending_submissions",
+        &[("height", None), ("payload", None), ("created_at", None)],
+    ),
+    (
+        "failed_submissions",
+        &[
+            ("id", None),
+            ("height", None),
+            ("payload", None),
+            ("error", Some("TEXT NOT NULL DEFAULT ''")),
+            ("attempts", Some("INTEGER NOT NULL DEFAULT 1")),
+            ("failed_at", None),
+        ],
+    ),
+    (
+        "alerts",
+        &[
+            ("id", None),
+            ("condition", None),
+            ("message", None),
+            ("state", None),
+            ("fired_at", None),
+            ("notifications", Some("INTEGER NOT NULL DEFAULT 1")),
+            ("last_notified_at", None),
+            ("acknowledged_at", Some("TEXT")),
+            ("acknowledged_by", Some("TEXT")),
+            ("resolved_at", Some("TEXT")),
+        ],
+    ),
+    (
+        "relay_attempts",
+        &[
+            ("id", None),
+            ("height", None),
+            ("endpoint", None),
+            ("attempt", Some("INTEGER NOT NULL DEFAULT 1")),
+            ("payload_hash", Some("TEXT NOT NULL DEFAULT ''")),
+            ("status", Some("INTEGER")),
+            ("response", Some("TEXT NOT NULL DEFAULT ''")),
+            ("latency_ms", Some("INTEGER NOT NULL DEFAULT 0")),
+            ("attempted_at", None),
+        ],
+    ),
+    (
+        "relay_confirmations",
+        &[
+            ("height", None),
+            ("endpoint", None),
+            ("root", None),
+            ("state", None),
+            ("submissions", None),
+            ("error", None),
+            ("submitted_at", None),
+            ("updated_at", None),
+        ],
+    ),
+    (
+        "registry_cutovers",
+        &[
+            ("id", None),
+            ("from_endpoint", None),
+            ("to_endpoint", None),
+            ("latest_only", None),
+            ("validate_endpoint", None),
+            ("drained", None),
+            ("replayed_height", None),
+            ("cut_over_at", None),
+        ],
+    ),
+    (
+        "decode_failures",
+        &[
+            ("id", None),
+            ("error", None),
+            ("prover", None),
+            ("public_values", None),
+            ("failed_at", None),
+        ],
+    ),
+    (
+        "metric_samples",
+        &[("metric", None), ("value", None), ("sampled_at", None)],
+    ),
+    ("sequences", &[("name", None), ("value", None)]),
+];
+
+/// The version of the schema `init_tables` creates, which files from before versioned
+/// migrations are brought up to
+const BASELINE_VERSION: u32 = 1;
+
+/// A schema change applied once to each file, recorded in `schema_version`
+struct Migration {
+    version: u32,
+    description: &'static str,
+    sql: &'static str,
+}
+
+/// Every change since the baseline, in version order. Schema changes are appended here
+/// rather than made to `init_tables`; a released migration is never edited.
+const MIGRATIONS: &[Migration] = &[Migration {
+    version: 2,
+    description: "index relay confirmations by update time",
+    sql: "CREATE INDEX IF NOT EXISTS relay_confirmations_updated ON relay_confirmations (updated_at)",
+}];
+
+/// What an existing database file lacks compared to `SCHEMA`
+#[derive(Debug, Default)]
+struct SchemaGaps {
+    tables: Vec<&'static str>,
+    columns: Vec<(&'static str, &'static str, Option<&'static str>)>,
+}
+
+impl SchemaGaps {
+    fn is_empty(&self) -> bool {
+        self.tables.is_empty() && self.columns.is_empty()
+    }
+
+    fn describe(&self) -> String {
+        self.tables
+            .iter()
+            .map(|table| format!("table {}", table))
+            .chain(
+                self.columns
+                    .iter()
+                    .map(|(table, column, _)| format!("column {}.{}", table, column)),
+            )
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
 }
 
 impl Database {
+    /// Opens (or creates) the database, adding any tables and columns an older file lacks
+    /// and applying pending migrations
     pub fn new(db_path: &str) -> Result<Self> {
+        Self::open(db_path, true)
+    }
+
+    /// Like `new`, but with `repair_schema` off an older file missing tables or columns
+    /// fails to open, naming them, instead of being upgraded
+    pub fn open(db_path: &str, repair_schema: bool) -> Result<Self> {
+        Self::open_with(
+            db_path,
+            &SqliteOptions {
+                repair_schema,
+                ..SqliteOptions::default()
+            },
+        )
+    }
+
+    /// Opens the database with the configured journal mode, synchronous level and busy
+    /// timeout
+    pub fn open_with(db_path: &str, options: &SqliteOptions) -> Result<Self> {
         let conn = Connection::open(db_path)?;
+        conn.busy_timeout(std::time::Duration::from_millis(options.busy_timeout_ms))?;
+        // SQLite keeps its current mode when it can't switch, e.g. to WAL on a filesystem
+        // without shared memory, or in memory
+        let journal_mode: String = conn.pragma_update_and_check(
+            None,
+            "journal_mode",
+            options.journal_mode.as_str(),
+            |row| row.get(0),
+        )?;
+        if !journal_mode.eq_ignore_ascii_case(options.journal_mode.as_str()) {
+            warn!(
+                "⚠️  {} stays in {} journal mode rather than {}",
+                db_path,
+                journal_mode,
+                options.journal_mode.as_str()
+            );
+        }
+        conn.pragma_update(None, "synchronous", options.synchronous.as_str())?;
+        let mut db = Self::with_schema(conn, options.repair_schema)
+            .map_err(|e| anyhow::anyhow!("Failed to open database {}: {}", db_path, e))?;
+
+        // Other journal modes lock readers out during writes, so a second connection
+        // wouldn't help there
+        if journal_mode.eq_ignore_ascii_case("wal") {
+            let reader = Connection::open_with_flags(
+                db_path,
+                OpenFlags::SQLITE_OPEN_READ_ONLY | OpenFlags::SQLITE_OPEN_NO_MUTEX,
+            )?;
+            reader.busy_timeout(std::time::Duration::from_millis(options.busy_timeout_ms))?;
+            db.reader = Some(Mutex::new(reader));
+        }
+        Ok(db)
+    }
+
+    /// The connection reads go through: the read-only one when there is one
+    fn reader(&self) -> MutexGuard<'_, Connection> {
+        self.reader.as_ref().unwrap_or(&self.conn).lock().unwrap()
+    }
+
+    /// Puts a backup in place of the file at `target`, after checking that it is an intact
+    /// relayer database. The backup is copied next to `target` and renamed over it, and
+    /// any WAL left by the previous file is removed so it can't be replayed onto the
+    /// backup. Whatever has `target` open must be stopped first.
+    pub fn restore(backup: &Path, target: &Path) -> Result<()> {
+        if let Some(dir) = target.parent()
+            && !dir.as_os_str().is_empty()
+        {
+            std::fs::create_dir_all(dir)?;
+        }
+        let staged = sibling(target, ".restoring");
+        std::fs::copy(backup, &staged)
+            .map_err(|e| anyhow::anyhow!("Failed to copy {}: {}", backup.display(), e))?;
+
+        if let Err(e) = check_backup(&staged) {
+            remove_if_present(&staged)?;
+            return Err(anyhow::anyhow!(
+                "{} isn't a usable backup: {}",
+                backup.display(),
+                e
+            ));
+        }
+
+        for suffix in ["-wal", "-shm"] {
+            remove_if_present(&sibling(target, suffix))?;
+        }
+        std::fs::rename(&staged, target)?;
+        Ok(())
+    }
+
+    /// An empty database held in memory, gone once it is dropped
+    pub fn in_memory() -> Result<Self> {
+        Self::with_schema(Connection::open_in_memory()?, true)
+            .map_err(|e| anyhow::anyhow!("Failed to open in-memory database: {}", e))
+    }
+
+    fn with_schema(conn: Connection, repair_schema: bool) -> Result<Self> {
         let db = Database {
             conn: Mutex::new(conn),
+            reader: None,
         };
-        db.init_tables()?;
+        db.init_tables(repair_schema)?;
+        db.migrate()?;
         Ok(db)
     }
 
-    fn init_tables(&self) -> Result<()> {
+    /// The last migration applied to the file, or `None` for a new file or one from before
+    /// versioned migrations
+    fn schema_version(conn: &Connection) -> Result<Option<u32>> {
+        let versioned = conn
+            .prepare(
+                "SELECT 1 FROM sqlite_master WHERE type = 'table' AND name = 'schema_version'",
+            )?
+            .exists([])?;
+        if !versioned {
+            return Ok(None);
+        }
+        Ok(
+            conn.query_row("SELECT MAX(version) FROM schema_version", [], |row| {
+                row.get(0)
+            })?,
+        )
+    }
+
+    /// Applies every migration newer than the file, each in a transaction of its own
+    fn migrate(&self) -> Result<()> {
+        let mut conn = self.conn.lock().unwrap();
+        let version = Self::schema_version(&conn)?.unwrap_or(BASELINE_VERSION);
+        for migration in MIGRATIONS.iter().filter(|m| m.version > version) {
+            apply_migration(&mut conn, migration).map_err(|e| {
+                anyhow::anyhow!(
+                    "migration {} ({}) failed: {}",
+                    migration.version,
+                    migration.description,
+                    e
+                )
+            })?;
+            info!(
+                "🗄️  Applied database migration {}: {}",
+                migration.version, migration.description
+            );
+        }
+        Ok(())
+    }
+
+    /// Compares an existing file's tables and columns against `SCHEMA`. A new, empty
+    /// file has no gaps.
+    fn schema_gaps(conn: &Connection) -> Result<SchemaGaps> {
+        let mut gaps = SchemaGaps::default();
+        let existing: Vec<String> = conn
+            .prepare("SELECT name FROM sqlite_master WHERE type = 'table'")?
+            .query_map([], |row| row.get(0))?
+            .collect::<rusqlite::Result<_>>()?;
+        if existing.is_empty() {
+            return Ok(gaps);
+        }
+
+        for (table, columns) in SCHEMA {
+            if !existing.iter().any(|name| name == table) {
+                gaps.tables.push(table);
+                continue;
+            }
+            let present: Vec<String> = conn
+                .prepare("SELECT name FROM pragma_table_info(?1)")?
+                .query_map(params![table], |row| row.get(0))?
+                .collect::<rusqlite::Result<_>>()?;
+            for (column, definition) in *columns {
+                if !present.iter().any(|name| name == column) {
+                    gaps.columns.push((table, column, *definition));
+                }
+            }
+        }
+        Ok(gaps)
+    }
+
+    fn init_tables(&self, repair_schema: bool) -> Result<()> {
         let conn = self.conn.lock().unwrap();
 
+        let version = Self::schema_version(&conn)?;
+        let latest = MIGRATIONS.last().map_or(BASELINE_VERSION, |m| m.version);
+        if let Some(version) = version
+            && version > latest
+        {
+            return Err(anyhow::anyhow!(
+                "schema version {} was written by a newer relayer, this one knows up to {}",
+                version,
+                latest
+            ));
+        }
+
+        // Refuse a file we can't bring up to date before touching it, rather than failing
+        // on the first query that needs what it lacks. Versioned files were checked when
+        // they were first brought up to the baseline, and migrations keep them current.
+        let gaps = match version {
+            Some(_) => SchemaGaps::default(),
+            None => Self::schema_gaps(&conn)?,
+        };
+        if !gaps.is_empty() {
+            let unrepairable: Vec<String> = gaps
+                .columns
+                .iter()
+                .filter(|(_, _, definition)| definition.is_none())
+                .map(|(table, column, _)| format!("{}.{}", table, column))
+                .collect();
+            if !unrepairable.is_empty() {
+                return Err(anyhow::anyhow!(
+                    "schema is missing {}; {} can't be added to existing rows, so restore a newer backup or move the file aside",
+                    gaps.describe(),
+                    unrepairable.join(", ")
+                ));
+            }
+            if !repair_schema {
+                return Err(anyhow::anyhow!(
+                    "schema is missing {} and schema repair is disabled",
+                    gaps.describe()
+                ));
+            }
+            warn!("🩹 Database is missing {}, adding them", gaps.describe());
+        }
+
         // Create health_check table
         conn.execute(
             "CREATE TABLE IF NOT EXISTS health_check (
@@ -55,10 +776,246 @@ impl Database {
             [],
         )?;
 
+        // Create proofs table (append-only history)
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS proofs (
+                height INTEGER PRIMARY KEY,
+                proof BLOB NOT NULL,
+                public_values BLOB NOT NULL,
+                root BLOB NOT NULL,
+                timestamp TEXT NOT NULL,
+                seq INTEGER,
+                proof_id TEXT
+            )",
+            [],
+        )?;
+
+        // Histories created before sequence numbers get them in height order
+        let has_seq = conn
+            .prepare("SELECT 1 FROM pragma_table_info('proofs') WHERE name = 'seq'")?
+            .exists([])?;
+        if !has_seq {
+            conn.execute("ALTER TABLE proofs ADD COLUMN seq INTEGER", [])?;
+            conn.execute(
+                "UPDATE proofs SET seq = (SELECT COUNT(*) FROM proofs AS p WHERE p.height <= proofs.height)",
+                [],
+            )?;
+        }
+        conn.execute("CREATE INDEX IF NOT EXISTS proofs_seq ON proofs (seq)", [])?;
+
+        // Create pending_submissions table (payloads not yet accepted by the registry)
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS pending_submissions (
+                height INTEGER PRIMARY KEY,
+                payload TEXT NOT NULL,
+                created_at TEXT NOT NULL
+            )",
+            [],
+        )?;
+
+        // Create failed_submissions table (payloads that failed every retry)
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS failed_submissions (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                height INTEGER NOT NULL UNIQUE,
+                payload TEXT NOT NULL,
+                error TEXT NOT NULL,
+                attempts INTEGER NOT NULL,
+                failed_at TEXT NOT NULL
+            )",
+            [],
+        )?;
+
+        // Create alerts table (alert history and acknowledgements)
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS alerts (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                condition TEXT NOT NULL,
+                message TEXT NOT NULL,
+                state TEXT NOT NULL,
+                fired_at TEXT NOT NULL,
+                notifications INTEGER NOT NULL,
+                last_notified_at TEXT NOT NULL,
+                acknowledged_at TEXT,
+                acknowledged_by TEXT,
+                resolved_at TEXT
+            )",
+            [],
+        )?;
+
+        // Create relay_attempts table (every registry submission and its response)
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS relay_attempts (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                height INTEGER NOT NULL,
+                endpoint TEXT NOT NULL,
+                attempt INTEGER NOT NULL,
+                payload_hash TEXT NOT NULL,
+                status INTEGER,
+                response TEXT NOT NULL,
+                latency_ms INTEGER NOT NULL,
+                attempted_at TEXT NOT NULL
+            )",
+            [],
+        )?;
+        conn.execute(
+            "CREATE INDEX IF NOT EXISTS relay_attempts_height ON relay_attempts (height)",
+            [],
+        )?;
+
+        // Create relay_confirmations table (whether asynchronous registries confirmed a submission)
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS relay_confirmations (
+                height INTEGER NOT NULL,
+                endpoint TEXT NOT NULL,
+                root TEXT NOT NULL,
+                state TEXT NOT NULL,
+                submissions INTEGER NOT NULL,
+                error TEXT,
+                submitted_at TEXT NOT NULL,
+                updated_at TEXT NOT NULL,
+                PRIMARY KEY (endpoint, height)
+            )",
+            [],
+        )?;
+
+        // Create registry_cutovers table (moves of the primary registry to another endpoint)
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS registry_cutovers (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                from_endpoint TEXT NOT NULL,
+                to_endpoint TEXT NOT NULL,
+                latest_only INTEGER NOT NULL,
+                validate_endpoint TEXT,
+                drained INTEGER NOT NULL,
+                replayed_height INTEGER,
+                cut_over_at TEXT NOT NULL
+            )",
+            [],
+        )?;
+
+        // Create decode_failures table (proofs whose public values couldn't be decoded)
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS decode_failures (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                error TEXT NOT NULL,
+                prover TEXT,
+                public_values TEXT NOT NULL,
+                failed_at TEXT NOT NULL
+            )",
+            [],
+        )?;
+
+        // Create metric_samples table (periodic metric readings). Timestamps are stored with
+        // second precision so they sort as text.
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS metric_samples (
+                metric TEXT NOT NULL,
+                value REAL NOT NULL,
+                sampled_at TEXT NOT NULL
+            )",
+            [],
+        )?;
+        conn.execute(
+            "CREATE INDEX IF NOT EXISTS metric_samples_metric ON metric_samples (metric, sampled_at)",
+            [],
+        )?;
+
+        // Create sequences table, holding the last sequence number handed out
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS sequences (
+                name TEXT PRIMARY KEY,
+                value INTEGER NOT NULL
+            )",
+            [],
+        )?;
+        conn.execute(
+            "INSERT OR IGNORE INTO sequences (name, value) SELECT 'proofs', COALESCE(MAX(seq), 0) FROM proofs",
+            [],
+        )?;
+
+        // Tables missing from the file were created above, and `seq` was numbered
+        for (table, column, definition) in gaps.columns {
+            let Some(definition) = definition else {
+                continue;
+            };
+            let present = conn
+                .prepare("SELECT 1 FROM pragma_table_info(?1) WHERE name = ?2")?
+                .exists(params![table, column])?;
+            if !present {
+                conn.execute(
+                    &format!("ALTER TABLE {} ADD COLUMN {} {}", table, column, definition),
+                    [],
+                )?;
+            }
+        }
+
+        // Create schema_version table (migrations applied, starting with the baseline above)
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS schema_version (
+                version INTEGER PRIMARY KEY,
+                description TEXT NOT NULL,
+                applied_at TEXT NOT NULL
+            )",
+            [],
+        )?;
+        if version.is_none() {
+            conn.execute(
+                "INSERT INTO schema_version (version, description, applied_at) VALUES (?1, 'baseline schema', ?2)",
+                params![BASELINE_VERSION, Utc::now().to_rfc3339()],
+            )?;
+        }
+
         Ok(())
     }
+}
+
+/// Fails unless the file at `path` is an intact database with a proof history
+fn check_backup(path: &Path) -> Result<()> {
+    let conn = Connection::open(path)?;
+    let integrity: String = conn.query_row("PRAGMA integrity_check", [], |row| row.get(0))?;
+    if integrity != "ok" {
+        return Err(anyhow::anyhow!("integrity check failed: {}", integrity));
+    }
+    let has_proofs = conn
+        .prepare("SELECT 1 FROM sqlite_master WHERE type = 'table' AND name = 'proofs'")?
+        .exists([])?;
+    if !has_proofs {
+        return Err(anyhow::anyhow!("it has no proof history"));
+    }
+    Ok(())
+}
+
+/// `path` with `suffix` appended to its file name, as SQLite names its WAL files
+fn sibling(path: &Path, suffix: &str) -> std::path::PathBuf {
+    let mut name = path.as_os_str().to_owned();
+    name.push(suffix);
+    name.into()
+}
+
+fn remove_if_present(path: &Path) -> Result<()> {
+    match std::fs::remove_file(path) {
+        Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e.into()),
+        _ => Ok(()),
+    }
+}
+
+fn apply_migration(conn: &mut Connection, migration: &Migration) -> rusqlite::Result<()> {
+    let tx = conn.transaction()?;
+    tx.execute_batch(migration.sql)?;
+    tx.execute(
+        "INSERT INTO schema_version (version, description, applied_at) VALUES (?1, ?2, ?3)",
+        params![
+            migration.version,
+            migration.description,
+            Utc::now().to_rfc3339()
+        ],
+    )?;
+    tx.commit()
+}
 
-    pub fn update_health_check(&self, data: &HealthCheckData) -> Result<()> {
+impl Storage for Database {
+    fn update_health_check(&self, data: &HealthCheckData) -> Result<()> {
         let conn = self.conn.lock().unwrap();
 
         // Delete existing record and insert new one (keeping only latest)
@@ -76,8 +1033,8 @@ impl Database {
         Ok(())
     }
 
-    pub fn get_latest_health_check(&self) -> Result<Option<HealthCheckData>> {
-        let conn = self.conn.lock().unwrap();
+    fn get_latest_health_check(&self) -> Result<Option<HealthCheckData>> {
+        let conn = self.reader();
         let mut stmt = conn.prepare(
             "SELECT current_height, current_root, timestamp FROM health_check ORDER BY id DESC LIMIT 1"
         )?;
@@ -100,7 +1057,7 @@ impl Database {
         }
     }
 
-    pub fn update_previous_proof(&self, proof: &PreviousProof) -> Result<()> {
+    fn update_previous_proof(&self, proof: &PreviousProof) -> Result<()> {
         let conn = self.conn.lock().unwrap();
 
         // Delete existing record and insert new one (keeping only latest)
@@ -114,8 +1071,8 @@ impl Database {
         Ok(())
     }
 
-    pub fn get_previous_proof(&self) -> Result<Option<PreviousProof>> {
-        let conn = self.conn.lock().unwrap();
+    fn get_previous_proof(&self) -> Result<Option<PreviousProof>> {
+        let conn = self.reader();
         let mut stmt = conn
             .prepare("SELECT proof_data, timestamp FROM previous_proof ORDER BY id DESC LIMIT 1")?;
 
@@ -135,40 +1092,734 @@ impl Database {
         }
     }
 
-    pub fn clear_all_tables(&self) -> Result<()> {
-        let conn = self.conn.lock().unwrap();
+    fn insert_proof(&self, record: &ProofRecord) -> Result<Option<u64>> {
+        let mut conn = self.conn.lock().unwrap();
+        let tx = conn.transaction()?;
 
-        // Clear health_check table
-        conn.execute("DELETE FROM health_check", [])?;
+        let seq: u64 = tx.query_row(
+            "SELECT value + 1 FROM sequences WHERE name = 'proofs'",
+            [],
+            |row| row.get(0),
+        )?;
+        let inserted = tx.execute(
+            "INSERT OR IGNORE INTO proofs (height, proof, public_values, root, timestamp, seq, proof_id) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+            params![
+                record.height,
+                record.proof,
+                record.public_values,
+                record.root,
+                record.timestamp.to_rfc3339(),
+                seq,
+                record.proof_id.as_str()
+            ],
+        )?;
+        if inserted == 0 {
+            return Ok(None);
+        }
 
-        // Clear previous_proof table
-        conn.execute("DELETE FROM previous_proof", [])?;
+        tx.execute(
+            "UPDATE sequences SET value = ?1 WHERE name = 'proofs'",
+            params![seq],
+        )?;
+        tx.commit()?;
 
-        Ok(())
+        Ok(Some(seq))
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use tempfile::NamedTempFile;
+    fn get_proof_by_height(&self, height: u64) -> Result<Option<ProofRecord>> {
+        let conn = self.reader();
+        let mut stmt = conn.prepare(
+            "SELECT height, proof, public_values, root, timestamp, seq, proof_id FROM proofs WHERE height = ?1",
+        )?;
 
-    #[test]
-    fn test_database_operations() -> Result<()> {
-        // Create a temporary database file
-        let temp_file = NamedTempFile::new()?;
-        let db_path = temp_file.path().to_str().unwrap();
+        let mut rows = stmt.query(params![height])?;
 
-        let db = Database::new(db_path)?;
+        match rows.next()? {
+            Some(row) => Ok(Some(read_proof_record(row)?)),
+            None => Ok(None),
+        }
+    }
 
-        // Test health check data
-        let health_data = HealthCheckData {
-            current_height: 12345,
-            current_root: vec![1, 2, 3, 4, 5],
-            timestamp: Utc::now(),
-        };
+    fn get_latest_proof(&self) -> Result<Option<ProofRecord>> {
+        let conn = self.reader();
+        let mut stmt = conn.prepare(
+            "SELECT height, proof, public_values, root, timestamp, seq, proof_id FROM proofs ORDER BY height DESC LIMIT 1",
+        )?;
 
-        db.update_health_check(&health_data)?;
+        let mut rows = stmt.query([])?;
+
+        match rows.next()? {
+            Some(row) => Ok(Some(read_proof_record(row)?)),
+            None => Ok(None),
+        }
+    }
+
+    fn get_proofs_in_range(&self, from: u64, to: u64) -> Result<Vec<ProofRecord>> {
+        let conn = self.reader();
+        let mut stmt = conn.prepare(
+            "SELECT height, proof, public_values, root, timestamp, seq, proof_id FROM proofs WHERE height >= ?1 AND height <= ?2 ORDER BY height ASC",
+        )?;
+
+        let mut rows = stmt.query(params![from, to])?;
+        let mut records = Vec::new();
+        while let Some(row) = rows.next()? {
+            records.push(read_proof_record(row)?);
+        }
+
+        Ok(records)
+    }
+
+    fn get_proofs_page(
+        &self,
+        from: u64,
+        to: u64,
+        limit: usize,
+        offset: usize,
+    ) -> Result<Vec<ProofRecord>> {
+        let conn = self.reader();
+        let mut stmt = conn.prepare(
+            "SELECT height, proof, public_values, root, timestamp, seq, proof_id FROM proofs WHERE height >= ?1 AND height <= ?2 ORDER BY height ASC LIMIT ?3 OFFSET ?4",
+        )?;
+
+        let mut rows = stmt.query(params![
+            from,
+            to.min(i64::MAX as u64),
+            limit as u64,
+            offset as u64
+        ])?;
+        let mut records = Vec::new();
+        while let Some(row) = rows.next()? {
+            records.push(read_proof_record(row)?);
+        }
+
+        Ok(records)
+    }
+
+    fn get_latest_proofs(&self, limit: usize) -> Result<Vec<ProofRecord>> {
+        let conn = self.reader();
+        let mut stmt = conn.prepare(
+            "SELECT * FROM (SELECT height, proof, public_values, root, timestamp, seq, proof_id FROM proofs ORDER BY height DESC LIMIT ?1) ORDER BY height ASC",
+        )?;
+
+        let mut rows = stmt.query(params![limit as u64])?;
+        let mut records = Vec::new();
+        while let Some(row) = rows.next()? {
+            records.push(read_proof_record(row)?);
+        }
+
+        Ok(records)
+    }
+
+    fn get_proofs_since_seq(&self, since: u64, limit: usize) -> Result<Vec<ProofRecord>> {
+        let conn = self.reader();
+        let mut stmt = conn.prepare(
+            "SELECT height, proof, public_values, root, timestamp, seq, proof_id FROM proofs WHERE seq > ?1 ORDER BY seq ASC LIMIT ?2",
+        )?;
+
+        let mut rows = stmt.query(params![since, limit as u64])?;
+        let mut records = Vec::new();
+        while let Some(row) = rows.next()? {
+            records.push(read_proof_record(row)?);
+        }
+
+        Ok(records)
+    }
+
+    fn delete_proofs_from_height(&self, height: u64) -> Result<usize> {
+        let conn = self.conn.lock().unwrap();
+
+        let removed = conn.execute("DELETE FROM proofs WHERE height >= ?1", params![height])?;
+
+        Ok(removed)
+    }
+
+    fn prune_proof_history(&self, keep: u64) -> Result<usize> {
+        let conn = self.conn.lock().unwrap();
+
+        let removed = conn.execute(
+            "DELETE FROM proofs WHERE height NOT IN (SELECT height FROM proofs ORDER BY height DESC LIMIT ?1)",
+            params![keep],
+        )?;
+
+        Ok(removed)
+    }
+
+    fn get_proof_timestamps(&self) -> Result<Vec<(u64, DateTime<Utc>)>> {
+        let conn = self.reader();
+        let mut stmt = conn.prepare("SELECT height, timestamp FROM proofs ORDER BY height ASC")?;
+
+        let mut rows = stmt.query([])?;
+        let mut proofs = Vec::new();
+        while let Some(row) = rows.next()? {
+            let timestamp_str: String = row.get(1)?;
+            proofs.push((
+                row.get(0)?,
+                DateTime::parse_from_rfc3339(&timestamp_str)?.with_timezone(&Utc),
+            ));
+        }
+
+        Ok(proofs)
+    }
+
+    fn delete_proofs(&self, heights: &[u64]) -> Result<usize> {
+        let mut conn = self.conn.lock().unwrap();
+
+        let tx = conn.transaction()?;
+        let mut removed = 0;
+        {
+            let mut stmt = tx.prepare("DELETE FROM proofs WHERE height = ?1")?;
+            for height in heights {
+                removed += stmt.execute(params![height])?;
+            }
+        }
+        tx.commit()?;
+
+        Ok(removed)
+    }
+
+    fn enqueue_submission(&self, submission: &PendingSubmission) -> Result<()> {
+        let conn = self.conn.lock().unwrap();
+
+        conn.execute(
+            "INSERT OR IGNORE INTO pending_submissions (height, payload, created_at) VALUES (?1, ?2, ?3)",
+            params![
+                submission.height,
+                submission.payload,
+                submission.created_at.to_rfc3339()
+            ],
+        )?;
+
+        Ok(())
+    }
+
+    fn get_pending_submissions(&self) -> Result<Vec<PendingSubmission>> {
+        let conn = self.reader();
+        let mut stmt = conn.prepare(
+            "SELECT height, payload, created_at FROM pending_submissions ORDER BY height ASC",
+        )?;
+
+        let mut rows = stmt.query([])?;
+        let mut submissions = Vec::new();
+        while let Some(row) = rows.next()? {
+            let created_at_str: String = row.get(2)?;
+            submissions.push(PendingSubmission {
+                height: row.get(0)?,
+                payload: row.get(1)?,
+                created_at: DateTime::parse_from_rfc3339(&created_at_str)?.with_timezone(&Utc),
+            });
+        }
+
+        Ok(submissions)
+    }
+
+    fn remove_pending_submissions_through(&self, height: u64) -> Result<usize> {
+        let conn = self.conn.lock().unwrap();
+
+        let removed = conn.execute(
+            "DELETE FROM pending_submissions WHERE height <= ?1",
+            params![height],
+        )?;
+
+        Ok(removed)
+    }
+
+    fn record_failed_submission(&self, failed: &FailedSubmission) -> Result<u64> {
+        let conn = self.conn.lock().unwrap();
+
+        conn.execute(
+            "INSERT INTO failed_submissions (height, payload, error, attempts, failed_at) VALUES (?1, ?2, ?3, ?4, ?5)
+             ON CONFLICT (height) DO UPDATE SET
+                error = excluded.error,
+                attempts = attempts + excluded.attempts,
+                failed_at = excluded.failed_at",
+            params![
+                failed.height,
+                failed.payload,
+                failed.error,
+                failed.attempts,
+                failed.failed_at.to_rfc3339()
+            ],
+        )?;
+        let id = conn.query_row(
+            "SELECT id FROM failed_submissions WHERE height = ?1",
+            params![failed.height],
+            |row| row.get(0),
+        )?;
+
+        Ok(id)
+    }
+
+    fn get_failed_submissions(&self) -> Result<Vec<FailedSubmission>> {
+        let conn = self.reader();
+        let mut stmt = conn.prepare(
+            "SELECT id, height, payload, error, attempts, failed_at FROM failed_submissions ORDER BY id ASC",
+        )?;
+
+        let mut rows = stmt.query([])?;
+        let mut failed = Vec::new();
+        while let Some(row) = rows.next()? {
+            failed.push(read_failed_submission(row)?);
+        }
+
+        Ok(failed)
+    }
+
+    fn get_failed_submission(&self, id: u64) -> Result<Option<FailedSubmission>> {
+        let conn = self.reader();
+        let mut stmt = conn.prepare(
+            "SELECT id, height, payload, error, attempts, failed_at FROM failed_submissions WHERE id = ?1",
+        )?;
+
+        let mut rows = stmt.query(params![id])?;
+
+        match rows.next()? {
+            Some(row) => Ok(Some(read_failed_submission(row)?)),
+            None => Ok(None),
+        }
+    }
+
+    fn remove_failed_submission(&self, id: u64) -> Result<bool> {
+        let conn = self.conn.lock().unwrap();
+
+        let removed = conn.execute("DELETE FROM failed_submissions WHERE id = ?1", params![id])?;
+
+        Ok(removed > 0)
+    }
+
+    fn insert_alert(&self, alert: &AlertRecord) -> Result<u64> {
+        let conn = self.conn.lock().unwrap();
+
+        conn.execute(
+            "INSERT INTO alerts (condition, message, state, fired_at, notifications, last_notified_at, acknowledged_at, acknowledged_by, resolved_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+            params![
+                alert.condition,
+                alert.message,
+                alert.state.as_str(),
+                alert.fired_at.to_rfc3339(),
+                alert.notifications,
+                alert.last_notified_at.to_rfc3339(),
+                alert.acknowledged_at.map(|at| at.to_rfc3339()),
+                alert.acknowledged_by,
+                alert.resolved_at.map(|at| at.to_rfc3339())
+            ],
+        )?;
+
+        Ok(conn.last_insert_rowid() as u64)
+    }
+
+    fn update_alert(&self, alert: &AlertRecord) -> Result<bool> {
+        let conn = self.conn.lock().unwrap();
+
+        let updated = conn.execute(
+            "UPDATE alerts SET condition = ?2, message = ?3, state = ?4, fired_at = ?5, notifications = ?6,
+                last_notified_at = ?7, acknowledged_at = ?8, acknowledged_by = ?9, resolved_at = ?10
+             WHERE id = ?1",
+            params![
+                alert.id,
+                alert.condition,
+                alert.message,
+                alert.state.as_str(),
+                alert.fired_at.to_rfc3339(),
+                alert.notifications,
+                alert.last_notified_at.to_rfc3339(),
+                alert.acknowledged_at.map(|at| at.to_rfc3339()),
+                alert.acknowledged_by,
+                alert.resolved_at.map(|at| at.to_rfc3339())
+            ],
+        )?;
+
+        Ok(updated > 0)
+    }
+
+    fn get_alert(&self, id: u64) -> Result<Option<AlertRecord>> {
+        let conn = self.reader();
+        let mut stmt = conn.prepare(
+            "SELECT id, condition, message, state, fired_at, notifications, last_notified_at, acknowledged_at, acknowledged_by, resolved_at
+             FROM alerts WHERE id = ?1",
+        )?;
+
+        let mut rows = stmt.query(params![id])?;
+
+        match rows.next()? {
+            Some(row) => Ok(Some(read_alert(row)?)),
+            None => Ok(None),
+        }
+    }
+
+    fn get_alerts(&self, limit: usize) -> Result<Vec<AlertRecord>> {
+        let conn = self.reader();
+        let mut stmt = conn.prepare(
+            "SELECT id, condition, message, state, fired_at, notifications, last_notified_at, acknowledged_at, acknowledged_by, resolved_at
+             FROM alerts ORDER BY id DESC LIMIT ?1",
+        )?;
+
+        let mut rows = stmt.query(params![limit.min(i64::MAX as usize) as i64])?;
+        let mut alerts = Vec::new();
+        while let Some(row) = rows.next()? {
+            alerts.push(read_alert(row)?);
+        }
+
+        Ok(alerts)
+    }
+
+    fn insert_relay_attempt(&self, attempt: &RelayAttempt) -> Result<u64> {
+        let conn = self.conn.lock().unwrap();
+
+        conn.execute(
+            "INSERT INTO relay_attempts (height, endpoint, attempt, payload_hash, status, response, latency_ms, attempted_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+            params![
+                attempt.height,
+                attempt.endpoint,
+                attempt.attempt,
+                attempt.payload_hash,
+                attempt.status,
+                attempt.response,
+                attempt.latency_ms,
+                attempt.attempted_at.to_rfc3339()
+            ],
+        )?;
+
+        Ok(conn.last_insert_rowid() as u64)
+    }
+
+    fn get_relay_attempts(&self, limit: usize, height: Option<u64>) -> Result<Vec<RelayAttempt>> {
+        let conn = self.reader();
+        let mut stmt = conn.prepare(
+            "SELECT id, height, endpoint, attempt, payload_hash, status, response, latency_ms, attempted_at
+             FROM relay_attempts WHERE ?2 IS NULL OR height = ?2 ORDER BY id DESC LIMIT ?1",
+        )?;
+
+        let mut rows = stmt.query(params![limit.min(i64::MAX as usize) as i64, height])?;
+        let mut attempts = Vec::new();
+        while let Some(row) = rows.next()? {
+            attempts.push(read_relay_attempt(row)?);
+        }
+
+        Ok(attempts)
+    }
+
+    fn record_relay_confirmation(&self, confirmation: &RelayConfirmation) -> Result<()> {
+        let conn = self.conn.lock().unwrap();
+
+        conn.execute(
+            "INSERT OR REPLACE INTO relay_confirmations (height, endpoint, root, state, submissions, error, submitted_at, updated_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+            params![
+                confirmation.height,
+                confirmation.endpoint,
+                confirmation.root,
+                confirmation.state.as_str(),
+                confirmation.submissions,
+                confirmation.error,
+                confirmation.submitted_at.to_rfc3339(),
+                sortable_time(confirmation.updated_at)
+            ],
+        )?;
+
+        Ok(())
+    }
+
+    fn get_relay_confirmations(
+        &self,
+        limit: usize,
+        height: Option<u64>,
+    ) -> Result<Vec<RelayConfirmation>> {
+        let conn = self.reader();
+        let mut stmt = conn.prepare(
+            "SELECT height, endpoint, root, state, submissions, error, submitted_at, updated_at
+             FROM relay_confirmations WHERE ?2 IS NULL OR height = ?2
+             ORDER BY updated_at DESC, height DESC LIMIT ?1",
+        )?;
+
+        let mut rows = stmt.query(params![limit.min(i64::MAX as usize) as i64, height])?;
+        let mut confirmations = Vec::new();
+        while let Some(row) = rows.next()? {
+            let time = |index| -> Result<DateTime<Utc>> {
+                let value: String = row.get(index)?;
+                Ok(DateTime::parse_from_rfc3339(&value)?.with_timezone(&Utc))
+            };
+            let state: String = row.get(3)?;
+            confirmations.push(RelayConfirmation {
+                height: row.get(0)?,
+                endpoint: row.get(1)?,
+                root: row.get(2)?,
+                state: ConfirmationState::parse(&state)?,
+                submissions: row.get(4)?,
+                error: row.get(5)?,
+                submitted_at: time(6)?,
+                updated_at: time(7)?,
+            });
+        }
+
+        Ok(confirmations)
+    }
+
+    fn insert_cutover(&self, cutover: &CutoverRecord) -> Result<u64> {
+        let conn = self.conn.lock().unwrap();
+
+        conn.execute(
+            "INSERT INTO registry_cutovers (from_endpoint, to_endpoint, latest_only, validate_endpoint, drained, replayed_height, cut_over_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+            params![
+                cutover.from_endpoint,
+                cutover.to_endpoint,
+                cutover.latest_only,
+                cutover.validate_endpoint,
+                cutover.drained,
+                cutover.replayed_height,
+                cutover.cut_over_at.to_rfc3339()
+            ],
+        )?;
+
+        Ok(conn.last_insert_rowid() as u64)
+    }
+
+    fn get_cutovers(&self, limit: usize) -> Result<Vec<CutoverRecord>> {
+        let conn = self.reader();
+        let mut stmt = conn.prepare(
+            "SELECT id, from_endpoint, to_endpoint, latest_only, validate_endpoint, drained, replayed_height, cut_over_at
+             FROM registry_cutovers ORDER BY id DESC LIMIT ?1",
+        )?;
+
+        let mut rows = stmt.query(params![limit.min(i64::MAX as usize) as i64])?;
+        let mut cutovers = Vec::new();
+        while let Some(row) = rows.next()? {
+            let cut_over_at: String = row.get(7)?;
+            cutovers.push(CutoverRecord {
+                id: row.get(0)?,
+                from_endpoint: row.get(1)?,
+                to_endpoint: row.get(2)?,
+                latest_only: row.get(3)?,
+                validate_endpoint: row.get(4)?,
+                drained: row.get(5)?,
+                replayed_height: row.get(6)?,
+                cut_over_at: DateTime::parse_from_rfc3339(&cut_over_at)?.with_timezone(&Utc),
+            });
+        }
+
+        Ok(cutovers)
+    }
+
+    fn record_decode_failure(&self, failure: &DecodeFailure) -> Result<u64> {
+        let mut conn = self.conn.lock().unwrap();
+        let tx = conn.transaction()?;
+
+        tx.execute(
+            "INSERT INTO decode_failures (error, prover, public_values, failed_at) VALUES (?1, ?2, ?3, ?4)",
+            params![
+                failure.error,
+                failure.prover,
+                failure.public_values,
+                failure.failed_at.to_rfc3339()
+            ],
+        )?;
+        let id = tx.last_insert_rowid();
+        tx.execute(
+            "DELETE FROM decode_failures WHERE id <= ?1",
+            params![id - DECODE_FAILURES_KEPT as i64],
+        )?;
+        tx.commit()?;
+
+        Ok(id as u64)
+    }
+
+    fn get_decode_failures(&self, limit: usize) -> Result<Vec<DecodeFailure>> {
+        let conn = self.reader();
+        let mut stmt = conn.prepare(
+            "SELECT id, error, prover, public_values, failed_at FROM decode_failures ORDER BY id DESC LIMIT ?1",
+        )?;
+
+        let mut rows = stmt.query(params![limit.min(i64::MAX as usize) as i64])?;
+        let mut failures = Vec::new();
+        while let Some(row) = rows.next()? {
+            let failed_at: String = row.get(4)?;
+            failures.push(DecodeFailure {
+                id: row.get(0)?,
+                error: row.get(1)?,
+                prover: row.get(2)?,
+                public_values: row.get(3)?,
+                failed_at: DateTime::parse_from_rfc3339(&failed_at)?.with_timezone(&Utc),
+            });
+        }
+
+        Ok(failures)
+    }
+
+    fn insert_metric_samples(&self, samples: &[MetricSample]) -> Result<()> {
+        let mut conn = self.conn.lock().unwrap();
+        let tx = conn.transaction()?;
+
+        for sample in samples {
+            tx.execute(
+                "INSERT INTO metric_samples (metric, value, sampled_at) VALUES (?1, ?2, ?3)",
+                params![
+                    sample.metric,
+                    sample.value,
+                    sortable_time(sample.sampled_at)
+                ],
+            )?;
+        }
+        tx.commit()?;
+
+        Ok(())
+    }
+
+    fn get_metric_samples(&self, metric: &str, since: DateTime<Utc>) -> Result<Vec<MetricSample>> {
+        let conn = self.reader();
+        let mut stmt = conn.prepare(
+            "SELECT metric, value, sampled_at FROM metric_samples WHERE metric = ?1 AND sampled_at >= ?2 ORDER BY sampled_at ASC",
+        )?;
+
+        let mut rows = stmt.query(params![metric, sortable_time(since)])?;
+        let mut samples = Vec::new();
+        while let Some(row) = rows.next()? {
+            let sampled_at_str: String = row.get(2)?;
+            samples.push(MetricSample {
+                metric: row.get(0)?,
+                value: row.get(1)?,
+                sampled_at: DateTime::parse_from_rfc3339(&sampled_at_str)?.with_timezone(&Utc),
+            });
+        }
+
+        Ok(samples)
+    }
+
+    fn prune_metric_samples(&self, before: DateTime<Utc>) -> Result<usize> {
+        let conn = self.conn.lock().unwrap();
+
+        let removed = conn.execute(
+            "DELETE FROM metric_samples WHERE sampled_at < ?1",
+            params![sortable_time(before)],
+        )?;
+
+        Ok(removed)
+    }
+
+    fn backup(&self, path: &Path) -> Result<()> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute("VACUUM INTO ?1", params![path.to_string_lossy()])?;
+        Ok(())
+    }
+
+    fn clear_all_tables(&self) -> Result<()> {
+        let conn = self.conn.lock().unwrap();
+
+        // Clear health_check table
+        conn.execute("DELETE FROM health_check", [])?;
+
+        // Clear previous_proof table
+        conn.execute("DELETE FROM previous_proof", [])?;
+
+        Ok(())
+    }
+}
+
+/// The id stored with a proof, or for proofs recorded before ids existed, its id under
+/// the default algorithm
+pub fn stored_proof_id(stored: Option<String>, proof: &[u8]) -> Result<ProofId> {
+    match stored {
+        Some(id) => ProofId::parse(&id),
+        None => Ok(ProofId::of(HashAlgorithm::default(), proof)),
+    }
+}
+
+fn sortable_time(time: DateTime<Utc>) -> String {
+    time.to_rfc3339_opts(chrono::SecondsFormat::Secs, true)
+}
+
+fn read_proof_record(row: &Row) -> Result<ProofRecord> {
+    let timestamp_str: String = row.get(4)?;
+    let timestamp = DateTime::parse_from_rfc3339(&timestamp_str)?.with_timezone(&Utc);
+
+    let proof: Vec<u8> = row.get(1)?;
+    let proof_id = stored_proof_id(row.get(6)?, &proof)?;
+
+    Ok(ProofRecord {
+        seq: row.get::<_, Option<u64>>(5)?.unwrap_or_default(),
+        height: row.get(0)?,
+        proof,
+        public_values: row.get(2)?,
+        root: row.get(3)?,
+        proof_id,
+        timestamp,
+    })
+}
+
+fn read_failed_submission(row: &Row) -> Result<FailedSubmission> {
+    let failed_at_str: String = row.get(5)?;
+    let failed_at = DateTime::parse_from_rfc3339(&failed_at_str)?.with_timezone(&Utc);
+
+    Ok(FailedSubmission {
+        id: row.get(0)?,
+        height: row.get(1)?,
+        payload: row.get(2)?,
+        error: row.get(3)?,
+        attempts: row.get(4)?,
+        failed_at,
+    })
+}
+
+fn read_alert(row: &Row) -> Result<AlertRecord> {
+    let time = |text: String| -> Result<DateTime<Utc>> {
+        Ok(DateTime::parse_from_rfc3339(&text)?.with_timezone(&Utc))
+    };
+    let state: String = row.get(3)?;
+
+    Ok(AlertRecord {
+        id: row.get(0)?,
+        condition: row.get(1)?,
+        message: row.get(2)?,
+        state: AlertState::parse(&state)?,
+        fired_at: time(row.get(4)?)?,
+        notifications: row.get(5)?,
+        last_notified_at: time(row.get(6)?)?,
+        acknowledged_at: row.get::<_, Option<String>>(7)?.map(time).transpose()?,
+        acknowledged_by: row.get(8)?,
+        resolved_at: row.get::<_, Option<String>>(9)?.map(time).transpose()?,
+    })
+}
+
+fn read_relay_attempt(row: &Row) -> Result<RelayAttempt> {
+    let attempted_at_str: String = row.get(8)?;
+    let attempted_at = DateTime::parse_from_rfc3339(&attempted_at_str)?.with_timezone(&Utc);
+
+    Ok(RelayAttempt {
+        id: row.get(0)?,
+        height: row.get(1)?,
+        endpoint: row.get(2)?,
+        attempt: row.get(3)?,
+        payload_hash: row.get(4)?,
+        status: row.get(5)?,
+        response: row.get(6)?,
+        latency_ms: row.get(7)?,
+        attempted_at,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{SqliteJournalMode, SqliteSynchronous};
+    use tempfile::NamedTempFile;
+
+    #[test]
+    fn test_database_operations() -> Result<()> {
+        // Create a temporary database file
+        let temp_file = NamedTempFile::new()?;
+        let db_path = temp_file.path().to_str().unwrap();
+
+        let db = Database::new(db_path)?;
+
+        // Test health check data
+        let health_data = HealthCheckData {
+            current_height: 12345,
+            current_root: vec![1, 2, 3, 4, 5],
+            timestamp: Utc::now(),
+        };
+
+        db.update_health_check(&health_data)?;
 
         let retrieved_health = db.get_latest_health_check()?;
         assert!(retrieved_health.is_some());
@@ -191,4 +1842,627 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn test_proof_history() -> Result<()> {
+        let temp_file = NamedTempFile::new()?;
+        let db = Database::new(temp_file.path().to_str().unwrap())?;
+
+        for height in 1..=5 {
+            let record = ProofRecord {
+                seq: 0,
+                height,
+                proof: vec![height as u8; 4],
+                public_values: vec![0xaa],
+                root: vec![height as u8; 32],
+                proof_id: ProofId::of(HashAlgorithm::Sha256, &[height as u8; 4]),
+                timestamp: Utc::now(),
+            };
+            assert_eq!(db.insert_proof(&record)?, Some(height));
+        }
+
+        // Re-inserting an existing height does not overwrite it
+        let duplicate = ProofRecord {
+            seq: 0,
+            height: 3,
+            proof: vec![0xff],
+            public_values: vec![],
+            root: vec![],
+            proof_id: ProofId::of(HashAlgorithm::Sha256, &[0xff]),
+            timestamp: Utc::now(),
+        };
+        assert_eq!(db.insert_proof(&duplicate)?, None);
+        let stored = db.get_proof_by_height(3)?.unwrap();
+        assert_eq!(stored.proof, vec![3; 4]);
+        assert_eq!(stored.proof_id, ProofId::of(HashAlgorithm::Sha256, &[3; 4]));
+
+        let range = db.get_proofs_in_range(2, 4)?;
+        assert_eq!(
+            range.iter().map(|r| r.height).collect::<Vec<_>>(),
+            vec![2, 3, 4]
+        );
+
+        let page = db.get_proofs_page(2, u64::MAX, 2, 1)?;
+        assert_eq!(
+            page.iter().map(|r| r.height).collect::<Vec<_>>(),
+            vec![3, 4]
+        );
+        assert!(db.get_proofs_page(2, 4, 10, 3)?.is_empty());
+
+        let latest = db.get_latest_proofs(2)?;
+        assert_eq!(
+            latest.iter().map(|r| r.height).collect::<Vec<_>>(),
+            vec![4, 5]
+        );
+
+        let since = db.get_proofs_since_seq(2, 2)?;
+        assert_eq!(
+            since.iter().map(|r| (r.seq, r.height)).collect::<Vec<_>>(),
+            vec![(3, 3), (4, 4)]
+        );
+
+        assert_eq!(db.prune_proof_history(2)?, 3);
+        assert!(db.get_proof_by_height(3)?.is_none());
+        assert_eq!(db.get_proofs_in_range(0, 100)?.len(), 2);
+
+        let timestamps = db.get_proof_timestamps()?;
+        assert_eq!(
+            timestamps
+                .iter()
+                .map(|(height, _)| *height)
+                .collect::<Vec<_>>(),
+            vec![4, 5]
+        );
+        assert_eq!(db.delete_proofs(&[4, 9])?, 1);
+
+        // Sequence numbers keep growing after the newest proofs are deleted
+        assert_eq!(db.delete_proofs_from_height(5)?, 1);
+        let replacement = ProofRecord {
+            seq: 0,
+            height: 5,
+            proof: vec![0xee],
+            public_values: vec![],
+            root: vec![],
+            proof_id: ProofId::of(HashAlgorithm::Sha512, &[0xee]),
+            timestamp: Utc::now(),
+        };
+        assert_eq!(db.insert_proof(&replacement)?, Some(6));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_schema_repair() -> Result<()> {
+        // A backup from before proof ids and alert acknowledgements
+        let old_schema = |path: &str| -> Result<()> {
+            let conn = Connection::open(path)?;
+            conn.execute_batch(
+                "CREATE TABLE proofs (height INTEGER PRIMARY KEY, proof BLOB NOT NULL,
+                    public_values BLOB NOT NULL, root BLOB NOT NULL, timestamp TEXT NOT NULL,
+                    seq INTEGER);
+                 CREATE TABLE alerts (id INTEGER PRIMARY KEY AUTOINCREMENT, condition TEXT NOT NULL,
+                    message TEXT NOT NULL, state TEXT NOT NULL, fired_at TEXT NOT NULL,
+                    notifications INTEGER NOT NULL, last_notified_at TEXT NOT NULL,
+                    resolved_at TEXT);
+                 INSERT INTO proofs VALUES (7, x'0707', x'aa', x'01', '2024-01-01T00:00:00Z', 1);",
+            )?;
+            Ok(())
+        };
+
+        let unrepaired = NamedTempFile::new()?;
+        old_schema(unrepaired.path().to_str().unwrap())?;
+        let error = Database::open(unrepaired.path().to_str().unwrap(), false)
+            .err()
+            .unwrap()
+            .to_string();
+        assert!(error.contains("column proofs.proof_id"));
+        assert!(error.contains("column alerts.acknowledged_by"));
+        assert!(error.contains("table relay_attempts"));
+
+        let repaired = NamedTempFile::new()?;
+        old_schema(repaired.path().to_str().unwrap())?;
+        let db = Database::open(repaired.path().to_str().unwrap(), true)?;
+        let proof = db.get_proof_by_height(7)?.unwrap();
+        assert_eq!(proof.proof_id, ProofId::of(HashAlgorithm::Sha256, &[7, 7]));
+        assert!(db.get_relay_attempts(10, None)?.is_empty());
+        drop(db);
+        // Nothing is left to repair on the next start
+        Database::open(repaired.path().to_str().unwrap(), false)?;
+
+        // Columns existing rows can't be given a value are only reported
+        let broken = NamedTempFile::new()?;
+        Connection::open(broken.path())?.execute_batch(
+            "CREATE TABLE pending_submissions (height INTEGER PRIMARY KEY, created_at TEXT NOT NULL);",
+        )?;
+        let error = Database::open(broken.path().to_str().unwrap(), true)
+            .err()
+            .unwrap()
+            .to_string();
+        assert!(error.contains("pending_submissions.payload"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_migrations() -> Result<()> {
+        let latest = MIGRATIONS.last().map_or(BASELINE_VERSION, |m| m.version);
+        let temp_file = NamedTempFile::new()?;
+        let path = temp_file.path().to_str().unwrap();
+        let db = Database::new(path)?;
+        let version = Database::schema_version(&db.conn.lock().unwrap())?;
+        assert_eq!(version, Some(latest));
+        db.insert_proof(&ProofRecord {
+            seq: 0,
+            height: 7,
+            proof: vec![7],
+            public_values: vec![],
+            root: vec![1],
+            proof_id: ProofId::of(HashAlgorithm::Sha256, &[7]),
+            timestamp: Utc::now(),
+        })?;
+
+        // A file left at the baseline gets every migration, keeping its rows
+        db.conn.lock().unwrap().execute_batch(
+            "DELETE FROM schema_version WHERE version > 1;
+             DROP INDEX relay_confirmations_updated;",
+        )?;
+        drop(db);
+        let db = Database::open(path, false)?;
+        let conn = db.conn.lock().unwrap();
+        assert_eq!(Database::schema_version(&conn)?, Some(latest));
+        assert!(
+            conn.prepare("SELECT 1 FROM sqlite_master WHERE name = 'relay_confirmations_updated'")?
+                .exists([])?
+        );
+        drop(conn);
+        assert!(db.get_proof_by_height(7)?.is_some());
+
+        // A file from a newer relayer is refused rather than misread
+        db.conn.lock().unwrap().execute(
+            "INSERT INTO schema_version (version, description, applied_at) VALUES (?1, 'future', '')",
+            params![latest + 1],
+        )?;
+        drop(db);
+        let error = Database::new(path).err().unwrap().to_string();
+        assert!(error.contains("newer relayer"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_connection_pragmas() -> Result<()> {
+        let temp_file = NamedTempFile::new()?;
+        let db = Database::new(temp_file.path().to_str().unwrap())?;
+        let conn = db.conn.lock().unwrap();
+        let journal_mode: String = conn.query_row("PRAGMA journal_mode", [], |row| row.get(0))?;
+        assert_eq!(journal_mode, "wal");
+        let synchronous: i64 = conn.query_row("PRAGMA synchronous", [], |row| row.get(0))?;
+        assert_eq!(synchronous, 2); // FULL
+        drop(conn);
+        drop(db);
+
+        let db = Database::open_with(
+            temp_file.path().to_str().unwrap(),
+            &SqliteOptions {
+                journal_mode: SqliteJournalMode::Delete,
+                synchronous: SqliteSynchronous::Normal,
+                busy_timeout_ms: 100,
+                ..SqliteOptions::default()
+            },
+        )?;
+        let conn = db.conn.lock().unwrap();
+        let journal_mode: String = conn.query_row("PRAGMA journal_mode", [], |row| row.get(0))?;
+        assert_eq!(journal_mode, "delete");
+        let busy_timeout: i64 = conn.query_row("PRAGMA busy_timeout", [], |row| row.get(0))?;
+        assert_eq!(busy_timeout, 100);
+        Ok(())
+    }
+
+    #[test]
+    fn test_reads_during_write() -> Result<()> {
+        let temp_file = NamedTempFile::new()?;
+        let db = std::sync::Arc::new(Database::new(temp_file.path().to_str().unwrap())?);
+        let record = |height: u64| ProofRecord {
+            seq: 0,
+            height,
+            proof: vec![height as u8],
+            public_values: vec![],
+            root: vec![1],
+            proof_id: ProofId::of(HashAlgorithm::Sha256, &[height as u8]),
+            timestamp: Utc::now(),
+        };
+        db.insert_proof(&record(7))?;
+
+        // Hold the write connection inside an open transaction
+        let conn = db.conn.lock().unwrap();
+        conn.execute_batch("BEGIN IMMEDIATE; DELETE FROM proofs;")?;
+        let (done, finished) = std::sync::mpsc::channel();
+        let reader = db.clone();
+        std::thread::spawn(move || {
+            let _ = done.send(
+                reader
+                    .get_latest_proof()
+                    .map(|latest| latest.map(|p| p.height)),
+            );
+        });
+        // The read sees the last commit rather than waiting for the transaction
+        let latest = finished
+            .recv_timeout(std::time::Duration::from_secs(5))
+            .map_err(|_| anyhow::anyhow!("Read blocked behind the open write"))??;
+        assert_eq!(latest, Some(7));
+        conn.execute_batch("COMMIT")?;
+        drop(conn);
+        assert!(db.get_latest_proof()?.is_none());
+
+        // Without WAL there is no second connection
+        drop(db);
+        let db = Database::open_with(
+            temp_file.path().to_str().unwrap(),
+            &SqliteOptions {
+                journal_mode: SqliteJournalMode::Delete,
+                ..SqliteOptions::default()
+            },
+        )?;
+        assert!(db.reader.is_none());
+        Ok(())
+    }
+
+    #[test]
+    fn test_in_memory() -> Result<()> {
+        let db = Database::in_memory()?;
+        db.insert_proof(&ProofRecord {
+            seq: 0,
+            height: 7,
+            proof: vec![7],
+            public_values: vec![],
+            root: vec![1],
+            proof_id: ProofId::of(HashAlgorithm::Sha256, &[7]),
+            timestamp: Utc::now(),
+        })?;
+        assert_eq!(db.get_latest_proof()?.unwrap().height, 7);
+
+        // Every in-memory database is a new, empty one
+        assert!(Database::in_memory()?.get_latest_proof()?.is_none());
+        Ok(())
+    }
+
+    #[test]
+    fn test_backup_restore() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let record = |height: u64| ProofRecord {
+            seq: 0,
+            height,
+            proof: vec![height as u8],
+            public_values: vec![],
+            root: vec![1],
+            proof_id: ProofId::of(HashAlgorithm::Sha256, &[height as u8]),
+            timestamp: Utc::now(),
+        };
+        let source = Database::new(dir.path().join("source.db").to_str().unwrap())?;
+        source.insert_proof(&record(7))?;
+        let backup = dir.path().join("backup.db");
+        source.backup(&backup)?;
+
+        // The target's own history, WAL included, is replaced by the backup's
+        let target = dir.path().join("target.db");
+        let db = Database::new(target.to_str().unwrap())?;
+        db.insert_proof(&record(9))?;
+        drop(db);
+        Database::restore(&backup, &target)?;
+        let db = Database::new(target.to_str().unwrap())?;
+        assert_eq!(db.get_latest_proof()?.unwrap().height, 7);
+        drop(db);
+
+        // Anything but a relayer database is refused, leaving the target alone
+        let bogus = dir.path().join("bogus.db");
+        std::fs::write(&bogus, b"not a database")?;
+        assert!(Database::restore(&bogus, &target).is_err());
+        let empty = dir.path().join("empty.db");
+        Connection::open(&empty)?.execute_batch("CREATE TABLE other (id INTEGER);")?;
+        let error = Database::restore(&empty, &target)
+            .err()
+            .unwrap()
+            .to_string();
+        assert!(error.contains("no proof history"));
+        let db = Database::new(target.to_str().unwrap())?;
+        assert_eq!(db.get_latest_proof()?.unwrap().height, 7);
+        Ok(())
+    }
+
+    #[test]
+    fn test_pending_submissions() -> Result<()> {
+        let temp_file = NamedTempFile::new()?;
+        let db = Database::new(temp_file.path().to_str().unwrap())?;
+
+        for height in [12, 10, 11] {
+            db.enqueue_submission(&PendingSubmission {
+                height,
+                payload: format!("{{\"height\":{}}}", height),
+                created_at: Utc::now(),
+            })?;
+        }
+
+        // Queuing the same height again keeps the original payload
+        db.enqueue_submission(&PendingSubmission {
+            height: 10,
+            payload: "replacement".to_string(),
+            created_at: Utc::now(),
+        })?;
+
+        let pending = db.get_pending_submissions()?;
+        assert_eq!(
+            pending.iter().map(|s| s.height).collect::<Vec<_>>(),
+            vec![10, 11, 12]
+        );
+        assert_eq!(pending[0].payload, "{\"height\":10}");
+
+        // The queue survives the startup clear
+        db.clear_all_tables()?;
+        assert_eq!(db.remove_pending_submissions_through(11)?, 2);
+        assert_eq!(db.get_pending_submissions()?.len(), 1);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_failed_submissions() -> Result<()> {
+        let temp_file = NamedTempFile::new()?;
+        let db = Database::new(temp_file.path().to_str().unwrap())?;
+
+        let failed = |height, error: &str| FailedSubmission {
+            id: 0,
+            height,
+            payload: format!("{{\"height\":{}}}", height),
+            error: error.to_string(),
+            attempts: 5,
+            failed_at: Utc::now(),
+        };
+        let first = db.record_failed_submission(&failed(20, "status 500"))?;
+        let second = db.record_failed_submission(&failed(21, "status 500"))?;
+        assert_ne!(first, second);
+
+        // Failing the same height again updates the existing entry
+        assert_eq!(
+            db.record_failed_submission(&failed(20, "status 503"))?,
+            first
+        );
+        let entry = db.get_failed_submission(first)?.unwrap();
+        assert_eq!(entry.attempts, 10);
+        assert_eq!(entry.error, "status 503");
+        assert_eq!(entry.payload, "{\"height\":20}");
+
+        assert_eq!(db.get_failed_submissions()?.len(), 2);
+        assert!(db.remove_failed_submission(first)?);
+        assert!(!db.remove_failed_submission(first)?);
+        assert!(db.get_failed_submission(first)?.is_none());
+        assert_eq!(db.get_failed_submissions()?.len(), 1);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_alert_history() -> Result<()> {
+        let temp_file = NamedTempFile::new()?;
+        let db = Database::new(temp_file.path().to_str().unwrap())?;
+
+        let alert = |condition: &str| AlertRecord {
+            id: 0,
+            condition: condition.to_string(),
+            message: format!("{} fired", condition),
+            state: AlertState::Firing,
+            fired_at: Utc::now(),
+            notifications: 1,
+            last_notified_at: Utc::now(),
+            acknowledged_at: None,
+            acknowledged_by: None,
+            resolved_at: None,
+        };
+        let first = db.insert_alert(&alert("send_failure"))?;
+        let second = db.insert_alert(&alert("stale"))?;
+        assert_ne!(first, second);
+
+        let mut acknowledged = db.get_alert(first)?.unwrap();
+        acknowledged.state = AlertState::Acknowledged;
+        acknowledged.acknowledged_at = Some(Utc::now());
+        acknowledged.acknowledged_by = Some("on-call".to_string());
+        assert!(db.update_alert(&acknowledged)?);
+
+        let entry = db.get_alert(first)?.unwrap();
+        assert_eq!(entry.state, AlertState::Acknowledged);
+        assert_eq!(entry.acknowledged_by.as_deref(), Some("on-call"));
+        assert!(entry.resolved_at.is_none());
+
+        // Newest first
+        let alerts = db.get_alerts(10)?;
+        assert_eq!(
+            alerts.iter().map(|alert| alert.id).collect::<Vec<_>>(),
+            vec![second, first]
+        );
+        assert_eq!(db.get_alerts(1)?.len(), 1);
+        assert!(db.get_alert(second + 1)?.is_none());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_relay_attempts() -> Result<()> {
+        let temp_file = NamedTempFile::new()?;
+        let db = Database::new(temp_file.path().to_str().unwrap())?;
+
+        let attempt = |height, attempt, status: Option<u16>| RelayAttempt {
+            id: 0,
+            height,
+            endpoint: "http://registry".to_string(),
+            attempt,
+            payload_hash: "ab".repeat(32),
+            status,
+            response: format!("response {}", attempt),
+            latency_ms: 40,
+            attempted_at: Utc::now(),
+        };
+        let first = db.insert_relay_attempt(&attempt(7, 1, None))?;
+        let second = db.insert_relay_attempt(&attempt(7, 2, Some(200)))?;
+        let third = db.insert_relay_attempt(&attempt(8, 1, Some(503)))?;
+
+        let all = db.get_relay_attempts(10, None)?;
+        assert_eq!(
+            all.iter().map(|attempt| attempt.id).collect::<Vec<_>>(),
+            vec![third, second, first]
+        );
+        assert_eq!(all[2].status, None);
+        assert_eq!(all[1].status, Some(200));
+
+        let for_height = db.get_relay_attempts(10, Some(7))?;
+        assert_eq!(for_height.len(), 2);
+        assert_eq!(for_height[0].response, "response 2");
+        assert_eq!(db.get_relay_attempts(1, None)?.len(), 1);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_relay_confirmations() -> Result<()> {
+        let temp_file = NamedTempFile::new()?;
+        let db = Database::new(temp_file.path().to_str().unwrap())?;
+
+        let submitted_at = Utc::now() - chrono::Duration::minutes(5);
+        let confirmation = |height, state, submissions, minutes| RelayConfirmation {
+            height,
+            endpoint: "http://registry".to_string(),
+            root: "ab".repeat(32),
+            state,
+            submissions,
+            error: None,
+            submitted_at,
+            updated_at: submitted_at + chrono::Duration::minutes(minutes),
+        };
+        db.record_relay_confirmation(&confirmation(7, ConfirmationState::Pending, 1, 0))?;
+        db.record_relay_confirmation(&confirmation(8, ConfirmationState::Pending, 1, 1))?;
+        db.record_relay_confirmation(&RelayConfirmation {
+            error: Some("not confirmed".to_string()),
+            ..confirmation(7, ConfirmationState::Failed, 2, 2)
+        })?;
+
+        let all = db.get_relay_confirmations(10, None)?;
+        assert_eq!(
+            all.iter().map(|entry| entry.height).collect::<Vec<_>>(),
+            vec![7, 8]
+        );
+        assert_eq!(all[0].state, ConfirmationState::Failed);
+        assert_eq!(all[0].submissions, 2);
+        assert_eq!(all[0].error.as_deref(), Some("not confirmed"));
+        assert_eq!(all[1].state, ConfirmationState::Pending);
+
+        let for_height = db.get_relay_confirmations(10, Some(8))?;
+        assert_eq!(for_height.len(), 1);
+        assert_eq!(db.get_relay_confirmations(1, None)?.len(), 1);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_registry_cutovers() -> Result<()> {
+        let temp_file = NamedTempFile::new()?;
+        let db = Database::new(temp_file.path().to_str().unwrap())?;
+        assert!(db.get_cutovers(10)?.is_empty());
+
+        let cutover = |to: &str, replayed_height| CutoverRecord {
+            id: 0,
+            from_endpoint: "http://old-registry".to_string(),
+            to_endpoint: to.to_string(),
+            latest_only: false,
+            validate_endpoint: None,
+            drained: 2,
+            replayed_height,
+            cut_over_at: Utc::now(),
+        };
+        let first = db.insert_cutover(&cutover("http://new-registry", None))?;
+        let second = db.insert_cutover(&cutover("http://newer-registry", Some(12)))?;
+
+        let cutovers = db.get_cutovers(10)?;
+        assert_eq!(
+            cutovers
+                .iter()
+                .map(|cutover| cutover.id)
+                .collect::<Vec<_>>(),
+            vec![second, first]
+        );
+        assert_eq!(cutovers[0].to_endpoint, "http://newer-registry");
+        assert_eq!(cutovers[0].replayed_height, Some(12));
+        assert_eq!(cutovers[1].replayed_height, None);
+        assert_eq!(db.get_cutovers(1)?.len(), 1);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_decode_failures() -> Result<()> {
+        let temp_file = NamedTempFile::new()?;
+        let db = Database::new(temp_file.path().to_str().unwrap())?;
+        assert!(db.get_decode_failures(10)?.is_empty());
+
+        let error = anyhow::anyhow!("Unexpected length of input");
+        let mut last = 0;
+        for value in 0..DECODE_FAILURES_KEPT + 5 {
+            last = db.record_decode_failure(&DecodeFailure::new(
+                &error,
+                Some("http://prover"),
+                &[value as u8],
+            ))?;
+        }
+
+        let failures = db.get_decode_failures(usize::MAX)?;
+        assert_eq!(failures.len(), DECODE_FAILURES_KEPT as usize);
+        assert_eq!(failures[0].id, last);
+        assert_eq!(failures[0].error, "Unexpected length of input");
+        assert_eq!(failures[0].prover.as_deref(), Some("http://prover"));
+        assert_eq!(
+            failures[0].public_values,
+            hex::encode([(DECODE_FAILURES_KEPT + 4) as u8])
+        );
+        assert_eq!(db.get_decode_failures(1)?.len(), 1);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_metric_samples() -> Result<()> {
+        let temp_file = NamedTempFile::new()?;
+        let db = Database::new(temp_file.path().to_str().unwrap())?;
+
+        let now = Utc::now();
+        let sample = |metric: &str, value, minutes_ago| MetricSample {
+            metric: metric.to_string(),
+            value,
+            sampled_at: now - chrono::Duration::minutes(minutes_ago),
+        };
+        db.insert_metric_samples(&[
+            sample("relay_latency", 120.0, 90),
+            sample("relay_latency", 80.0, 30),
+            sample("latest_height", 100.0, 30),
+            sample("relay_latency", 95.0, 0),
+        ])?;
+
+        let recent = db.get_metric_samples("relay_latency", now - chrono::Duration::hours(1))?;
+        assert_eq!(
+            recent.iter().map(|sample| sample.value).collect::<Vec<_>>(),
+            vec![80.0, 95.0]
+        );
+
+        assert_eq!(
+            db.prune_metric_samples(now - chrono::Duration::hours(1))?,
+            1
+        );
+        assert_eq!(
+            db.get_metric_samples("relay_latency", now - chrono::Duration::days(1))?
+                .len(),
+            2
+        );
+        assert_eq!(
+            db.get_metric_samples("latest_height", now - chrono::Duration::days(1))?
+                .len(),
+            1
+        );
+
+        Ok(())
+    }
 }
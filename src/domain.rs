@@ -0,0 +1,405 @@
+use anyhow::{Result, anyhow};
+use serde::Deserialize;
+use std::sync::{Arc, RwLock};
+use tokio::sync::oneshot;
+
+use crate::candidate::CandidateTracker;
+use crate::config::{DomainConfig, SqliteOptions, StorageConfig};
+use crate::control::RelayControl;
+use crate::crosscheck::RootCrossCheck;
+use crate::db::{CutoverRecord, Database, Storage};
+use crate::envelope::EnvelopeCheck;
+use crate::equivocation::ConflictTracker;
+use crate::errors::ErrorTracker;
+use crate::freshness::FreshnessTracker;
+use crate::gaps::GapTracker;
+use crate::metrics::{LatencyObserver, Metrics};
+use crate::polling::PollSchedule;
+use crate::postgres::PostgresStorage;
+use crate::prover::ProverPool;
+use crate::pruning::PruneTracker;
+use crate::redis_store::RedisStorage;
+use crate::reference::ReferenceChain;
+use crate::rollback::RollbackGuard;
+use crate::scheduler::SchedulerStats;
+use crate::timeseries::LastLatency;
+use crate::vk::VkMonitor;
+use crate::writer::DbWriter;
+
+/// Everything a relay or health-check loop needs for one domain
+pub struct Domain {
+    pub config: DomainConfig,
+    pub db: Arc<dyn Storage>,
+    /// Queues writes to `db` so the relay loop never waits on the database
+    pub writer: DbWriter,
+    pub provers: Arc<ProverPool>,
+    pub rollback: Arc<RollbackGuard>,
+    /// Roots served for heights already in the history that differ from the stored ones
+    pub conflicts: Arc<ConflictTracker>,
+    pub candidate: Option<Arc<CandidateTracker>>,
+    pub control: Arc<RelayControl>,
+    pub vk_monitor: Arc<VkMonitor>,
+    /// Last failure of each subsystem, for `/status`
+    pub errors: Arc<ErrorTracker>,
+    /// Height progression across polls, when health is judged by it
+    pub freshness: Arc<FreshnessTracker>,
+    /// Heights skipped between relayed proofs, for `/stats/gaps`
+    pub gaps: Arc<GapTracker>,
+    /// Cycle timing of the domain's loop, for `/stats/scheduler`
+    pub scheduler: Arc<SchedulerStats>,
+    /// Background pruning of the proof history, for `/metrics`
+    pub pruning: Arc<PruneTracker>,
+    /// When the domain's loop polls next
+    pub polling: Arc<PollSchedule>,
+    /// Chain head proofs are checked against, when configured
+    pub reference: Option<Arc<ReferenceChain>>,
+    /// Lookup of new proofs' roots on the chain, when configured
+    pub root_check: Option<Arc<RootCrossCheck>>,
+    /// Structural checks on fetched proofs, when configured
+    pub envelope: Option<Arc<EnvelopeCheck>>,
+    /// How long the latest submission took, sampled into the metrics history
+    pub relay_latency: Arc<LastLatency>,
+    /// Prover, submission and verification latencies for the `/metrics` histograms
+    pub latency: LatencyObserver,
+    /// Where the primary registry is and how it's submitted to, which a cutover can move
+    /// away from `config`'s registry settings
+    pub registry: RwLock<RegistryTarget>,
+}
+
+impl Domain {
+    /// Opens the domain's storage (for SQLite, `<db_prefix>_<name>.db` unless the domain
+    /// sets `db_path`), starts its database
+    /// writer and sets up its prover pool
+    pub fn open(config: DomainConfig, storage: &StorageConfig, db_prefix: &str) -> Result<Self> {
+        let db = open_domain_storage(&config, storage, db_prefix)?;
+        let provers = Arc::new(
+            ProverPool::new(config.prover_endpoints.clone(), config.prover_selection)
+                .with_http(&config)
+                .with_source(config.proof_source)
+                .with_quorum(config.mode, config.prover_quorum),
+        );
+        let rollback = Arc::new(RollbackGuard::new(
+            config.mode,
+            config.rollback_policy,
+            config.reorg_tolerance,
+        ));
+        let candidate = config
+            .candidate_prover
+            .clone()
+            .map(|endpoint| Arc::new(CandidateTracker::new(endpoint)));
+
+        let vk_monitor = Arc::new(VkMonitor::new(&config.vk));
+        let errors = Arc::new(ErrorTracker::default());
+        let writer = DbWriter::spawn(&config.name, db.clone(), errors.clone());
+        let freshness = Arc::new(FreshnessTracker::new(config.freshness));
+        let reference = config
+            .reference_chain
+            .as_ref()
+            .map(|reference| ReferenceChain::new(config.mode, reference).map(Arc::new))
+            .transpose()?;
+        let root_check = config
+            .root_check
+            .as_ref()
+            .map(|check| RootCrossCheck::new(config.mode, check).map(Arc::new))
+            .transpose()?;
+        let envelope = config
+            .envelope
+            .as_ref()
+            .map(|envelope| EnvelopeCheck::new(config.mode, envelope).map(Arc::new))
+            .transpose()?;
+
+        let polling = Arc::new(PollSchedule::new(&config.polling));
+        let registry = RwLock::new(RegistryTarget {
+            endpoint: config.registry_endpoint.clone(),
+            latest_only: config.registry_latest_only,
+            validate_endpoint: config.registry_validate_endpoint.clone(),
+        });
+
+        Ok(Domain {
+            config,
+            db,
+            writer,
+            provers,
+            rollback,
+            conflicts: Arc::new(ConflictTracker::default()),
+            candidate,
+            control: Arc::new(RelayControl::new()),
+            vk_monitor,
+            errors,
+            freshness,
+            gaps: Arc::new(GapTracker::default()),
+            scheduler: Arc::new(SchedulerStats::new()),
+            pruning: Arc::new(PruneTracker::default()),
+            polling,
+            reference,
+            root_check,
+            envelope,
+            relay_latency: Arc::new(LastLatency::default()),
+            latency: LatencyObserver::default(),
+            registry,
+        })
+    }
+
+    /// Records the domain's latencies in `metrics`' histograms and its polls and pruned
+    /// proofs in their counters
+    pub fn with_metrics(mut self, metrics: &Metrics) -> Self {
+        self.latency = metrics.latency_observer(self.name());
+        self.pruning = Arc::new(metrics.prune_tracker(self.name()));
+        self.errors.count_polls(metrics.polls.clone(), self.name());
+        self
+    }
+
+    pub fn name(&self) -> &str {
+        &self.config.name
+    }
+
+    /// Endpoint of the primary registry, after any cutover
+    pub fn registry_endpoint(&self) -> String {
+        self.registry.read().unwrap().endpoint.clone()
+    }
+
+    /// The domain's config with the primary registry's settings after any cutover, for
+    /// building a sink outside the relay loop
+    pub fn registry_config(&self) -> DomainConfig {
+        let target = self.registry.read().unwrap().clone();
+        DomainConfig {
+            registry_endpoint: target.endpoint,
+            registry_latest_only: target.latest_only,
+            registry_validate_endpoint: target.validate_endpoint,
+            ..self.config.clone()
+        }
+    }
+
+    /// Runs `read` against the domain's storage on tokio's blocking pool, so a slow query
+    /// never stalls the tasks sharing the runtime with it, such as the API's. Writes go
+    /// through `writer`.
+    pub async fn read<T: Send + 'static>(
+        &self,
+        read: impl FnOnce(&dyn Storage) -> Result<T> + Send + 'static,
+    ) -> Result<T> {
+        let db = self.db.clone();
+        tokio::task::spawn_blocking(move || read(db.as_ref()))
+            .await
+            .map_err(|e| anyhow!("Database read did not complete: {}", e))?
+    }
+}
+
+/// The domains being served. Relayer mode can add and remove them at runtime through
+/// `DomainLifecycle`, so readers take a snapshot rather than holding on to the set.
+#[derive(Default)]
+pub struct DomainSet {
+    domains: RwLock<Vec<Arc<Domain>>>,
+}
+
+impl DomainSet {
+    pub fn new(domains: Vec<Arc<Domain>>) -> Self {
+        DomainSet {
+            domains: RwLock::new(domains),
+        }
+    }
+
+    pub fn all(&self) -> Vec<Arc<Domain>> {
+        self.domains.read().unwrap().clone()
+    }
+
+    pub fn get(&self, name: &str) -> Option<Arc<Domain>> {
+        self.domains
+            .read()
+            .unwrap()
+            .iter()
+            .find(|domain| domain.name() == name)
+            .cloned()
+    }
+
+    /// The first configured domain, the default for routes that act on a single one
+    pub fn first(&self) -> Option<Arc<Domain>> {
+        self.domains.read().unwrap().first().cloned()
+    }
+
+    pub fn insert(&self, domain: Arc<Domain>) -> Result<()> {
+        let mut domains = self.domains.write().unwrap();
+        if domains
+            .iter()
+            .any(|existing| existing.name() == domain.name())
+        {
+            return Err(anyhow!("Domain {} already exists", domain.name()));
+        }
+        domains.push(domain);
+        Ok(())
+    }
+
+    pub fn remove(&self, name: &str) -> Option<Arc<Domain>> {
+        let mut domains = self.domains.write().unwrap();
+        let index = domains.iter().position(|domain| domain.name() == name)?;
+        Some(domains.remove(index))
+    }
+}
+
+/// The registry a cutover moves a domain's submissions to, see `DomainLifecycle::cut_over`.
+/// The settings mean what `registry_latest_only` and `registry_validate_endpoint` do.
+#[derive(Debug, Clone, Deserialize)]
+pub struct RegistryTarget {
+    pub endpoint: String,
+    #[serde(default)]
+    pub latest_only: bool,
+    #[serde(default)]
+    pub validate_endpoint: Option<String>,
+}
+
+/// Adds and removes domains while the process runs, for `/admin/domains`, and moves
+/// their registries, for `/admin/registry/cutover`
+pub trait DomainLifecycle: Send + Sync {
+    /// Validates the config, opens the domain's storage and starts its loops
+    fn add(&self, config: DomainConfig) -> Result<Arc<Domain>>;
+
+    /// Hands a cutover of the domain's primary registry to its relay loop, which runs it
+    /// at the start of its next cycle. The receiver gets the recorded cutover, or why it
+    /// was abandoned with the old registry kept.
+    fn cut_over(
+        &self,
+        name: &str,
+        target: RegistryTarget,
+    ) -> Result<oneshot::Receiver<Result<CutoverRecord>>>;
+
+    /// Stops the domain's loops after their current cycle and stops serving it; its
+    /// stored data is kept. Returns false if there is no such domain.
+    fn remove(&self, name: &str) -> bool;
+}
+
+/// Domain names end up in file names, database keys and metric labels
+pub fn validate_name(name: &str) -> Result<()> {
+    if name.is_empty()
+        || !name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+    {
+        return Err(anyhow!(
+            "Invalid domain name {:?}, use letters, digits, '-' and '_'",
+            name
+        ));
+    }
+    Ok(())
+}
+
+/// Opens a domain's storage like `open_storage`, but a dry run gets a SQLite file of its
+/// own, `<db_prefix>_dry_run_<name>.db`, whatever the configured storage. Proofs a dry
+/// run records then can't keep the real relayer from submitting them.
+pub fn open_domain_storage(
+    config: &DomainConfig,
+    storage: &StorageConfig,
+    db_prefix: &str,
+) -> Result<Arc<dyn Storage>> {
+    if !config.dry_run {
+        if let StorageConfig::Sqlite(options) = storage {
+            return Ok(Arc::new(open_sqlite(
+                &sqlite_path(config, db_prefix),
+                options,
+            )?));
+        }
+        return open_storage(&config.name, storage, db_prefix);
+    }
+    // Dry runs always use SQLite, opened as configured if the real storage is SQLite too
+    let options = match storage {
+        StorageConfig::Sqlite(options) => options.clone(),
+        _ => SqliteOptions::default(),
+    };
+    open_storage(
+        &config.name,
+        &StorageConfig::Sqlite(options),
+        &format!("{}_dry_run", db_prefix),
+    )
+}
+
+/// Opens a domain's storage on its own, without starting anything
+pub fn open_storage(
+    name: &str,
+    storage: &StorageConfig,
+    db_prefix: &str,
+) -> Result<Arc<dyn Storage>> {
+    let db: Arc<dyn Storage> = match storage {
+        StorageConfig::Sqlite(options) => {
+            Arc::new(open_sqlite(&format!("{}_{}.db", db_prefix, name), options)?)
+        }
+        StorageConfig::Memory => Arc::new(Database::in_memory()?),
+        StorageConfig::Postgres {
+            url,
+            max_connections,
+        } => Arc::new(PostgresStorage::connect(url, *max_connections, name)?),
+        StorageConfig::Redis { url, key_prefix } => {
+            Arc::new(RedisStorage::connect(url, key_prefix, name)?)
+        }
+    };
+    Ok(db)
+}
+
+/// The domain's SQLite file outside dry runs: its `db_path`, or `<db_prefix>_<name>.db`.
+/// A relative `db_path` is taken from `db_prefix`'s directory, which is `data_dir` when set.
+pub fn sqlite_path(config: &DomainConfig, db_prefix: &str) -> String {
+    match &config.db_path {
+        // Joining an absolute path replaces the directory
+        Some(path) => std::path::Path::new(db_prefix).parent().map_or_else(
+            || path.clone(),
+            |dir| dir.join(path).to_string_lossy().into_owned(),
+        ),
+        None => format!("{}_{}.db", db_prefix, config.name),
+    }
+}
+
+/// Opens the SQLite file at `path`, creating its directory if missing
+fn open_sqlite(path: &str, options: &SqliteOptions) -> Result<Database> {
+    if let Some(dir) = std::path::Path::new(path).parent()
+        && !dir.as_os_str().is_empty()
+    {
+        std::fs::create_dir_all(dir).map_err(|e| {
+            anyhow!(
+                "Failed to create database directory {}: {}",
+                dir.display(),
+                e
+            )
+        })?;
+    }
+    Database::open_with(path, options)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sqlite_path() -> Result<()> {
+        let data_dir = tempfile::tempdir()?;
+        let prefix = data_dir
+            .path()
+            .join("helios")
+            .to_string_lossy()
+            .into_owned();
+        let config = |db_path: Option<&str>| DomainConfig {
+            name: "alpha".to_string(),
+            db_path: db_path.map(str::to_string),
+            ..DomainConfig::default()
+        };
+
+        assert_eq!(sqlite_path(&config(None), "helios"), "helios_alpha.db");
+        assert_eq!(sqlite_path(&config(Some("alpha.db")), "helios"), "alpha.db");
+        assert_eq!(
+            sqlite_path(&config(None), &prefix),
+            format!("{}_alpha.db", prefix)
+        );
+        assert_eq!(
+            sqlite_path(&config(Some("/srv/alpha.db")), &prefix),
+            "/srv/alpha.db"
+        );
+
+        // A relative db_path lands in data_dir, its missing directories created on open
+        let config = config(Some("nested/alpha.db"));
+        let path = data_dir.path().join("nested").join("alpha.db");
+        assert_eq!(sqlite_path(&config, &prefix), path.to_string_lossy());
+        open_domain_storage(
+            &config,
+            &StorageConfig::Sqlite(SqliteOptions::default()),
+            &prefix,
+        )?;
+        assert!(path.is_file());
+        Ok(())
+    }
+}
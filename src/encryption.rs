@@ -0,0 +1,107 @@
+use anyhow::{Result, anyhow};
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD as BASE64;
+use crypto_box::aead::OsRng;
+use serde_json::json;
+
+use crate::config::PayloadEncryption;
+
+/// Encrypts payloads to one registry's public key. Registries that require it receive
+/// `{"encryption": "<scheme>", "ciphertext": "<base64>"}` instead of the payload, with
+/// the payload's JSON as the plaintext.
+pub struct PayloadEncryptor {
+    recipient: Recipient,
+}
+
+enum Recipient {
+    Age(age::x25519::Recipient),
+    SealedBox(crypto_box::PublicKey),
+}
+
+impl PayloadEncryptor {
+    /// Parses the configured key, so a bad one fails at startup rather than on submission
+    pub fn new(config: &PayloadEncryption) -> Result<Self> {
+        let recipient = match config {
+            PayloadEncryption::Age { recipient } => Recipient::Age(
+                recipient
+                    .parse()
+                    .map_err(|e| anyhow!("Invalid age recipient {}: {}", recipient, e))?,
+            ),
+            PayloadEncryption::SealedBox { public_key } => {
+                let bytes = match hex::decode(public_key.trim_start_matches("0x")) {
+                    Ok(bytes) => bytes,
+                    Err(_) => BASE64.decode(public_key)?,
+                };
+                let bytes: [u8; 32] = bytes
+                    .try_into()
+                    .map_err(|_| anyhow!("Sealed box public key must be 32 bytes"))?;
+                Recipient::SealedBox(crypto_box::PublicKey::from(bytes))
+            }
+        };
+        Ok(PayloadEncryptor { recipient })
+    }
+
+    pub fn encrypt(&self, payload: &serde_json::Value) -> Result<serde_json::Value> {
+        let plaintext = serde_json::to_vec(payload)?;
+        let (scheme, ciphertext) = match &self.recipient {
+            Recipient::Age(recipient) => (
+                "age",
+                age::encrypt(recipient, &plaintext)
+                    .map_err(|e| anyhow!("Failed to encrypt payload: {}", e))?,
+            ),
+            Recipient::SealedBox(public_key) => (
+                "sealed_box",
+                public_key
+                    .seal(&mut OsRng, &plaintext)
+                    .map_err(|e| anyhow!("Failed to encrypt payload: {}", e))?,
+            ),
+        };
+        Ok(json!({
+            "encryption": scheme,
+            "ciphertext": BASE64.encode(ciphertext),
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ciphertext(envelope: &serde_json::Value) -> Vec<u8> {
+        BASE64
+            .decode(envelope["ciphertext"].as_str().unwrap())
+            .unwrap()
+    }
+
+    #[test]
+    fn test_payload_encryption_round_trip() -> Result<()> {
+        let payload = json!({ "proof": "01", "public_values": "02", "vk": "0x03" });
+
+        let identity = age::x25519::Identity::generate();
+        let encryptor = PayloadEncryptor::new(&PayloadEncryption::Age {
+            recipient: identity.to_public().to_string(),
+        })?;
+        let envelope = encryptor.encrypt(&payload)?;
+        assert_eq!(envelope["encryption"], "age");
+        let plaintext = age::decrypt(&identity, &ciphertext(&envelope))?;
+        assert_eq!(
+            serde_json::from_slice::<serde_json::Value>(&plaintext)?,
+            payload
+        );
+
+        let secret_key = crypto_box::SecretKey::generate(&mut OsRng);
+        let encryptor = PayloadEncryptor::new(&PayloadEncryption::SealedBox {
+            public_key: hex::encode(secret_key.public_key().as_bytes()),
+        })?;
+        let envelope = encryptor.encrypt(&payload)?;
+        assert_eq!(envelope["encryption"], "sealed_box");
+        let plaintext = secret_key
+            .unseal(&ciphertext(&envelope))
+            .map_err(|e| anyhow!("{}", e))?;
+        assert_eq!(
+            serde_json::from_slice::<serde_json::Value>(&plaintext)?,
+            payload
+        );
+        Ok(())
+    }
+}
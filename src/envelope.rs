@@ -0,0 +1,202 @@
+use anyhow::{Result, anyhow};
+use borsh::BorshDeserialize;
+use helios_recursion_types::WrapperCircuitOutputs as HeliosWrapperCircuitOutputs;
+use sp1_sdk::{SP1Proof, SP1ProofWithPublicValues};
+use tendermint_recursion_types::WrapperCircuitOutputs as TendermintWrapperCircuitOutputs;
+
+use crate::config::{EnvelopeConfig, MODE};
+use crate::verifier::ProofSystem;
+
+/// Structural checks on a fetched proof, run before it is decoded or verified so a
+/// truncated or mismatched proof is reported as such rather than as a decode or
+/// verification failure
+pub struct EnvelopeCheck {
+    mode: MODE,
+    proof_system: Option<ProofSystem>,
+    min_version: Option<Version>,
+    max_version: Option<Version>,
+}
+
+/// `major.minor.patch`, parsed from e.g. `v5.0.0`
+type Version = (u64, u64, u64);
+
+impl EnvelopeCheck {
+    pub fn new(mode: MODE, config: &EnvelopeConfig) -> Result<Self> {
+        let version = |value: &Option<String>, field: &str| {
+            value
+                .as_deref()
+                .map(|value| {
+                    parse_version(value).ok_or_else(|| anyhow!("Invalid {} {}", field, value))
+                })
+                .transpose()
+        };
+        Ok(EnvelopeCheck {
+            mode,
+            proof_system: config.proof_system,
+            min_version: version(&config.min_sp1_version, "min_sp1_version")?,
+            max_version: version(&config.max_sp1_version, "max_sp1_version")?,
+        })
+    }
+
+    /// Fails with every violated invariant, not just the first
+    pub fn check(&self, proof: &SP1ProofWithPublicValues) -> Result<()> {
+        let mut violations = Vec::new();
+        self.check_proof(&proof.proof, &mut violations);
+        self.check_public_values(proof.public_values.as_slice(), &mut violations);
+        self.check_version(&proof.sp1_version, &mut violations);
+
+        if violations.is_empty() {
+            return Ok(());
+        }
+        Err(anyhow!(
+            "Proof envelope has {} violation(s): {}",
+            violations.len(),
+            violations.join("; ")
+        ))
+    }
+
+    fn check_proof(&self, proof: &SP1Proof, violations: &mut Vec<String>) {
+        let (proof_system, encoded_proof) = match proof {
+            SP1Proof::Groth16(groth16) => (ProofSystem::Groth16, &groth16.encoded_proof),
+            SP1Proof::Plonk(plonk) => (ProofSystem::Plonk, &plonk.encoded_proof),
+            SP1Proof::Core(_) => {
+                violations.push("proof is a core proof, not a wrapper proof".to_string());
+                return;
+            }
+            SP1Proof::Compressed(_) => {
+                violations.push("proof is a compressed proof, not a wrapper proof".to_string());
+                return;
+            }
+        };
+
+        if let Some(expected) = self.proof_system
+            && expected != proof_system
+        {
+            violations.push(format!(
+                "proof is {}, expected {}",
+                proof_system.as_str(),
+                expected.as_str()
+            ));
+        }
+        if encoded_proof.is_empty() {
+            violations.push(format!("{} proof is empty", proof_system.as_str()));
+        } else if hex::decode(encoded_proof).is_err() {
+            violations.push(format!(
+                "{} proof is not valid hex ({} characters)",
+                proof_system.as_str(),
+                encoded_proof.len()
+            ));
+        }
+    }
+
+    fn check_public_values(&self, public_values: &[u8], violations: &mut Vec<String>) {
+        let mut rest = public_values;
+        let (decoded, trailing_allowed) = match self.mode {
+            MODE::HELIOS => (
+                HeliosWrapperCircuitOutputs::deserialize(&mut rest).map(|_| ()),
+                false,
+            ),
+            // Extended outputs carry the chain id after the standard fields
+            MODE::TENDERMINT => (
+                TendermintWrapperCircuitOutputs::deserialize(&mut rest).map(|_| ()),
+                true,
+            ),
+        };
+
+        if let Err(e) = decoded {
+            violations.push(format!(
+                "public values are truncated or malformed at {} bytes: {}",
+                public_values.len(),
+                e
+            ));
+        } else if !rest.is_empty()
+            && (!trailing_allowed || borsh::from_slice::<String>(rest).is_err())
+        {
+            violations.push(format!(
+                "public values have {} unexpected trailing bytes after the wrapper outputs",
+                rest.len()
+            ));
+        }
+    }
+
+    fn check_version(&self, sp1_version: &str, violations: &mut Vec<String>) {
+        if self.min_version.is_none() && self.max_version.is_none() {
+            return;
+        }
+        let Some(version) = parse_version(sp1_version) else {
+            violations.push(format!("sp1_version {:?} is not a version", sp1_version));
+            return;
+        };
+        if self.min_version.is_some_and(|min| version < min) {
+            violations.push(format!(
+                "sp1_version {} is below the minimum supported",
+                sp1_version
+            ));
+        }
+        if self.max_version.is_some_and(|max| version > max) {
+            violations.push(format!(
+                "sp1_version {} is above the maximum supported",
+                sp1_version
+            ));
+        }
+    }
+}
+
+/// Reads `v5.0.0`, `5.0` or `5`; missing components count as zero and pre-release
+/// suffixes are ignored
+fn parse_version(value: &str) -> Option<Version> {
+    let value = value.trim().trim_start_matches('v');
+    let value = value.split(['-', '+']).next()?;
+    let mut parts = value.split('.').map(|part| part.parse::<u64>());
+    let major = parts.next()?.ok()?;
+    let minor = parts.next().transpose().ok()?.unwrap_or(0);
+    let patch = parts.next().transpose().ok()?.unwrap_or(0);
+    if parts.next().is_some() {
+        return None;
+    }
+    Some((major, minor, patch))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_version_range() {
+        assert_eq!(parse_version("v5.0.0"), Some((5, 0, 0)));
+        assert_eq!(parse_version("4.2"), Some((4, 2, 0)));
+        assert_eq!(parse_version("v5.1.0-rc.1"), Some((5, 1, 0)));
+        assert_eq!(parse_version("five"), None);
+        assert_eq!(parse_version("1.2.3.4"), None);
+
+        let check = EnvelopeCheck::new(
+            MODE::HELIOS,
+            &EnvelopeConfig {
+                proof_system: None,
+                min_sp1_version: Some("v5.0.0".to_string()),
+                max_sp1_version: Some("v5.99.0".to_string()),
+            },
+        )
+        .unwrap();
+        let violations = |version: &str| {
+            let mut violations = Vec::new();
+            check.check_version(version, &mut violations);
+            violations
+        };
+        assert!(violations("v5.1.0").is_empty());
+        assert_eq!(violations("v4.2.0").len(), 1);
+        assert_eq!(violations("v6.0.0").len(), 1);
+        assert!(violations("unknown")[0].contains("not a version"));
+
+        assert!(
+            EnvelopeCheck::new(
+                MODE::HELIOS,
+                &EnvelopeConfig {
+                    min_sp1_version: Some("latest".to_string()),
+                    ..Default::default()
+                },
+            )
+            .is_err()
+        );
+    }
+}
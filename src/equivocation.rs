@@ -0,0 +1,121 @@
+use anyhow::Result;
+use serde::Serialize;
+use std::sync::Mutex;
+use tracing::error;
+
+use crate::db::Storage;
+
+/// A height the prover served with a different root than the one in the proof history
+#[derive(Debug, Clone, Serialize)]
+pub struct RootConflict {
+    pub height: u64,
+    pub stored_root: String, // hex encoded
+    pub served_root: String, // hex encoded
+    /// Prover that served the conflicting root, when known
+    pub prover: Option<String>,
+    pub detected_at: String,
+}
+
+/// Compares the (height, root) pairs a prover serves with the proof history. Two roots
+/// for one height mean the prover equivocated or the chain reorganised; either way the
+/// conflict stays flagged, and `/health` reports `conflict`, until an operator resolves it.
+#[derive(Default)]
+pub struct ConflictTracker {
+    state: Mutex<ConflictState>,
+}
+
+#[derive(Default)]
+struct ConflictState {
+    current: Option<RootConflict>,
+    detected: u64,
+}
+
+impl ConflictTracker {
+    /// Looks `height` up in the history, returning the conflict if its stored root differs
+    pub fn check(
+        &self,
+        db: &dyn Storage,
+        height: u64,
+        root: &[u8],
+        prover: Option<&str>,
+    ) -> Result<Option<RootConflict>> {
+        let Some(stored) = db.get_proof_by_height(height)? else {
+            return Ok(None);
+        };
+        if stored.root == root {
+            return Ok(None);
+        }
+
+        let conflict = RootConflict {
+            height,
+            stored_root: hex::encode(&stored.root),
+            served_root: hex::encode(root),
+            prover: prover.map(str::to_string),
+            detected_at: chrono::Utc::now().to_rfc3339(),
+        };
+        let mut state = self.state.lock().unwrap();
+        let repeated = state.current.as_ref().is_some_and(|current| {
+            current.height == conflict.height && current.served_root == conflict.served_root
+        });
+        if !repeated {
+            error!(
+                "🚨 Root conflict at height {}: prover served {} but {} is stored",
+                height, conflict.served_root, conflict.stored_root
+            );
+            state.detected += 1;
+            state.current = Some(conflict.clone());
+        }
+        Ok(Some(conflict))
+    }
+
+    /// The latest unresolved conflict
+    pub fn current(&self) -> Option<RootConflict> {
+        self.state.lock().unwrap().current.clone()
+    }
+
+    /// Conflicts detected since startup
+    pub fn detected(&self) -> u64 {
+        self.state.lock().unwrap().detected
+    }
+
+    /// Clears the flagged conflict once an operator has looked into it
+    pub fn resolve(&self) -> Option<RootConflict> {
+        self.state.lock().unwrap().current.take()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::{Database, ProofRecord};
+    use crate::proof_id::{HashAlgorithm, ProofId};
+    use tempfile::NamedTempFile;
+
+    #[test]
+    fn test_root_conflicts() -> Result<()> {
+        let temp_file = NamedTempFile::new()?;
+        let db = Database::new(temp_file.path().to_str().unwrap())?;
+        db.insert_proof(&ProofRecord {
+            seq: 0,
+            height: 7,
+            proof: vec![7; 4],
+            public_values: vec![],
+            root: vec![7; 32],
+            proof_id: ProofId::of(HashAlgorithm::Sha256, &[7; 4]),
+            timestamp: chrono::Utc::now(),
+        })?;
+
+        let tracker = ConflictTracker::default();
+        assert!(tracker.check(&db, 7, &[7; 32], None)?.is_none());
+        assert!(tracker.check(&db, 8, &[9; 32], None)?.is_none());
+
+        for _ in 0..2 {
+            let conflict = tracker.check(&db, 7, &[9; 32], Some("http://prover"))?;
+            assert_eq!(conflict.unwrap().stored_root, hex::encode([7; 32]));
+        }
+        assert_eq!(tracker.detected(), 1);
+        assert_eq!(tracker.resolve().unwrap().height, 7);
+        assert!(tracker.current().is_none());
+        Ok(())
+    }
+}
@@ -0,0 +1,258 @@
+use prometheus::IntCounterVec;
+use serde::Serialize;
+use std::sync::{Mutex, OnceLock};
+
+use crate::crosscheck::RootDivergence;
+use crate::prover::QuorumNotReached;
+use crate::registry::RegistryRejection;
+
+/// Parts of a domain's pipeline whose failures are tracked separately
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Subsystem {
+    Prover,
+    Registry,
+    Database,
+    Verifier,
+}
+
+/// The failure classes the loops, metrics and alerting act on. Errors still travel as
+/// `anyhow::Error`, with a `RelayerError` somewhere in the chain deciding how they are
+/// handled; `RelayerError::of` finds it.
+#[derive(Debug, thiserror::Error)]
+pub enum RelayerError {
+    /// Every prover endpoint failed; `cause` is the classification of the last failure
+    #[error("All {endpoints} prover endpoints failed, last error: {reason}")]
+    ProverUnreachable {
+        endpoints: usize,
+        cause: &'static str,
+        reason: String,
+    },
+    /// Proof data that couldn't be decoded. Fails the cycle, never the process.
+    #[error("Failed to decode {what}: {reason}")]
+    ProofDecode {
+        /// What was being decoded, e.g. `public values`
+        what: &'static str,
+        reason: String,
+    },
+    #[error("Proof failed verification against vk {vk}: {reason}")]
+    ProofVerification { vk: String, reason: String },
+    #[error(transparent)]
+    RegistryRejected(#[from] RegistryRejection),
+    /// A database write failed; `operation` is the write's description
+    #[error("Database error: {reason}")]
+    Storage {
+        operation: &'static str,
+        reason: String,
+    },
+    #[error("Invalid configuration: {0}")]
+    Config(String),
+}
+
+impl RelayerError {
+    pub fn decode(what: &'static str, reason: impl std::fmt::Display) -> Self {
+        RelayerError::ProofDecode {
+            what,
+            reason: reason.to_string(),
+        }
+    }
+
+    /// All `endpoints` provers failed, the last one with `last_error`
+    pub fn prover_unreachable(endpoints: usize, last_error: Option<&anyhow::Error>) -> Self {
+        match last_error {
+            Some(e) => RelayerError::ProverUnreachable {
+                endpoints,
+                cause: classify(Subsystem::Prover, e),
+                reason: e.to_string(),
+            },
+            None => RelayerError::ProverUnreachable {
+                endpoints,
+                cause: "other",
+                reason: "no endpoints configured".to_string(),
+            },
+        }
+    }
+
+    /// The first `RelayerError` in the error's chain
+    pub fn of(error: &anyhow::Error) -> Option<&RelayerError> {
+        error
+            .chain()
+            .find_map(|cause| cause.downcast_ref::<RelayerError>())
+    }
+
+    /// The registry's refusal, for errors that are one
+    pub fn rejection(&self) -> Option<&RegistryRejection> {
+        match self {
+            RelayerError::RegistryRejected(rejection) => Some(rejection),
+            _ => None,
+        }
+    }
+
+    /// Coarse cause as shown on `/status`
+    pub fn class(&self) -> &'static str {
+        match self {
+            RelayerError::ProverUnreachable { cause, .. } => *cause,
+            RelayerError::ProofDecode { .. } => "decode",
+            RelayerError::ProofVerification { .. } => "invalid_proof",
+            RelayerError::RegistryRejected(rejection) => rejection.class.as_str(),
+            RelayerError::Storage { .. } => "database",
+            RelayerError::Config(_) => "config",
+        }
+    }
+}
+
+/// The most recent failure of one subsystem
+#[derive(Debug, Clone, Serialize)]
+pub struct LastError {
+    pub message: String,
+    /// Coarse cause, e.g. `timeout`, `connection`, `http_status`, `decode`, `database`,
+    /// `invalid_proof`, `root_divergence` or `quorum`
+    pub classification: String,
+    pub at: String,
+    /// Failures of this subsystem since startup
+    pub count: u64,
+}
+
+/// Last error per subsystem, as shown on `/status`; `None` for subsystems that haven't failed
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct SubsystemErrors {
+    pub prover: Option<LastError>,
+    pub registry: Option<LastError>,
+    pub database: Option<LastError>,
+    pub verifier: Option<LastError>,
+}
+
+/// How one poll of the prover ended
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PollOutcome {
+    /// A proof was fetched, new or not
+    Proof,
+    /// The prover had no proof yet, as between epochs. Normal, so not an error.
+    NoNewProof,
+    Error,
+}
+
+impl PollOutcome {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PollOutcome::Proof => "proof",
+            PollOutcome::NoNewProof => "no_new_proof",
+            PollOutcome::Error => "error",
+        }
+    }
+}
+
+/// A domain's polls of the prover since startup by outcome, as shown on `/status`
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct PollOutcomes {
+    pub proof: u64,
+    pub no_new_proof: u64,
+    pub error: u64,
+    pub last: Option<&'static str>,
+}
+
+/// Collects one domain's failures by subsystem, and its poll outcomes
+#[derive(Default)]
+pub struct ErrorTracker {
+    errors: Mutex<SubsystemErrors>,
+    polls: Mutex<PollOutcomes>,
+    /// `relayer_polls_total` and the domain label polls are counted under
+    poll_counter: OnceLock<(IntCounterVec, String)>,
+}
+
+impl ErrorTracker {
+    /// Counts every poll recorded from now on in `counter`, by outcome
+    pub fn count_polls(&self, counter: IntCounterVec, domain: &str) {
+        let _ = self.poll_counter.set((counter, domain.to_string()));
+    }
+
+    pub fn record_poll(&self, outcome: PollOutcome) {
+        if let Some((counter, domain)) = self.poll_counter.get() {
+            counter
+                .with_label_values(&[domain.as_str(), outcome.as_str()])
+                .inc();
+        }
+        let mut polls = self.polls.lock().unwrap();
+        match outcome {
+            PollOutcome::Proof => polls.proof += 1,
+            PollOutcome::NoNewProof => polls.no_new_proof += 1,
+            PollOutcome::Error => polls.error += 1,
+        }
+        polls.last = Some(outcome.as_str());
+    }
+
+    pub fn polls(&self) -> PollOutcomes {
+        self.polls.lock().unwrap().clone()
+    }
+
+    pub fn record(&self, subsystem: Subsystem, error: &anyhow::Error) {
+        let mut errors = self.errors.lock().unwrap();
+        let slot = match subsystem {
+            Subsystem::Prover => &mut errors.prover,
+            Subsystem::Registry => &mut errors.registry,
+            Subsystem::Database => &mut errors.database,
+            Subsystem::Verifier => &mut errors.verifier,
+        };
+        let count = slot.as_ref().map_or(0, |last| last.count) + 1;
+        *slot = Some(LastError {
+            message: error.to_string(),
+            classification: classify(subsystem, error).to_string(),
+            at: chrono::Utc::now().to_rfc3339(),
+            count,
+        });
+    }
+
+    pub fn snapshot(&self) -> SubsystemErrors {
+        self.errors.lock().unwrap().clone()
+    }
+}
+
+fn classify(subsystem: Subsystem, error: &anyhow::Error) -> &'static str {
+    if let Some(e) = RelayerError::of(error) {
+        return e.class();
+    }
+    for cause in error.chain() {
+        if cause.is::<RootDivergence>() {
+            return "root_divergence";
+        }
+        if cause.is::<QuorumNotReached>() {
+            return "quorum";
+        }
+        if let Some(e) = cause.downcast_ref::<reqwest::Error>() {
+            return if e.is_timeout() {
+                "timeout"
+            } else if e.is_connect() {
+                "connection"
+            } else if e.is_status() {
+                "http_status"
+            } else if e.is_decode() {
+                "decode"
+            } else {
+                "network"
+            };
+        }
+        if cause.is::<rusqlite::Error>()
+            || cause.is::<sqlx::Error>()
+            || cause.is::<redis::RedisError>()
+        {
+            return "database";
+        }
+        if cause.is::<serde_json::Error>()
+            || cause.is::<hex::FromHexError>()
+            || cause.is::<bincode::Error>()
+        {
+            return "decode";
+        }
+        if cause.is::<tokio::time::error::Elapsed>() {
+            return "timeout";
+        }
+        if cause.is::<std::io::Error>() {
+            return "io";
+        }
+    }
+
+    match subsystem {
+        Subsystem::Verifier => "invalid_proof",
+        Subsystem::Prover | Subsystem::Registry => "other",
+        Subsystem::Database => "database",
+    }
+}
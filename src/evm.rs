@@ -0,0 +1,173 @@
+use alloy::network::{EthereumWallet, TransactionBuilder};
+use alloy::primitives::{Address, Bytes, keccak256};
+use alloy::providers::{DynProvider, Provider, ProviderBuilder};
+use alloy::rpc::types::TransactionRequest;
+use alloy::signers::local::PrivateKeySigner;
+use alloy::sol_types::SolValue;
+use anyhow::{Result, anyhow};
+use async_trait::async_trait;
+use std::time::Duration;
+use tokio::sync::Mutex;
+use tracing::{info, warn};
+
+use crate::sink::ProofSink;
+
+/// Submits proofs straight to an Ethereum verifier contract, as `function(proof, publicValues)`
+/// with both arguments ABI-encoded as `bytes`
+pub struct EvmSink {
+    contract: Address,
+    selector: [u8; 4],
+    from: Address,
+    provider: DynProvider,
+    confirmations: u64,
+    receipt_timeout: Duration,
+    gas_multiplier: f64,
+    latest_only: bool,
+    /// Next nonce to use, or `None` to read it from the chain. Holding the lock for the
+    /// whole submission keeps transactions from this sink strictly ordered.
+    next_nonce: Mutex<Option<u64>>,
+}
+
+/// Settings for `EvmSink::new`, see `SinkConfig::Evm`
+pub struct EvmSinkConfig<'a> {
+    pub rpc_url: &'a str,
+    pub contract: &'a str,
+    pub private_key: &'a str,
+    pub function: &'a str,
+    pub confirmations: u64,
+    pub receipt_timeout_secs: u64,
+    pub gas_multiplier: f64,
+    pub latest_only: bool,
+}
+
+impl EvmSink {
+    pub fn new(config: EvmSinkConfig<'_>) -> Result<Self> {
+        let signer: PrivateKeySigner = config
+            .private_key
+            .trim_start_matches("0x")
+            .parse()
+            .map_err(|_| anyhow!("Invalid EVM sink private key"))?;
+        let from = signer.address();
+        let contract: Address = config
+            .contract
+            .parse()
+            .map_err(|e| anyhow!("Invalid contract address {}: {}", config.contract, e))?;
+
+        let provider = ProviderBuilder::new()
+            .wallet(EthereumWallet::from(signer))
+            .connect_http(config.rpc_url.parse()?)
+            .erased();
+
+        let mut selector = [0u8; 4];
+        selector.copy_from_slice(&keccak256(config.function.as_bytes())[..4]);
+
+        Ok(EvmSink {
+            contract,
+            selector,
+            from,
+            provider,
+            confirmations: config.confirmations.max(1),
+            receipt_timeout: Duration::from_secs(config.receipt_timeout_secs),
+            gas_multiplier: config.gas_multiplier.max(1.0),
+            latest_only: config.latest_only,
+            next_nonce: Mutex::new(None),
+        })
+    }
+
+    fn calldata(&self, payload: &serde_json::Value) -> Result<Bytes> {
+        let field = |name: &str| -> Result<Bytes> {
+            let encoded = payload[name]
+                .as_str()
+                .ok_or_else(|| anyhow!("Payload has no {} field", name))?;
+            Ok(Bytes::from(hex::decode(encoded)?))
+        };
+        let arguments = (field("proof")?, field("public_values")?).abi_encode_params();
+
+        let mut calldata = self.selector.to_vec();
+        calldata.extend_from_slice(&arguments);
+        Ok(calldata.into())
+    }
+}
+
+#[async_trait]
+impl ProofSink for EvmSink {
+    fn describe(&self) -> String {
+        format!("EVM contract {}", self.contract)
+    }
+
+    fn latest_only(&self) -> bool {
+        self.latest_only
+    }
+
+    async fn submit(&self, payload: &serde_json::Value, height: u64) -> Result<()> {
+        let calldata = self.calldata(payload)?;
+        let mut next_nonce = self.next_nonce.lock().await;
+
+        let nonce = match *next_nonce {
+            Some(nonce) => nonce,
+            None => {
+                self.provider
+                    .get_transaction_count(self.from)
+                    .pending()
+                    .await?
+            }
+        };
+        let mut tx = TransactionRequest::default()
+            .with_from(self.from)
+            .with_to(self.contract)
+            .with_input(calldata)
+            .with_nonce(nonce);
+
+        // Estimating also simulates the call, so a proof the contract rejects fails here
+        // without spending gas
+        let gas = self.provider.estimate_gas(tx.clone()).await?;
+        let fees = self.provider.estimate_eip1559_fees().await?;
+        tx = tx
+            .with_gas_limit((gas as f64 * self.gas_multiplier) as u64)
+            .with_max_fee_per_gas(fees.max_fee_per_gas)
+            .with_max_priority_fee_per_gas(fees.max_priority_fee_per_gas);
+
+        let pending = match self.provider.send_transaction(tx).await {
+            Ok(pending) => pending,
+            Err(e) => {
+                // The node may know of transactions we don't, so resync before the next one
+                *next_nonce = None;
+                return Err(e.into());
+            }
+        };
+        *next_nonce = Some(nonce + 1);
+        let tx_hash = *pending.tx_hash();
+        info!(
+            "⛓️  Sent proof at height {} in transaction {} (nonce {})",
+            height, tx_hash, nonce
+        );
+
+        let receipt = pending
+            .with_required_confirmations(self.confirmations)
+            .with_timeout(Some(self.receipt_timeout))
+            .get_receipt()
+            .await
+            .map_err(|e| {
+                // If it was dropped, the next transaction must reuse its nonce
+                *next_nonce = None;
+                anyhow!(
+                    "Transaction {} was not confirmed within {:?}: {}",
+                    tx_hash,
+                    self.receipt_timeout,
+                    e
+                )
+            })?;
+        if !receipt.status() {
+            warn!("⚠️  Transaction {} reverted", tx_hash);
+            return Err(anyhow!("Transaction {} reverted", tx_hash));
+        }
+
+        info!(
+            "✅ Proof at height {} confirmed in block {} ({} gas)",
+            height,
+            receipt.block_number.unwrap_or_default(),
+            receipt.gas_used
+        );
+        Ok(())
+    }
+}
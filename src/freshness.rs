@@ -0,0 +1,169 @@
+use serde::Serialize;
+use std::sync::Mutex;
+
+use crate::config::{FreshnessConfig, StalenessConfig};
+
+/// How far a domain's height has progressed across the loop's recent polls
+#[derive(Debug, Clone, Copy, Default, Serialize)]
+pub struct Progress {
+    /// Highest height observed since startup
+    pub last_height: Option<u64>,
+    /// Polls since the height last advanced, including polls that got no proof
+    pub stalled_polls: u64,
+    /// `stalled_polls` in whole expected epochs
+    pub stalled_epochs: u64,
+    pub degraded: bool,
+}
+
+/// How stale a wall-clock domain's latest proof is
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StalenessTier {
+    Fresh,
+    Degraded,
+    Unhealthy,
+}
+
+impl StalenessTier {
+    /// Tier of a proof recorded `age` ago
+    pub fn of(config: &StalenessConfig, age: chrono::Duration) -> Self {
+        let (degraded, unhealthy) = config.thresholds();
+        if age >= unhealthy {
+            StalenessTier::Unhealthy
+        } else if degraded.is_some_and(|degraded| age >= degraded) {
+            StalenessTier::Degraded
+        } else {
+            StalenessTier::Fresh
+        }
+    }
+
+    /// As reported in `/health`'s `status`
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            StalenessTier::Fresh => "healthy",
+            StalenessTier::Degraded => "degraded",
+            StalenessTier::Unhealthy => "unhealthy",
+        }
+    }
+}
+
+/// Judges freshness by whether the height keeps advancing from poll to poll, so that a
+/// jumping or drifting wall clock can't make a domain look healthy or stale. The
+/// counts start over on restart: a domain is healthy until it has stalled for
+/// `max_stalled_epochs` from its first poll.
+pub struct FreshnessTracker {
+    config: FreshnessConfig,
+    progress: Mutex<Progress>,
+}
+
+impl FreshnessTracker {
+    pub fn new(config: FreshnessConfig) -> Self {
+        FreshnessTracker {
+            config,
+            progress: Mutex::new(Progress::default()),
+        }
+    }
+
+    /// Records one poll of the prover: the height it returned, or `None` if it returned
+    /// no usable proof
+    pub fn observe(&self, height: Option<u64>) {
+        let FreshnessConfig::HeightProgression {
+            max_stalled_epochs,
+            polls_per_epoch,
+        } = self.config
+        else {
+            return;
+        };
+        let polls_per_epoch = polls_per_epoch.max(1);
+
+        let mut progress = self.progress.lock().unwrap();
+        match (height, progress.last_height) {
+            (Some(height), last) if last.is_none_or(|last| height > last) => {
+                progress.last_height = Some(height);
+                progress.stalled_polls = 0;
+            }
+            _ => progress.stalled_polls += 1,
+        }
+        progress.stalled_epochs = progress.stalled_polls / polls_per_epoch;
+        progress.degraded = progress.stalled_epochs >= max_stalled_epochs.max(1);
+    }
+
+    /// `None` when freshness is judged by wall-clock timestamps instead
+    pub fn progress(&self) -> Option<Progress> {
+        match self.config {
+            FreshnessConfig::WallClock => None,
+            FreshnessConfig::HeightProgression { .. } => Some(*self.progress.lock().unwrap()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_height_progression_degrades_after_stalled_epochs() {
+        let tracker = FreshnessTracker::new(FreshnessConfig::HeightProgression {
+            max_stalled_epochs: 2,
+            polls_per_epoch: 2,
+        });
+
+        tracker.observe(Some(10));
+        tracker.observe(Some(10));
+        tracker.observe(None);
+        tracker.observe(Some(9));
+        let progress = tracker.progress().unwrap();
+        assert_eq!(progress.stalled_polls, 3);
+        assert!(!progress.degraded);
+
+        tracker.observe(Some(10));
+        assert!(tracker.progress().unwrap().degraded);
+
+        tracker.observe(Some(11));
+        let progress = tracker.progress().unwrap();
+        assert_eq!(progress.last_height, Some(11));
+        assert_eq!(progress.stalled_polls, 0);
+        assert!(!progress.degraded);
+
+        let wall_clock = FreshnessTracker::new(FreshnessConfig::WallClock);
+        wall_clock.observe(Some(1));
+        assert!(wall_clock.progress().is_none());
+    }
+
+    #[test]
+    fn test_staleness_tiers_from_expected_interval() {
+        let minutes = chrono::Duration::minutes;
+        let fixed = StalenessConfig::default();
+        assert_eq!(StalenessTier::of(&fixed, minutes(29)), StalenessTier::Fresh);
+        assert_eq!(
+            StalenessTier::of(&fixed, minutes(30)),
+            StalenessTier::Unhealthy
+        );
+
+        // Helios epochs: degraded after 2 missed epochs, unhealthy after 5
+        let helios = StalenessConfig {
+            expected_interval_secs: Some(384),
+            ..Default::default()
+        };
+        assert_eq!(
+            StalenessTier::of(&helios, minutes(12)),
+            StalenessTier::Fresh
+        );
+        assert_eq!(
+            StalenessTier::of(&helios, minutes(13)),
+            StalenessTier::Degraded
+        );
+        assert_eq!(
+            StalenessTier::of(&helios, minutes(32)),
+            StalenessTier::Unhealthy
+        );
+
+        let overridden = StalenessConfig {
+            unhealthy_after_secs: Some(3600),
+            ..helios
+        };
+        assert_eq!(
+            StalenessTier::of(&overridden, minutes(40)),
+            StalenessTier::Degraded
+        );
+    }
+}
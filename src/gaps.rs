@@ -0,0 +1,96 @@
+use serde::Serialize;
+use std::collections::VecDeque;
+use std::sync::Mutex;
+
+/// Gaps kept for `/stats/gaps`, newest last
+const RECENT_GAPS: usize = 32;
+
+/// Heights skipped between the last relayed proof and a newer one
+#[derive(Debug, Clone, Serialize)]
+pub struct HeightGap {
+    /// First and last height that was missing
+    pub first: u64,
+    pub last: u64,
+    /// Heights in the gap that were relayed by backfill
+    pub backfilled: u64,
+    /// Why the rest of the gap wasn't filled, `None` if it was
+    pub unfilled_reason: Option<String>,
+    pub detected_at: String,
+}
+
+impl HeightGap {
+    pub fn new(first: u64, last: u64) -> Self {
+        HeightGap {
+            first,
+            last,
+            backfilled: 0,
+            unfilled_reason: None,
+            detected_at: chrono::Utc::now().to_rfc3339(),
+        }
+    }
+
+    /// Number of heights missing
+    pub fn heights(&self) -> u64 {
+        self.last - self.first + 1
+    }
+}
+
+/// Height gaps seen by one domain since startup, for `/stats/gaps`
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct GapSnapshot {
+    pub gaps_detected: u64,
+    pub heights_missing: u64,
+    pub heights_backfilled: u64,
+    pub recent: Vec<HeightGap>,
+}
+
+#[derive(Default)]
+pub struct GapTracker {
+    state: Mutex<(GapSnapshot, VecDeque<HeightGap>)>,
+}
+
+impl GapTracker {
+    pub fn record(&self, gap: HeightGap) {
+        let mut state = self.state.lock().unwrap();
+        let (totals, recent) = &mut *state;
+        totals.gaps_detected += 1;
+        totals.heights_missing += gap.heights();
+        totals.heights_backfilled += gap.backfilled;
+        if recent.len() == RECENT_GAPS {
+            recent.pop_front();
+        }
+        recent.push_back(gap);
+    }
+
+    pub fn snapshot(&self) -> GapSnapshot {
+        let state = self.state.lock().unwrap();
+        GapSnapshot {
+            recent: state.1.iter().cloned().collect(),
+            ..state.0.clone()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_gap_totals() {
+        let tracker = GapTracker::default();
+        let mut gap = HeightGap::new(11, 15);
+        gap.backfilled = 2;
+        gap.unfilled_reason = Some("backfill stopped at height 13".to_string());
+        tracker.record(gap);
+        for first in 0..RECENT_GAPS as u64 {
+            tracker.record(HeightGap::new(100 + first * 10, 100 + first * 10));
+        }
+
+        let snapshot = tracker.snapshot();
+        assert_eq!(snapshot.gaps_detected, RECENT_GAPS as u64 + 1);
+        assert_eq!(snapshot.heights_missing, 5 + RECENT_GAPS as u64);
+        assert_eq!(snapshot.heights_backfilled, 2);
+        assert_eq!(snapshot.recent.len(), RECENT_GAPS);
+        assert_eq!(snapshot.recent[0].first, 100);
+    }
+}
@@ -0,0 +1,184 @@
+use anyhow::Result;
+use futures::Stream;
+use std::pin::Pin;
+use std::sync::Arc;
+use tokio::sync::mpsc;
+use tonic::{Request, Response, Status};
+use tracing::{info, warn};
+
+use crate::api::{EventStreams, ProofEvent};
+use crate::broadcast::Broadcaster;
+use crate::config::{GrpcConfig, MODE};
+use crate::db::ProofRecord;
+use crate::domain::{Domain, DomainSet};
+use crate::relayer::{decode_public_values, decode_tendermint_chain_id};
+
+pub mod proto {
+    tonic::include_proto!("helios_proof_relayer.v1");
+}
+
+use proto::proof_stream_server::{ProofStream, ProofStreamServer};
+use proto::{DecodedOutputs, ProofMessage, SubscribeProofsRequest};
+
+/// Proofs read from the history per query while a stream catches up
+const CATCH_UP_BATCH: usize = 100;
+
+type ProofMessages = Pin<Box<dyn Stream<Item = Result<ProofMessage, Status>> + Send>>;
+
+/// Serves `SubscribeProofs` from the same proof events as `/ws`. The events only wake a
+/// stream up; proofs are read from the history by sequence number, so a client that
+/// falls behind resumes where it was instead of losing proofs.
+pub struct ProofStreamService {
+    domains: Arc<DomainSet>,
+    proofs: Arc<Broadcaster<ProofEvent>>,
+    stream_buffer: usize,
+}
+
+impl ProofStreamService {
+    pub fn new(domains: Arc<DomainSet>, events: &EventStreams, config: &GrpcConfig) -> Self {
+        ProofStreamService {
+            domains,
+            proofs: events.proofs.clone(),
+            stream_buffer: config.stream_buffer.max(1),
+        }
+    }
+}
+
+#[tonic::async_trait]
+impl ProofStream for ProofStreamService {
+    type SubscribeProofsStream = ProofMessages;
+
+    async fn subscribe_proofs(
+        &self,
+        request: Request<SubscribeProofsRequest>,
+    ) -> Result<Response<Self::SubscribeProofsStream>, Status> {
+        let request = request.into_inner();
+        let domain = self
+            .domains
+            .get(&request.domain)
+            .ok_or_else(|| Status::not_found(format!("Unknown domain {}", request.domain)))?;
+
+        // Without a checkpoint, start from the latest proof
+        let since_seq = match request.since_seq {
+            Some(since_seq) => since_seq,
+            None => domain
+                .read(|db| db.get_latest_proof())
+                .await
+                .map_err(|e| Status::internal(e.to_string()))?
+                .map_or(0, |latest| latest.seq.saturating_sub(1)),
+        };
+        info!(
+            "📡 [{}] gRPC client subscribed from seq {}",
+            domain.name(),
+            since_seq
+        );
+
+        // A full channel stops the feed until the client reads, on top of HTTP/2 flow control
+        let (tx, rx) = mpsc::channel(self.stream_buffer);
+        tokio::spawn(feed(domain, self.proofs.clone(), since_seq, tx));
+        let messages = futures::stream::unfold(rx, |mut rx| async move {
+            rx.recv().await.map(|message| (message, rx))
+        });
+        Ok(Response::new(Box::pin(messages)))
+    }
+}
+
+/// Sends the domain's proofs above `last_seq` to `tx` until the client goes away
+async fn feed(
+    domain: Arc<Domain>,
+    proofs: Arc<Broadcaster<ProofEvent>>,
+    mut last_seq: u64,
+    tx: mpsc::Sender<Result<ProofMessage, Status>>,
+) {
+    // Subscribe before reading the history so nothing falls in between
+    let mut subscription = proofs.subscribe();
+    loop {
+        loop {
+            let records = match domain
+                .read(move |db| db.get_proofs_since_seq(last_seq, CATCH_UP_BATCH))
+                .await
+            {
+                Ok(records) => records,
+                Err(e) => {
+                    warn!(
+                        "⚠️  [{}] Failed to read proofs for gRPC stream: {}",
+                        domain.name(),
+                        e
+                    );
+                    let _ = tx.send(Err(Status::internal(e.to_string()))).await;
+                    return;
+                }
+            };
+            let caught_up = records.len() < CATCH_UP_BATCH;
+            for record in records {
+                last_seq = record.seq;
+                if tx.send(Ok(proof_message(&domain, record))).await.is_err() {
+                    return;
+                }
+            }
+            if caught_up {
+                break;
+            }
+        }
+
+        loop {
+            tokio::select! {
+                event = subscription.recv() => match event {
+                    Some(event) if event.domain == domain.name() && event.seq > last_seq => break,
+                    Some(_) => continue,
+                    // Dropped for falling behind; the history covers what was missed
+                    None => {
+                        subscription = proofs.subscribe();
+                        break;
+                    }
+                },
+                _ = tx.closed() => return,
+            }
+        }
+    }
+}
+
+fn proof_message(domain: &Domain, record: ProofRecord) -> ProofMessage {
+    let mode = domain.config.mode;
+    let outputs = decode_public_values(mode, &record.public_values)
+        .ok()
+        .map(|(height, root)| DecodedOutputs {
+            height,
+            root: root.to_vec(),
+            chain_id: match mode {
+                MODE::TENDERMINT => decode_tendermint_chain_id(&record.public_values)
+                    .ok()
+                    .flatten(),
+                MODE::HELIOS => None,
+            },
+        });
+
+    ProofMessage {
+        seq: record.seq,
+        domain: domain.name().to_string(),
+        mode: mode.as_str().to_string(),
+        height: record.height,
+        root: record.root,
+        proof_id: record.proof_id.to_string(),
+        proof: record.proof,
+        public_values: record.public_values,
+        timestamp: record.timestamp.to_rfc3339(),
+        outputs,
+    }
+}
+
+/// Serves the proof stream on `config.listen_addr` until the server fails
+pub async fn start_grpc_server(
+    config: GrpcConfig,
+    domains: Arc<DomainSet>,
+    events: EventStreams,
+) -> Result<()> {
+    let addr = config.listen_addr.parse()?;
+    let service = ProofStreamService::new(domains, &events, &config);
+    info!("📡 gRPC proof stream listening on {}", addr);
+    tonic::transport::Server::builder()
+        .add_service(ProofStreamServer::new(service))
+        .serve(addr)
+        .await?;
+    Ok(())
+}
@@ -0,0 +1,492 @@
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::time::sleep;
+use tracing::{Instrument, error, info, info_span, warn};
+
+use crate::alerting::{Alerter, DomainAlerts, spawn_alerts};
+use crate::api::{EventStreams, ProofEvent, StreamEvent};
+use crate::candidate::run_candidate_comparison;
+use crate::config::Config;
+use crate::crosscheck::RootDivergence;
+use crate::db::{DecodeFailure, HealthCheckData, PreviousProof, ProofRecord, Storage};
+use crate::domain::Domain;
+use crate::errors::{PollOutcome, Subsystem};
+use crate::metrics::{Latency, Metrics};
+use crate::polling::PollLoop;
+use crate::proof_id::ProofId;
+use crate::reference::fetch_with_head;
+use crate::relayer::{check_chain_id, decode_public_values, get_proof};
+use crate::rollback::Verdict;
+use crate::source::NoNewProof;
+use crate::watchdog::{CycleWatchdog, spawn_watchdog};
+
+/// Monitors every configured domain's proofs; the entry point for embedding the health
+/// checker. Newly accepted proofs are published on `events` and counted in `metrics`.
+pub struct HealthChecker {
+    config: Arc<Config>,
+    domains: Vec<Arc<Domain>>,
+    metrics: Arc<Metrics>,
+    events: EventStreams,
+    alerter: Option<Arc<Alerter>>,
+}
+
+impl HealthChecker {
+    /// Opens every domain's storage, clearing the latest-value tables for a fresh start.
+    /// This blocks on storage, so call it through `spawn_blocking` from async code.
+    pub fn open(config: Arc<Config>) -> anyhow::Result<Self> {
+        info!("💾 Initializing databases...");
+        let metrics = Arc::new(Metrics::new(&config.metrics)?);
+        let mut domains = Vec::new();
+        for domain_config in &config.domains {
+            let domain = Arc::new(
+                Domain::open(domain_config.clone(), &config.storage, &config.db_prefix())?
+                    .with_metrics(&metrics),
+            );
+
+            // Clear database for testing
+            info!(
+                "🧹 [{}] Clearing database tables for fresh start...",
+                domain.name()
+            );
+            if let Err(e) = domain.db.clear_all_tables() {
+                warn!("⚠️  Failed to clear database tables: {}", e);
+            } else {
+                info!("✅ Database tables cleared successfully");
+            }
+
+            domains.push(domain);
+        }
+        info!("✅ Databases initialized successfully");
+
+        Ok(HealthChecker {
+            domains,
+            metrics,
+            events: EventStreams::default(),
+            alerter: Alerter::new(&config.alerting)?.map(Arc::new),
+            config,
+        })
+    }
+
+    pub fn domains(&self) -> &[Arc<Domain>] {
+        &self.domains
+    }
+
+    pub fn metrics(&self) -> Arc<Metrics> {
+        self.metrics.clone()
+    }
+
+    pub fn events(&self) -> EventStreams {
+        self.events.clone()
+    }
+
+    /// Runs a health check loop per domain, plus candidate comparisons where configured.
+    /// Only returns if a health check loop crashes.
+    pub async fn run(self) -> anyhow::Result<()> {
+        info!("🔍 Starting health check services...");
+        let mut handles = Vec::new();
+        for domain in &self.domains {
+            let span = info_span!("domain", domain = %domain.name());
+
+            if let Some(candidate) = domain.candidate.clone() {
+                tokio::spawn(
+                    run_candidate_comparison(
+                        domain.clone(),
+                        candidate,
+                        Duration::from_secs(domain.config.polling.health_check_interval_secs),
+                    )
+                    .instrument(span.clone()),
+                );
+            }
+
+            handles.push(tokio::spawn(
+                run_health_check_loop(
+                    domain.clone(),
+                    self.metrics.clone(),
+                    self.events.clone(),
+                    self.config.inline_history_retention(),
+                    spawn_watchdog(domain, self.config.fail_fast),
+                    spawn_alerts(domain, self.alerter.clone(), &self.config.alerting),
+                )
+                .instrument(span),
+            ));
+        }
+
+        for handle in handles {
+            if let Err(e) = handle.await {
+                error!("❌ Health check service crashed: {}", e);
+                return Err(anyhow::anyhow!("{}", e));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Waits out the check interval, recording when the next check is due. `found_new` is
+/// whether this check fetched a proof above the last one.
+async fn wait_for_next_check(domain: &Domain, found_new: bool) {
+    let interval = domain
+        .polling
+        .next_interval(PollLoop::HealthCheck, found_new);
+    info!("⏰ Waiting {:?} before next check...", interval);
+    domain.scheduler.waiting(interval);
+    sleep(interval).await;
+}
+
+/// Polls the domain's provers and records every new proof's height and root
+pub async fn run_health_check_loop(
+    domain: Arc<Domain>,
+    metrics: Arc<Metrics>,
+    events: EventStreams,
+    history_retention: Option<u64>,
+    watchdog: Arc<CycleWatchdog>,
+    alerts: Arc<DomainAlerts>,
+) {
+    let name = domain.name().to_string();
+    domain.provers.detect_api_versions().await;
+    info!("✅ [{}] Health check service started", name);
+
+    loop {
+        domain.scheduler.cycle_started();
+        let mut found_new = false;
+        info!("🔍 [{}] Fetching latest proof...", name);
+        let fetch = async {
+            let started = std::time::Instant::now();
+            let fetched = get_proof(&domain.provers).await;
+            if fetched.is_ok() {
+                domain.latency.observe(Latency::Prover, started.elapsed());
+            }
+            fetched
+        };
+        let (fetched, head) = fetch_with_head(domain.reference.as_deref(), fetch).await;
+        match fetched {
+            Ok(proof) => {
+                info!("✅ [{}] Proof fetched successfully", name);
+                domain.errors.record_poll(PollOutcome::Proof);
+                watchdog.record_success();
+
+                if let Some(envelope) = &domain.envelope
+                    && let Err(e) = envelope.check(&proof)
+                {
+                    error!("🚨 [{}] Prover returned a malformed proof: {}", name, e);
+                    domain.errors.record(Subsystem::Verifier, &e);
+                    domain.freshness.observe(None);
+                    wait_for_next_check(&domain, false).await;
+                    continue;
+                }
+
+                let (current_height, current_root) = match decode_public_values(
+                    domain.config.mode,
+                    proof.public_values.as_slice(),
+                ) {
+                    Ok(outputs) => outputs,
+                    Err(e) => {
+                        error!("❌ [{}] {}", name, e);
+                        alerts.record_decode_failure(&e);
+                        let failure = DecodeFailure::new(
+                            &e,
+                            domain.provers.active_endpoint(),
+                            proof.public_values.as_slice(),
+                        );
+                        domain.writer.submit("record decode failure", move |db| {
+                            db.record_decode_failure(&failure).map(|_| ())
+                        });
+                        domain.freshness.observe(None);
+                        wait_for_next_check(&domain, false).await;
+                        continue;
+                    }
+                };
+                if let Err(e) = check_chain_id(
+                    domain.config.mode,
+                    domain.config.chain_id.as_deref(),
+                    proof.public_values.as_slice(),
+                ) {
+                    error!("🚨 [{}] Rejecting proof from the wrong chain: {}", name, e);
+                    domain.freshness.observe(None);
+                    wait_for_next_check(&domain, false).await;
+                    continue;
+                }
+                if let (Some(reference), Some(head)) = (&domain.reference, head)
+                    && let Err(e) = reference.check(current_height, head)
+                {
+                    error!("🚨 [{}] Rejecting proof: {}", name, e);
+                    domain.freshness.observe(None);
+                    wait_for_next_check(&domain, false).await;
+                    continue;
+                }
+                domain.freshness.observe(Some(current_height));
+
+                // A second root for a stored height is critical whatever happens to the proof
+                let conflicts = domain.conflicts.clone();
+                let prover = domain.provers.active_endpoint().map(str::to_string);
+                if let Err(e) = domain
+                    .read(move |db| {
+                        conflicts.check(db, current_height, &current_root, prover.as_deref())
+                    })
+                    .await
+                {
+                    warn!("⚠️  Failed to compare root with proof history: {}", e);
+                    domain.errors.record(Subsystem::Database, &e);
+                }
+                alerts.record_root_conflict(domain.conflicts.current().as_ref());
+
+                // Only heights above the last processed one are new. Lower heights within the
+                // reorg tolerance go on to the rollback guard, which skips them unless the
+                // rollback policy allows them; deeper ones are refused outright.
+                let last_height = match domain.read(|db| db.get_latest_health_check()).await {
+                    Ok(health) => health.map(|health| health.current_height),
+                    Err(e) => {
+                        warn!(
+                            "⚠️  Error getting last processed height from database: {}",
+                            e
+                        );
+                        domain.errors.record(Subsystem::Database, &e);
+                        None
+                    }
+                };
+                alerts.record_decoded(
+                    current_height,
+                    last_height.is_none_or(|last| current_height > last),
+                );
+                if let Some(depth) = domain.rollback.regression(current_height, last_height) {
+                    let last = last_height.unwrap_or_default();
+                    error!(
+                        "🚨 [{}] Refusing proof at height {}, {} below last processed height {} (reorg tolerance {})",
+                        name, current_height, depth, last, domain.config.reorg_tolerance
+                    );
+                    alerts.record_height_regression(current_height, last);
+                    wait_for_next_check(&domain, false).await;
+                    continue;
+                }
+                if let Some(check) = &domain.root_check
+                    && last_height.is_none_or(|last| current_height > last)
+                {
+                    let result = check.check(current_height, &current_root).await;
+                    alerts.record_root_check(
+                        result
+                            .as_ref()
+                            .err()
+                            .and_then(|e| e.downcast_ref::<RootDivergence>()),
+                    );
+                    if let Err(e) = result {
+                        error!("🚨 [{}] Refusing proof: {}", name, e);
+                        domain.errors.record(Subsystem::Prover, &e);
+                        wait_for_next_check(&domain, false).await;
+                        continue;
+                    }
+                }
+                match last_height {
+                    None => info!("🆕 No previous proof found, processing new proof"),
+                    Some(last) if current_height > last => info!(
+                        "🔄 New proof at height {} (last processed {}), processing",
+                        current_height, last
+                    ),
+                    Some(last) if current_height < last => info!(
+                        "⏪ Proof at height {} is below last processed height {}",
+                        current_height, last
+                    ),
+                    Some(_) => {
+                        info!(
+                            "⏳ No new proof (height {} already processed), skipping update",
+                            current_height
+                        );
+                        wait_for_next_check(&domain, false).await;
+                        continue;
+                    }
+                }
+                found_new = true;
+
+                domain.vk_monitor.observe(&proof, Some(current_height));
+
+                info!(
+                    height = current_height,
+                    root = %hex::encode(current_root),
+                    "📊 [{}] Processing proof - Height: {}, Root: {}",
+                    name,
+                    current_height,
+                    hex::encode(current_root)
+                );
+
+                // Guard against heights below the high-water mark
+                let rollback = domain.rollback.clone();
+                let rolled_back = match domain
+                    .read(move |db| rollback.check(db, current_height, &current_root))
+                    .await
+                {
+                    Ok(Verdict::Accept) => false,
+                    Ok(Verdict::AcceptRollback) => true,
+                    Ok(Verdict::Skip) => {
+                        wait_for_next_check(&domain, false).await;
+                        continue;
+                    }
+                    Err(e) => {
+                        error!("❌ Failed to check proof height against history: {}", e);
+                        domain.errors.record(Subsystem::Database, &e);
+                        wait_for_next_check(&domain, false).await;
+                        continue;
+                    }
+                };
+
+                let proof_bytes = proof.bytes();
+                let record = ProofRecord {
+                    seq: 0,
+                    height: current_height,
+                    proof_id: ProofId::of(domain.config.proof_id_algorithm, &proof_bytes),
+                    proof: proof_bytes,
+                    public_values: proof.public_values.to_vec(),
+                    root: current_root.to_vec(),
+                    timestamp: chrono::Utc::now(),
+                };
+                let (proof_id, recorded_at) = (record.proof_id.clone(), record.timestamp);
+                let (writer_name, writer_events) = (name.clone(), events.clone());
+                let recorded = domain
+                    .writer
+                    .write("record health check", move |db| {
+                        record_checked(
+                            db,
+                            &writer_name,
+                            &record,
+                            rolled_back,
+                            history_retention,
+                            &writer_events,
+                        )
+                    })
+                    .await;
+                if let Err(e) = recorded {
+                    domain.errors.record(Subsystem::Database, &e);
+                }
+
+                metrics
+                    .proofs_accepted
+                    .with_label_values(&[name.as_str()])
+                    .inc();
+                metrics
+                    .latest_height
+                    .with_label_values(&[name.as_str()])
+                    .set(current_height as i64);
+                metrics
+                    .last_proof_timestamp
+                    .with_label_values(&[name.as_str()])
+                    .set(recorded_at.timestamp());
+                metrics.set_latest_proof(&name, &proof_id);
+            }
+            Err(e) if e.is::<NoNewProof>() => {
+                info!("⏳ [{}] {}, waiting for next check...", name, e);
+                domain.errors.record_poll(PollOutcome::NoNewProof);
+                domain.freshness.observe(None);
+                watchdog.record_success();
+            }
+            Err(e) => {
+                error!("❌ [{}] Health check failed: {}", name, e);
+                domain.errors.record(Subsystem::Prover, &e);
+                domain.errors.record_poll(PollOutcome::Error);
+                domain.freshness.observe(None);
+                watchdog.record_failure();
+            }
+        }
+        wait_for_next_check(&domain, found_new).await;
+    }
+}
+
+/// Records a proof the health check accepted, after discarding the abandoned fork on a
+/// rollback: the latest-value tables, the history and its pruning. Newly recorded proofs
+/// are published on `events`. Every step is attempted; the first failure is returned.
+fn record_checked(
+    db: &dyn Storage,
+    domain: &str,
+    record: &ProofRecord,
+    rolled_back: bool,
+    history_retention: Option<u64>,
+    events: &EventStreams,
+) -> anyhow::Result<()> {
+    let mut first_error = None;
+
+    if rolled_back {
+        match db.delete_proofs_from_height(record.height) {
+            Ok(removed) => warn!("⚠️  Discarded {} proofs from the abandoned fork", removed),
+            Err(e) => {
+                error!("❌ Failed to discard proofs above rollback height: {}", e);
+                first_error.get_or_insert(e);
+            }
+        }
+    }
+
+    let health_data = HealthCheckData {
+        current_height: record.height,
+        current_root: record.root.clone(),
+        timestamp: record.timestamp,
+    };
+    match db.update_health_check(&health_data) {
+        Ok(()) => info!(
+            height = record.height,
+            root = %hex::encode(&record.root),
+            "💾 [{}] Health check data updated - Height: {}, Root: {}",
+            domain,
+            record.height,
+            hex::encode(&record.root)
+        ),
+        Err(e) => {
+            error!("❌ Failed to update health check data in database: {}", e);
+            first_error.get_or_insert(e);
+        }
+    }
+
+    // Append the proof to the history table
+    match db.insert_proof(record) {
+        Ok(Some(seq)) => {
+            info!(
+                height = record.height,
+                seq,
+                proof_id = %record.proof_id,
+                "📚 Proof {} at height {} added to history (seq {})",
+                record.proof_id.short(),
+                record.height,
+                seq
+            );
+            // Consumers checkpoint on the sequence number, so only newly recorded proofs are pushed
+            events.proofs.publish(ProofEvent {
+                seq,
+                domain: domain.to_string(),
+                height: record.height,
+                root: hex::encode(&record.root),
+                proof_id: Some(record.proof_id.clone()),
+                timestamp: record.timestamp.to_rfc3339(),
+            });
+            events.lifecycle.publish(StreamEvent::ProofAccepted {
+                domain: domain.to_string(),
+                height: record.height,
+                root: hex::encode(&record.root),
+                proof_id: record.proof_id.clone(),
+            });
+        }
+        Ok(None) => info!("📚 Proof at height {} already in history", record.height),
+        Err(e) => {
+            error!("❌ Failed to store proof in history: {}", e);
+            first_error.get_or_insert(e);
+        }
+    }
+    if let Some(keep) = history_retention {
+        match db.prune_proof_history(keep) {
+            Ok(0) => {}
+            Ok(removed) => info!("🧹 Pruned {} old proofs from history", removed),
+            Err(e) => {
+                warn!("⚠️  Failed to prune proof history: {}", e);
+                first_error.get_or_insert(e);
+            }
+        }
+    }
+
+    // Store the new proof in database
+    let proof_data = PreviousProof {
+        proof_data: hex::encode(&record.proof),
+        timestamp: chrono::Utc::now(),
+    };
+    match db.update_previous_proof(&proof_data) {
+        Ok(()) => info!("💾 Proof stored in database"),
+        Err(e) => {
+            error!("❌ Failed to update previous proof in database: {}", e);
+            first_error.get_or_insert(e);
+        }
+    }
+
+    first_error.map_or(Ok(()), Err)
+}
@@ -0,0 +1,284 @@
+use anyhow::{Result, anyhow};
+use async_trait::async_trait;
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD as BASE64;
+use ed25519_dalek::{Signer as _, SigningKey};
+use serde_json::json;
+use std::time::Duration;
+
+use crate::aws::{AwsCredentials, sign_v4};
+use crate::config::{KmsSigningConfig, PayloadSigningConfig};
+use crate::relayer::http_client;
+
+/// Largest message KMS signs as is (`MessageType: RAW`)
+const KMS_MAX_MESSAGE: usize = 4096;
+
+/// Prefixed to every signed payload message, so a payload signature can't pass for any
+/// other message signed with the same key
+const PAYLOAD_DOMAIN: &[u8] = b"helios-proof-relayer/payload/v1";
+
+/// Holds an ed25519 key and signs with it, wherever the key lives
+#[async_trait]
+pub trait Signer: Send + Sync {
+    /// Where the key is, for logs
+    fn describe(&self) -> String;
+
+    async fn sign(&self, message: &[u8]) -> Result<[u8; 64]>;
+}
+
+/// The relayer's ed25519 identity, used to sign API responses so consumers can tell they
+/// weren't altered by a proxy in between
+pub struct IdentityKey {
+    signing_key: SigningKey,
+}
+
+impl IdentityKey {
+    /// Loads the 32-byte secret key, hex encoded, from `key_path` or else the
+    /// `RELAYER_IDENTITY_KEY` environment variable
+    pub fn load(key_path: Option<&str>) -> Result<Self> {
+        let encoded = match key_path {
+            Some(path) => std::fs::read_to_string(path)
+                .map_err(|e| anyhow!("Failed to read identity key {}: {}", path, e))?,
+            None => std::env::var("RELAYER_IDENTITY_KEY")
+                .map_err(|_| anyhow!("Signing is enabled but no identity key is set"))?,
+        };
+        let secret: [u8; 32] = hex::decode(encoded.trim().trim_start_matches("0x"))?
+            .try_into()
+            .map_err(|_| anyhow!("Identity key must be 32 bytes"))?;
+        Ok(IdentityKey {
+            signing_key: SigningKey::from_bytes(&secret),
+        })
+    }
+
+    /// Hex-encoded public key consumers verify signatures with
+    pub fn public_key(&self) -> String {
+        hex::encode(self.signing_key.verifying_key().as_bytes())
+    }
+
+    pub fn sign(&self, message: &[u8]) -> [u8; 64] {
+        self.signing_key.sign(message).to_bytes()
+    }
+}
+
+#[async_trait]
+impl Signer for IdentityKey {
+    fn describe(&self) -> String {
+        format!("local key {}", self.public_key())
+    }
+
+    async fn sign(&self, message: &[u8]) -> Result<[u8; 64]> {
+        Ok(IdentityKey::sign(self, message))
+    }
+}
+
+/// Signs through the AWS KMS `Sign` API with `ED25519_SHA_512`, so the secret key never
+/// leaves KMS. The signatures are plain ed25519, verifiable like a local key's.
+pub struct KmsSigner {
+    key_id: String,
+    region: String,
+    url: reqwest::Url,
+    credentials: AwsCredentials,
+    client: reqwest::Client,
+}
+
+impl KmsSigner {
+    pub fn new(config: &KmsSigningConfig) -> Result<Self> {
+        let endpoint = match &config.endpoint {
+            Some(endpoint) => endpoint.clone(),
+            None => format!("https://kms.{}.amazonaws.com/", config.region),
+        };
+        Ok(KmsSigner {
+            key_id: config.key_id.clone(),
+            region: config.region.clone(),
+            url: reqwest::Url::parse(&endpoint)?,
+            credentials: AwsCredentials::resolve(
+                config.access_key_id.as_deref(),
+                config.secret_access_key.as_deref(),
+                &format!("KMS key {}", config.key_id),
+            )?,
+            client: http_client(None, Some(Duration::from_secs(10)))?,
+        })
+    }
+}
+
+#[async_trait]
+impl Signer for KmsSigner {
+    fn describe(&self) -> String {
+        format!("KMS key {}", self.key_id)
+    }
+
+    async fn sign(&self, message: &[u8]) -> Result<[u8; 64]> {
+        if message.len() > KMS_MAX_MESSAGE {
+            return Err(anyhow!(
+                "Message of {} bytes is over the {} KMS signs",
+                message.len(),
+                KMS_MAX_MESSAGE
+            ));
+        }
+        let body = serde_json::to_vec(&json!({
+            "KeyId": self.key_id,
+            "Message": BASE64.encode(message),
+            "MessageType": "RAW",
+            "SigningAlgorithm": "ED25519_SHA_512",
+        }))?;
+        let headers = sign_v4(
+            "POST",
+            &self.url,
+            "kms",
+            &self.region,
+            vec![
+                ("content-type", "application/x-amz-json-1.1".to_string()),
+                ("x-amz-target", "TrentService.Sign".to_string()),
+            ],
+            &body,
+            &self.credentials,
+        );
+        let mut request = self.client.post(self.url.clone());
+        for (name, value) in headers {
+            request = request.header(name, value);
+        }
+
+        let response = request.body(body).send().await?;
+        let status = response.status();
+        let text = response.text().await?;
+        if !status.is_success() {
+            return Err(anyhow!("KMS responded with status {}: {}", status, text));
+        }
+        let response: serde_json::Value = serde_json::from_str(&text)?;
+        let signature = response["Signature"]
+            .as_str()
+            .ok_or_else(|| anyhow!("KMS response has no signature"))?;
+        BASE64
+            .decode(signature)?
+            .try_into()
+            .map_err(|_| anyhow!("KMS returned a signature that isn't 64 bytes"))
+    }
+}
+
+/// Signs registry payloads as one relayer. The signature covers the proof, public values
+/// and VK the registry receives, the relayer id and the signing time, so the registry can
+/// reject submissions it can't attribute to a known relayer, and stale replays.
+pub struct PayloadSigner {
+    relayer_id: String,
+    signer: Box<dyn Signer>,
+}
+
+impl PayloadSigner {
+    /// Signs with the KMS key when one is configured, else with the local key
+    pub fn new(config: &PayloadSigningConfig) -> Result<Self> {
+        let signer: Box<dyn Signer> = match &config.kms {
+            Some(kms) => Box::new(KmsSigner::new(kms)?),
+            None => Box::new(IdentityKey::load(config.key_path.as_deref())?),
+        };
+        Ok(PayloadSigner {
+            relayer_id: config.relayer_id.clone(),
+            signer,
+        })
+    }
+
+    pub fn describe(&self) -> String {
+        format!("{} ({})", self.relayer_id, self.signer.describe())
+    }
+
+    /// The payload with `relayer_id`, `signed_at` (unix seconds) and `signature` (hex
+    /// ed25519 over `payload_message`) added
+    pub async fn sign(&self, payload: &serde_json::Value) -> Result<serde_json::Value> {
+        let field = |name: &str| -> Result<Vec<u8>> {
+            let encoded = payload[name]
+                .as_str()
+                .ok_or_else(|| anyhow!("Payload has no {} to sign", name))?;
+            Ok(hex::decode(encoded.trim_start_matches("0x"))?)
+        };
+        let signed_at = chrono::Utc::now().timestamp() as u64;
+        let message = payload_message(
+            &field("proof")?,
+            &field("public_values")?,
+            &field("vk")?,
+            &self.relayer_id,
+            signed_at,
+        );
+
+        let mut signed = payload.clone();
+        signed["relayer_id"] = json!(self.relayer_id);
+        signed["signed_at"] = json!(signed_at);
+        signed["signature"] = json!(hex::encode(self.signer.sign(&message).await?));
+        Ok(signed)
+    }
+}
+
+/// What a payload signature is over: `PAYLOAD_DOMAIN`, then the proof, public values, VK
+/// and relayer id, each prefixed with its length as a big-endian u32, then `signed_at` as
+/// a big-endian u64. The length prefixes keep one field's bytes from being read as part of
+/// the next.
+pub fn payload_message(
+    proof: &[u8],
+    public_values: &[u8],
+    vk: &[u8],
+    relayer_id: &str,
+    signed_at: u64,
+) -> Vec<u8> {
+    let mut message = PAYLOAD_DOMAIN.to_vec();
+    for field in [proof, public_values, vk, relayer_id.as_bytes()] {
+        message.extend_from_slice(&(field.len() as u32).to_be_bytes());
+        message.extend_from_slice(field);
+    }
+    message.extend_from_slice(&signed_at.to_be_bytes());
+    message
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ed25519_dalek::{Signature, Verifier, VerifyingKey};
+
+    #[test]
+    fn test_signature_verifies_against_public_key() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("identity.key");
+        std::fs::write(&path, format!("{}\n", hex::encode([7u8; 32])))?;
+        let identity = IdentityKey::load(path.to_str())?;
+
+        let body = br#"{"status":"healthy"}"#;
+        let signature = Signature::from_bytes(&identity.sign(body));
+        let public_key: [u8; 32] = hex::decode(identity.public_key())?.try_into().unwrap();
+        let verifying_key = VerifyingKey::from_bytes(&public_key)?;
+        assert!(verifying_key.verify(body, &signature).is_ok());
+        assert!(verifying_key.verify(b"tampered", &signature).is_err());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_payload_signature_covers_proof_and_relayer() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("identity.key");
+        std::fs::write(&path, hex::encode([9u8; 32]))?;
+        let signer = PayloadSigner::new(&PayloadSigningConfig {
+            relayer_id: "relayer-1".to_string(),
+            key_path: Some(path.to_str().unwrap().to_string()),
+            kms: None,
+        })?;
+
+        let payload = json!({
+            "proof": hex::encode([1u8; 8]),
+            "public_values": hex::encode([2u8; 4]),
+            "vk": format!("0x{}", hex::encode([3u8; 32])),
+            "proof_id": "abc",
+        });
+        let signed = signer.sign(&payload).await?;
+        assert_eq!(signed["relayer_id"], "relayer-1");
+        assert_eq!(signed["proof_id"], "abc");
+
+        let signed_at = signed["signed_at"].as_u64().unwrap();
+        let signature: [u8; 64] = hex::decode(signed["signature"].as_str().unwrap())?
+            .try_into()
+            .unwrap();
+        let signature = Signature::from_bytes(&signature);
+        let verifying_key = SigningKey::from_bytes(&[9; 32]).verifying_key();
+
+        let message = payload_message(&[1; 8], &[2; 4], &[3; 32], "relayer-1", signed_at);
+        assert!(verifying_key.verify(&message, &signature).is_ok());
+        let spoofed = payload_message(&[1; 8], &[2; 4], &[3; 32], "relayer-2", signed_at);
+        assert!(verifying_key.verify(&spoofed, &signature).is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,595 @@
+use anyhow::{Result, anyhow};
+use chrono::{DateTime, Datelike, TimeZone, Timelike, Utc};
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+use tracing::{error, info, warn};
+
+use crate::alerting::Alerter;
+use crate::config::{Config, HistoryPruningConfig, JobConfig, JobKind};
+use crate::domain::{Domain, DomainSet};
+use crate::errors::Subsystem;
+use crate::pruning::prunable;
+use crate::shutdown::domain_report;
+use crate::timeseries::{backfill_history, parse_window, sample_all};
+use crate::verifier::verify_wrapper_proof;
+
+/// When a job runs: `@every <window>` (e.g. `@every 30s`, counted from the end of the
+/// previous run), `@hourly`, `@daily`, `@weekly`, or a five-field cron expression
+/// (minute, hour, day of month, month, day of week) evaluated in UTC
+#[derive(Debug, Clone)]
+pub enum Schedule {
+    Every(chrono::Duration),
+    Cron(Cron),
+}
+
+/// The values each cron field allows, indexed by value
+#[derive(Debug, Clone)]
+pub struct Cron {
+    minutes: Vec<bool>,
+    hours: Vec<bool>,
+    days: Vec<bool>,
+    months: Vec<bool>,
+    /// Sunday is 0
+    weekdays: Vec<bool>,
+    /// Like cron, a day matches either field when both are restricted
+    days_restricted: bool,
+    weekdays_restricted: bool,
+}
+
+impl Schedule {
+    pub fn parse(expression: &str) -> Result<Self> {
+        let expression = expression.trim();
+        if let Some(window) = expression.strip_prefix("@every ") {
+            let every = parse_window(window)?;
+            if every <= chrono::Duration::zero() {
+                return Err(anyhow!(
+                    "Schedule {} must have a positive interval",
+                    expression
+                ));
+            }
+            return Ok(Schedule::Every(every));
+        }
+
+        let cron = match expression {
+            "@hourly" => "0 * * * *",
+            "@daily" => "0 0 * * *",
+            "@weekly" => "0 0 * * 0",
+            _ => expression,
+        };
+        let fields: Vec<&str> = cron.split_whitespace().collect();
+        let [minute, hour, day, month, weekday] = fields[..] else {
+            return Err(anyhow!(
+                "Invalid schedule {}, expected five cron fields, @hourly, @daily, @weekly or @every <window>",
+                expression
+            ));
+        };
+
+        let field = |value: &str, min: u32, max: u32| {
+            parse_field(value, min, max)
+                .map_err(|e| anyhow!("Invalid schedule {}: {}", expression, e))
+        };
+        let (mut weekdays, weekdays_restricted) = field(weekday, 0, 7)?;
+        // 7 is Sunday too
+        weekdays[0] |= weekdays.pop().unwrap_or_default();
+        let (days, days_restricted) = field(day, 1, 31)?;
+        let schedule = Schedule::Cron(Cron {
+            minutes: field(minute, 0, 59)?.0,
+            hours: field(hour, 0, 23)?.0,
+            days,
+            months: field(month, 1, 12)?.0,
+            weekdays,
+            days_restricted,
+            weekdays_restricted,
+        });
+
+        if schedule.next_after(Utc::now()).is_none() {
+            return Err(anyhow!("Schedule {} never runs", expression));
+        }
+        Ok(schedule)
+    }
+
+    /// The first time strictly after `after` the job should run
+    pub fn next_after(&self, after: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        match self {
+            Schedule::Every(every) => Some(after + *every),
+            Schedule::Cron(cron) => cron.next_after(after),
+        }
+    }
+}
+
+impl Cron {
+    fn next_after(&self, after: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        let mut next = after.with_second(0)?.with_nanosecond(0)? + chrono::Duration::minutes(1);
+        // Long enough for any satisfiable expression, such as 29 February
+        let limit = after + chrono::Duration::days(5 * 366);
+
+        while next < limit {
+            if !self.months[next.month() as usize] {
+                let (year, month) = match next.month() {
+                    12 => (next.year() + 1, 1),
+                    month => (next.year(), month + 1),
+                };
+                next = Utc.with_ymd_and_hms(year, month, 1, 0, 0, 0).single()?;
+            } else if !self.day_matches(&next) {
+                next = next
+                    .date_naive()
+                    .succ_opt()?
+                    .and_hms_opt(0, 0, 0)?
+                    .and_utc();
+            } else if !self.hours[next.hour() as usize] {
+                next = next.with_minute(0)? + chrono::Duration::hours(1);
+            } else if !self.minutes[next.minute() as usize] {
+                next += chrono::Duration::minutes(1);
+            } else {
+                return Some(next);
+            }
+        }
+        None
+    }
+
+    fn day_matches(&self, time: &DateTime<Utc>) -> bool {
+        let day = self.days[time.day() as usize];
+        let weekday = self.weekdays[time.weekday().num_days_from_sunday() as usize];
+        if self.days_restricted && self.weekdays_restricted {
+            day || weekday
+        } else {
+            day && weekday
+        }
+    }
+}
+
+/// Reads a comma-separated list of `*`, values, `a-b` ranges and `/step`s, returning
+/// the allowed values and whether the field is restricted at all
+fn parse_field(field: &str, min: u32, max: u32) -> Result<(Vec<bool>, bool)> {
+    let value = |value: &str| -> Result<u32> {
+        value
+            .parse()
+            .map_err(|_| anyhow!("{} is not a number", value))
+    };
+
+    let mut allowed = vec![false; max as usize + 1];
+    for part in field.split(',') {
+        let (range, step) = match part.split_once('/') {
+            Some((range, step)) => (range, Some(value(step)?)),
+            None => (part, None),
+        };
+        let (start, end) = match range.split_once('-') {
+            _ if range == "*" => (min, max),
+            Some((start, end)) => (value(start)?, value(end)?),
+            // `5/15` runs from 5 to the end of the range
+            None if step.is_some() => (value(range)?, max),
+            None => (value(range)?, value(range)?),
+        };
+        if start < min || end > max || start > end {
+            return Err(anyhow!("{} is outside {}-{}", part, min, max));
+        }
+        if step == Some(0) {
+            return Err(anyhow!("{} has a zero step", part));
+        }
+        for allowed in allowed
+            .iter_mut()
+            .take(end as usize + 1)
+            .skip(start as usize)
+            .step_by(step.unwrap_or(1) as usize)
+        {
+            *allowed = true;
+        }
+    }
+    Ok((allowed, field != "*"))
+}
+
+/// One job's runs since startup, for `/jobs`
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct JobSnapshot {
+    pub name: String,
+    pub job: &'static str,
+    pub schedule: String,
+    pub running: bool,
+    pub runs: u64,
+    pub failures: u64,
+    pub next_run_at: Option<DateTime<Utc>>,
+    pub last_started_at: Option<DateTime<Utc>>,
+    /// How long after its scheduled time the latest run started
+    pub last_delay_ms: Option<u64>,
+    pub last_duration_ms: Option<u64>,
+    /// What the latest successful run did
+    pub last_summary: Option<String>,
+    /// Why the latest run failed; cleared by a successful one
+    pub last_error: Option<String>,
+}
+
+struct Job {
+    config: JobConfig,
+    schedule: Schedule,
+    state: Mutex<JobSnapshot>,
+}
+
+/// Runs the configured maintenance jobs, each in its own task so a slow backup never
+/// holds up the metrics snapshot. Runs of one job never overlap: one that lasts past
+/// the next scheduled time is followed by the first matching time after it ends.
+pub struct JobScheduler {
+    jobs: Vec<Arc<Job>>,
+}
+
+impl JobScheduler {
+    /// Builds the jobs in `config.jobs`, plus a `metrics_snapshot` every
+    /// `metrics_history.interval_secs` when the metrics history is on and no such job is
+    /// configured, and likewise a `prune_history` for `history_pruning`
+    pub fn new(config: &Config) -> Result<Self> {
+        let mut configs = config.jobs.clone();
+        if let Some(pruning) = &config.history_pruning
+            && !configs
+                .iter()
+                .any(|job| matches!(job.job, JobKind::PruneHistory))
+        {
+            configs.push(JobConfig {
+                name: None,
+                schedule: format!("@every {}s", pruning.interval_secs.max(1)),
+                job: JobKind::PruneHistory,
+            });
+        }
+        if let Some(history) = &config.metrics_history
+            && !configs
+                .iter()
+                .any(|job| matches!(job.job, JobKind::MetricsSnapshot))
+        {
+            configs.push(JobConfig {
+                name: None,
+                schedule: format!("@every {}s", history.interval_secs.max(1)),
+                job: JobKind::MetricsSnapshot,
+            });
+        }
+
+        let jobs = configs
+            .into_iter()
+            .map(|config| {
+                let schedule = Schedule::parse(&config.schedule)?;
+                let state = Mutex::new(JobSnapshot {
+                    name: config.name().to_string(),
+                    job: config.job.as_str(),
+                    schedule: config.schedule.clone(),
+                    ..JobSnapshot::default()
+                });
+                Ok(Arc::new(Job {
+                    config,
+                    schedule,
+                    state,
+                }))
+            })
+            .collect::<Result<_>>()?;
+        Ok(JobScheduler { jobs })
+    }
+
+    pub fn snapshot(&self) -> Vec<JobSnapshot> {
+        self.jobs
+            .iter()
+            .map(|job| job.state.lock().unwrap().clone())
+            .collect()
+    }
+
+    /// Starts every job's task. Jobs act on the domains in `domains` when they run, so
+    /// domains added at runtime are included from their next run. With the metrics
+    /// history on, its backfill from stored proofs also runs once.
+    pub fn spawn(&self, domains: Arc<DomainSet>, config: Arc<Config>) -> Result<()> {
+        if let Some(history) = &config.metrics_history {
+            let retention_hours = history.retention_hours;
+            let domains = domains.clone();
+            tokio::spawn(async move {
+                for domain in domains.all() {
+                    if let Err(e) = backfill_history(&domain, retention_hours).await {
+                        warn!(
+                            "⚠️  [{}] Failed to backfill the metrics history: {}",
+                            domain.name(),
+                            e
+                        );
+                    }
+                }
+            });
+        }
+
+        let reports = self
+            .jobs
+            .iter()
+            .any(|job| matches!(job.config.job, JobKind::Report));
+        let alerter = if reports {
+            Alerter::new(&config.alerting)?.map(Arc::new)
+        } else {
+            None
+        };
+
+        for job in &self.jobs {
+            info!(
+                "🗓️  Scheduled job {} ({})",
+                job.config.name(),
+                job.config.schedule
+            );
+            tokio::spawn(run_job(
+                job.clone(),
+                domains.clone(),
+                config.clone(),
+                alerter.clone(),
+            ));
+        }
+        Ok(())
+    }
+}
+
+async fn run_job(
+    job: Arc<Job>,
+    domains: Arc<DomainSet>,
+    config: Arc<Config>,
+    alerter: Option<Arc<Alerter>>,
+) {
+    let name = job.config.name().to_string();
+    loop {
+        let Some(next) = job.schedule.next_after(Utc::now()) else {
+            warn!("⚠️  Job {} has no further runs scheduled", name);
+            return;
+        };
+        job.state.lock().unwrap().next_run_at = Some(next);
+        tokio::time::sleep((next - Utc::now()).to_std().unwrap_or_default()).await;
+
+        let started_at = Utc::now();
+        {
+            let mut state = job.state.lock().unwrap();
+            state.running = true;
+            state.last_started_at = Some(started_at);
+            state.last_delay_ms = Some((started_at - next).num_milliseconds().max(0) as u64);
+        }
+        let started = Instant::now();
+        let result = run(&job.config.job, &domains, &config, alerter.as_ref()).await;
+
+        let mut state = job.state.lock().unwrap();
+        state.running = false;
+        state.runs += 1;
+        state.last_duration_ms = Some(started.elapsed().as_millis() as u64);
+        match result {
+            Ok(summary) => {
+                info!("🗓️  Job {} finished: {}", name, summary);
+                state.last_summary = Some(summary);
+                state.last_error = None;
+            }
+            Err(e) => {
+                error!("❌ Job {} failed: {}", name, e);
+                state.failures += 1;
+                state.last_error = Some(e.to_string());
+            }
+        }
+    }
+}
+
+/// Runs a job once across every domain, returning a summary of what it did
+async fn run(
+    job: &JobKind,
+    domains: &DomainSet,
+    config: &Config,
+    alerter: Option<&Arc<Alerter>>,
+) -> Result<String> {
+    match job {
+        JobKind::PruneHistory => prune_history(&domains.all(), config).await,
+        JobKind::Backup { dir, keep } => backup(&domains.all(), dir, *keep).await,
+        JobKind::Reverify { proofs } => reverify(&domains.all(), *proofs).await,
+        JobKind::Report => Ok(report(&domains.all(), alerter).await),
+        JobKind::MetricsSnapshot => {
+            let retention_hours = config
+                .metrics_history
+                .clone()
+                .unwrap_or_default()
+                .retention_hours;
+            sample_all(domains, retention_hours).await;
+            Ok(format!("sampled {} domains", domains.all().len()))
+        }
+    }
+}
+
+/// `Ok` with `summary` unless some domain failed
+fn outcome(summary: String, failed: Vec<String>) -> Result<String> {
+    if failed.is_empty() {
+        Ok(summary)
+    } else {
+        Err(anyhow!("{}; failed: {}", summary, failed.join(", ")))
+    }
+}
+
+/// Prunes each domain's proof history by `history_pruning`, or else to the newest
+/// `proof_history_retention` proofs
+async fn prune_history(domains: &[Arc<Domain>], config: &Config) -> Result<String> {
+    let policy = match (&config.history_pruning, config.proof_history_retention) {
+        (Some(policy), _) => policy.clone(),
+        (None, Some(keep)) => HistoryPruningConfig {
+            keep_last: Some(keep),
+            ..HistoryPruningConfig::default()
+        },
+        (None, None) => {
+            return Ok("proof_history_retention is unset, nothing to prune".to_string());
+        }
+    };
+
+    let removed = Arc::new(AtomicUsize::new(0));
+    let kept = Arc::new(AtomicUsize::new(0));
+    let mut failed = Vec::new();
+    for domain in domains {
+        let (policy, tracker) = (policy.clone(), domain.pruning.clone());
+        let (removed, kept, name) = (removed.clone(), kept.clone(), domain.name().to_string());
+        let result = domain
+            .writer
+            .write("prune proof history", move |db| {
+                let proofs = db.get_proof_timestamps()?;
+                let heights = prunable(&proofs, &policy, Utc::now());
+                let pruned = db.delete_proofs(&heights)?;
+                if pruned > 0 {
+                    info!("🧹 [{}] Pruned {} old proofs from history", name, pruned);
+                }
+                tracker.record(pruned, proofs.len() - pruned);
+                removed.fetch_add(pruned, Ordering::Relaxed);
+                kept.fetch_add(proofs.len() - pruned, Ordering::Relaxed);
+                Ok(())
+            })
+            .await;
+        if let Err(e) = result {
+            failed.push(format!("{} ({})", domain.name(), e));
+        }
+    }
+    outcome(
+        format!(
+            "removed {} proofs, kept {}",
+            removed.load(Ordering::Relaxed),
+            kept.load(Ordering::Relaxed)
+        ),
+        failed,
+    )
+}
+
+/// Copies the domain's database to `<dir>/<domain>_<stamp>.db`, consistent even while the
+/// relay loop writes to it, creating `dir` if missing
+pub async fn write_backup(domain: &Domain, dir: &Path, stamp: &str) -> Result<PathBuf> {
+    std::fs::create_dir_all(dir)
+        .map_err(|e| anyhow!("Failed to create {}: {}", dir.display(), e))?;
+    let path = dir.join(format!("{}_{}.db", domain.name(), stamp));
+    let copy = path.clone();
+    domain.read(move |db| db.backup(&copy)).await?;
+    info!("💾 [{}] Backed up to {}", domain.name(), path.display());
+    Ok(path)
+}
+
+/// The timestamp in backup file names, which sorts chronologically
+pub fn backup_stamp() -> String {
+    Utc::now().format("%Y%m%dT%H%M%SZ").to_string()
+}
+
+async fn backup(domains: &[Arc<Domain>], dir: &Path, keep: usize) -> Result<String> {
+    let stamp = backup_stamp();
+    let mut written = 0;
+    let mut failed = Vec::new();
+    for domain in domains {
+        let result = match write_backup(domain, dir, &stamp).await {
+            Ok(_) => prune_backups(dir, domain.name(), keep),
+            Err(e) => Err(e),
+        };
+        match result {
+            Ok(()) => written += 1,
+            Err(e) => failed.push(format!("{} ({})", domain.name(), e)),
+        }
+    }
+    outcome(
+        format!("wrote {} backups to {}", written, dir.display()),
+        failed,
+    )
+}
+
+/// Deletes all but the newest `keep` backups of a domain in `dir`
+fn prune_backups(dir: &Path, domain: &str, keep: usize) -> Result<()> {
+    let prefix = format!("{}_", domain);
+    let mut backups: Vec<PathBuf> = std::fs::read_dir(dir)?
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| {
+            path.file_name()
+                .and_then(|name| name.to_str())
+                .and_then(|name| name.strip_prefix(&prefix))
+                .and_then(|rest| rest.strip_suffix(".db"))
+                // Only our own timestamps, not another domain sharing the prefix
+                .is_some_and(|stamp| stamp.len() == 16 && stamp.ends_with('Z'))
+        })
+        .collect();
+    // Timestamps sort chronologically
+    backups.sort();
+    let excess = backups.len().saturating_sub(keep.max(1));
+    for old in &backups[..excess] {
+        std::fs::remove_file(old)?;
+    }
+    Ok(())
+}
+
+async fn reverify(domains: &[Arc<Domain>], proofs: usize) -> Result<String> {
+    let mut checked = 0;
+    let mut failed = Vec::new();
+    for domain in domains {
+        let records = match domain.read(move |db| db.get_latest_proofs(proofs)).await {
+            Ok(records) => records,
+            Err(e) => {
+                failed.push(format!("{} ({})", domain.name(), e));
+                continue;
+            }
+        };
+        let vk = domain.config.vk.clone();
+        let results = tokio::task::spawn_blocking(move || {
+            records
+                .into_iter()
+                .map(|record| {
+                    let result = verify_wrapper_proof(&record.proof, &record.public_values, &vk);
+                    (record.height, result)
+                })
+                .collect::<Vec<_>>()
+        })
+        .await?;
+
+        for (height, result) in results {
+            checked += 1;
+            if let Err(e) = result {
+                error!(
+                    "🚨 [{}] Stored proof at height {} failed re-verification: {}",
+                    domain.name(),
+                    height,
+                    e
+                );
+                domain.errors.record(Subsystem::Verifier, &e);
+                failed.push(format!("{} at height {}", domain.name(), height));
+            }
+        }
+    }
+    outcome(format!("verified {} stored proofs", checked), failed)
+}
+
+/// Logs every domain's state and posts it to the alerting webhooks
+async fn report(domains: &[Arc<Domain>], alerter: Option<&Arc<Alerter>>) -> String {
+    let mut lines = Vec::new();
+    for domain in domains {
+        let summary = domain_report(domain).await.summary();
+        info!("📰 [{}] {}", domain.name(), summary);
+        lines.push(format!("[{}] {}", domain.name(), summary));
+    }
+    if let Some(alerter) = alerter {
+        alerter.notify(format!("📰 Relayer report\n{}", lines.join("\n")));
+    }
+    format!("reported on {} domains", lines.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(time: &str) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(time)
+            .unwrap()
+            .with_timezone(&Utc)
+    }
+
+    #[test]
+    fn test_schedules() {
+        // Tuesday
+        let now = at("2026-10-13T10:07:30Z");
+        let next = |expression: &str| Schedule::parse(expression).unwrap().next_after(now);
+
+        assert_eq!(next("*/15 * * * *"), Some(at("2026-10-13T10:15:00Z")));
+        assert_eq!(next("@daily"), Some(at("2026-10-14T00:00:00Z")));
+        assert_eq!(next("30 3 * * 1-5"), Some(at("2026-10-14T03:30:00Z")));
+        assert_eq!(next("0 9 1 * *"), Some(at("2026-11-01T09:00:00Z")));
+        assert_eq!(next("@weekly"), Some(at("2026-10-18T00:00:00Z")));
+        // Both day fields restricted: either matches
+        assert_eq!(next("0 0 20 * 3"), Some(at("2026-10-14T00:00:00Z")));
+        assert_eq!(next("@every 30s"), Some(at("2026-10-13T10:08:00Z")));
+
+        for invalid in [
+            "* * * *",
+            "60 * * * *",
+            "*/0 * * * *",
+            "0 0 30 2 *",
+            "@every x",
+        ] {
+            assert!(Schedule::parse(invalid).is_err(), "{} parsed", invalid);
+        }
+    }
+}
@@ -0,0 +1,156 @@
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use std::fs::{self, File, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use crate::config::JournalConfig;
+
+/// One line of the submission journal
+#[derive(Debug, Clone, Serialize)]
+pub struct JournalEntry {
+    pub domain: String,
+    /// SHA-256 of the JSON payload, hex encoded
+    pub payload_hash: String,
+    pub target: String,
+    pub attempt: u32,
+    /// `started` is written before the request goes out, then `success` or `failure`
+    pub outcome: &'static str,
+    pub error: Option<String>,
+    pub started_at: DateTime<Utc>,
+    pub finished_at: Option<DateTime<Utc>>,
+}
+
+/// Append-only JSONL record of every registry submission attempt, kept apart from
+/// the database so it survives the database being lost or corrupted.
+///
+/// When the active file would grow past `max_bytes` it is renamed to `<path>.1`,
+/// older files shift up by one, and anything beyond `max_files` is deleted.
+pub struct SubmissionJournal {
+    path: PathBuf,
+    max_bytes: u64,
+    max_files: u32,
+    file: Mutex<(File, u64)>,
+}
+
+impl SubmissionJournal {
+    pub fn open(config: &JournalConfig) -> Result<Self> {
+        let path = PathBuf::from(&config.path);
+        if let Some(parent) = path
+            .parent()
+            .filter(|parent| !parent.as_os_str().is_empty())
+        {
+            fs::create_dir_all(parent)?;
+        }
+        let file = open_append(&path)?;
+        let size = file.metadata()?.len();
+
+        Ok(SubmissionJournal {
+            path,
+            max_bytes: config.max_bytes,
+            max_files: config.max_files,
+            file: Mutex::new((file, size)),
+        })
+    }
+
+    pub fn payload_hash(payload: &serde_json::Value) -> String {
+        hex::encode(Sha256::digest(payload.to_string().as_bytes()))
+    }
+
+    /// Appends an entry and syncs it to disk before returning
+    pub fn record(&self, entry: &JournalEntry) -> Result<()> {
+        let mut line = serde_json::to_vec(entry)?;
+        line.push(b'\n');
+
+        let mut guard = self.file.lock().unwrap();
+        if guard.1 > 0 && guard.1 + line.len() as u64 > self.max_bytes {
+            self.rotate()?;
+            *guard = (open_append(&self.path)?, 0);
+        }
+
+        let (file, size) = &mut *guard;
+        file.write_all(&line)?;
+        file.sync_data()?;
+        *size += line.len() as u64;
+        Ok(())
+    }
+
+    fn rotate(&self) -> Result<()> {
+        let rotated = |index: u32| PathBuf::from(format!("{}.{}", self.path.display(), index));
+
+        if self.max_files == 0 {
+            fs::remove_file(&self.path)?;
+            return Ok(());
+        }
+
+        let oldest = rotated(self.max_files);
+        if oldest.exists() {
+            fs::remove_file(&oldest)?;
+        }
+        for index in (1..self.max_files).rev() {
+            let from = rotated(index);
+            if from.exists() {
+                fs::rename(&from, rotated(index + 1))?;
+            }
+        }
+        fs::rename(&self.path, rotated(1))?;
+        Ok(())
+    }
+}
+
+fn open_append(path: &Path) -> Result<File> {
+    Ok(OpenOptions::new().create(true).append(true).open(path)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    fn entry(attempt: u32) -> JournalEntry {
+        JournalEntry {
+            domain: "test".to_string(),
+            payload_hash: SubmissionJournal::payload_hash(&serde_json::json!({"proof": "00"})),
+            target: "http://registry".to_string(),
+            attempt,
+            outcome: "started",
+            error: None,
+            started_at: Utc::now(),
+            finished_at: None,
+        }
+    }
+
+    #[test]
+    fn test_journal_rotation() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("journal").join("submissions.jsonl");
+        let journal = SubmissionJournal::open(&JournalConfig {
+            path: path.display().to_string(),
+            max_bytes: 512,
+            max_files: 2,
+        })
+        .unwrap();
+
+        for attempt in 0..20 {
+            journal.record(&entry(attempt)).unwrap();
+        }
+
+        // Rotated files are capped and none exceeds the size limit
+        let rotated = |index: u32| PathBuf::from(format!("{}.{}", path.display(), index));
+        assert!(rotated(1).exists());
+        assert!(rotated(2).exists());
+        assert!(!rotated(3).exists());
+        for file in [path.clone(), rotated(1), rotated(2)] {
+            assert!(fs::metadata(&file).unwrap().len() <= 512);
+        }
+
+        // The active file ends with the latest attempt
+        let contents = fs::read_to_string(&path).unwrap();
+        let last: serde_json::Value =
+            serde_json::from_str(contents.lines().last().unwrap()).unwrap();
+        assert_eq!(last["attempt"], 19);
+    }
+}
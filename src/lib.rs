@@ -0,0 +1,71 @@
+//! Relays light client proofs from lightwave provers to a registry, or monitors them in
+//! health-check mode. The binary is a thin wrapper around this library: services can
+//! embed a [`Relayer`] or [`HealthChecker`] directly and serve their domains with
+//! [`create_api_server`].
+//!
+//! Which of the two is available depends on the same `relayer` / `health-check`
+//! features that select the binary's mode.
+
+pub mod alert_rules;
+pub mod alerting;
+pub mod api;
+pub mod aws;
+#[cfg(all(feature = "relayer", not(feature = "health-check")))]
+pub mod backfill;
+pub mod broadcast;
+pub mod candidate;
+pub mod cli;
+pub mod codec;
+pub mod config;
+pub mod control;
+#[cfg(all(feature = "relayer", not(feature = "health-check")))]
+pub mod cosmwasm;
+pub mod crosscheck;
+pub mod db;
+pub mod domain;
+#[cfg(all(feature = "relayer", not(feature = "health-check")))]
+pub mod encryption;
+pub mod envelope;
+pub mod equivocation;
+pub mod errors;
+#[cfg(all(feature = "relayer", not(feature = "health-check")))]
+pub mod evm;
+pub mod freshness;
+pub mod gaps;
+#[cfg(feature = "grpc")]
+pub mod grpc;
+pub mod health_check;
+pub mod identity;
+pub mod jobs;
+#[cfg(all(feature = "relayer", not(feature = "health-check")))]
+pub mod journal;
+pub mod metrics;
+pub mod polling;
+pub mod postgres;
+pub mod proof_id;
+pub mod prover;
+pub mod pruning;
+pub mod redis_store;
+pub mod reference;
+pub mod registry;
+pub mod relayer;
+pub mod rollback;
+pub mod scheduler;
+pub mod shutdown;
+#[cfg(all(feature = "relayer", not(feature = "health-check")))]
+pub mod sink;
+pub mod source;
+pub mod timeseries;
+pub mod verifier;
+pub mod vk;
+pub mod watchdog;
+pub mod writer;
+
+pub use api::{AppState, create_api_server, start_api_server};
+pub use config::Config;
+pub use db::{Database, Storage};
+pub use domain::Domain;
+#[cfg(any(feature = "health-check", not(feature = "relayer")))]
+pub use health_check::HealthChecker;
+#[cfg(all(feature = "relayer", not(feature = "health-check")))]
+pub use relayer::Relayer;
@@ -1,243 +1,180 @@
 use anyhow::Result;
-use std::time::Duration;
-use tokio::time::sleep;
-use tracing::{error, info, warn};
-
-use crate::api::{create_api_server, start_api_server};
-use crate::config::{LIGHT_CLIENT_MODE, MODE};
-use crate::db::{Database, HealthCheckData, PreviousProof};
-use crate::relayer::get_proof;
 #[cfg(all(feature = "relayer", not(feature = "health-check")))]
-use crate::relayer::{create_payload, send};
-mod api;
-mod config;
-mod db;
-mod relayer;
-
-use helios_recursion_types::WrapperCircuitOutputs as HeliosWrapperCircuitOutputs;
-use tendermint_recursion_types::WrapperCircuitOutputs as TendermintWrapperCircuitOutputs;
+use helios_proof_relayer::Relayer;
+use helios_proof_relayer::api::{AppState, create_api_server, start_api_server};
+use helios_proof_relayer::cli::{self, Cli};
+use helios_proof_relayer::config::Config;
+use helios_proof_relayer::identity::IdentityKey;
+use helios_proof_relayer::jobs::JobScheduler;
+use helios_proof_relayer::shutdown::{ShutdownReport, wait_for_signal};
+#[cfg(any(feature = "health-check", not(feature = "relayer")))]
+use helios_proof_relayer::{HealthChecker, domain::DomainSet};
+use std::sync::Arc;
+use tracing::{error, info};
 
 #[tokio::main]
 async fn main() -> Result<(), anyhow::Error> {
-    // Initialize tracing subscriber with proper configuration
-    tracing_subscriber::fmt()
+    // Inspection subcommands print to stdout and exit without starting the service
+    let cli = <Cli as clap::Parser>::parse();
+    let config = Config::load()?;
+    #[cfg(all(feature = "relayer", not(feature = "health-check")))]
+    let config = match cli.dry_run {
+        true => config.into_dry_run(),
+        false => config,
+    };
+    if let Some(command) = cli.command {
+        return cli::run(command, &config).await;
+    }
+
+    // Initialize tracing subscriber with proper configuration; LOG_FORMAT=json emits
+    // one JSON object per line, with the domain and request ID from the enclosing span
+    let subscriber = tracing_subscriber::fmt()
         .with_max_level(tracing::Level::INFO)
         .with_target(false)
         .with_thread_ids(false)
-        .with_thread_names(false)
-        .init();
+        .with_thread_names(false);
+    match std::env::var("LOG_FORMAT") {
+        Ok(format) if format.eq_ignore_ascii_case("json") => subscriber
+            .json()
+            .flatten_event(true)
+            .with_current_span(true)
+            .with_span_list(false)
+            .init(),
+        _ => subscriber.init(),
+    }
 
     info!("🚀 Starting Helios Proof Relayer...");
 
+    let started_at = chrono::Utc::now();
+    let config = Arc::new(config);
+    info!(
+        "🗂️  Configured domains: {}",
+        config
+            .domains
+            .iter()
+            .map(|domain| domain.name.as_str())
+            .collect::<Vec<_>>()
+            .join(", ")
+    );
+
+    let identity = match &config.response_signing {
+        Some(signing) => {
+            let identity = IdentityKey::load(signing.key_path.as_deref())?;
+            info!("🔏 Signing health responses as {}", identity.public_key());
+            Some(Arc::new(identity))
+        }
+        None => None,
+    };
+    let jobs = Arc::new(JobScheduler::new(&config)?);
+
     #[cfg(all(feature = "relayer", not(feature = "health-check")))]
     {
         info!("📡 Running in relayer mode");
-        // Initialize database
-        let db = std::sync::Arc::new(Database::new("relayer.db")?);
-
-        // Load previous proof from database if it exists
-        let mut previous_proof: Option<String> = match db.get_previous_proof()? {
-            Some(proof) => Some(proof.proof_data),
-            None => None,
+        let relayer = {
+            let config = config.clone();
+            tokio::task::spawn_blocking(move || Relayer::open(config)).await??
         };
 
-        // Start the relayer loop
-        loop {
-            match create_payload().await {
-                Ok(payload) => {
-                    // Extract the proof from the payload to compare
-                    let current_proof = payload["proof"].as_str().unwrap().to_string();
-
-                    // Check if this proof is different from the previous one
-                    let should_send = match &previous_proof {
-                        None => true,
-                        Some(prev) => {
-                            if prev != &current_proof {
-                                true
-                            } else {
-                                false
-                            }
-                        }
-                    };
+        // A single cycle for cron and debugging, with no API, jobs or gRPC
+        if cli.once {
+            let outcomes = relayer.run_once().await?;
+            let code = outcomes
+                .iter()
+                .map(|(_, outcome)| outcome.exit_code())
+                .max()
+                .unwrap_or(0);
+            std::process::exit(code);
+        }
 
-                    if should_send {
-                        match send(&payload).await {
-                            Ok(_) => {
-                                info!("✅ Successfully sent payload to registry");
-                                previous_proof = Some(current_proof.clone());
+        // The API serves the admin controls and relayed proofs; health data stays empty in this mode
+        let api_router = create_api_server(
+            AppState {
+                config: config.clone(),
+                domains: relayer.domains(),
+                lifecycle: Some(relayer.lifecycle()),
+                events: relayer.events(),
+                metrics: relayer.metrics(),
+                jobs: jobs.clone(),
+                admin_token: std::env::var("ADMIN_TOKEN").ok(),
+                identity: identity.clone(),
+            },
+            &config.api_limits,
+        );
+        let tls = config.api_tls.clone();
+        tokio::spawn(async move {
+            if let Err(e) = start_api_server(api_router, tls.as_ref()).await {
+                error!("❌ API server error: {}", e);
+            }
+        });
 
-                                // Store the new proof in database
-                                let proof_data = PreviousProof {
-                                    proof_data: current_proof,
-                                    timestamp: chrono::Utc::now(),
-                                };
-                                if let Err(e) = db.update_previous_proof(&proof_data) {
-                                    error!("❌ Failed to update previous proof in database: {}", e);
-                                }
-                            }
-                            Err(e) => {
-                                error!("❌ Failed to send payload to registry: {}", e);
-                            }
-                        }
-                    } else {
-                        info!("⏳ Waiting for next check...");
-                    }
-                }
-                Err(e) => {
-                    error!("❌ Failed to create payload: {}", e);
-                }
+        let domains = relayer.domains();
+        jobs.spawn(domains.clone(), config.clone())?;
+        start_grpc(&config, domains.clone(), relayer.events());
+        tokio::select! {
+            result = relayer.run() => result?,
+            _ = wait_for_signal() => {
+                ShutdownReport::collect(&domains.all(), started_at)
+                    .await
+                    .publish(&config.shutdown_report)
+                    .await;
+                return Ok(());
             }
-            sleep(Duration::from_secs(30)).await;
         }
     }
 
     #[cfg(any(feature = "health-check", not(feature = "relayer")))]
     {
         info!("🏥 Running in health-check mode");
-
-        // Initialize database
-        info!("💾 Initializing database...");
-        let db = std::sync::Arc::new(Database::new("health_check.db")?);
-        info!("✅ Database initialized successfully");
-
-        // Clear database for testing
-        info!("🧹 Clearing database tables for fresh start...");
-        if let Err(e) = db.clear_all_tables() {
-            warn!("⚠️  Failed to clear database tables: {}", e);
-        } else {
-            info!("✅ Database tables cleared successfully");
-        }
+        let checker = {
+            let config = config.clone();
+            tokio::task::spawn_blocking(move || HealthChecker::open(config)).await??
+        };
+        let domains = Arc::new(DomainSet::new(checker.domains().to_vec()));
 
         // Create API server
         info!("🌐 Creating API server...");
-        let api_router = create_api_server(db.clone());
+        let api_router = create_api_server(
+            AppState {
+                config: config.clone(),
+                domains: domains.clone(),
+                lifecycle: None,
+                events: checker.events(),
+                metrics: checker.metrics(),
+                jobs: jobs.clone(),
+                admin_token: std::env::var("ADMIN_TOKEN").ok(),
+                identity: identity.clone(),
+            },
+            &config.api_limits,
+        );
         info!("✅ API server created");
 
-        // Start the health check loop in a separate task
-        info!("🔍 Starting health check service...");
-        let health_check_handle = tokio::spawn(async move {
-            info!("✅ Health check service started");
-
-            loop {
-                info!("🔍 Fetching latest proof...");
-                match get_proof().await {
-                    Ok(proof) => {
-                        info!("✅ Proof fetched successfully");
-
-                        // Get previous proof from database
-                        let previous_proof = match db.get_previous_proof() {
-                            Ok(Some(prev)) => Some(prev.proof_data),
-                            Ok(None) => None,
-                            Err(e) => {
-                                warn!("⚠️  Error getting previous proof from database: {}", e);
-                                None
-                            }
-                        };
-
-                        // Check if proof has changed
-                        let current_proof_hex = hex::encode(proof.bytes());
-                        let should_update = match &previous_proof {
-                            None => {
-                                info!("🆕 No previous proof found, processing new proof");
-                                true
-                            }
-                            Some(prev) => {
-                                if prev != &current_proof_hex {
-                                    info!("🔄 Proof has changed, processing new proof");
-                                    true
-                                } else {
-                                    info!("⏳ Proof unchanged, skipping update");
-                                    sleep(Duration::from_secs(120)).await;
-                                    continue;
-                                }
-                            }
-                        };
-
-                        if should_update {
-                            let mut current_height: u64 = 0;
-                            let mut current_root: [u8; 32] = [0; 32];
-
-                            match LIGHT_CLIENT_MODE {
-                                MODE::HELIOS => {
-                                    let public_outputs: HeliosWrapperCircuitOutputs =
-                                        borsh::from_slice(&proof.public_values.as_slice()).unwrap();
-                                    current_height = public_outputs.height;
-                                    current_root = public_outputs.root;
-                                }
-                                MODE::TENDERMINT => {
-                                    let public_outputs: TendermintWrapperCircuitOutputs =
-                                        borsh::from_slice(proof.public_values.as_slice()).unwrap();
-                                    current_height = public_outputs.height;
-                                    current_root = public_outputs.root;
-                                }
-                            }
-
-                            info!(
-                                "📊 Processing proof - Height: {}, Root: {}",
-                                current_height,
-                                hex::encode(current_root)
-                            );
-
-                            // Store health check data in database when proof changes
-                            let health_data = HealthCheckData {
-                                current_height,
-                                current_root: current_root.to_vec(),
-                                timestamp: chrono::Utc::now(),
-                            };
-
-                            if let Err(e) = db.update_health_check(&health_data) {
-                                error!("❌ Failed to update health check data in database: {}", e);
-                            } else {
-                                info!(
-                                    "💾 Health check data updated - Height: {}, Root: {}",
-                                    current_height,
-                                    hex::encode(current_root)
-                                );
-                            }
-
-                            // Store the new proof in database
-                            let proof_data = PreviousProof {
-                                proof_data: current_proof_hex,
-                                timestamp: chrono::Utc::now(),
-                            };
-                            if let Err(e) = db.update_previous_proof(&proof_data) {
-                                error!("❌ Failed to update previous proof in database: {}", e);
-                            } else {
-                                info!("💾 Proof stored in database");
-                            }
-
-                            info!("⏰ Waiting 120 seconds before next check...");
-                        }
-                    }
-                    Err(e) => {
-                        error!("❌ Health check failed: {}", e);
-                    }
-                }
-                // Wait 2 minutes before next health check
-                sleep(Duration::from_secs(120)).await;
-            }
-        });
-
         // Start the API server in a separate task
         info!("🌐 Starting API server...");
+        let tls = config.api_tls.clone();
         let api_handle = tokio::spawn(async move {
             info!("✅ API server started");
-            if let Err(e) = start_api_server(api_router).await {
+            if let Err(e) = start_api_server(api_router, tls.as_ref()).await {
                 error!("❌ API server error: {}", e);
             }
         });
 
-        info!("🔄 Waiting for services to complete...");
-        // Wait for both tasks to conclude
-        let (health_check_result, api_result) = tokio::join!(health_check_handle, api_handle);
+        jobs.spawn(domains.clone(), config.clone())?;
+        start_grpc(&config, domains.clone(), checker.events());
 
-        // Handle any errors from the tasks
-        if let Err(e) = health_check_result {
-            error!("❌ Health check service crashed: {}", e);
-            return Err(anyhow::anyhow!("{}", e));
+        info!("🔄 Waiting for services to complete...");
+        // Wait for all tasks to conclude
+        tokio::select! {
+            result = checker.run() => result?,
+            _ = wait_for_signal() => {
+                ShutdownReport::collect(&domains.all(), started_at)
+                    .await
+                    .publish(&config.shutdown_report)
+                    .await;
+                return Ok(());
+            }
         }
 
-        if let Err(e) = api_result {
+        if let Err(e) = api_handle.await {
             error!("❌ API server crashed: {}", e);
             return Err(anyhow::anyhow!("{}", e));
         }
@@ -246,15 +183,59 @@ async fn main() -> Result<(), anyhow::Error> {
     Ok(())
 }
 
+/// Starts the gRPC proof stream when configured
+fn start_grpc(
+    config: &Config,
+    domains: Arc<helios_proof_relayer::domain::DomainSet>,
+    events: helios_proof_relayer::api::EventStreams,
+) {
+    let Some(grpc) = config.grpc.clone() else {
+        return;
+    };
+    #[cfg(feature = "grpc")]
+    tokio::spawn(async move {
+        if let Err(e) = helios_proof_relayer::grpc::start_grpc_server(grpc, domains, events).await {
+            error!("❌ gRPC server error: {}", e);
+        }
+    });
+    #[cfg(not(feature = "grpc"))]
+    {
+        let _ = (grpc, domains, events);
+        tracing::warn!("⚠️  gRPC is configured but this build lacks the grpc feature");
+    }
+}
+
 #[cfg(test)]
 #[cfg(all(feature = "relayer", not(feature = "health-check")))]
 mod tests {
-    use crate::create_payload;
+    use helios_proof_relayer::config::{
+        LIGHT_CLIENT_PROVER_ENDPOINT, LIGHT_CLIENT_VK, ProverSelection,
+    };
+    use helios_proof_relayer::errors::ErrorTracker;
+    use helios_proof_relayer::metrics::LatencyObserver;
+    use helios_proof_relayer::proof_id::HashAlgorithm;
+    use helios_proof_relayer::prover::ProverPool;
+    use helios_proof_relayer::relayer::create_payload;
+    use helios_proof_relayer::vk::VkMonitor;
+    use tracing::info;
 
     #[tokio::test]
     async fn test_get_latest_helios_block() {
         // get and validate a helios block
-        let payload = create_payload().await.unwrap();
+        let provers = ProverPool::new(
+            vec![LIGHT_CLIENT_PROVER_ENDPOINT.to_string()],
+            ProverSelection::Priority,
+        );
+        let payload = create_payload(
+            &provers,
+            &VkMonitor::new(LIGHT_CLIENT_VK),
+            &ErrorTracker::default(),
+            None,
+            HashAlgorithm::default(),
+            &LatencyObserver::default(),
+        )
+        .await
+        .unwrap();
         info!("Payload: {:?}", payload);
     }
 }
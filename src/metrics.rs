@@ -0,0 +1,285 @@
+use anyhow::Result;
+use prometheus::{
+    Encoder, HistogramOpts, HistogramVec, IntCounterVec, IntGaugeVec, Opts, Registry, TextEncoder,
+};
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use crate::config::MetricsConfig;
+use crate::proof_id::ProofId;
+use crate::pruning::PruneTracker;
+
+/// Which latency histogram an observation goes to
+#[derive(Debug, Clone, Copy)]
+pub enum Latency {
+    Prover,
+    Registry,
+    Verification,
+}
+
+#[derive(Clone)]
+struct LatencyHistograms {
+    prover: HistogramVec,
+    registry: HistogramVec,
+    verification: HistogramVec,
+}
+
+/// One domain's handle on the latency histograms. The default one, for domains not
+/// attached to a `Metrics`, drops its observations.
+#[derive(Clone, Default)]
+pub struct LatencyObserver {
+    histograms: Option<(LatencyHistograms, String)>,
+}
+
+impl LatencyObserver {
+    pub fn observe(&self, latency: Latency, elapsed: Duration) {
+        let Some((histograms, domain)) = &self.histograms else {
+            return;
+        };
+        let histogram = match latency {
+            Latency::Prover => &histograms.prover,
+            Latency::Registry => &histograms.registry,
+            Latency::Verification => &histograms.verification,
+        };
+        histogram
+            .with_label_values(&[domain.as_str()])
+            .observe(elapsed.as_secs_f64());
+    }
+}
+
+/// Prometheus metrics exposed at `/metrics`
+pub struct Metrics {
+    registry: Registry,
+    pub proofs_accepted: IntCounterVec,
+    pub latest_height: IntGaugeVec,
+    pub last_proof_timestamp: IntGaugeVec,
+    pub domain_unhealthy: IntGaugeVec,
+    pub rollback_pending: IntGaugeVec,
+    pub root_conflict: IntGaugeVec,
+    pub db_write_queue_depth: IntGaugeVec,
+    pub scheduler_last_drift: IntGaugeVec,
+    pub scheduler_accumulated_drift: IntGaugeVec,
+    pub scheduler_longest_stall: IntGaugeVec,
+    pub polls: IntCounterVec,
+    pub proofs_pruned: IntCounterVec,
+    pub proof_history_size: IntGaugeVec,
+    pub last_prune_timestamp: IntGaugeVec,
+    latest_proof: IntGaugeVec,
+    latency: LatencyHistograms,
+    /// Id currently labelling each domain's `latest_proof` series, to remove it once replaced
+    latest_proof_ids: Mutex<HashMap<String, ProofId>>,
+}
+
+impl Metrics {
+    pub fn new(config: &MetricsConfig) -> Result<Self> {
+        let registry = Registry::new();
+
+        let proofs_accepted = IntCounterVec::new(
+            Opts::new(
+                "relayer_proofs_accepted_total",
+                "Proofs accepted by the relayer",
+            ),
+            &["domain"],
+        )?;
+        let latest_height = IntGaugeVec::new(
+            Opts::new(
+                "relayer_latest_height",
+                "Height of the latest accepted proof",
+            ),
+            &["domain"],
+        )?;
+        let last_proof_timestamp = IntGaugeVec::new(
+            Opts::new(
+                "relayer_last_proof_timestamp_seconds",
+                "Unix time at which the latest proof was accepted",
+            ),
+            &["domain"],
+        )?;
+        let domain_unhealthy = IntGaugeVec::new(
+            Opts::new(
+                "relayer_domain_unhealthy",
+                "1 if the domain has no health check data or it is stale",
+            ),
+            &["domain"],
+        )?;
+        let rollback_pending = IntGaugeVec::new(
+            Opts::new(
+                "relayer_rollback_pending",
+                "1 if a chain rollback is waiting for operator approval",
+            ),
+            &["domain"],
+        )?;
+        let root_conflict = IntGaugeVec::new(
+            Opts::new(
+                "relayer_root_conflict",
+                "1 if the prover served a root that differs from the stored one for its height",
+            ),
+            &["domain"],
+        )?;
+
+        let db_write_queue_depth = IntGaugeVec::new(
+            Opts::new(
+                "relayer_db_write_queue_depth",
+                "Database writes queued but not yet applied",
+            ),
+            &["domain"],
+        )?;
+
+        let scheduler_last_drift = IntGaugeVec::new(
+            Opts::new(
+                "relayer_scheduler_last_drift_milliseconds",
+                "How late the loop's latest cycle started",
+            ),
+            &["domain"],
+        )?;
+        let scheduler_accumulated_drift = IntGaugeVec::new(
+            Opts::new(
+                "relayer_scheduler_accumulated_drift_milliseconds",
+                "Total lateness of the loop's cycle starts since startup",
+            ),
+            &["domain"],
+        )?;
+        let scheduler_longest_stall = IntGaugeVec::new(
+            Opts::new(
+                "relayer_scheduler_longest_stall_milliseconds",
+                "Longest gap between two cycle starts beyond the loop's interval",
+            ),
+            &["domain"],
+        )?;
+
+        let polls = IntCounterVec::new(
+            Opts::new(
+                "relayer_polls_total",
+                "Polls of the prover by outcome: proof, no_new_proof or error",
+            ),
+            &["domain", "outcome"],
+        )?;
+
+        let proofs_pruned = IntCounterVec::new(
+            Opts::new(
+                "relayer_proofs_pruned_total",
+                "Proofs deleted from the history by the prune_history job",
+            ),
+            &["domain"],
+        )?;
+        let proof_history_size = IntGaugeVec::new(
+            Opts::new(
+                "relayer_proof_history_size",
+                "Proofs left in the history after the latest prune_history run",
+            ),
+            &["domain"],
+        )?;
+        let last_prune_timestamp = IntGaugeVec::new(
+            Opts::new(
+                "relayer_last_prune_timestamp_seconds",
+                "Unix time of the latest prune_history run",
+            ),
+            &["domain"],
+        )?;
+
+        let latest_proof = IntGaugeVec::new(
+            Opts::new(
+                "relayer_latest_proof_info",
+                "1, labelled with the id of the latest accepted proof",
+            ),
+            &["domain", "proof_id"],
+        )?;
+
+        let histogram = |name: &str, help: &str, buckets: &[f64]| {
+            HistogramVec::new(
+                HistogramOpts::new(name, help).buckets(buckets.to_vec()),
+                &["domain"],
+            )
+        };
+        let latency = LatencyHistograms {
+            prover: histogram(
+                "relayer_prover_fetch_duration_seconds",
+                "Time to fetch the latest proof, across every prover endpoint tried",
+                &config.prover_latency_buckets,
+            )?,
+            registry: histogram(
+                "relayer_submission_duration_seconds",
+                "Time to submit a proof to the primary target, including retries",
+                &config.registry_latency_buckets,
+            )?,
+            verification: histogram(
+                "relayer_verification_duration_seconds",
+                "Time to verify a fetched proof locally",
+                &config.verification_latency_buckets,
+            )?,
+        };
+
+        registry.register(Box::new(proofs_accepted.clone()))?;
+        registry.register(Box::new(latest_height.clone()))?;
+        registry.register(Box::new(last_proof_timestamp.clone()))?;
+        registry.register(Box::new(domain_unhealthy.clone()))?;
+        registry.register(Box::new(rollback_pending.clone()))?;
+        registry.register(Box::new(root_conflict.clone()))?;
+        registry.register(Box::new(db_write_queue_depth.clone()))?;
+        registry.register(Box::new(scheduler_last_drift.clone()))?;
+        registry.register(Box::new(scheduler_accumulated_drift.clone()))?;
+        registry.register(Box::new(scheduler_longest_stall.clone()))?;
+        registry.register(Box::new(polls.clone()))?;
+        registry.register(Box::new(proofs_pruned.clone()))?;
+        registry.register(Box::new(proof_history_size.clone()))?;
+        registry.register(Box::new(last_prune_timestamp.clone()))?;
+        registry.register(Box::new(latest_proof.clone()))?;
+        registry.register(Box::new(latency.prover.clone()))?;
+        registry.register(Box::new(latency.registry.clone()))?;
+        registry.register(Box::new(latency.verification.clone()))?;
+
+        Ok(Metrics {
+            registry,
+            proofs_accepted,
+            latest_height,
+            last_proof_timestamp,
+            domain_unhealthy,
+            rollback_pending,
+            root_conflict,
+            db_write_queue_depth,
+            scheduler_last_drift,
+            scheduler_accumulated_drift,
+            scheduler_longest_stall,
+            polls,
+            proofs_pruned,
+            proof_history_size,
+            last_prune_timestamp,
+            latest_proof,
+            latency,
+            latest_proof_ids: Mutex::new(HashMap::new()),
+        })
+    }
+
+    /// Handle for recording one domain's latencies
+    pub fn latency_observer(&self, domain: &str) -> LatencyObserver {
+        LatencyObserver {
+            histograms: Some((self.latency.clone(), domain.to_string())),
+        }
+    }
+
+    /// Pruning tracker that counts one domain's pruned proofs in `relayer_proofs_pruned_total`
+    pub fn prune_tracker(&self, domain: &str) -> PruneTracker {
+        PruneTracker::counting(self.proofs_pruned.clone(), domain)
+    }
+
+    /// Points the domain's `relayer_latest_proof_info` series at a newly accepted proof
+    pub fn set_latest_proof(&self, domain: &str, proof_id: &ProofId) {
+        let mut ids = self.latest_proof_ids.lock().unwrap();
+        if let Some(previous) = ids.insert(domain.to_string(), proof_id.clone()) {
+            let _ = self
+                .latest_proof
+                .remove_label_values(&[domain, previous.as_str()]);
+        }
+        self.latest_proof
+            .with_label_values(&[domain, proof_id.as_str()])
+            .set(1);
+    }
+
+    /// Renders all metrics in the Prometheus text exposition format
+    pub fn render(&self) -> Result<String> {
+        let mut buffer = Vec::new();
+        TextEncoder::new().encode(&self.registry.gather(), &mut buffer)?;
+        Ok(String::from_utf8(buffer)?)
+    }
+}
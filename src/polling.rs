@@ -0,0 +1,201 @@
+use rand::Rng;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tracing::debug;
+
+use crate::config::{AdaptivePollingConfig, PollingConfig};
+
+/// The loop waiting for its next cycle, as relaying and health checks have separate
+/// intervals
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PollLoop {
+    Relay,
+    HealthCheck,
+}
+
+/// Decides how long a domain's loop waits between polls. With `adaptive` polling it
+/// keeps an exponential moving average of the interval between new proofs and aims just
+/// before the next one; otherwise, or until two new proofs have been seen, it uses the
+/// configured intervals.
+pub struct PollSchedule {
+    config: PollingConfig,
+    cadence: Mutex<Cadence>,
+}
+
+#[derive(Default)]
+struct Cadence {
+    last_new: Option<Instant>,
+    /// Seconds between new proofs
+    average: Option<f64>,
+}
+
+impl PollSchedule {
+    pub fn new(config: &PollingConfig) -> Self {
+        PollSchedule {
+            config: config.clone(),
+            cadence: Mutex::new(Cadence::default()),
+        }
+    }
+
+    /// How long `poll_loop` waits before its next cycle, with jitter. `found_new` is
+    /// whether this cycle fetched a proof above the last one.
+    pub fn next_interval(&self, poll_loop: PollLoop, found_new: bool) -> Duration {
+        jittered(
+            self.interval_at(poll_loop, found_new, Instant::now()),
+            self.config.jitter,
+        )
+    }
+
+    /// Average seconds between new proofs so far, if adaptive polling has observed any
+    pub fn observed_cadence(&self) -> Option<f64> {
+        self.cadence.lock().unwrap().average
+    }
+
+    fn interval_at(&self, poll_loop: PollLoop, found_new: bool, now: Instant) -> Duration {
+        let Some(adaptive) = &self.config.adaptive else {
+            return self.fixed_interval(poll_loop, found_new);
+        };
+
+        let mut cadence = self.cadence.lock().unwrap();
+        if found_new {
+            if let Some(last_new) = cadence.last_new {
+                let observed = (now - last_new).as_secs_f64();
+                let smoothing = adaptive.smoothing.clamp(0.0, 1.0);
+                cadence.average = Some(match cadence.average {
+                    Some(average) => smoothing * observed + (1.0 - smoothing) * average,
+                    None => observed,
+                });
+            }
+            cadence.last_new = Some(now);
+        }
+        match (cadence.last_new, cadence.average) {
+            (Some(last_new), Some(average)) => adaptive_interval(adaptive, last_new, average, now),
+            _ => self.fixed_interval(poll_loop, found_new),
+        }
+    }
+
+    /// The catch-up interval after a cycle that found a new proof (when configured), the
+    /// loop's usual interval otherwise
+    fn fixed_interval(&self, poll_loop: PollLoop, found_new: bool) -> Duration {
+        let secs = match (found_new, self.config.catch_up_interval_secs) {
+            (true, Some(catch_up)) => catch_up,
+            _ => match poll_loop {
+                PollLoop::Relay => self.config.relay_interval_secs,
+                PollLoop::HealthCheck => self.config.health_check_interval_secs,
+            },
+        };
+        Duration::from_secs(secs)
+    }
+}
+
+/// Until `lead_secs` before the expected proof, or the short `behind` interval once it
+/// is overdue
+fn adaptive_interval(
+    config: &AdaptivePollingConfig,
+    last_new: Instant,
+    average: f64,
+    now: Instant,
+) -> Duration {
+    let lead = Duration::from_secs(config.lead_secs);
+    let expected = last_new + Duration::from_secs_f64(average);
+    let interval = match expected.checked_sub(lead) {
+        Some(poll_at) if poll_at > now => poll_at - now,
+        _ => {
+            debug!(
+                "Next proof was expected {:?} ago, polling every {}s",
+                now.saturating_duration_since(expected),
+                config.behind_interval_secs
+            );
+            Duration::from_secs(config.behind_interval_secs)
+        }
+    };
+    interval.clamp(
+        Duration::from_secs(config.min_interval_secs),
+        Duration::from_secs(config.max_interval_secs.max(config.min_interval_secs)),
+    )
+}
+
+fn jittered(interval: Duration, jitter: f64) -> Duration {
+    let jitter = jitter.clamp(0.0, 1.0);
+    if jitter == 0.0 {
+        return interval;
+    }
+    interval.mul_f64(1.0 + rand::thread_rng().gen_range(-jitter..=jitter))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_next_interval() {
+        let mut config = PollingConfig::default();
+        let schedule = PollSchedule::new(&config);
+        assert_eq!(
+            schedule.next_interval(PollLoop::Relay, true),
+            Duration::from_secs(30)
+        );
+        assert_eq!(
+            schedule.next_interval(PollLoop::HealthCheck, false),
+            Duration::from_secs(120)
+        );
+
+        config.catch_up_interval_secs = Some(5);
+        let schedule = PollSchedule::new(&config);
+        assert_eq!(
+            schedule.next_interval(PollLoop::Relay, true),
+            Duration::from_secs(5)
+        );
+        assert_eq!(
+            schedule.next_interval(PollLoop::Relay, false),
+            Duration::from_secs(30)
+        );
+
+        config.jitter = 0.1;
+        let schedule = PollSchedule::new(&config);
+        for _ in 0..100 {
+            let interval = schedule.next_interval(PollLoop::HealthCheck, false);
+            assert!(interval >= Duration::from_secs(108) && interval <= Duration::from_secs(132));
+        }
+    }
+
+    #[test]
+    fn test_adaptive_interval() {
+        let config = PollingConfig {
+            adaptive: Some(AdaptivePollingConfig {
+                smoothing: 0.5,
+                ..AdaptivePollingConfig::default()
+            }),
+            ..PollingConfig::default()
+        };
+        let schedule = PollSchedule::new(&config);
+        let start = Instant::now();
+        let at = |secs| start + Duration::from_secs(secs);
+
+        // The fixed interval until the cadence is known
+        assert_eq!(
+            schedule.interval_at(PollLoop::Relay, true, at(0)),
+            Duration::from_secs(30)
+        );
+        // 60s between proofs: poll 5s before the next one is due
+        assert_eq!(
+            schedule.interval_at(PollLoop::Relay, true, at(60)),
+            Duration::from_secs(55)
+        );
+        assert_eq!(
+            schedule.interval_at(PollLoop::Relay, false, at(100)),
+            Duration::from_secs(15)
+        );
+        // Overdue, so poll aggressively until it arrives
+        assert_eq!(
+            schedule.interval_at(PollLoop::Relay, false, at(130)),
+            Duration::from_secs(5)
+        );
+        // 80s since the last one moves the average halfway, to 70s
+        assert_eq!(
+            schedule.interval_at(PollLoop::Relay, true, at(140)),
+            Duration::from_secs(65)
+        );
+        assert_eq!(schedule.observed_cadence(), Some(70.0));
+    }
+}
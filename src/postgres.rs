@@ -0,0 +1,1005 @@
+use anyhow::{Result, anyhow};
+use chrono::{DateTime, Utc};
+use sqlx::Row;
+use sqlx::postgres::{PgPool, PgPoolOptions, PgRow};
+use std::future::Future;
+use std::path::Path;
+
+use crate::db::{
+    AlertRecord, AlertState, ConfirmationState, CutoverRecord, DECODE_FAILURES_KEPT, DecodeFailure,
+    FailedSubmission, HealthCheckData, MetricSample, PendingSubmission, PreviousProof, ProofRecord,
+    RelayAttempt, RelayConfirmation, Storage, stored_proof_id,
+};
+
+/// Postgres storage for deployments where several replicas share one database.
+/// All domains live in the same tables, namespaced by a `domain` column.
+///
+/// `Storage` is synchronous, so queries are driven to completion on the current
+/// runtime with `block_in_place`, which requires the multi-threaded runtime.
+pub struct PostgresStorage {
+    pool: PgPool,
+    domain: String,
+}
+
+fn block_on<F: Future>(future: F) -> F::Output {
+    tokio::task::block_in_place(|| tokio::runtime::Handle::current().block_on(future))
+}
+
+impl PostgresStorage {
+    pub fn connect(url: &str, max_connections: u32, domain: &str) -> Result<Self> {
+        let pool = block_on(
+            PgPoolOptions::new()
+                .max_connections(max_connections)
+                .connect(url),
+        )?;
+        let storage = PostgresStorage {
+            pool,
+            domain: domain.to_string(),
+        };
+        storage.init_tables()?;
+        Ok(storage)
+    }
+
+    fn init_tables(&self) -> Result<()> {
+        block_on(async {
+            sqlx::query(
+                "CREATE TABLE IF NOT EXISTS health_check (
+                    domain TEXT PRIMARY KEY,
+                    current_height BIGINT NOT NULL,
+                    current_root BYTEA NOT NULL,
+                    timestamp TIMESTAMPTZ NOT NULL
+                )",
+            )
+            .execute(&self.pool)
+            .await?;
+
+            sqlx::query(
+                "CREATE TABLE IF NOT EXISTS previous_proof (
+                    domain TEXT PRIMARY KEY,
+                    proof_data TEXT NOT NULL,
+                    timestamp TIMESTAMPTZ NOT NULL
+                )",
+            )
+            .execute(&self.pool)
+            .await?;
+
+            sqlx::query(
+                "CREATE TABLE IF NOT EXISTS proofs (
+                    domain TEXT NOT NULL,
+                    height BIGINT NOT NULL,
+                    proof BYTEA NOT NULL,
+                    public_values BYTEA NOT NULL,
+                    root BYTEA NOT NULL,
+                    timestamp TIMESTAMPTZ NOT NULL,
+                    seq BIGINT,
+                    proof_id TEXT,
+                    PRIMARY KEY (domain, height)
+                )",
+            )
+            .execute(&self.pool)
+            .await?;
+
+            sqlx::query("ALTER TABLE proofs ADD COLUMN IF NOT EXISTS seq BIGINT")
+                .execute(&self.pool)
+                .await?;
+
+            sqlx::query("ALTER TABLE proofs ADD COLUMN IF NOT EXISTS proof_id TEXT")
+                .execute(&self.pool)
+                .await?;
+
+            sqlx::query("CREATE INDEX IF NOT EXISTS proofs_seq ON proofs (domain, seq)")
+                .execute(&self.pool)
+                .await?;
+
+            sqlx::query(
+                "CREATE TABLE IF NOT EXISTS pending_submissions (
+                    domain TEXT NOT NULL,
+                    height BIGINT NOT NULL,
+                    payload TEXT NOT NULL,
+                    created_at TIMESTAMPTZ NOT NULL,
+                    PRIMARY KEY (domain, height)
+                )",
+            )
+            .execute(&self.pool)
+            .await?;
+
+            sqlx::query(
+                "CREATE TABLE IF NOT EXISTS failed_submissions (
+                    id BIGSERIAL PRIMARY KEY,
+                    domain TEXT NOT NULL,
+                    height BIGINT NOT NULL,
+                    payload TEXT NOT NULL,
+                    error TEXT NOT NULL,
+                    attempts INTEGER NOT NULL,
+                    failed_at TIMESTAMPTZ NOT NULL,
+                    UNIQUE (domain, height)
+                )",
+            )
+            .execute(&self.pool)
+            .await?;
+
+            sqlx::query(
+                "CREATE TABLE IF NOT EXISTS alerts (
+                    id BIGSERIAL PRIMARY KEY,
+                    domain TEXT NOT NULL,
+                    condition TEXT NOT NULL,
+                    message TEXT NOT NULL,
+                    state TEXT NOT NULL,
+                    fired_at TIMESTAMPTZ NOT NULL,
+                    notifications INTEGER NOT NULL,
+                    last_notified_at TIMESTAMPTZ NOT NULL,
+                    acknowledged_at TIMESTAMPTZ,
+                    acknowledged_by TEXT,
+                    resolved_at TIMESTAMPTZ
+                )",
+            )
+            .execute(&self.pool)
+            .await?;
+
+            sqlx::query(
+                "CREATE TABLE IF NOT EXISTS relay_attempts (
+                    id BIGSERIAL PRIMARY KEY,
+                    domain TEXT NOT NULL,
+                    height BIGINT NOT NULL,
+                    endpoint TEXT NOT NULL,
+                    attempt INTEGER NOT NULL,
+                    payload_hash TEXT NOT NULL,
+                    status INTEGER,
+                    response TEXT NOT NULL,
+                    latency_ms BIGINT NOT NULL,
+                    attempted_at TIMESTAMPTZ NOT NULL
+                )",
+            )
+            .execute(&self.pool)
+            .await?;
+
+            sqlx::query(
+                "CREATE INDEX IF NOT EXISTS relay_attempts_height ON relay_attempts (domain, height)",
+            )
+            .execute(&self.pool)
+            .await?;
+
+            sqlx::query(
+                "CREATE TABLE IF NOT EXISTS relay_confirmations (
+                    domain TEXT NOT NULL,
+                    height BIGINT NOT NULL,
+                    endpoint TEXT NOT NULL,
+                    root TEXT NOT NULL,
+                    state TEXT NOT NULL,
+                    submissions INTEGER NOT NULL,
+                    error TEXT,
+                    submitted_at TIMESTAMPTZ NOT NULL,
+                    updated_at TIMESTAMPTZ NOT NULL,
+                    PRIMARY KEY (domain, endpoint, height)
+                )",
+            )
+            .execute(&self.pool)
+            .await?;
+
+            sqlx::query(
+                "CREATE TABLE IF NOT EXISTS registry_cutovers (
+                    id BIGSERIAL PRIMARY KEY,
+                    domain TEXT NOT NULL,
+                    from_endpoint TEXT NOT NULL,
+                    to_endpoint TEXT NOT NULL,
+                    latest_only BOOLEAN NOT NULL,
+                    validate_endpoint TEXT,
+                    drained BIGINT NOT NULL,
+                    replayed_height BIGINT,
+                    cut_over_at TIMESTAMPTZ NOT NULL
+                )",
+            )
+            .execute(&self.pool)
+            .await?;
+
+            sqlx::query(
+                "CREATE TABLE IF NOT EXISTS decode_failures (
+                    id BIGSERIAL PRIMARY KEY,
+                    domain TEXT NOT NULL,
+                    error TEXT NOT NULL,
+                    prover TEXT,
+                    public_values TEXT NOT NULL,
+                    failed_at TIMESTAMPTZ NOT NULL
+                )",
+            )
+            .execute(&self.pool)
+            .await?;
+
+            sqlx::query(
+                "CREATE TABLE IF NOT EXISTS metric_samples (
+                    domain TEXT NOT NULL,
+                    metric TEXT NOT NULL,
+                    value DOUBLE PRECISION NOT NULL,
+                    sampled_at TIMESTAMPTZ NOT NULL
+                )",
+            )
+            .execute(&self.pool)
+            .await?;
+
+            sqlx::query(
+                "CREATE INDEX IF NOT EXISTS metric_samples_metric ON metric_samples (domain, metric, sampled_at)",
+            )
+            .execute(&self.pool)
+            .await?;
+
+            // Last sequence number handed out per domain
+            sqlx::query(
+                "CREATE TABLE IF NOT EXISTS proof_sequences (
+                    domain TEXT PRIMARY KEY,
+                    value BIGINT NOT NULL
+                )",
+            )
+            .execute(&self.pool)
+            .await?;
+
+            Ok::<(), anyhow::Error>(())
+        })
+    }
+}
+
+impl Storage for PostgresStorage {
+    fn update_health_check(&self, data: &HealthCheckData) -> Result<()> {
+        block_on(
+            sqlx::query(
+                "INSERT INTO health_check (domain, current_height, current_root, timestamp)
+                 VALUES ($1, $2, $3, $4)
+                 ON CONFLICT (domain) DO UPDATE SET
+                    current_height = EXCLUDED.current_height,
+                    current_root = EXCLUDED.current_root,
+                    timestamp = EXCLUDED.timestamp",
+            )
+            .bind(&self.domain)
+            .bind(data.current_height as i64)
+            .bind(&data.current_root)
+            .bind(data.timestamp)
+            .execute(&self.pool),
+        )?;
+        Ok(())
+    }
+
+    fn get_latest_health_check(&self) -> Result<Option<HealthCheckData>> {
+        let row = block_on(
+            sqlx::query(
+                "SELECT current_height, current_root, timestamp FROM health_check WHERE domain = $1",
+            )
+            .bind(&self.domain)
+            .fetch_optional(&self.pool),
+        )?;
+
+        match row {
+            Some(row) => Ok(Some(HealthCheckData {
+                current_height: row.try_get::<i64, _>("current_height")? as u64,
+                current_root: row.try_get("current_root")?,
+                timestamp: row.try_get("timestamp")?,
+            })),
+            None => Ok(None),
+        }
+    }
+
+    fn update_previous_proof(&self, proof: &PreviousProof) -> Result<()> {
+        block_on(
+            sqlx::query(
+                "INSERT INTO previous_proof (domain, proof_data, timestamp)
+                 VALUES ($1, $2, $3)
+                 ON CONFLICT (domain) DO UPDATE SET
+                    proof_data = EXCLUDED.proof_data,
+                    timestamp = EXCLUDED.timestamp",
+            )
+            .bind(&self.domain)
+            .bind(&proof.proof_data)
+            .bind(proof.timestamp)
+            .execute(&self.pool),
+        )?;
+        Ok(())
+    }
+
+    fn get_previous_proof(&self) -> Result<Option<PreviousProof>> {
+        let row = block_on(
+            sqlx::query("SELECT proof_data, timestamp FROM previous_proof WHERE domain = $1")
+                .bind(&self.domain)
+                .fetch_optional(&self.pool),
+        )?;
+
+        match row {
+            Some(row) => Ok(Some(PreviousProof {
+                proof_data: row.try_get("proof_data")?,
+                timestamp: row.try_get("timestamp")?,
+            })),
+            None => Ok(None),
+        }
+    }
+
+    fn insert_proof(&self, record: &ProofRecord) -> Result<Option<u64>> {
+        block_on(async {
+            let mut tx = self.pool.begin().await?;
+
+            // Row lock on the counter serializes inserts from all replicas
+            let seq: i64 = sqlx::query_scalar(
+                "INSERT INTO proof_sequences (domain, value) VALUES ($1, 1)
+                 ON CONFLICT (domain) DO UPDATE SET value = proof_sequences.value + 1
+                 RETURNING value",
+            )
+            .bind(&self.domain)
+            .fetch_one(&mut *tx)
+            .await?;
+
+            let result = sqlx::query(
+                "INSERT INTO proofs (domain, height, proof, public_values, root, timestamp, seq, proof_id)
+                 VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+                 ON CONFLICT (domain, height) DO NOTHING",
+            )
+            .bind(&self.domain)
+            .bind(record.height as i64)
+            .bind(&record.proof)
+            .bind(&record.public_values)
+            .bind(&record.root)
+            .bind(record.timestamp)
+            .bind(seq)
+            .bind(record.proof_id.as_str())
+            .execute(&mut *tx)
+            .await?;
+
+            if result.rows_affected() == 0 {
+                // Roll back the counter too, the height was already recorded
+                tx.rollback().await?;
+                return Ok(None);
+            }
+
+            tx.commit().await?;
+            Ok::<_, anyhow::Error>(Some(seq as u64))
+        })
+    }
+
+    fn get_proof_by_height(&self, height: u64) -> Result<Option<ProofRecord>> {
+        let row = block_on(
+            sqlx::query(
+                "SELECT height, proof, public_values, root, timestamp, seq, proof_id FROM proofs
+                 WHERE domain = $1 AND height = $2",
+            )
+            .bind(&self.domain)
+            .bind(height as i64)
+            .fetch_optional(&self.pool),
+        )?;
+
+        row.map(|row| read_proof_record(&row)).transpose()
+    }
+
+    fn get_latest_proof(&self) -> Result<Option<ProofRecord>> {
+        let row = block_on(
+            sqlx::query(
+                "SELECT height, proof, public_values, root, timestamp, seq, proof_id FROM proofs
+                 WHERE domain = $1 ORDER BY height DESC LIMIT 1",
+            )
+            .bind(&self.domain)
+            .fetch_optional(&self.pool),
+        )?;
+
+        row.map(|row| read_proof_record(&row)).transpose()
+    }
+
+    fn get_proofs_in_range(&self, from: u64, to: u64) -> Result<Vec<ProofRecord>> {
+        let rows = block_on(
+            sqlx::query(
+                "SELECT height, proof, public_values, root, timestamp, seq, proof_id FROM proofs
+                 WHERE domain = $1 AND height >= $2 AND height <= $3 ORDER BY height ASC",
+            )
+            .bind(&self.domain)
+            .bind(from as i64)
+            .bind(to.min(i64::MAX as u64) as i64)
+            .fetch_all(&self.pool),
+        )?;
+
+        rows.iter().map(read_proof_record).collect()
+    }
+
+    fn get_proofs_page(
+        &self,
+        from: u64,
+        to: u64,
+        limit: usize,
+        offset: usize,
+    ) -> Result<Vec<ProofRecord>> {
+        let rows = block_on(
+            sqlx::query(
+                "SELECT height, proof, public_values, root, timestamp, seq, proof_id FROM proofs
+                 WHERE domain = $1 AND height >= $2 AND height <= $3 ORDER BY height ASC
+                 LIMIT $4 OFFSET $5",
+            )
+            .bind(&self.domain)
+            .bind(from.min(i64::MAX as u64) as i64)
+            .bind(to.min(i64::MAX as u64) as i64)
+            .bind(limit.min(i64::MAX as usize) as i64)
+            .bind(offset.min(i64::MAX as usize) as i64)
+            .fetch_all(&self.pool),
+        )?;
+
+        rows.iter().map(read_proof_record).collect()
+    }
+
+    fn get_latest_proofs(&self, limit: usize) -> Result<Vec<ProofRecord>> {
+        let rows = block_on(
+            sqlx::query(
+                "SELECT * FROM (
+                    SELECT height, proof, public_values, root, timestamp, seq, proof_id FROM proofs
+                    WHERE domain = $1 ORDER BY height DESC LIMIT $2
+                 ) AS latest ORDER BY height ASC",
+            )
+            .bind(&self.domain)
+            .bind(limit.min(i64::MAX as usize) as i64)
+            .fetch_all(&self.pool),
+        )?;
+
+        rows.iter().map(read_proof_record).collect()
+    }
+
+    fn get_proofs_since_seq(&self, since: u64, limit: usize) -> Result<Vec<ProofRecord>> {
+        let rows = block_on(
+            sqlx::query(
+                "SELECT height, proof, public_values, root, timestamp, seq, proof_id FROM proofs
+                 WHERE domain = $1 AND seq > $2 ORDER BY seq ASC LIMIT $3",
+            )
+            .bind(&self.domain)
+            .bind(since.min(i64::MAX as u64) as i64)
+            .bind(limit.min(i64::MAX as usize) as i64)
+            .fetch_all(&self.pool),
+        )?;
+
+        rows.iter().map(read_proof_record).collect()
+    }
+
+    fn delete_proofs_from_height(&self, height: u64) -> Result<usize> {
+        let result = block_on(
+            sqlx::query("DELETE FROM proofs WHERE domain = $1 AND height >= $2")
+                .bind(&self.domain)
+                .bind(height as i64)
+                .execute(&self.pool),
+        )?;
+        Ok(result.rows_affected() as usize)
+    }
+
+    fn prune_proof_history(&self, keep: u64) -> Result<usize> {
+        let result = block_on(
+            sqlx::query(
+                "DELETE FROM proofs WHERE domain = $1 AND height NOT IN (
+                    SELECT height FROM proofs WHERE domain = $1 ORDER BY height DESC LIMIT $2
+                 )",
+            )
+            .bind(&self.domain)
+            .bind(keep.min(i64::MAX as u64) as i64)
+            .execute(&self.pool),
+        )?;
+        Ok(result.rows_affected() as usize)
+    }
+
+    fn get_proof_timestamps(&self) -> Result<Vec<(u64, DateTime<Utc>)>> {
+        let rows = block_on(
+            sqlx::query(
+                "SELECT height, timestamp FROM proofs WHERE domain = $1 ORDER BY height ASC",
+            )
+            .bind(&self.domain)
+            .fetch_all(&self.pool),
+        )?;
+
+        rows.iter()
+            .map(|row| {
+                Ok((
+                    row.try_get::<i64, _>("height")? as u64,
+                    row.try_get("timestamp")?,
+                ))
+            })
+            .collect()
+    }
+
+    fn delete_proofs(&self, heights: &[u64]) -> Result<usize> {
+        let heights: Vec<i64> = heights.iter().map(|&height| height as i64).collect();
+        let result = block_on(
+            sqlx::query("DELETE FROM proofs WHERE domain = $1 AND height = ANY($2)")
+                .bind(&self.domain)
+                .bind(heights)
+                .execute(&self.pool),
+        )?;
+        Ok(result.rows_affected() as usize)
+    }
+
+    fn enqueue_submission(&self, submission: &PendingSubmission) -> Result<()> {
+        block_on(
+            sqlx::query(
+                "INSERT INTO pending_submissions (domain, height, payload, created_at)
+                 VALUES ($1, $2, $3, $4)
+                 ON CONFLICT (domain, height) DO NOTHING",
+            )
+            .bind(&self.domain)
+            .bind(submission.height as i64)
+            .bind(&submission.payload)
+            .bind(submission.created_at)
+            .execute(&self.pool),
+        )?;
+        Ok(())
+    }
+
+    fn get_pending_submissions(&self) -> Result<Vec<PendingSubmission>> {
+        let rows = block_on(
+            sqlx::query(
+                "SELECT height, payload, created_at FROM pending_submissions
+                 WHERE domain = $1 ORDER BY height ASC",
+            )
+            .bind(&self.domain)
+            .fetch_all(&self.pool),
+        )?;
+
+        rows.iter()
+            .map(|row| {
+                Ok(PendingSubmission {
+                    height: row.try_get::<i64, _>("height")? as u64,
+                    payload: row.try_get("payload")?,
+                    created_at: row.try_get("created_at")?,
+                })
+            })
+            .collect()
+    }
+
+    fn remove_pending_submissions_through(&self, height: u64) -> Result<usize> {
+        let result = block_on(
+            sqlx::query("DELETE FROM pending_submissions WHERE domain = $1 AND height <= $2")
+                .bind(&self.domain)
+                .bind(height.min(i64::MAX as u64) as i64)
+                .execute(&self.pool),
+        )?;
+        Ok(result.rows_affected() as usize)
+    }
+
+    fn record_failed_submission(&self, failed: &FailedSubmission) -> Result<u64> {
+        let id: i64 = block_on(
+            sqlx::query_scalar(
+                "INSERT INTO failed_submissions (domain, height, payload, error, attempts, failed_at)
+                 VALUES ($1, $2, $3, $4, $5, $6)
+                 ON CONFLICT (domain, height) DO UPDATE SET
+                    error = EXCLUDED.error,
+                    attempts = failed_submissions.attempts + EXCLUDED.attempts,
+                    failed_at = EXCLUDED.failed_at
+                 RETURNING id",
+            )
+            .bind(&self.domain)
+            .bind(failed.height as i64)
+            .bind(&failed.payload)
+            .bind(&failed.error)
+            .bind(failed.attempts as i32)
+            .bind(failed.failed_at)
+            .fetch_one(&self.pool),
+        )?;
+        Ok(id as u64)
+    }
+
+    fn get_failed_submissions(&self) -> Result<Vec<FailedSubmission>> {
+        let rows = block_on(
+            sqlx::query(
+                "SELECT id, height, payload, error, attempts, failed_at FROM failed_submissions
+                 WHERE domain = $1 ORDER BY id ASC",
+            )
+            .bind(&self.domain)
+            .fetch_all(&self.pool),
+        )?;
+
+        rows.iter().map(read_failed_submission).collect()
+    }
+
+    fn get_failed_submission(&self, id: u64) -> Result<Option<FailedSubmission>> {
+        let row = block_on(
+            sqlx::query(
+                "SELECT id, height, payload, error, attempts, failed_at FROM failed_submissions
+                 WHERE domain = $1 AND id = $2",
+            )
+            .bind(&self.domain)
+            .bind(id.min(i64::MAX as u64) as i64)
+            .fetch_optional(&self.pool),
+        )?;
+
+        row.map(|row| read_failed_submission(&row)).transpose()
+    }
+
+    fn remove_failed_submission(&self, id: u64) -> Result<bool> {
+        let result = block_on(
+            sqlx::query("DELETE FROM failed_submissions WHERE domain = $1 AND id = $2")
+                .bind(&self.domain)
+                .bind(id.min(i64::MAX as u64) as i64)
+                .execute(&self.pool),
+        )?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    fn insert_alert(&self, alert: &AlertRecord) -> Result<u64> {
+        let id: i64 = block_on(
+            sqlx::query_scalar(
+                "INSERT INTO alerts (domain, condition, message, state, fired_at, notifications,
+                    last_notified_at, acknowledged_at, acknowledged_by, resolved_at)
+                 VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)
+                 RETURNING id",
+            )
+            .bind(&self.domain)
+            .bind(&alert.condition)
+            .bind(&alert.message)
+            .bind(alert.state.as_str())
+            .bind(alert.fired_at)
+            .bind(alert.notifications as i32)
+            .bind(alert.last_notified_at)
+            .bind(alert.acknowledged_at)
+            .bind(&alert.acknowledged_by)
+            .bind(alert.resolved_at)
+            .fetch_one(&self.pool),
+        )?;
+        Ok(id as u64)
+    }
+
+    fn update_alert(&self, alert: &AlertRecord) -> Result<bool> {
+        let result = block_on(
+            sqlx::query(
+                "UPDATE alerts SET condition = $3, message = $4, state = $5, fired_at = $6,
+                    notifications = $7, last_notified_at = $8, acknowledged_at = $9,
+                    acknowledged_by = $10, resolved_at = $11
+                 WHERE domain = $1 AND id = $2",
+            )
+            .bind(&self.domain)
+            .bind(alert.id.min(i64::MAX as u64) as i64)
+            .bind(&alert.condition)
+            .bind(&alert.message)
+            .bind(alert.state.as_str())
+            .bind(alert.fired_at)
+            .bind(alert.notifications as i32)
+            .bind(alert.last_notified_at)
+            .bind(alert.acknowledged_at)
+            .bind(&alert.acknowledged_by)
+            .bind(alert.resolved_at)
+            .execute(&self.pool),
+        )?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    fn get_alert(&self, id: u64) -> Result<Option<AlertRecord>> {
+        let row = block_on(
+            sqlx::query("SELECT * FROM alerts WHERE domain = $1 AND id = $2")
+                .bind(&self.domain)
+                .bind(id.min(i64::MAX as u64) as i64)
+                .fetch_optional(&self.pool),
+        )?;
+
+        row.map(|row| read_alert(&row)).transpose()
+    }
+
+    fn get_alerts(&self, limit: usize) -> Result<Vec<AlertRecord>> {
+        let rows = block_on(
+            sqlx::query("SELECT * FROM alerts WHERE domain = $1 ORDER BY id DESC LIMIT $2")
+                .bind(&self.domain)
+                .bind(limit.min(i64::MAX as usize) as i64)
+                .fetch_all(&self.pool),
+        )?;
+
+        rows.iter().map(read_alert).collect()
+    }
+
+    fn insert_relay_attempt(&self, attempt: &RelayAttempt) -> Result<u64> {
+        let id: i64 = block_on(
+            sqlx::query_scalar(
+                "INSERT INTO relay_attempts (domain, height, endpoint, attempt, payload_hash, status,
+                    response, latency_ms, attempted_at)
+                 VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+                 RETURNING id",
+            )
+            .bind(&self.domain)
+            .bind(attempt.height as i64)
+            .bind(&attempt.endpoint)
+            .bind(attempt.attempt as i32)
+            .bind(&attempt.payload_hash)
+            .bind(attempt.status.map(i32::from))
+            .bind(&attempt.response)
+            .bind(attempt.latency_ms.min(i64::MAX as u64) as i64)
+            .bind(attempt.attempted_at)
+            .fetch_one(&self.pool),
+        )?;
+        Ok(id as u64)
+    }
+
+    fn get_relay_attempts(&self, limit: usize, height: Option<u64>) -> Result<Vec<RelayAttempt>> {
+        let rows = block_on(
+            sqlx::query(
+                "SELECT * FROM relay_attempts
+                 WHERE domain = $1 AND ($3::BIGINT IS NULL OR height = $3)
+                 ORDER BY id DESC LIMIT $2",
+            )
+            .bind(&self.domain)
+            .bind(limit.min(i64::MAX as usize) as i64)
+            .bind(height.map(|height| height as i64))
+            .fetch_all(&self.pool),
+        )?;
+
+        rows.iter().map(read_relay_attempt).collect()
+    }
+
+    fn record_relay_confirmation(&self, confirmation: &RelayConfirmation) -> Result<()> {
+        block_on(
+            sqlx::query(
+                "INSERT INTO relay_confirmations (domain, height, endpoint, root, state, submissions,
+                    error, submitted_at, updated_at)
+                 VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+                 ON CONFLICT (domain, endpoint, height) DO UPDATE SET root = EXCLUDED.root,
+                    state = EXCLUDED.state, submissions = EXCLUDED.submissions,
+                    error = EXCLUDED.error, submitted_at = EXCLUDED.submitted_at,
+                    updated_at = EXCLUDED.updated_at",
+            )
+            .bind(&self.domain)
+            .bind(confirmation.height as i64)
+            .bind(&confirmation.endpoint)
+            .bind(&confirmation.root)
+            .bind(confirmation.state.as_str())
+            .bind(confirmation.submissions as i32)
+            .bind(&confirmation.error)
+            .bind(confirmation.submitted_at)
+            .bind(confirmation.updated_at)
+            .execute(&self.pool),
+        )?;
+        Ok(())
+    }
+
+    fn get_relay_confirmations(
+        &self,
+        limit: usize,
+        height: Option<u64>,
+    ) -> Result<Vec<RelayConfirmation>> {
+        let rows = block_on(
+            sqlx::query(
+                "SELECT * FROM relay_confirmations
+                 WHERE domain = $1 AND ($3::BIGINT IS NULL OR height = $3)
+                 ORDER BY updated_at DESC, height DESC LIMIT $2",
+            )
+            .bind(&self.domain)
+            .bind(limit.min(i64::MAX as usize) as i64)
+            .bind(height.map(|height| height as i64))
+            .fetch_all(&self.pool),
+        )?;
+
+        rows.iter()
+            .map(|row| {
+                Ok(RelayConfirmation {
+                    height: row.try_get::<i64, _>("height")? as u64,
+                    endpoint: row.try_get("endpoint")?,
+                    root: row.try_get("root")?,
+                    state: ConfirmationState::parse(row.try_get("state")?)?,
+                    submissions: row.try_get::<i32, _>("submissions")? as u32,
+                    error: row.try_get("error")?,
+                    submitted_at: row.try_get("submitted_at")?,
+                    updated_at: row.try_get("updated_at")?,
+                })
+            })
+            .collect()
+    }
+
+    fn insert_cutover(&self, cutover: &CutoverRecord) -> Result<u64> {
+        let id: i64 = block_on(
+            sqlx::query_scalar(
+                "INSERT INTO registry_cutovers (domain, from_endpoint, to_endpoint, latest_only,
+                    validate_endpoint, drained, replayed_height, cut_over_at)
+                 VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+                 RETURNING id",
+            )
+            .bind(&self.domain)
+            .bind(&cutover.from_endpoint)
+            .bind(&cutover.to_endpoint)
+            .bind(cutover.latest_only)
+            .bind(&cutover.validate_endpoint)
+            .bind(cutover.drained as i64)
+            .bind(cutover.replayed_height.map(|height| height as i64))
+            .bind(cutover.cut_over_at)
+            .fetch_one(&self.pool),
+        )?;
+        Ok(id as u64)
+    }
+
+    fn get_cutovers(&self, limit: usize) -> Result<Vec<CutoverRecord>> {
+        let rows = block_on(
+            sqlx::query(
+                "SELECT * FROM registry_cutovers WHERE domain = $1 ORDER BY id DESC LIMIT $2",
+            )
+            .bind(&self.domain)
+            .bind(limit.min(i64::MAX as usize) as i64)
+            .fetch_all(&self.pool),
+        )?;
+
+        rows.iter()
+            .map(|row| {
+                Ok(CutoverRecord {
+                    id: row.try_get::<i64, _>("id")? as u64,
+                    from_endpoint: row.try_get("from_endpoint")?,
+                    to_endpoint: row.try_get("to_endpoint")?,
+                    latest_only: row.try_get("latest_only")?,
+                    validate_endpoint: row.try_get("validate_endpoint")?,
+                    drained: row.try_get::<i64, _>("drained")? as u64,
+                    replayed_height: row
+                        .try_get::<Option<i64>, _>("replayed_height")?
+                        .map(|height| height as u64),
+                    cut_over_at: row.try_get("cut_over_at")?,
+                })
+            })
+            .collect()
+    }
+
+    fn record_decode_failure(&self, failure: &DecodeFailure) -> Result<u64> {
+        block_on(async {
+            let mut tx = self.pool.begin().await?;
+            let id: i64 = sqlx::query_scalar(
+                "INSERT INTO decode_failures (domain, error, prover, public_values, failed_at)
+                 VALUES ($1, $2, $3, $4, $5)
+                 RETURNING id",
+            )
+            .bind(&self.domain)
+            .bind(&failure.error)
+            .bind(&failure.prover)
+            .bind(&failure.public_values)
+            .bind(failure.failed_at)
+            .fetch_one(&mut *tx)
+            .await?;
+            sqlx::query(
+                "DELETE FROM decode_failures WHERE domain = $1 AND id NOT IN
+                    (SELECT id FROM decode_failures WHERE domain = $1 ORDER BY id DESC LIMIT $2)",
+            )
+            .bind(&self.domain)
+            .bind(DECODE_FAILURES_KEPT as i64)
+            .execute(&mut *tx)
+            .await?;
+            tx.commit().await?;
+            Ok(id as u64)
+        })
+    }
+
+    fn get_decode_failures(&self, limit: usize) -> Result<Vec<DecodeFailure>> {
+        let rows = block_on(
+            sqlx::query(
+                "SELECT * FROM decode_failures WHERE domain = $1 ORDER BY id DESC LIMIT $2",
+            )
+            .bind(&self.domain)
+            .bind(limit.min(i64::MAX as usize) as i64)
+            .fetch_all(&self.pool),
+        )?;
+
+        rows.iter()
+            .map(|row| {
+                Ok(DecodeFailure {
+                    id: row.try_get::<i64, _>("id")? as u64,
+                    error: row.try_get("error")?,
+                    prover: row.try_get("prover")?,
+                    public_values: row.try_get("public_values")?,
+                    failed_at: row.try_get("failed_at")?,
+                })
+            })
+            .collect()
+    }
+
+    fn insert_metric_samples(&self, samples: &[MetricSample]) -> Result<()> {
+        block_on(async {
+            let mut tx = self.pool.begin().await?;
+            for sample in samples {
+                sqlx::query(
+                    "INSERT INTO metric_samples (domain, metric, value, sampled_at) VALUES ($1, $2, $3, $4)",
+                )
+                .bind(&self.domain)
+                .bind(&sample.metric)
+                .bind(sample.value)
+                .bind(sample.sampled_at)
+                .execute(&mut *tx)
+                .await?;
+            }
+            tx.commit().await?;
+            Ok::<(), anyhow::Error>(())
+        })
+    }
+
+    fn get_metric_samples(&self, metric: &str, since: DateTime<Utc>) -> Result<Vec<MetricSample>> {
+        let rows = block_on(
+            sqlx::query(
+                "SELECT metric, value, sampled_at FROM metric_samples
+                 WHERE domain = $1 AND metric = $2 AND sampled_at >= $3
+                 ORDER BY sampled_at ASC",
+            )
+            .bind(&self.domain)
+            .bind(metric)
+            .bind(since)
+            .fetch_all(&self.pool),
+        )?;
+
+        rows.iter()
+            .map(|row| {
+                Ok(MetricSample {
+                    metric: row.try_get("metric")?,
+                    value: row.try_get("value")?,
+                    sampled_at: row.try_get("sampled_at")?,
+                })
+            })
+            .collect()
+    }
+
+    fn prune_metric_samples(&self, before: DateTime<Utc>) -> Result<usize> {
+        let result = block_on(
+            sqlx::query("DELETE FROM metric_samples WHERE domain = $1 AND sampled_at < $2")
+                .bind(&self.domain)
+                .bind(before)
+                .execute(&self.pool),
+        )?;
+        Ok(result.rows_affected() as usize)
+    }
+
+    fn backup(&self, _path: &Path) -> Result<()> {
+        Err(anyhow!(
+            "Postgres storage can't be backed up by the relayer, use pg_dump"
+        ))
+    }
+
+    fn clear_all_tables(&self) -> Result<()> {
+        block_on(async {
+            sqlx::query("DELETE FROM health_check WHERE domain = $1")
+                .bind(&self.domain)
+                .execute(&self.pool)
+                .await?;
+            sqlx::query("DELETE FROM previous_proof WHERE domain = $1")
+                .bind(&self.domain)
+                .execute(&self.pool)
+                .await?;
+            Ok::<(), anyhow::Error>(())
+        })
+    }
+}
+
+fn read_proof_record(row: &PgRow) -> Result<ProofRecord> {
+    let timestamp: DateTime<Utc> = row.try_get("timestamp")?;
+    let proof: Vec<u8> = row.try_get("proof")?;
+    let proof_id = stored_proof_id(row.try_get("proof_id")?, &proof)?;
+
+    Ok(ProofRecord {
+        seq: row.try_get::<Option<i64>, _>("seq")?.unwrap_or_default() as u64,
+        height: row.try_get::<i64, _>("height")? as u64,
+        proof,
+        public_values: row.try_get("public_values")?,
+        root: row.try_get("root")?,
+        proof_id,
+        timestamp,
+    })
+}
+
+fn read_failed_submission(row: &PgRow) -> Result<FailedSubmission> {
+    Ok(FailedSubmission {
+        id: row.try_get::<i64, _>("id")? as u64,
+        height: row.try_get::<i64, _>("height")? as u64,
+        payload: row.try_get("payload")?,
+        error: row.try_get("error")?,
+        attempts: row.try_get::<i32, _>("attempts")? as u32,
+        failed_at: row.try_get("failed_at")?,
+    })
+}
+
+fn read_relay_attempt(row: &PgRow) -> Result<RelayAttempt> {
+    Ok(RelayAttempt {
+        id: row.try_get::<i64, _>("id")? as u64,
+        height: row.try_get::<i64, _>("height")? as u64,
+        endpoint: row.try_get("endpoint")?,
+        attempt: row.try_get::<i32, _>("attempt")? as u32,
+        payload_hash: row.try_get("payload_hash")?,
+        status: row
+            .try_get::<Option<i32>, _>("status")?
+            .map(|status| status as u16),
+        response: row.try_get("response")?,
+        latency_ms: row.try_get::<i64, _>("latency_ms")? as u64,
+        attempted_at: row.try_get("attempted_at")?,
+    })
+}
+
+fn read_alert(row: &PgRow) -> Result<AlertRecord> {
+    Ok(AlertRecord {
+        id: row.try_get::<i64, _>("id")? as u64,
+        condition: row.try_get("condition")?,
+        message: row.try_get("message")?,
+        state: AlertState::parse(row.try_get("state")?)?,
+        fired_at: row.try_get("fired_at")?,
+        notifications: row.try_get::<i32, _>("notifications")? as u32,
+        last_notified_at: row.try_get("last_notified_at")?,
+        acknowledged_at: row.try_get("acknowledged_at")?,
+        acknowledged_by: row.try_get("acknowledged_by")?,
+        resolved_at: row.try_get("resolved_at")?,
+    })
+}
@@ -0,0 +1,117 @@
+use anyhow::{Result, anyhow};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256, Sha512};
+use std::fmt;
+
+/// Hash function behind `ProofId`s. Ids carry its name, so a domain can move to another
+/// one without ids from before the switch becoming ambiguous.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HashAlgorithm {
+    #[default]
+    Sha256,
+    Sha512,
+}
+
+impl HashAlgorithm {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            HashAlgorithm::Sha256 => "sha256",
+            HashAlgorithm::Sha512 => "sha512",
+        }
+    }
+
+    pub fn digest(&self, bytes: &[u8]) -> Vec<u8> {
+        match self {
+            HashAlgorithm::Sha256 => Sha256::digest(bytes).to_vec(),
+            HashAlgorithm::Sha512 => Sha512::digest(bytes).to_vec(),
+        }
+    }
+
+    fn parse(name: &str) -> Result<Self> {
+        match name {
+            "sha256" => Ok(HashAlgorithm::Sha256),
+            "sha512" => Ok(HashAlgorithm::Sha512),
+            other => Err(anyhow!("Unknown hash algorithm {}", other)),
+        }
+    }
+
+    fn digest_len(&self) -> usize {
+        match self {
+            HashAlgorithm::Sha256 => 32,
+            HashAlgorithm::Sha512 => 64,
+        }
+    }
+}
+
+/// Canonical identifier of a proof: the algorithm-prefixed hash of its bytes as relayed,
+/// e.g. `sha256:9f86d0…`. The same id names the proof in storage, API responses, logs,
+/// metrics and registry payloads.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct ProofId(String);
+
+impl ProofId {
+    pub fn of(algorithm: HashAlgorithm, proof: &[u8]) -> Self {
+        ProofId(format!(
+            "{}:{}",
+            algorithm.as_str(),
+            hex::encode(algorithm.digest(proof))
+        ))
+    }
+
+    /// Reads an id as stored or shown, checking its algorithm and digest length
+    pub fn parse(value: &str) -> Result<Self> {
+        let (algorithm, digest) = value
+            .split_once(':')
+            .ok_or_else(|| anyhow!("Proof id {} has no algorithm prefix", value))?;
+        let algorithm = HashAlgorithm::parse(algorithm)?;
+        let digest = hex::decode(digest)?;
+        if digest.len() != algorithm.digest_len() {
+            return Err(anyhow!(
+                "Proof id {} is not a {} digest",
+                value,
+                algorithm.as_str()
+            ));
+        }
+        Ok(ProofId(value.to_string()))
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    /// The algorithm and first 8 bytes of the digest, for log lines
+    pub fn short(&self) -> &str {
+        let end = self.0.find(':').map_or(0, |colon| colon + 1) + 16;
+        &self.0[..end.min(self.0.len())]
+    }
+}
+
+impl fmt::Display for ProofId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_proof_id() {
+        let id = ProofId::of(HashAlgorithm::Sha256, b"proof");
+        assert!(id.as_str().starts_with("sha256:"));
+        assert_eq!(id.as_str().len(), "sha256:".len() + 64);
+        assert_eq!(id.short().len(), "sha256:".len() + 16);
+        assert_eq!(ProofId::parse(id.as_str()).unwrap(), id);
+
+        let longer = ProofId::of(HashAlgorithm::Sha512, b"proof");
+        assert!(longer.as_str().starts_with("sha512:"));
+        assert_ne!(longer, id);
+
+        assert!(ProofId::parse("abcd").is_err());
+        assert!(ProofId::parse("md5:abcd").is_err());
+        assert!(ProofId::parse("sha256:abcd").is_err());
+    }
+}
@@ -0,0 +1,230 @@
+use serde::Serialize;
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Duration;
+use tracing::{info, warn};
+
+use crate::config::{DomainConfig, EndpointHttp, MODE, ProofSourceKind, ProverSelection};
+use crate::source::{self, ProofSource};
+
+/// Health of a single prover endpoint as observed by the relayer
+#[derive(Debug, Clone, Serialize)]
+pub struct EndpointHealth {
+    pub endpoint: String,
+    pub healthy: bool,
+    pub successes: u64,
+    pub failures: u64,
+    pub consecutive_failures: u64,
+    pub last_error: Option<String>,
+    pub last_success: Option<String>,
+    pub last_latency_ms: Option<u64>,
+    /// As reported by the prover at startup, for `auto` sources
+    pub api_version: Option<String>,
+    pub codec: ProofSourceKind,
+}
+
+/// No height and root were served by as many provers as the quorum requires
+#[derive(Debug, Clone)]
+pub struct QuorumNotReached {
+    pub quorum: usize,
+    pub endpoints: usize,
+    /// Each (height, hex root) served, with how many provers served it
+    pub votes: Vec<(u64, String, usize)>,
+}
+
+impl std::fmt::Display for QuorumNotReached {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "No proof is backed by {} of {} provers",
+            self.quorum, self.endpoints
+        )?;
+        if self.votes.is_empty() {
+            return write!(f, ", none served one");
+        }
+        let votes: Vec<String> = self
+            .votes
+            .iter()
+            .map(|(height, root, count)| format!("height {} root {} ({})", height, root, count))
+            .collect();
+        write!(f, ", served: {}", votes.join(", "))
+    }
+}
+
+impl std::error::Error for QuorumNotReached {}
+
+/// The configured prover endpoints and their observed health
+pub struct ProverPool {
+    endpoints: Vec<String>,
+    /// How each endpoint is reached
+    http: Vec<EndpointHttp>,
+    source: ProofSourceKind,
+    selection: ProverSelection,
+    /// Agreeing provers needed for a proof, and the mode to decode their outputs in
+    quorum: Option<(MODE, usize)>,
+    next: AtomicUsize,
+    active: Mutex<Option<usize>>,
+    health: Mutex<Vec<EndpointHealth>>,
+}
+
+impl ProverPool {
+    pub fn new(endpoints: Vec<String>, selection: ProverSelection) -> Self {
+        let health = endpoints
+            .iter()
+            .map(|endpoint| EndpointHealth {
+                endpoint: endpoint.clone(),
+                healthy: true,
+                successes: 0,
+                failures: 0,
+                consecutive_failures: 0,
+                last_error: None,
+                last_success: None,
+                last_latency_ms: None,
+                api_version: None,
+                codec: ProofSourceKind::HexHttp,
+            })
+            .collect();
+
+        ProverPool {
+            http: vec![EndpointHttp::default(); endpoints.len()],
+            source: ProofSourceKind::HexHttp,
+            endpoints,
+            selection,
+            quorum: None,
+            next: AtomicUsize::new(0),
+            active: Mutex::new(None),
+            health: Mutex::new(health),
+        }
+    }
+
+    /// Reaches each endpoint with the proxy, timeouts and headers configured for it
+    pub fn with_http(mut self, config: &DomainConfig) -> Self {
+        self.http = self
+            .endpoints
+            .iter()
+            .map(|endpoint| config.http_for(endpoint))
+            .collect();
+        self
+    }
+
+    /// Fetches from every endpoint in the given format instead of the hex HTTP default
+    pub fn with_source(mut self, source: ProofSourceKind) -> Self {
+        self.source = source;
+        let codec = match source {
+            ProofSourceKind::Auto => ProofSourceKind::HexHttp,
+            codec => codec,
+        };
+        for entry in self.health.get_mut().unwrap().iter_mut() {
+            entry.codec = codec;
+        }
+        self
+    }
+
+    /// Only takes proofs that `quorum` endpoints agree on, see `get_quorum_proof`
+    pub fn with_quorum(mut self, mode: MODE, quorum: Option<usize>) -> Self {
+        self.quorum = quorum.map(|quorum| (mode, quorum));
+        self
+    }
+
+    pub fn quorum(&self) -> Option<(MODE, usize)> {
+        self.quorum
+    }
+
+    /// For `auto` sources, asks each prover for its API version and switches it to the
+    /// matching codec. Provers that can't be reached keep the legacy codec.
+    pub async fn detect_api_versions(&self) {
+        if self.source != ProofSourceKind::Auto {
+            return;
+        }
+
+        for (index, endpoint) in self.endpoints.iter().enumerate() {
+            let version = match source::detect_api_version(endpoint, &self.http[index]).await {
+                Ok(version) => version,
+                Err(e) => {
+                    warn!(
+                        "⚠️  Could not detect API version of prover {}, using the legacy codec: {}",
+                        endpoint, e
+                    );
+                    continue;
+                }
+            };
+            let codec = source::codec_for(endpoint, version.as_deref());
+            info!(
+                "🔌 Prover {} reports API version {}, using {:?} codec",
+                endpoint,
+                version.as_deref().unwrap_or("unknown (legacy)"),
+                codec
+            );
+
+            let mut health = self.health.lock().unwrap();
+            health[index].api_version = version;
+            health[index].codec = codec;
+        }
+    }
+
+    /// Endpoint indices in the order they should be tried for the next fetch
+    pub fn order(&self) -> Vec<usize> {
+        let len = self.endpoints.len();
+        let start = match self.selection {
+            ProverSelection::Priority => 0,
+            ProverSelection::RoundRobin => self.next.fetch_add(1, Ordering::Relaxed) % len.max(1),
+        };
+        (0..len).map(|offset| (start + offset) % len).collect()
+    }
+
+    pub fn endpoint(&self, index: usize) -> &str {
+        &self.endpoints[index]
+    }
+
+    /// Source fetching proofs from one endpoint, with its detected codec
+    pub fn source(&self, index: usize) -> Box<dyn ProofSource> {
+        let codec = self.health.lock().unwrap()[index].codec;
+        source::build(codec, &self.endpoints[index], self.http[index].clone())
+    }
+
+    /// Whether any endpoint can serve proofs by height, for backfill
+    pub fn serves_heights(&self) -> bool {
+        (0..self.endpoints.len()).any(|index| self.source(index).serves_heights())
+    }
+
+    pub fn endpoint_count(&self) -> usize {
+        self.endpoints.len()
+    }
+
+    /// Endpoint that served the most recent successful fetch
+    pub fn active_endpoint(&self) -> Option<&str> {
+        self.active
+            .lock()
+            .unwrap()
+            .map(|index| self.endpoints[index].as_str())
+    }
+
+    /// Attributes the latest fetch to `index`, as a quorum fetch hears from several
+    pub fn set_active(&self, index: usize) {
+        *self.active.lock().unwrap() = Some(index);
+    }
+
+    pub fn record_success(&self, index: usize, latency: Duration) {
+        *self.active.lock().unwrap() = Some(index);
+        let mut health = self.health.lock().unwrap();
+        let entry = &mut health[index];
+        entry.healthy = true;
+        entry.successes += 1;
+        entry.consecutive_failures = 0;
+        entry.last_success = Some(chrono::Utc::now().to_rfc3339());
+        entry.last_latency_ms = Some(latency.as_millis() as u64);
+    }
+
+    pub fn record_failure(&self, index: usize, error: &anyhow::Error) {
+        let mut health = self.health.lock().unwrap();
+        let entry = &mut health[index];
+        entry.healthy = false;
+        entry.failures += 1;
+        entry.consecutive_failures += 1;
+        entry.last_error = Some(error.to_string());
+    }
+
+    pub fn snapshot(&self) -> Vec<EndpointHealth> {
+        self.health.lock().unwrap().clone()
+    }
+}
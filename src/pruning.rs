@@ -0,0 +1,162 @@
+use chrono::{DateTime, Utc};
+use prometheus::IntCounterVec;
+use serde::Serialize;
+use std::sync::Mutex;
+
+use crate::config::HistoryPruningConfig;
+
+/// Heights of the proofs `policy` doesn't keep at `now`. `proofs` are the stored proofs'
+/// heights and record times, lowest height first; the highest is always kept.
+pub fn prunable(
+    proofs: &[(u64, DateTime<Utc>)],
+    policy: &HistoryPruningConfig,
+    now: DateTime<Utc>,
+) -> Vec<u64> {
+    let recent_from = match policy.keep_last {
+        Some(keep) => proofs.len().saturating_sub(keep as usize),
+        None => proofs.len(),
+    }
+    .min(proofs.len().saturating_sub(1));
+    let kept_since = policy
+        .keep_days
+        .map(|days| now - chrono::Duration::days(days.min(i64::MAX as u64 / 86_400) as i64));
+
+    proofs
+        .iter()
+        .enumerate()
+        .filter(|(index, (height, recorded_at))| {
+            let kept = *index >= recent_from
+                || kept_since.is_some_and(|since| *recorded_at >= since)
+                || policy
+                    .keep_every
+                    .is_some_and(|every| every > 0 && height % every == 0);
+            !kept
+        })
+        .map(|(_, (height, _))| *height)
+        .collect()
+}
+
+/// Pruning runs of one domain since startup, exported at `/metrics`
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct PruneSnapshot {
+    pub runs: u64,
+    pub proofs_removed: u64,
+    /// Proofs left in the history after the latest run
+    pub proofs_kept: u64,
+    pub last_run_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Default)]
+pub struct PruneTracker {
+    state: Mutex<PruneSnapshot>,
+    /// Counter of removed proofs and the domain label it's recorded under
+    pruned: Option<(IntCounterVec, String)>,
+}
+
+impl PruneTracker {
+    pub fn counting(pruned: IntCounterVec, domain: &str) -> Self {
+        PruneTracker {
+            state: Mutex::default(),
+            pruned: Some((pruned, domain.to_string())),
+        }
+    }
+
+    pub fn record(&self, removed: usize, kept: usize) {
+        if let Some((pruned, domain)) = &self.pruned {
+            pruned
+                .with_label_values(&[domain.as_str()])
+                .inc_by(removed as u64);
+        }
+        let mut state = self.state.lock().unwrap();
+        state.runs += 1;
+        state.proofs_removed += removed as u64;
+        state.proofs_kept = kept as u64;
+        state.last_run_at = Some(Utc::now());
+    }
+
+    pub fn snapshot(&self) -> PruneSnapshot {
+        self.state.lock().unwrap().clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn policy(
+        keep_last: Option<u64>,
+        keep_days: Option<u64>,
+        keep_every: Option<u64>,
+    ) -> HistoryPruningConfig {
+        HistoryPruningConfig {
+            keep_last,
+            keep_days,
+            keep_every,
+            ..HistoryPruningConfig::default()
+        }
+    }
+
+    #[test]
+    fn test_prunable() {
+        let now = Utc::now();
+        // Heights 1 to 10, one a day, height 10 recorded today
+        let proofs: Vec<_> = (1..=10u64)
+            .map(|height| (height, now - chrono::Duration::days(10 - height as i64)))
+            .collect();
+
+        assert_eq!(
+            prunable(&proofs, &policy(Some(3), None, None), now),
+            (1..=7).collect::<Vec<_>>()
+        );
+        // Recorded within the last 2 days: heights 8, 9 and 10
+        assert_eq!(
+            prunable(&proofs, &policy(None, Some(2), None), now),
+            (1..=7).collect::<Vec<_>>()
+        );
+        assert_eq!(
+            prunable(&proofs, &policy(Some(2), None, Some(4)), now),
+            vec![1, 2, 3, 5, 6, 7]
+        );
+        assert_eq!(
+            prunable(&proofs, &policy(Some(1), Some(3), Some(5)), now),
+            vec![1, 2, 3, 4, 6]
+        );
+
+        // The latest proof survives even when no rule keeps it
+        assert_eq!(
+            prunable(&proofs, &policy(None, None, Some(3)), now),
+            vec![1, 2, 4, 5, 7, 8]
+        );
+        assert_eq!(
+            prunable(&proofs, &policy(Some(0), None, None), now),
+            (1..=9).collect::<Vec<_>>()
+        );
+        assert!(prunable(&[], &policy(Some(0), None, None), now).is_empty());
+    }
+
+    #[test]
+    fn test_prune_totals() {
+        let tracker = PruneTracker::default();
+        tracker.record(7, 3);
+        tracker.record(0, 4);
+
+        let snapshot = tracker.snapshot();
+        assert_eq!(snapshot.runs, 2);
+        assert_eq!(snapshot.proofs_removed, 7);
+        assert_eq!(snapshot.proofs_kept, 4);
+        assert!(snapshot.last_run_at.is_some());
+    }
+
+    #[test]
+    fn test_prune_counter() -> anyhow::Result<()> {
+        let pruned = IntCounterVec::new(
+            prometheus::Opts::new("relayer_proofs_pruned_total", "Pruned proofs"),
+            &["domain"],
+        )?;
+        let tracker = PruneTracker::counting(pruned.clone(), "alpha");
+        tracker.record(7, 3);
+        tracker.record(2, 4);
+        assert_eq!(pruned.with_label_values(&["alpha"]).get(), 9);
+        Ok(())
+    }
+}
@@ -0,0 +1,775 @@
+use anyhow::{Result, anyhow};
+use chrono::{DateTime, Utc};
+use redis::{Commands, Connection, Script};
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::Mutex;
+
+use crate::db::{
+    AlertRecord, CutoverRecord, DECODE_FAILURES_KEPT, DecodeFailure, FailedSubmission,
+    HealthCheckData, MetricSample, PendingSubmission, PreviousProof, ProofRecord, RelayAttempt,
+    RelayConfirmation, Storage, stored_proof_id,
+};
+
+/// Records a proof only if no proof for its height exists yet, so that concurrent
+/// replicas agree on a single winner per height, and hands out its sequence number.
+const INSERT_PROOF_SCRIPT: &str = r#"
+if redis.call('ZADD', KEYS[1], 'NX', ARGV[1], ARGV[1]) == 0 then
+    return 0
+end
+local seq = redis.call('INCR', KEYS[3])
+redis.call('ZADD', KEYS[4], seq, ARGV[1])
+redis.call('HSET', KEYS[2], 'proof', ARGV[2], 'public_values', ARGV[3], 'root', ARGV[4], 'timestamp', ARGV[5], 'seq', seq, 'proof_id', ARGV[6])
+return seq
+"#;
+
+/// Dead-letters a payload, reusing the entry already recorded for its height if any,
+/// and returns the entry's id.
+const RECORD_FAILED_SCRIPT: &str = r#"
+local id = redis.call('HGET', KEYS[1], ARGV[1])
+if id then
+    redis.call('HINCRBY', ARGV[6] .. id, 'attempts', ARGV[4])
+    redis.call('HSET', ARGV[6] .. id, 'error', ARGV[3], 'failed_at', ARGV[5])
+else
+    id = redis.call('INCR', KEYS[2])
+    redis.call('HSET', KEYS[1], ARGV[1], id)
+    redis.call('ZADD', KEYS[3], id, id)
+    redis.call('HSET', ARGV[6] .. id, 'height', ARGV[1], 'payload', ARGV[2], 'error', ARGV[3], 'attempts', ARGV[4], 'failed_at', ARGV[5])
+end
+return tonumber(id)
+"#;
+
+/// Redis storage for multi-replica deployments without Postgres. Replicas share the
+/// dedup state (previous proof) and the proof history, whose highest entry is the
+/// high-water mark.
+///
+/// Keys are namespaced as `<prefix>:<domain>:...`; the history is a sorted set of
+/// heights, a second one ordering them by sequence number, and one hash per proof.
+/// The submission queue is a sorted set of payloads scored by height; dead-lettered
+/// payloads are one hash each, listed in a sorted set of ids. Alerts are stored as
+/// JSON, one key each, listed in a sorted set of ids, and so are relay attempts. Relay
+/// confirmations are JSON in one hash keyed by height and endpoint, listed in a sorted
+/// set scored by update time. Metric samples are one sorted set per metric, scored by
+/// sample time in milliseconds.
+pub struct RedisStorage {
+    conn: Mutex<Connection>,
+    namespace: String,
+}
+
+type Fields = HashMap<String, Vec<u8>>;
+
+impl RedisStorage {
+    pub fn connect(url: &str, key_prefix: &str, domain: &str) -> Result<Self> {
+        let conn = redis::Client::open(url)?.get_connection()?;
+        Ok(RedisStorage {
+            conn: Mutex::new(conn),
+            namespace: format!("{}:{}", key_prefix, domain),
+        })
+    }
+
+    fn key(&self, name: &str) -> String {
+        format!("{}:{}", self.namespace, name)
+    }
+
+    fn proof_key(&self, height: u64) -> String {
+        format!("{}:proof:{}", self.namespace, height)
+    }
+
+    fn failed_key(&self, id: u64) -> String {
+        format!("{}:failed:{}", self.namespace, id)
+    }
+
+    fn alert_key(&self, id: u64) -> String {
+        format!("{}:alert:{}", self.namespace, id)
+    }
+
+    fn relay_key(&self, id: u64) -> String {
+        format!("{}:relay:{}", self.namespace, id)
+    }
+
+    fn metric_key(&self, metric: &str) -> String {
+        format!("{}:metric:{}", self.namespace, metric)
+    }
+
+    fn read_failed(&self, conn: &mut Connection, id: u64) -> Result<Option<FailedSubmission>> {
+        let fields: Fields = conn.hgetall(self.failed_key(id))?;
+        if fields.is_empty() {
+            return Ok(None);
+        }
+
+        let text = |name: &str| -> Result<String> {
+            Ok(String::from_utf8(field(&fields, name)?.clone())?)
+        };
+        let failed_at = DateTime::parse_from_rfc3339(&text("failed_at")?)?.with_timezone(&Utc);
+
+        Ok(Some(FailedSubmission {
+            id,
+            height: text("height")?.parse()?,
+            payload: text("payload")?,
+            error: text("error")?,
+            attempts: text("attempts")?.parse()?,
+            failed_at,
+        }))
+    }
+
+    fn read_proof(&self, conn: &mut Connection, height: u64) -> Result<Option<ProofRecord>> {
+        let fields: Fields = conn.hgetall(self.proof_key(height))?;
+        if fields.is_empty() {
+            return Ok(None);
+        }
+
+        let seq = match fields.get("seq") {
+            Some(seq) => String::from_utf8(seq.clone())?.parse()?,
+            None => 0,
+        };
+
+        let proof = field(&fields, "proof")?.clone();
+        let proof_id = fields
+            .get("proof_id")
+            .map(|id| String::from_utf8(id.clone()))
+            .transpose()?;
+
+        Ok(Some(ProofRecord {
+            seq,
+            height,
+            proof_id: stored_proof_id(proof_id, &proof)?,
+            proof,
+            public_values: field(&fields, "public_values")?.clone(),
+            root: field(&fields, "root")?.clone(),
+            timestamp: timestamp_field(&fields)?,
+        }))
+    }
+}
+
+fn field<'a>(fields: &'a Fields, name: &str) -> Result<&'a Vec<u8>> {
+    fields
+        .get(name)
+        .ok_or_else(|| anyhow!("Redis record is missing field {}", name))
+}
+
+fn timestamp_field(fields: &Fields) -> Result<DateTime<Utc>> {
+    let raw = String::from_utf8(field(fields, "timestamp")?.clone())?;
+    Ok(DateTime::parse_from_rfc3339(&raw)?.with_timezone(&Utc))
+}
+
+impl Storage for RedisStorage {
+    fn update_health_check(&self, data: &HealthCheckData) -> Result<()> {
+        let mut conn = self.conn.lock().unwrap();
+
+        let _: () = conn.hset_multiple(
+            self.key("health_check"),
+            &[
+                (
+                    "current_height",
+                    data.current_height.to_string().into_bytes(),
+                ),
+                ("current_root", data.current_root.clone()),
+                ("timestamp", data.timestamp.to_rfc3339().into_bytes()),
+            ],
+        )?;
+
+        Ok(())
+    }
+
+    fn get_latest_health_check(&self) -> Result<Option<HealthCheckData>> {
+        let mut conn = self.conn.lock().unwrap();
+
+        let fields: Fields = conn.hgetall(self.key("health_check"))?;
+        if fields.is_empty() {
+            return Ok(None);
+        }
+
+        Ok(Some(HealthCheckData {
+            current_height: String::from_utf8(field(&fields, "current_height")?.clone())?
+                .parse()?,
+            current_root: field(&fields, "current_root")?.clone(),
+            timestamp: timestamp_field(&fields)?,
+        }))
+    }
+
+    fn update_previous_proof(&self, proof: &PreviousProof) -> Result<()> {
+        let mut conn = self.conn.lock().unwrap();
+
+        let _: () = conn.hset_multiple(
+            self.key("previous_proof"),
+            &[
+                ("proof_data", proof.proof_data.clone().into_bytes()),
+                ("timestamp", proof.timestamp.to_rfc3339().into_bytes()),
+            ],
+        )?;
+
+        Ok(())
+    }
+
+    fn get_previous_proof(&self) -> Result<Option<PreviousProof>> {
+        let mut conn = self.conn.lock().unwrap();
+
+        let fields: Fields = conn.hgetall(self.key("previous_proof"))?;
+        if fields.is_empty() {
+            return Ok(None);
+        }
+
+        Ok(Some(PreviousProof {
+            proof_data: String::from_utf8(field(&fields, "proof_data")?.clone())?,
+            timestamp: timestamp_field(&fields)?,
+        }))
+    }
+
+    fn insert_proof(&self, record: &ProofRecord) -> Result<Option<u64>> {
+        let mut conn = self.conn.lock().unwrap();
+
+        let seq: u64 = Script::new(INSERT_PROOF_SCRIPT)
+            .key(self.key("proofs"))
+            .key(self.proof_key(record.height))
+            .key(self.key("proof_seq"))
+            .key(self.key("proofs_by_seq"))
+            .arg(record.height)
+            .arg(&record.proof)
+            .arg(&record.public_values)
+            .arg(&record.root)
+            .arg(record.timestamp.to_rfc3339())
+            .arg(record.proof_id.as_str())
+            .invoke(&mut *conn)?;
+
+        Ok((seq > 0).then_some(seq))
+    }
+
+    fn get_proof_by_height(&self, height: u64) -> Result<Option<ProofRecord>> {
+        let mut conn = self.conn.lock().unwrap();
+        self.read_proof(&mut conn, height)
+    }
+
+    fn get_latest_proof(&self) -> Result<Option<ProofRecord>> {
+        let mut conn = self.conn.lock().unwrap();
+
+        let heights: Vec<u64> = conn.zrevrange(self.key("proofs"), 0, 0)?;
+        match heights.first() {
+            Some(&height) => self.read_proof(&mut conn, height),
+            None => Ok(None),
+        }
+    }
+
+    fn get_proofs_in_range(&self, from: u64, to: u64) -> Result<Vec<ProofRecord>> {
+        let mut conn = self.conn.lock().unwrap();
+
+        let heights: Vec<u64> = conn.zrangebyscore(self.key("proofs"), from, to)?;
+        let mut records = Vec::with_capacity(heights.len());
+        for height in heights {
+            if let Some(record) = self.read_proof(&mut conn, height)? {
+                records.push(record);
+            }
+        }
+
+        Ok(records)
+    }
+
+    fn get_proofs_page(
+        &self,
+        from: u64,
+        to: u64,
+        limit: usize,
+        offset: usize,
+    ) -> Result<Vec<ProofRecord>> {
+        if limit == 0 {
+            return Ok(Vec::new());
+        }
+        let mut conn = self.conn.lock().unwrap();
+
+        let heights: Vec<u64> = conn.zrangebyscore_limit(
+            self.key("proofs"),
+            from,
+            to,
+            offset.min(isize::MAX as usize) as isize,
+            limit.min(isize::MAX as usize) as isize,
+        )?;
+        let mut records = Vec::with_capacity(heights.len());
+        for height in heights {
+            if let Some(record) = self.read_proof(&mut conn, height)? {
+                records.push(record);
+            }
+        }
+
+        Ok(records)
+    }
+
+    fn get_latest_proofs(&self, limit: usize) -> Result<Vec<ProofRecord>> {
+        if limit == 0 {
+            return Ok(Vec::new());
+        }
+        let mut conn = self.conn.lock().unwrap();
+
+        let heights: Vec<u64> = conn.zrevrange(self.key("proofs"), 0, limit as isize - 1)?;
+        let mut records = Vec::with_capacity(heights.len());
+        for height in heights.into_iter().rev() {
+            if let Some(record) = self.read_proof(&mut conn, height)? {
+                records.push(record);
+            }
+        }
+
+        Ok(records)
+    }
+
+    fn get_proofs_since_seq(&self, since: u64, limit: usize) -> Result<Vec<ProofRecord>> {
+        let mut conn = self.conn.lock().unwrap();
+
+        let heights: Vec<u64> = conn.zrangebyscore_limit(
+            self.key("proofs_by_seq"),
+            format!("({}", since),
+            "+inf",
+            0,
+            limit as isize,
+        )?;
+        let mut records = Vec::with_capacity(heights.len());
+        for height in heights {
+            if let Some(record) = self.read_proof(&mut conn, height)? {
+                records.push(record);
+            }
+        }
+
+        Ok(records)
+    }
+
+    fn delete_proofs_from_height(&self, height: u64) -> Result<usize> {
+        let mut conn = self.conn.lock().unwrap();
+
+        let heights: Vec<u64> = conn.zrangebyscore(self.key("proofs"), height, "+inf")?;
+        remove_proofs(self, &mut conn, &heights)
+    }
+
+    fn prune_proof_history(&self, keep: u64) -> Result<usize> {
+        let mut conn = self.conn.lock().unwrap();
+
+        // Everything ranked below the `keep` highest heights
+        let stop = -(keep.min(i64::MAX as u64) as i64) - 1;
+        let heights: Vec<u64> = conn.zrange(self.key("proofs"), 0, stop as isize)?;
+        remove_proofs(self, &mut conn, &heights)
+    }
+
+    fn get_proof_timestamps(&self) -> Result<Vec<(u64, DateTime<Utc>)>> {
+        let mut conn = self.conn.lock().unwrap();
+
+        let heights: Vec<u64> = conn.zrange(self.key("proofs"), 0, -1)?;
+        let mut pipe = redis::pipe();
+        for &height in &heights {
+            pipe.hget(self.proof_key(height), "timestamp");
+        }
+        let timestamps: Vec<String> = pipe.query(&mut *conn)?;
+
+        heights
+            .into_iter()
+            .zip(timestamps)
+            .map(|(height, raw)| {
+                Ok((
+                    height,
+                    DateTime::parse_from_rfc3339(&raw)?.with_timezone(&Utc),
+                ))
+            })
+            .collect()
+    }
+
+    fn delete_proofs(&self, heights: &[u64]) -> Result<usize> {
+        let mut conn = self.conn.lock().unwrap();
+        remove_proofs(self, &mut conn, heights)
+    }
+
+    fn enqueue_submission(&self, submission: &PendingSubmission) -> Result<()> {
+        let mut conn = self.conn.lock().unwrap();
+
+        // Only the first payload queued for a height is kept
+        let queued: Vec<String> = conn.zrangebyscore(
+            self.key("pending_submissions"),
+            submission.height,
+            submission.height,
+        )?;
+        if queued.is_empty() {
+            let member = serde_json::to_string(submission)?;
+            let _: () = conn.zadd(self.key("pending_submissions"), member, submission.height)?;
+        }
+
+        Ok(())
+    }
+
+    fn get_pending_submissions(&self) -> Result<Vec<PendingSubmission>> {
+        let mut conn = self.conn.lock().unwrap();
+
+        let members: Vec<String> = conn.zrange(self.key("pending_submissions"), 0, -1)?;
+        members
+            .iter()
+            .map(|member| Ok(serde_json::from_str(member)?))
+            .collect()
+    }
+
+    fn remove_pending_submissions_through(&self, height: u64) -> Result<usize> {
+        let mut conn = self.conn.lock().unwrap();
+
+        let removed: usize = conn.zrembyscore(self.key("pending_submissions"), "-inf", height)?;
+
+        Ok(removed)
+    }
+
+    fn record_failed_submission(&self, failed: &FailedSubmission) -> Result<u64> {
+        let mut conn = self.conn.lock().unwrap();
+
+        let id: u64 = Script::new(RECORD_FAILED_SCRIPT)
+            .key(self.key("failed_by_height"))
+            .key(self.key("failed_seq"))
+            .key(self.key("failed"))
+            .arg(failed.height)
+            .arg(&failed.payload)
+            .arg(&failed.error)
+            .arg(failed.attempts)
+            .arg(failed.failed_at.to_rfc3339())
+            .arg(format!("{}:failed:", self.namespace))
+            .invoke(&mut *conn)?;
+
+        Ok(id)
+    }
+
+    fn get_failed_submissions(&self) -> Result<Vec<FailedSubmission>> {
+        let mut conn = self.conn.lock().unwrap();
+
+        let ids: Vec<u64> = conn.zrange(self.key("failed"), 0, -1)?;
+        let mut failed = Vec::with_capacity(ids.len());
+        for id in ids {
+            if let Some(entry) = self.read_failed(&mut conn, id)? {
+                failed.push(entry);
+            }
+        }
+
+        Ok(failed)
+    }
+
+    fn get_failed_submission(&self, id: u64) -> Result<Option<FailedSubmission>> {
+        let mut conn = self.conn.lock().unwrap();
+        self.read_failed(&mut conn, id)
+    }
+
+    fn remove_failed_submission(&self, id: u64) -> Result<bool> {
+        let mut conn = self.conn.lock().unwrap();
+
+        let Some(failed) = self.read_failed(&mut conn, id)? else {
+            return Ok(false);
+        };
+        let (removed,): (usize,) = redis::pipe()
+            .atomic()
+            .del(self.failed_key(id))
+            .zrem(self.key("failed"), id)
+            .ignore()
+            .hdel(self.key("failed_by_height"), failed.height)
+            .ignore()
+            .query(&mut *conn)?;
+
+        Ok(removed > 0)
+    }
+
+    fn insert_alert(&self, alert: &AlertRecord) -> Result<u64> {
+        let mut conn = self.conn.lock().unwrap();
+
+        let id: u64 = conn.incr(self.key("alert_seq"), 1)?;
+        let stored = AlertRecord {
+            id,
+            ..alert.clone()
+        };
+        let _: () = redis::pipe()
+            .atomic()
+            .set(self.alert_key(id), serde_json::to_string(&stored)?)
+            .ignore()
+            .zadd(self.key("alerts"), id, id)
+            .ignore()
+            .query(&mut *conn)?;
+
+        Ok(id)
+    }
+
+    fn update_alert(&self, alert: &AlertRecord) -> Result<bool> {
+        let mut conn = self.conn.lock().unwrap();
+
+        // XX: only overwrite an alert that exists
+        let updated: Option<String> = redis::cmd("SET")
+            .arg(self.alert_key(alert.id))
+            .arg(serde_json::to_string(alert)?)
+            .arg("XX")
+            .query(&mut *conn)?;
+
+        Ok(updated.is_some())
+    }
+
+    fn get_alert(&self, id: u64) -> Result<Option<AlertRecord>> {
+        let mut conn = self.conn.lock().unwrap();
+
+        let stored: Option<String> = conn.get(self.alert_key(id))?;
+        Ok(stored
+            .map(|stored| serde_json::from_str(&stored))
+            .transpose()?)
+    }
+
+    fn get_alerts(&self, limit: usize) -> Result<Vec<AlertRecord>> {
+        if limit == 0 {
+            return Ok(Vec::new());
+        }
+        let mut conn = self.conn.lock().unwrap();
+
+        let ids: Vec<u64> = conn.zrevrange(self.key("alerts"), 0, limit as isize - 1)?;
+        let mut alerts = Vec::with_capacity(ids.len());
+        for id in ids {
+            let stored: Option<String> = conn.get(self.alert_key(id))?;
+            if let Some(stored) = stored {
+                alerts.push(serde_json::from_str(&stored)?);
+            }
+        }
+
+        Ok(alerts)
+    }
+
+    fn insert_relay_attempt(&self, attempt: &RelayAttempt) -> Result<u64> {
+        let mut conn = self.conn.lock().unwrap();
+
+        let id: u64 = conn.incr(self.key("relay_seq"), 1)?;
+        let stored = RelayAttempt {
+            id,
+            ..attempt.clone()
+        };
+        let _: () = redis::pipe()
+            .atomic()
+            .set(self.relay_key(id), serde_json::to_string(&stored)?)
+            .ignore()
+            .zadd(self.key("relays"), id, id)
+            .ignore()
+            .query(&mut *conn)?;
+
+        Ok(id)
+    }
+
+    fn get_relay_attempts(&self, limit: usize, height: Option<u64>) -> Result<Vec<RelayAttempt>> {
+        let mut conn = self.conn.lock().unwrap();
+
+        // Filtering by height walks the ids newest first until enough have matched
+        let ids: Vec<u64> = conn.zrevrange(self.key("relays"), 0, -1)?;
+        let mut attempts = Vec::new();
+        for id in ids {
+            if attempts.len() >= limit {
+                break;
+            }
+            let stored: Option<String> = conn.get(self.relay_key(id))?;
+            if let Some(stored) = stored {
+                let attempt: RelayAttempt = serde_json::from_str(&stored)?;
+                if height.is_none_or(|height| attempt.height == height) {
+                    attempts.push(attempt);
+                }
+            }
+        }
+
+        Ok(attempts)
+    }
+
+    fn record_relay_confirmation(&self, confirmation: &RelayConfirmation) -> Result<()> {
+        let mut conn = self.conn.lock().unwrap();
+
+        let field = format!("{}:{}", confirmation.height, confirmation.endpoint);
+        let _: () = redis::pipe()
+            .atomic()
+            .hset(
+                self.key("confirmations"),
+                &field,
+                serde_json::to_string(confirmation)?,
+            )
+            .ignore()
+            .zadd(
+                self.key("confirmations_by_update"),
+                &field,
+                confirmation.updated_at.timestamp_millis(),
+            )
+            .ignore()
+            .query(&mut *conn)?;
+
+        Ok(())
+    }
+
+    fn get_relay_confirmations(
+        &self,
+        limit: usize,
+        height: Option<u64>,
+    ) -> Result<Vec<RelayConfirmation>> {
+        let mut conn = self.conn.lock().unwrap();
+
+        let fields: Vec<String> = conn.zrevrange(self.key("confirmations_by_update"), 0, -1)?;
+        let mut confirmations = Vec::new();
+        for field in fields {
+            if confirmations.len() >= limit {
+                break;
+            }
+            if height.is_some_and(|height| !field.starts_with(&format!("{}:", height))) {
+                continue;
+            }
+            let stored: Option<String> = conn.hget(self.key("confirmations"), &field)?;
+            if let Some(stored) = stored {
+                confirmations.push(serde_json::from_str(&stored)?);
+            }
+        }
+
+        Ok(confirmations)
+    }
+
+    fn insert_cutover(&self, cutover: &CutoverRecord) -> Result<u64> {
+        let mut conn = self.conn.lock().unwrap();
+
+        // Cutovers are rare enough to keep as one list, newest first
+        let id: u64 = conn.incr(self.key("cutover_seq"), 1)?;
+        let stored = CutoverRecord {
+            id,
+            ..cutover.clone()
+        };
+        let _: () = conn.lpush(self.key("cutovers"), serde_json::to_string(&stored)?)?;
+
+        Ok(id)
+    }
+
+    fn get_cutovers(&self, limit: usize) -> Result<Vec<CutoverRecord>> {
+        if limit == 0 {
+            return Ok(Vec::new());
+        }
+        let mut conn = self.conn.lock().unwrap();
+
+        let stored: Vec<String> = conn.lrange(self.key("cutovers"), 0, limit as isize - 1)?;
+        stored
+            .iter()
+            .map(|stored| Ok(serde_json::from_str(stored)?))
+            .collect()
+    }
+
+    fn record_decode_failure(&self, failure: &DecodeFailure) -> Result<u64> {
+        let mut conn = self.conn.lock().unwrap();
+
+        let id: u64 = conn.incr(self.key("decode_failure_seq"), 1)?;
+        let stored = DecodeFailure {
+            id,
+            ..failure.clone()
+        };
+        let _: () = redis::pipe()
+            .atomic()
+            .lpush(self.key("decode_failures"), serde_json::to_string(&stored)?)
+            .ignore()
+            .ltrim(
+                self.key("decode_failures"),
+                0,
+                DECODE_FAILURES_KEPT as isize - 1,
+            )
+            .ignore()
+            .query(&mut *conn)?;
+
+        Ok(id)
+    }
+
+    fn get_decode_failures(&self, limit: usize) -> Result<Vec<DecodeFailure>> {
+        if limit == 0 {
+            return Ok(Vec::new());
+        }
+        let mut conn = self.conn.lock().unwrap();
+
+        let stored: Vec<String> = conn.lrange(
+            self.key("decode_failures"),
+            0,
+            limit.min(isize::MAX as usize) as isize - 1,
+        )?;
+        stored
+            .iter()
+            .map(|stored| Ok(serde_json::from_str(stored)?))
+            .collect()
+    }
+
+    fn insert_metric_samples(&self, samples: &[MetricSample]) -> Result<()> {
+        let mut conn = self.conn.lock().unwrap();
+
+        let mut pipe = redis::pipe();
+        pipe.atomic();
+        for sample in samples {
+            let at = sample.sampled_at.timestamp_millis();
+            // Members must be unique, so each one carries its own time
+            pipe.zadd(
+                self.metric_key(&sample.metric),
+                format!("{}:{}", at, sample.value),
+                at,
+            )
+            .ignore()
+            .sadd(self.key("metrics"), &sample.metric)
+            .ignore();
+        }
+        let _: () = pipe.query(&mut *conn)?;
+
+        Ok(())
+    }
+
+    fn get_metric_samples(&self, metric: &str, since: DateTime<Utc>) -> Result<Vec<MetricSample>> {
+        let mut conn = self.conn.lock().unwrap();
+
+        let members: Vec<String> =
+            conn.zrangebyscore(self.metric_key(metric), since.timestamp_millis(), "+inf")?;
+        members
+            .iter()
+            .map(|member| {
+                let (at, value) = member
+                    .split_once(':')
+                    .ok_or_else(|| anyhow!("Malformed metric sample {}", member))?;
+                Ok(MetricSample {
+                    metric: metric.to_string(),
+                    value: value.parse()?,
+                    sampled_at: DateTime::from_timestamp_millis(at.parse()?)
+                        .ok_or_else(|| anyhow!("Malformed metric sample {}", member))?,
+                })
+            })
+            .collect()
+    }
+
+    fn prune_metric_samples(&self, before: DateTime<Utc>) -> Result<usize> {
+        let mut conn = self.conn.lock().unwrap();
+
+        let metrics: Vec<String> = conn.smembers(self.key("metrics"))?;
+        let mut removed = 0;
+        for metric in metrics {
+            // Exclusive upper bound
+            let count: usize = conn.zrembyscore(
+                self.metric_key(&metric),
+                "-inf",
+                format!("({}", before.timestamp_millis()),
+            )?;
+            removed += count;
+        }
+
+        Ok(removed)
+    }
+
+    fn backup(&self, _path: &Path) -> Result<()> {
+        Err(anyhow!(
+            "Redis storage can't be backed up by the relayer, use the server's snapshots"
+        ))
+    }
+
+    fn clear_all_tables(&self) -> Result<()> {
+        let mut conn = self.conn.lock().unwrap();
+
+        let _: () = conn.del(&[self.key("health_check"), self.key("previous_proof")])?;
+
+        Ok(())
+    }
+}
+
+fn remove_proofs(storage: &RedisStorage, conn: &mut Connection, heights: &[u64]) -> Result<usize> {
+    if heights.is_empty() {
+        return Ok(0);
+    }
+
+    let proof_keys: Vec<String> = heights
+        .iter()
+        .map(|&height| storage.proof_key(height))
+        .collect();
+    let (removed,): (usize,) = redis::pipe()
+        .atomic()
+        .zrem(storage.key("proofs"), heights)
+        .zrem(storage.key("proofs_by_seq"), heights)
+        .ignore()
+        .del(proof_keys)
+        .ignore()
+        .query(conn)?;
+
+    Ok(removed)
+}
@@ -0,0 +1,124 @@
+use anyhow::{Result, anyhow};
+use std::future::Future;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+use tracing::{info, warn};
+
+use crate::config::{MODE, ReferenceChainConfig};
+use crate::relayer::http_client;
+
+/// The chain a domain's proofs attest to, queried for its head so a proof claiming a
+/// height the chain hasn't reached is caught before it is relayed or recorded
+pub struct ReferenceChain {
+    mode: MODE,
+    rpc_url: String,
+    every_n_cycles: u64,
+    parallel: bool,
+    client: reqwest::Client,
+    cycles: AtomicU64,
+}
+
+impl ReferenceChain {
+    pub fn new(mode: MODE, config: &ReferenceChainConfig) -> Result<Self> {
+        Ok(ReferenceChain {
+            mode,
+            rpc_url: config.rpc_url.trim_end_matches('/').to_string(),
+            every_n_cycles: config.every_n_cycles.max(1),
+            parallel: config.parallel,
+            client: http_client(None, Some(Duration::from_secs(config.timeout_secs)))?,
+            cycles: AtomicU64::new(0),
+        })
+    }
+
+    /// Counts a cycle, returning whether it is one of the every-Nth that check the head
+    fn due(&self) -> bool {
+        self.cycles.fetch_add(1, Ordering::Relaxed) % self.every_n_cycles == 0
+    }
+
+    /// Latest height of the chain: the block height from CometBFT's `/status` in
+    /// Tendermint mode, the head slot from the beacon API in Helios mode
+    pub async fn head(&self) -> Result<u64> {
+        let (path, pointer) = match self.mode {
+            MODE::TENDERMINT => ("/status", "/result/sync_info/latest_block_height"),
+            MODE::HELIOS => ("/eth/v1/beacon/headers/head", "/data/header/message/slot"),
+        };
+        let response: serde_json::Value = self
+            .client
+            .get(format!("{}{}", self.rpc_url, path))
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+        response
+            .pointer(pointer)
+            .and_then(|value| value.as_str())
+            .and_then(|value| value.parse().ok())
+            .ok_or_else(|| anyhow!("{} response has no {}", path, pointer))
+    }
+
+    /// Rejects a proof above the chain head
+    pub fn check(&self, height: u64, head: u64) -> Result<()> {
+        if height > head {
+            return Err(anyhow!(
+                "Proof height {} is ahead of the reference chain head {}",
+                height,
+                head
+            ));
+        }
+        info!(
+            "🧭 Proof height {} is {} behind the reference chain head",
+            height,
+            head - height
+        );
+        Ok(())
+    }
+}
+
+/// Runs `fetch`, plus a query of the chain head when a reference chain is configured and
+/// due this cycle, concurrently unless configured otherwise. A failed head query only
+/// skips the check.
+pub async fn fetch_with_head<T>(
+    reference: Option<&ReferenceChain>,
+    fetch: impl Future<Output = T>,
+) -> (T, Option<u64>) {
+    let Some(reference) = reference.filter(|reference| reference.due()) else {
+        return (fetch.await, None);
+    };
+
+    let (fetched, head) = if reference.parallel {
+        tokio::join!(fetch, reference.head())
+    } else {
+        let fetched = fetch.await;
+        (fetched, reference.head().await)
+    };
+    let head = head
+        .inspect_err(|e| warn!("⚠️  Failed to query the reference chain head: {}", e))
+        .ok();
+    (fetched, head)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_checks_every_nth_cycle_and_rejects_heights_above_head() -> Result<()> {
+        let reference = ReferenceChain::new(
+            MODE::TENDERMINT,
+            &ReferenceChainConfig {
+                rpc_url: "http://localhost:26657/".to_string(),
+                every_n_cycles: 3,
+                ..Default::default()
+            },
+        )?;
+
+        let due: Vec<bool> = (0..6).map(|_| reference.due()).collect();
+        assert_eq!(due, vec![true, false, false, true, false, false]);
+
+        assert!(reference.check(100, 100).is_ok());
+        assert!(reference.check(90, 100).is_ok());
+        assert!(reference.check(101, 100).is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,266 @@
+use reqwest::StatusCode;
+use std::fmt;
+use std::time::Duration;
+
+/// A registry's answer to one submission
+#[derive(Debug, Clone)]
+pub struct RegistryResponse {
+    pub status: StatusCode,
+    pub body: String,
+    /// From a `Retry-After` header given in seconds
+    pub retry_after: Option<Duration>,
+}
+
+/// What a registry response means for the submission
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ResponseClass {
+    Accepted,
+    /// The registry already has this proof, so there is nothing left to do
+    Duplicate,
+    /// The registry expects proofs for a different VK; retrying can't help
+    InvalidVk,
+    /// Retried after `Retry-After`, or the policy's backoff without one
+    RateLimited,
+    /// Retried with backoff
+    ServerError,
+    /// Any other refusal; not retried
+    Rejected,
+}
+
+impl ResponseClass {
+    /// Classifies by status first, then by the error text of the body (a JSON `error`,
+    /// `message` or `code` field, or the plain body). A 2xx with an `error` field or
+    /// `"success": false` is a refusal too.
+    pub fn of(response: &RegistryResponse) -> Self {
+        let json: Option<serde_json::Value> = serde_json::from_str(&response.body).ok();
+        let error_text = json.as_ref().and_then(|json| {
+            let fields: Vec<&str> = ["error", "message", "code"]
+                .iter()
+                .filter_map(|field| json.get(*field).and_then(|value| value.as_str()))
+                .collect();
+            (!fields.is_empty()).then(|| fields.join(" "))
+        });
+
+        let status = response.status;
+        if status.is_success() {
+            let refused = json.as_ref().is_some_and(|json| {
+                json.get("error").is_some_and(|error| !error.is_null())
+                    || json.get("success") == Some(&serde_json::Value::Bool(false))
+            });
+            if !refused {
+                return ResponseClass::Accepted;
+            }
+        }
+
+        let text = error_text
+            .unwrap_or_else(|| response.body.clone())
+            .to_ascii_lowercase();
+        let mentions = |needles: &[&str]| needles.iter().any(|needle| text.contains(needle));
+
+        if status == StatusCode::CONFLICT
+            || mentions(&[
+                "duplicate",
+                "already exists",
+                "already submitted",
+                "already have",
+            ])
+        {
+            ResponseClass::Duplicate
+        } else if status == StatusCode::TOO_MANY_REQUESTS || mentions(&["rate limit"]) {
+            ResponseClass::RateLimited
+        } else if status.is_server_error() {
+            ResponseClass::ServerError
+        } else if mentions(&[
+            "invalid vk",
+            "vk mismatch",
+            "verification key",
+            "vkey",
+            "unknown vk",
+        ]) {
+            ResponseClass::InvalidVk
+        } else {
+            ResponseClass::Rejected
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ResponseClass::Accepted => "accepted",
+            ResponseClass::Duplicate => "duplicate",
+            ResponseClass::InvalidVk => "invalid_vk",
+            ResponseClass::RateLimited => "rate_limited",
+            ResponseClass::ServerError => "server_error",
+            ResponseClass::Rejected => "rejected",
+        }
+    }
+
+    pub fn is_retryable(&self) -> bool {
+        matches!(
+            self,
+            ResponseClass::RateLimited | ResponseClass::ServerError
+        )
+    }
+}
+
+/// A submission the registry refused, kept as a typed error so the relay loop can act on
+/// its class
+#[derive(Debug)]
+pub struct RegistryRejection {
+    pub class: ResponseClass,
+    pub status: StatusCode,
+    pub body: String,
+}
+
+impl fmt::Display for RegistryRejection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Registry responded with status {} ({}): {}",
+            self.status,
+            self.class.as_str(),
+            self.body
+        )
+    }
+}
+
+impl std::error::Error for RegistryRejection {}
+
+/// What a registry's status endpoint reports for a submission
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SubmissionStatus {
+    Confirmed,
+    /// Not processed yet, or the status couldn't be read; asked again later
+    Pending,
+    /// The registry failed the submission, or confirmed a different root
+    Failed(String),
+}
+
+impl SubmissionStatus {
+    /// Reads a `{"status": ..., "root": ...}` answer. A 404 or an error status counts as
+    /// pending; a confirmation naming a root other than `root` (hex) counts as failed.
+    pub fn of(status: StatusCode, body: &str, root: &str) -> Self {
+        if !status.is_success() {
+            return SubmissionStatus::Pending;
+        }
+        let Ok(json) = serde_json::from_str::<serde_json::Value>(body) else {
+            return SubmissionStatus::Pending;
+        };
+        let text = |field: &str| json.get(field).and_then(|value| value.as_str());
+
+        match text("status").map(str::to_ascii_lowercase).as_deref() {
+            Some("confirmed" | "accepted" | "finalized" | "verified") => match text("root") {
+                Some(confirmed)
+                    if !confirmed
+                        .trim_start_matches("0x")
+                        .eq_ignore_ascii_case(root.trim_start_matches("0x")) =>
+                {
+                    SubmissionStatus::Failed(format!("confirmed a different root {}", confirmed))
+                }
+                _ => SubmissionStatus::Confirmed,
+            },
+            Some("failed" | "rejected" | "invalid") => SubmissionStatus::Failed(
+                ["error", "message", "reason"]
+                    .iter()
+                    .find_map(|field| text(field))
+                    .unwrap_or("failed")
+                    .to_string(),
+            ),
+            _ => SubmissionStatus::Pending,
+        }
+    }
+}
+
+/// Reads `Retry-After` when it is a number of seconds; HTTP dates are left to the backoff
+pub fn retry_after(headers: &reqwest::header::HeaderMap) -> Option<Duration> {
+    headers
+        .get(reqwest::header::RETRY_AFTER)?
+        .to_str()
+        .ok()?
+        .trim()
+        .parse()
+        .ok()
+        .map(Duration::from_secs)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn class(status: u16, body: &str) -> ResponseClass {
+        ResponseClass::of(&RegistryResponse {
+            status: StatusCode::from_u16(status).unwrap(),
+            body: body.to_string(),
+            retry_after: None,
+        })
+    }
+
+    #[test]
+    fn test_response_classes() {
+        assert_eq!(class(200, "ok"), ResponseClass::Accepted);
+        assert_eq!(class(201, r#"{"id": 4}"#), ResponseClass::Accepted);
+        assert_eq!(class(200, r#"{"error": null}"#), ResponseClass::Accepted);
+        assert_eq!(
+            class(
+                200,
+                r#"{"success": false, "message": "proof already exists"}"#
+            ),
+            ResponseClass::Duplicate
+        );
+        assert_eq!(class(409, ""), ResponseClass::Duplicate);
+        assert_eq!(
+            class(400, r#"{"error": "Duplicate proof for height 12"}"#),
+            ResponseClass::Duplicate
+        );
+        assert_eq!(class(429, "slow down"), ResponseClass::RateLimited);
+        assert_eq!(class(503, "unavailable"), ResponseClass::ServerError);
+        assert_eq!(
+            class(400, r#"{"error": "Invalid vk for domain"}"#),
+            ResponseClass::InvalidVk
+        );
+        assert_eq!(class(400, "bad payload"), ResponseClass::Rejected);
+        assert_eq!(
+            class(200, r#"{"error": "bad payload"}"#),
+            ResponseClass::Rejected
+        );
+
+        assert!(ResponseClass::ServerError.is_retryable());
+        assert!(!ResponseClass::InvalidVk.is_retryable());
+    }
+
+    #[test]
+    fn test_submission_status() {
+        let root = "ab".repeat(32);
+        let status = |code: u16, body: &str| {
+            SubmissionStatus::of(StatusCode::from_u16(code).unwrap(), body, &root)
+        };
+
+        assert_eq!(status(404, ""), SubmissionStatus::Pending);
+        assert_eq!(status(503, "down"), SubmissionStatus::Pending);
+        assert_eq!(
+            status(200, r#"{"status": "processing"}"#),
+            SubmissionStatus::Pending
+        );
+        assert_eq!(
+            status(200, r#"{"status": "Confirmed"}"#),
+            SubmissionStatus::Confirmed
+        );
+        assert_eq!(
+            status(
+                200,
+                &format!(r#"{{"status": "confirmed", "root": "0x{}"}}"#, root)
+            ),
+            SubmissionStatus::Confirmed
+        );
+        assert!(matches!(
+            status(200, r#"{"status": "confirmed", "root": "0x01"}"#),
+            SubmissionStatus::Failed(_)
+        ));
+        assert_eq!(
+            status(
+                200,
+                r#"{"status": "failed", "reason": "proof did not verify"}"#
+            ),
+            SubmissionStatus::Failed("proof did not verify".to_string())
+        );
+    }
+}
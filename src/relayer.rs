@@ -1,65 +1,2260 @@
 #[allow(unused)]
 use {
-    crate::config::{LIGHT_CLIENT_PROVER_ENDPOINT, LIGHT_CLIENT_VK, REGISTRY_ENDPOINT},
+    crate::alerting::DomainAlerts,
+    crate::config::{EndpointHttp, MODE, RetryPolicy, Socks5Proxy},
+    crate::crosscheck::RootDivergence,
+    crate::db::{
+        CutoverRecord, DecodeFailure, FailedSubmission, PendingSubmission, PreviousProof,
+        ProofRecord, RelayAttempt, Storage,
+    },
+    crate::domain::Domain,
+    crate::envelope::EnvelopeCheck,
+    crate::errors::{ErrorTracker, PollOutcome, RelayerError, Subsystem},
+    crate::metrics::{Latency, LatencyObserver},
+    crate::proof_id::{HashAlgorithm, ProofId},
+    crate::prover::{ProverPool, QuorumNotReached},
+    crate::source::NoNewProof,
+    crate::verifier::verify_wrapper_proof,
+    crate::vk::VkMonitor,
+    crate::watchdog::CycleWatchdog,
+    crate::writer::DbWriter,
+    borsh::BorshDeserialize,
+    helios_recursion_types::WrapperCircuitOutputs as HeliosWrapperCircuitOutputs,
     hex,
     serde_json::json,
     sp1_sdk::SP1ProofWithPublicValues,
-    tracing::{debug, info},
+    std::collections::BTreeMap,
+    tendermint_recursion_types::WrapperCircuitOutputs as TendermintWrapperCircuitOutputs,
+    tracing::{debug, error, info, warn},
+};
+#[cfg(all(feature = "relayer", not(feature = "health-check")))]
+use {
+    crate::alerting::{Alerter, spawn_alerts},
+    crate::api::{EventStreams, ProofEvent, StreamEvent},
+    crate::backfill,
+    crate::codec::{compress, encode},
+    crate::config::{BackfillConfig, Config, DomainConfig, PayloadCodec, PayloadWire},
+    crate::domain::{DomainLifecycle, DomainSet, RegistryTarget, validate_name},
+    crate::gaps::HeightGap,
+    crate::journal::{JournalEntry, SubmissionJournal},
+    crate::metrics::Metrics,
+    crate::polling::PollLoop,
+    crate::reference::fetch_with_head,
+    crate::registry::{RegistryRejection, RegistryResponse, ResponseClass, retry_after},
+    crate::rollback::Verdict,
+    crate::sink::{ProofSink, Sinks, primary_sink},
+    crate::watchdog::spawn_watchdog,
+    futures::StreamExt,
+    std::collections::{HashMap, HashSet},
+    std::sync::atomic::{AtomicU64, Ordering},
+    std::sync::{Arc, Mutex, OnceLock},
+    tokio::sync::{mpsc, oneshot},
+    tracing::{Instrument, info_span},
 };
 
-pub async fn get_proof() -> Result<SP1ProofWithPublicValues, anyhow::Error> {
-    info!("🔍 Fetching proof from {}", LIGHT_CLIENT_PROVER_ENDPOINT);
+/// Fetches the latest proof, trying each configured prover endpoint until one succeeds.
+/// A prover that answers it has no proof yet ends the search with a `NoNewProof` error.
+/// With a quorum configured, every endpoint is asked instead, see `get_quorum_proof`.
+pub async fn get_proof(provers: &ProverPool) -> Result<SP1ProofWithPublicValues, anyhow::Error> {
+    if let Some((mode, quorum)) = provers.quorum() {
+        return get_quorum_proof(provers, mode, quorum).await;
+    }
+    let mut last_error = None;
 
-    let client = reqwest::Client::builder()
-        .timeout(std::time::Duration::from_secs(10))
-        .build()?;
+    for index in provers.order() {
+        let endpoint = provers.endpoint(index);
+        let started = std::time::Instant::now();
 
-    let response = client.get(LIGHT_CLIENT_PROVER_ENDPOINT).send().await?;
+        match provers.source(index).fetch().await {
+            Ok(proof) => {
+                provers.record_success(index, started.elapsed());
+                return Ok(proof);
+            }
+            Err(e) if e.is::<NoNewProof>() => {
+                provers.record_success(index, started.elapsed());
+                return Err(e);
+            }
+            Err(e) => {
+                warn!("⚠️  Prover {} failed: {}", endpoint, e);
+                provers.record_failure(index, &e);
+                last_error = Some(e);
+            }
+        }
+    }
 
-    info!("📡 Received response with status: {}", response.status());
+    Err(RelayerError::prover_unreachable(provers.endpoint_count(), last_error.as_ref()).into())
+}
 
-    if !response.status().is_success() {
-        return Err(anyhow::anyhow!(
-            "HTTP request failed with status: {}",
-            response.status()
-        ));
+/// Fetches the latest proof from every prover endpoint at once and returns one that at
+/// least `quorum` of them agree on by height and root, the highest such height if more
+/// than one qualifies, so a single compromised or buggy prover can't get a proof
+/// relayed. Fails with `QuorumNotReached` otherwise, or with `NoNewProof` when no
+/// prover had a proof and one said it has none yet.
+pub async fn get_quorum_proof(
+    provers: &ProverPool,
+    mode: MODE,
+    quorum: usize,
+) -> Result<SP1ProofWithPublicValues, anyhow::Error> {
+    let fetches = provers.order().into_iter().map(|index| async move {
+        let started = std::time::Instant::now();
+        let fetched = provers.source(index).fetch().await;
+        (index, started.elapsed(), fetched)
+    });
+    let fetched = futures::future::join_all(fetches).await;
+
+    // Served proofs by (height, root), each with the endpoints that served it in order
+    let mut votes: BTreeMap<_, Vec<(usize, SP1ProofWithPublicValues)>> = BTreeMap::new();
+    let mut no_new_proof = None;
+    for (index, elapsed, result) in fetched {
+        let endpoint = provers.endpoint(index);
+        match result {
+            Ok(proof) => match decode_public_values(mode, proof.public_values.as_slice()) {
+                Ok(outputs) => {
+                    provers.record_success(index, elapsed);
+                    votes.entry(outputs).or_default().push((index, proof));
+                }
+                Err(e) => {
+                    warn!("⚠️  Prover {} served undecodable outputs: {}", endpoint, e);
+                    provers.record_failure(index, &e);
+                }
+            },
+            Err(e) if e.is::<NoNewProof>() => {
+                provers.record_success(index, elapsed);
+                no_new_proof.get_or_insert(e);
+            }
+            Err(e) => {
+                warn!("⚠️  Prover {} failed: {}", endpoint, e);
+                provers.record_failure(index, &e);
+            }
+        }
+    }
+
+    let agreed = votes
+        .iter()
+        .rev()
+        .find(|(_, voters)| voters.len() >= quorum)
+        .map(|(outputs, _)| *outputs);
+    if let Some(outputs) = agreed
+        && let Some(mut voters) = votes.remove(&outputs)
+    {
+        info!(
+            "🤝 {} of {} provers agree on height {}",
+            voters.len(),
+            provers.endpoint_count(),
+            outputs.0
+        );
+        let (index, proof) = voters.remove(0);
+        provers.set_active(index);
+        return Ok(proof);
+    }
+    if votes.is_empty()
+        && let Some(e) = no_new_proof
+    {
+        return Err(e);
+    }
+    Err(QuorumNotReached {
+        quorum,
+        endpoints: provers.endpoint_count(),
+        votes: votes
+            .iter()
+            .map(|((height, root), voters)| (*height, hex::encode(root), voters.len()))
+            .collect(),
+    }
+    .into())
+}
+
+/// Fetches the proof at one height, trying each configured prover endpoint until one
+/// answers. `None` if the first to answer has no proof at that height.
+pub async fn get_proof_at(
+    provers: &ProverPool,
+    height: u64,
+) -> Result<Option<SP1ProofWithPublicValues>, anyhow::Error> {
+    let mut last_error = None;
+
+    for index in provers.order() {
+        let endpoint = provers.endpoint(index);
+        let started = std::time::Instant::now();
+
+        match provers.source(index).fetch_at(height).await {
+            Ok(proof) => {
+                provers.record_success(index, started.elapsed());
+                return Ok(proof);
+            }
+            Err(e) => {
+                warn!(
+                    "⚠️  Prover {} failed to serve height {}: {}",
+                    endpoint, height, e
+                );
+                provers.record_failure(index, &e);
+                last_error = Some(e);
+            }
+        }
     }
 
-    let hex_str = response.text().await?;
-    info!("📦 Received hex string of length: {}", hex_str.len());
+    Err(anyhow::anyhow!(
+        "All {} prover endpoints failed for height {}, last error: {}",
+        provers.endpoint_count(),
+        height,
+        last_error.map_or_else(|| "no endpoints configured".to_string(), |e| e.to_string())
+    ))
+}
+
+/// Sent with every outbound request
+const USER_AGENT: &str = concat!("helios-proof-relayer/", env!("CARGO_PKG_VERSION"));
+
+/// Idle connections are kept for this long before they are closed
+const POOL_IDLE_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(90);
+
+/// What makes two clients differ
+#[derive(PartialEq, Eq, Hash)]
+struct ClientKey {
+    proxy_url: Option<String>,
+    connect_timeout: Option<std::time::Duration>,
+    timeout: Option<std::time::Duration>,
+    headers: Vec<(String, String)>,
+}
+
+static HTTP_CLIENTS: std::sync::OnceLock<
+    std::sync::Mutex<std::collections::HashMap<ClientKey, reqwest::Client>>,
+> = std::sync::OnceLock::new();
 
-    let bytes = hex::decode(hex_str)?;
-    let state_proof: SP1ProofWithPublicValues = serde_json::from_slice(&bytes)?;
+/// The HTTP client for the given SOCKS5 proxy (if any) and timeout. It is built on first
+/// use and shared from then on, so every fetch and submission through it reuses pooled
+/// connections and TLS sessions instead of opening new ones.
+pub fn http_client(
+    proxy: Option<&Socks5Proxy>,
+    timeout: Option<std::time::Duration>,
+) -> Result<reqwest::Client, anyhow::Error> {
+    shared_client(ClientKey {
+        proxy_url: proxy.map(proxy_url).transpose()?,
+        connect_timeout: None,
+        timeout,
+        headers: Vec::new(),
+    })
+}
 
-    info!("✅ Successfully parsed proof");
-    Ok(state_proof)
+/// The shared HTTP client for an endpoint, with its configured timeouts and headers. The
+/// read timeout isn't part of the client; `await_response` and `read_body` apply it.
+pub fn endpoint_client(http: &EndpointHttp) -> Result<reqwest::Client, anyhow::Error> {
+    shared_client(ClientKey {
+        proxy_url: http.proxy.as_ref().map(proxy_url).transpose()?,
+        connect_timeout: Some(http.timeouts.connect_timeout()),
+        timeout: http.timeouts.deadline(),
+        headers: http.headers.clone(),
+    })
+}
+
+fn shared_client(key: ClientKey) -> Result<reqwest::Client, anyhow::Error> {
+    let mut clients = HTTP_CLIENTS.get_or_init(Default::default).lock().unwrap();
+    if let Some(client) = clients.get(&key) {
+        return Ok(client.clone());
+    }
+
+    let mut headers = reqwest::header::HeaderMap::new();
+    for (name, value) in &key.headers {
+        let mut value = reqwest::header::HeaderValue::from_str(value)
+            .map_err(|_| anyhow::anyhow!("Invalid value for header {}", name))?;
+        // Keeps tokens out of debug output
+        value.set_sensitive(true);
+        headers.insert(
+            reqwest::header::HeaderName::from_bytes(name.as_bytes())?,
+            value,
+        );
+    }
+    // Without a SOCKS5 proxy, reqwest honours HTTP_PROXY, HTTPS_PROXY and NO_PROXY
+    let mut builder = reqwest::Client::builder()
+        .user_agent(USER_AGENT)
+        .default_headers(headers)
+        .pool_idle_timeout(POOL_IDLE_TIMEOUT)
+        .tcp_keepalive(POOL_IDLE_TIMEOUT);
+    if let Some(connect_timeout) = key.connect_timeout {
+        builder = builder.connect_timeout(connect_timeout);
+    }
+    if let Some(timeout) = key.timeout {
+        builder = builder.timeout(timeout);
+    }
+    if let Some(url) = &key.proxy_url {
+        builder = builder.proxy(reqwest::Proxy::all(url.as_str())?);
+    }
+
+    let client = builder.build()?;
+    clients.insert(key, client.clone());
+    Ok(client)
+}
+
+/// Sends a request, failing when the response headers don't arrive within `read_timeout`
+pub async fn await_response(
+    request: reqwest::RequestBuilder,
+    read_timeout: std::time::Duration,
+) -> Result<reqwest::Response, anyhow::Error> {
+    match tokio::time::timeout(read_timeout, request.send()).await {
+        Ok(response) => Ok(response?),
+        Err(elapsed) => {
+            Err(anyhow::Error::new(elapsed)
+                .context(format!("No response within {:?}", read_timeout)))
+        }
+    }
+}
+
+/// Reads a response body, failing once no data has arrived for `read_timeout`. There is
+/// no limit on the whole body, which an endpoint's deadline provides if configured.
+pub async fn read_body(
+    mut response: reqwest::Response,
+    read_timeout: std::time::Duration,
+) -> Result<Vec<u8>, anyhow::Error> {
+    let mut body = Vec::new();
+    loop {
+        match tokio::time::timeout(read_timeout, response.chunk()).await {
+            Ok(Ok(Some(chunk))) => body.extend_from_slice(&chunk),
+            Ok(Ok(None)) => return Ok(body),
+            Ok(Err(e)) => return Err(e.into()),
+            Err(elapsed) => {
+                return Err(anyhow::Error::new(elapsed).context(format!(
+                    "Response stalled for {:?} after {} bytes",
+                    read_timeout,
+                    body.len()
+                )));
+            }
+        }
+    }
+}
+
+/// `socks5h://` when the proxy resolves names, with the credentials in the URL
+fn proxy_url(proxy: &Socks5Proxy) -> Result<String, anyhow::Error> {
+    let scheme = if proxy.remote_dns {
+        "socks5h"
+    } else {
+        "socks5"
+    };
+    let mut url = reqwest::Url::parse(&format!("{}://{}", scheme, proxy.address))
+        .map_err(|e| anyhow::anyhow!("Invalid SOCKS5 proxy address {}: {}", proxy.address, e))?;
+    if let Some(username) = &proxy.username {
+        url.set_username(username)
+            .map_err(|_| anyhow::anyhow!("Invalid SOCKS5 proxy username"))?;
+        url.set_password(proxy.password.as_deref())
+            .map_err(|_| anyhow::anyhow!("Invalid SOCKS5 proxy password"))?;
+    }
+    Ok(url.to_string())
+}
+
+/// Decodes the wrapper circuit outputs for the given mode into (height, root)
+pub fn decode_public_values(
+    mode: MODE,
+    public_values: &[u8],
+) -> Result<(u64, [u8; 32]), anyhow::Error> {
+    Ok(decode_wrapper_outputs(mode, public_values)
+        .map_err(|e| RelayerError::decode("public values", e))?)
+}
+
+fn decode_wrapper_outputs(
+    mode: MODE,
+    public_values: &[u8],
+) -> Result<(u64, [u8; 32]), anyhow::Error> {
+    match mode {
+        MODE::HELIOS => {
+            let public_outputs: HeliosWrapperCircuitOutputs = borsh::from_slice(public_values)?;
+            Ok((public_outputs.height, public_outputs.root))
+        }
+        MODE::TENDERMINT => {
+            // Read as a prefix: extended outputs carry the chain id after the standard fields
+            let mut outputs = public_values;
+            let public_outputs = TendermintWrapperCircuitOutputs::deserialize(&mut outputs)?;
+            Ok((public_outputs.height, public_outputs.root))
+        }
+    }
+}
+
+/// Reads the chain id appended (as a borsh string) after the standard Tendermint wrapper
+/// outputs, or `None` for circuits that don't commit to one
+pub fn decode_tendermint_chain_id(public_values: &[u8]) -> Result<Option<String>, anyhow::Error> {
+    let mut rest = public_values;
+    TendermintWrapperCircuitOutputs::deserialize(&mut rest)
+        .map_err(|e| RelayerError::decode("public values", e))?;
+    if rest.is_empty() {
+        return Ok(None);
+    }
+    let chain_id =
+        borsh::from_slice::<String>(rest).map_err(|e| RelayerError::decode("chain id", e))?;
+    Ok(Some(chain_id))
+}
+
+/// Rejects a Tendermint proof whose outputs name a chain other than the expected one.
+/// Proofs without a chain id, and Helios proofs, always pass.
+pub fn check_chain_id(
+    mode: MODE,
+    expected: Option<&str>,
+    public_values: &[u8],
+) -> Result<(), anyhow::Error> {
+    let (MODE::TENDERMINT, Some(expected)) = (mode, expected) else {
+        return Ok(());
+    };
+
+    match decode_tendermint_chain_id(public_values)? {
+        Some(chain_id) if chain_id == expected => Ok(()),
+        Some(chain_id) => Err(anyhow::anyhow!(
+            "Proof is for chain {}, expected {}",
+            chain_id,
+            expected
+        )),
+        None => {
+            debug!("Proof outputs carry no chain id, skipping chain id check");
+            Ok(())
+        }
+    }
 }
 
 #[cfg(all(feature = "relayer", not(feature = "health-check")))]
-pub async fn create_payload() -> Result<serde_json::Value, anyhow::Error> {
-    let wrapper_proof = get_proof().await?;
-    let wrapper_proof_encoded = hex::encode(wrapper_proof.bytes());
-    let wrapper_proof_public_values_encoded = hex::encode(wrapper_proof.public_values.to_vec());
+pub async fn create_payload(
+    provers: &ProverPool,
+    vk_monitor: &VkMonitor,
+    errors: &ErrorTracker,
+    envelope: Option<&EnvelopeCheck>,
+    proof_ids: HashAlgorithm,
+    latency: &LatencyObserver,
+) -> Result<serde_json::Value, anyhow::Error> {
+    let started = std::time::Instant::now();
+    let wrapper_proof = get_proof(provers).await.inspect_err(|e| {
+        if !e.is::<NoNewProof>() {
+            errors.record(Subsystem::Prover, e)
+        }
+    })?;
+    latency.observe(Latency::Prover, started.elapsed());
+    verified_payload(
+        wrapper_proof,
+        vk_monitor,
+        errors,
+        envelope,
+        proof_ids,
+        latency,
+    )
+    .await
+}
 
-    let payload = json!({
-        "proof": wrapper_proof_encoded,
-        "public_values": wrapper_proof_public_values_encoded,
-        "vk": LIGHT_CLIENT_VK,
-    });
+/// Checks a fetched proof's envelope and verifies it locally, returning its registry payload
+#[cfg(all(feature = "relayer", not(feature = "health-check")))]
+pub async fn verified_payload(
+    wrapper_proof: SP1ProofWithPublicValues,
+    vk_monitor: &VkMonitor,
+    errors: &ErrorTracker,
+    envelope: Option<&EnvelopeCheck>,
+    proof_ids: HashAlgorithm,
+    latency: &LatencyObserver,
+) -> Result<serde_json::Value, anyhow::Error> {
+    let vk = vk_monitor.configured();
+    if let Some(envelope) = envelope
+        && let Err(e) = envelope.check(&wrapper_proof)
+    {
+        error!("🚨 Prover returned a malformed proof: {}", e);
+        errors.record(Subsystem::Verifier, &e);
+        return Err(e);
+    }
+    vk_monitor.observe(&wrapper_proof, None);
+    let proof_bytes = wrapper_proof.bytes();
+    let public_values = wrapper_proof.public_values.to_vec();
+    let proof_id = ProofId::of(proof_ids, &proof_bytes);
+
+    // Never forward a proof the registry would have to reject
+    let started = std::time::Instant::now();
+    let verification = {
+        let proof_bytes = proof_bytes.clone();
+        let public_values = public_values.clone();
+        let vk = vk.to_string();
+        tokio::task::spawn_blocking(move || verify_wrapper_proof(&proof_bytes, &public_values, &vk))
+            .await?
+    };
+    latency.observe(Latency::Verification, started.elapsed());
+    match verification {
+        Ok(proof_system) => info!(
+            "✅ Proof {} verified locally ({})",
+            proof_id.short(),
+            proof_system.as_str()
+        ),
+        Err(e) => {
+            error!(
+                "🚨 Prover returned a proof that fails verification ({}): {}",
+                proof_id, e
+            );
+            errors.record(Subsystem::Verifier, &e);
+            return Err(e);
+        }
+    }
+
+    Ok(build_payload(&proof_bytes, &public_values, vk, &proof_id))
+}
 
-    Ok(payload)
+/// The registry payload for a verified proof
+#[cfg(all(feature = "relayer", not(feature = "health-check")))]
+pub fn build_payload(
+    proof: &[u8],
+    public_values: &[u8],
+    vk: &str,
+    proof_id: &ProofId,
+) -> serde_json::Value {
+    json!({
+        "proof": hex::encode(proof),
+        "public_values": hex::encode(public_values),
+        "vk": vk,
+        "proof_id": proof_id,
+    })
+}
+
+/// Adds the proof's decoded outputs and where it was relayed from under `metadata`, when
+/// the domain enables `payload_metadata`. Provers don't report when they generated a
+/// proof, so `proof_timestamp` is when this relayer first fetched it.
+#[cfg(all(feature = "relayer", not(feature = "health-check")))]
+pub fn with_metadata(
+    mut payload: serde_json::Value,
+    config: &DomainConfig,
+    height: u64,
+    root: &[u8],
+    proof_timestamp: chrono::DateTime<chrono::Utc>,
+) -> serde_json::Value {
+    let Some(metadata) = &config.payload_metadata else {
+        return payload;
+    };
+    let instance_id = metadata
+        .instance_id
+        .clone()
+        .or_else(|| std::env::var("HOSTNAME").ok());
+    payload["metadata"] = json!({
+        "height": height,
+        "root": hex::encode(root),
+        "proof_timestamp": proof_timestamp.to_rfc3339(),
+        "relayer_instance_id": instance_id,
+        "relayer_version": env!("CARGO_PKG_VERSION"),
+    });
+    payload
 }
 
 #[cfg(all(feature = "relayer", not(feature = "health-check")))]
-pub async fn send(payload: &serde_json::Value) -> Result<(), anyhow::Error> {
+pub async fn send(
+    payload: &serde_json::Value,
+    height: u64,
+    registry_endpoint: &str,
+    http: &EndpointHttp,
+    wire: &PayloadWire,
+    retry: &RetryPolicy,
+    journal: Option<(&SubmissionJournal, &str)>,
+    attempts: Option<&DbWriter>,
+) -> Result<(), anyhow::Error> {
     debug!("Payload: {:?}", payload);
 
-    let client = reqwest::Client::new();
-    let response = client.post(REGISTRY_ENDPOINT).json(payload).send().await?;
+    let client = endpoint_client(http)?;
+    let payload_hash =
+        (journal.is_some() || attempts.is_some()).then(|| SubmissionJournal::payload_hash(payload));
+    let mut attempt = 1;
+
+    loop {
+        let started_at = chrono::Utc::now();
+        let journal_entry = |outcome, error: Option<String>, finished_at| {
+            if let Some((journal, domain)) = journal {
+                let entry = JournalEntry {
+                    domain: domain.to_string(),
+                    payload_hash: payload_hash.clone().unwrap_or_default(),
+                    target: registry_endpoint.to_string(),
+                    attempt,
+                    outcome,
+                    error,
+                    started_at,
+                    finished_at,
+                };
+                if let Err(e) = journal.record(&entry) {
+                    error!("❌ Failed to write submission journal: {}", e);
+                }
+            }
+        };
+
+        journal_entry("started", None, None);
+        let started = std::time::Instant::now();
+        let response = post_payload(&client, registry_endpoint, payload, wire).await;
+        if let Some(attempts) = attempts {
+            let (status, body) = match &response {
+                Ok(response) => (Some(response.status.as_u16()), response.body.clone()),
+                Err(e) => (None, e.to_string()),
+            };
+            let record = RelayAttempt {
+                id: 0,
+                height,
+                endpoint: registry_endpoint.to_string(),
+                attempt,
+                payload_hash: payload_hash.clone().unwrap_or_default(),
+                status,
+                response: body,
+                latency_ms: started.elapsed().as_millis() as u64,
+                attempted_at: started_at,
+            };
+            attempts.submit("record relay attempt", move |db| {
+                db.insert_relay_attempt(&record).map(|_| ())
+            });
+        }
+        // Transport errors are retried like server errors
+        let (class, retry_after, result) = match response {
+            Ok(response) => {
+                let class = ResponseClass::of(&response);
+                let result = match class {
+                    ResponseClass::Accepted => Ok(()),
+                    ResponseClass::Duplicate => {
+                        info!(
+                            "♻️  Registry already has the proof at height {}, skipping",
+                            height
+                        );
+                        Ok(())
+                    }
+                    _ => Err(RelayerError::from(RegistryRejection {
+                        class,
+                        status: response.status,
+                        body: response.body,
+                    })
+                    .into()),
+                };
+                (class, response.retry_after, result)
+            }
+            Err(e) => (ResponseClass::ServerError, None, Err(e)),
+        };
+        match &result {
+            Ok(()) => journal_entry(
+                if class == ResponseClass::Duplicate {
+                    "duplicate"
+                } else {
+                    "success"
+                },
+                None,
+                Some(chrono::Utc::now()),
+            ),
+            Err(e) => journal_entry("failure", Some(e.to_string()), Some(chrono::Utc::now())),
+        }
+
+        match result {
+            Ok(()) => return Ok(()),
+            // Retrying a refusal can't change the answer
+            Err(e) if !class.is_retryable() => {
+                warn!(
+                    "⚠️  Registry refused the proof at height {} ({}), not retrying",
+                    height,
+                    class.as_str()
+                );
+                return Err(e);
+            }
+            Err(e) if attempt < retry.max_attempts => {
+                let delay = retry_after.unwrap_or_else(|| retry.delay_for(attempt));
+                warn!(
+                    "⚠️  Registry submission attempt {}/{} failed: {}, retrying in {:?}",
+                    attempt, retry.max_attempts, e, delay
+                );
+                tokio::time::sleep(delay).await;
+                attempt += 1;
+            }
+            // Keep the error itself so its class survives the retries
+            Err(e) => {
+                warn!("⚠️  Registry submission failed after {} attempts", attempt);
+                return Err(e);
+            }
+        }
+    }
+}
+
+/// Registries that refused a zstd body; they are sent uncompressed payloads until they
+/// advertise zstd in an `Accept-Encoding` response header
+#[cfg(all(feature = "relayer", not(feature = "health-check")))]
+static ZSTD_REFUSED: OnceLock<Mutex<HashSet<String>>> = OnceLock::new();
+
+/// Whether the registry may be sent zstd bodies, as far as its responses tell
+#[cfg(all(feature = "relayer", not(feature = "health-check")))]
+fn zstd_accepted(registry_endpoint: &str) -> bool {
+    !ZSTD_REFUSED
+        .get_or_init(Default::default)
+        .lock()
+        .unwrap()
+        .contains(registry_endpoint)
+}
+
+/// Remembers what a registry's response tells about zstd support: a 415 to a
+/// compressed body refuses it unless the response lists zstd in `Accept-Encoding`
+/// (RFC 7694), and any response listing it accepts it again
+#[cfg(all(feature = "relayer", not(feature = "health-check")))]
+fn note_zstd_support(registry_endpoint: &str, response: &reqwest::Response, compressed: bool) {
+    let advertised = response
+        .headers()
+        .get_all(reqwest::header::ACCEPT_ENCODING)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .any(|coding| coding.split(';').next().unwrap_or_default().trim() == "zstd");
+    let mut refused = ZSTD_REFUSED.get_or_init(Default::default).lock().unwrap();
+    if advertised {
+        refused.remove(registry_endpoint);
+    } else if compressed && response.status() == reqwest::StatusCode::UNSUPPORTED_MEDIA_TYPE {
+        refused.insert(registry_endpoint.to_string());
+    }
+}
+
+/// Posts a payload once, returning the registry's response whatever the status
+#[cfg(all(feature = "relayer", not(feature = "health-check")))]
+pub async fn post_payload(
+    client: &reqwest::Client,
+    registry_endpoint: &str,
+    payload: &serde_json::Value,
+    wire: &PayloadWire,
+) -> Result<RegistryResponse, anyhow::Error> {
+    let (body, content_type) = encode(wire.codec, payload)?;
+    let post = |body: Vec<u8>, zstd: bool| {
+        let mut request = client
+            .post(registry_endpoint)
+            .header(reqwest::header::CONTENT_TYPE, content_type);
+        if zstd {
+            request = request.header(reqwest::header::CONTENT_ENCODING, "zstd");
+        }
+        request.body(body).send()
+    };
+
+    let compressed = match &wire.compression {
+        Some(compression) if zstd_accepted(registry_endpoint) => compress(&body, compression)?,
+        _ => None,
+    };
+    let mut response = match compressed {
+        Some(compressed) => {
+            debug!(
+                "Compressed the payload to {} from {} bytes",
+                compressed.len(),
+                body.len()
+            );
+            let response = post(compressed, true).await?;
+            note_zstd_support(registry_endpoint, &response, true);
+            if zstd_accepted(registry_endpoint) {
+                response
+            } else {
+                warn!(
+                    "⚠️  {} doesn't accept zstd payloads, sending them uncompressed",
+                    registry_endpoint
+                );
+                post(body, false).await?
+            }
+        }
+        None => {
+            let response = post(body, false).await?;
+            note_zstd_support(registry_endpoint, &response, false);
+            response
+        }
+    };
+    // A registry that doesn't take the configured encoding yet still gets the proof
+    if wire.codec != PayloadCodec::Json
+        && response.status() == reqwest::StatusCode::UNSUPPORTED_MEDIA_TYPE
+    {
+        warn!(
+            "⚠️  {} doesn't accept {} payloads, sending JSON",
+            registry_endpoint,
+            wire.codec.as_str()
+        );
+        response = client.post(registry_endpoint).json(payload).send().await?;
+    }
 
-    info!("Response status: {}", response.status());
+    let status = response.status();
+    info!("Response status: {}", status);
+    let retry_after = retry_after(response.headers());
     let response_text = response.text().await?;
     debug!("Response body: {}", response_text);
+    Ok(RegistryResponse {
+        status,
+        body: response_text,
+        retry_after,
+    })
+}
 
+/// Decodes a payload built by `create_payload` back into the history record it stands for
+#[cfg(all(feature = "relayer", not(feature = "health-check")))]
+fn payload_record(
+    payload: &serde_json::Value,
+    config: &DomainConfig,
+) -> Result<ProofRecord, anyhow::Error> {
+    let field = |name: &str| -> Result<Vec<u8>, anyhow::Error> {
+        let encoded = payload[name]
+            .as_str()
+            .ok_or_else(|| RelayerError::decode("payload", format!("no {} field", name)))?;
+        Ok(hex::decode(encoded).map_err(|e| RelayerError::decode("payload", e))?)
+    };
+
+    let proof = field("proof")?;
+    let public_values = field("public_values")?;
+    let (height, root) = decode_public_values(config.mode, &public_values)?;
+
+    Ok(ProofRecord {
+        seq: 0,
+        height,
+        proof_id: ProofId::of(config.proof_id_algorithm, &proof),
+        proof,
+        public_values,
+        root: root.to_vec(),
+        timestamp: chrono::Utc::now(),
+    })
+}
+
+/// Bookkeeping once the registry has accepted a proof: remember it for dedup, add it to
+/// the history (announcing it to `/ws` and `/events` subscribers) and drop it (and
+/// anything older) from the submission queue. Every step is attempted; the first failure
+/// is returned.
+#[cfg(all(feature = "relayer", not(feature = "health-check")))]
+fn record_relayed(
+    db: &dyn Storage,
+    domain: &str,
+    record: &ProofRecord,
+    history_retention: Option<u64>,
+    events: &EventStreams,
+) -> Result<(), anyhow::Error> {
+    let mut first_error = None;
+
+    let proof_data = PreviousProof {
+        proof_data: hex::encode(&record.proof),
+        timestamp: chrono::Utc::now(),
+    };
+    if let Err(e) = db.update_previous_proof(&proof_data) {
+        error!("❌ Failed to update previous proof in database: {}", e);
+        first_error.get_or_insert(e);
+    }
+    match db.insert_proof(record) {
+        Ok(Some(seq)) => {
+            let event = ProofEvent::from_record(
+                domain,
+                &ProofRecord {
+                    seq,
+                    ..record.clone()
+                },
+            );
+            events.proofs.publish(event.clone());
+            events.lifecycle.publish(StreamEvent::ProofRelayed(event));
+        }
+        Ok(None) => {}
+        Err(e) => {
+            error!("❌ Failed to store proof in history: {}", e);
+            first_error.get_or_insert(e);
+        }
+    }
+    if let Some(keep) = history_retention {
+        match db.prune_proof_history(keep) {
+            Ok(0) => {}
+            Ok(removed) => info!("🧹 Pruned {} old proofs from history", removed),
+            Err(e) => {
+                warn!("⚠️  Failed to prune proof history: {}", e);
+                first_error.get_or_insert(e);
+            }
+        }
+    }
+    if let Err(e) = db.remove_pending_submissions_through(record.height) {
+        error!(
+            "❌ Failed to remove submitted payloads from the queue: {}",
+            e
+        );
+        first_error.get_or_insert(e);
+    }
+    // A dead-lettered payload for this height made it after all
+    match db.get_failed_submissions() {
+        Ok(failed) => {
+            for entry in failed.iter().filter(|entry| entry.height == record.height) {
+                if let Err(e) = db.remove_failed_submission(entry.id) {
+                    warn!("⚠️  Failed to remove dead-lettered payload: {}", e);
+                    first_error.get_or_insert(e);
+                }
+            }
+        }
+        Err(e) => {
+            warn!("⚠️  Failed to load dead-lettered payloads: {}", e);
+            first_error.get_or_insert(e);
+        }
+    }
+
+    first_error.map_or(Ok(()), Err)
+}
+
+/// Submits the proofs between the last relayed height and a newer one, oldest first,
+/// while the heights after the one being submitted are fetched and verified. Stops at the
+/// first height that can't be fetched, verified or submitted, so the registry never
+/// receives them out of order. Records how much of `gap` was filled and returns the
+/// highest height relayed.
+#[cfg(all(feature = "relayer", not(feature = "health-check")))]
+async fn backfill_gap(
+    domain: &Arc<Domain>,
+    sinks: &Sinks,
+    config: &BackfillConfig,
+    gap: &mut HeightGap,
+    history_retention: Option<u64>,
+    events: &EventStreams,
+) -> Option<u64> {
+    let name = domain.name();
+    let next = gap.last + 1;
+    let first = gap.first.max(next.saturating_sub(config.max_heights));
+    if first > gap.first {
+        warn!(
+            "⚠️  [{}] Gap of {} heights exceeds the backfill limit, skipping heights {} to {}",
+            name,
+            gap.heights(),
+            gap.first,
+            first - 1
+        );
+        gap.unfilled_reason = Some(format!(
+            "gap exceeds the backfill limit of {} heights",
+            config.max_heights
+        ));
+    }
+    if first >= next {
+        return None;
+    }
+    info!(
+        "🧩 [{}] Backfilling heights {} to {} before relaying {}",
+        name,
+        first,
+        next - 1,
+        next
+    );
+
+    let mut relayed = None;
+    let mut fetched = std::pin::pin!(backfill::fetch_range(
+        domain.clone(),
+        first..next,
+        config.concurrency
+    ));
+    while let Some((height, payload)) = fetched.next().await {
+        let payload = match payload {
+            Ok(Some(payload)) => payload,
+            Ok(None) => {
+                debug!("[{}] Prover has no proof at height {}", name, height);
+                continue;
+            }
+            Err(e) => {
+                error!("❌ [{}] Backfill stopped at height {}: {}", name, height, e);
+                gap.unfilled_reason = Some(format!("failed to fetch height {}: {}", height, e));
+                break;
+            }
+        };
+        let record = match payload_record(&payload, &domain.config) {
+            Ok(record) if record.height == height => record,
+            Ok(record) => {
+                error!(
+                    "🚨 [{}] Prover returned height {} when asked for {}, stopping backfill",
+                    name, record.height, height
+                );
+                gap.unfilled_reason = Some(format!(
+                    "prover returned height {} for height {}",
+                    record.height, height
+                ));
+                break;
+            }
+            Err(e) => {
+                error!("❌ [{}] Failed to decode backfilled proof: {}", name, e);
+                gap.unfilled_reason = Some(format!("failed to decode height {}: {}", height, e));
+                break;
+            }
+        };
+        let payload = with_metadata(
+            payload,
+            &domain.config,
+            record.height,
+            &record.root,
+            record.timestamp,
+        );
+        if let Err(e) = check_chain_id(
+            domain.config.mode,
+            domain.config.chain_id.as_deref(),
+            &record.public_values,
+        ) {
+            error!("🚨 [{}] Rejecting backfilled proof: {}", name, e);
+            gap.unfilled_reason = Some(format!("rejected height {}: {}", height, e));
+            break;
+        }
+
+        sinks.fan_out(&payload, height, &domain.errors);
+        let started = std::time::Instant::now();
+        match sinks.primary.submit(&payload, height).await {
+            Ok(()) => {
+                domain.relay_latency.record(started.elapsed());
+                domain.latency.observe(Latency::Registry, started.elapsed());
+                info!("✅ [{}] Backfilled height {}", name, height);
+                relayed = Some(height);
+                gap.backfilled += 1;
+                let (name, events) = (name.to_string(), events.clone());
+                domain.writer.submit("record relayed proof", move |db| {
+                    record_relayed(db, &name, &record, history_retention, &events)
+                });
+            }
+            Err(e) => {
+                error!(
+                    "❌ [{}] Failed to send backfilled height {}: {}",
+                    name, height, e
+                );
+                domain.errors.record(Subsystem::Registry, &e);
+                publish_relay_failed(events, name, height, &e);
+                gap.unfilled_reason = Some(format!("failed to send height {}: {}", height, e));
+                break;
+            }
+        }
+    }
+    relayed
+}
+
+/// Records the heights skipped between the last relayed proof and the one about to be
+/// relayed, backfilling them first when configured and the prover serves proofs by
+/// height. Returns the highest height backfilled.
+#[cfg(all(feature = "relayer", not(feature = "health-check")))]
+async fn close_gap(
+    domain: &Arc<Domain>,
+    sinks: &Sinks,
+    last: u64,
+    next: u64,
+    history_retention: Option<u64>,
+    events: &EventStreams,
+) -> Option<u64> {
+    let name = domain.name();
+    let mut gap = HeightGap::new(last + 1, next - 1);
+    info!(
+        "🕳️  [{}] Detected a gap of {} heights ({} to {}) before height {}",
+        name,
+        gap.heights(),
+        gap.first,
+        gap.last,
+        next
+    );
+
+    let relayed = match &domain.config.backfill {
+        Some(backfill) if domain.provers.serves_heights() => {
+            backfill_gap(domain, sinks, backfill, &mut gap, history_retention, events).await
+        }
+        Some(_) => {
+            gap.unfilled_reason = Some("prover can't serve proofs by height".to_string());
+            None
+        }
+        None => {
+            gap.unfilled_reason = Some("backfill is not configured".to_string());
+            None
+        }
+    };
+    if let Some(reason) = &gap.unfilled_reason {
+        warn!(
+            "⚠️  [{}] {} of {} skipped heights not relayed: {}",
+            name,
+            gap.heights() - gap.backfilled,
+            gap.heights(),
+            reason
+        );
+    }
+    domain.gaps.record(gap);
+    relayed
+}
+
+/// Sends queued payloads above `last_height` to the primary target, oldest first,
+/// advancing `last_height` as they're accepted. Unreadable payloads are dropped; stops at
+/// the first one the target doesn't accept, which stays queued. Returns how many were sent.
+#[cfg(all(feature = "relayer", not(feature = "health-check")))]
+async fn drain_queued(
+    domain: &Domain,
+    sinks: &Sinks,
+    pending: Vec<PendingSubmission>,
+    last_height: &mut Option<u64>,
+    history_retention: Option<u64>,
+    events: &EventStreams,
+) -> Result<u64, anyhow::Error> {
+    let name = domain.name().to_string();
+    // Catching up is pointless for a target that only keeps the latest proof
+    let pending = match pending.iter().map(|submission| submission.height).max() {
+        Some(newest) if sinks.primary.latest_only() && pending.len() > 1 => {
+            prune_superseded(domain, newest);
+            pending
+                .into_iter()
+                .filter(|submission| submission.height == newest)
+                .collect()
+        }
+        _ => pending,
+    };
+
+    let mut drained = 0;
+    for submission in pending {
+        let queued = serde_json::from_str::<serde_json::Value>(&submission.payload)
+            .map_err(anyhow::Error::from)
+            .and_then(|payload| {
+                payload_record(&payload, &domain.config).map(|record| (payload, record))
+            });
+        let (payload, record) = match queued {
+            Ok(queued) => queued,
+            Err(e) => {
+                error!(
+                    "❌ [{}] Dropping unreadable queued payload at height {}: {}",
+                    name, submission.height, e
+                );
+                let height = submission.height;
+                domain.writer.submit("remove queued payload", move |db| {
+                    db.remove_pending_submissions_through(height).map(|_| ())
+                });
+                continue;
+            }
+        };
+        if last_height.is_some_and(|last| record.height <= last) {
+            domain.writer.submit("remove queued payload", move |db| {
+                db.remove_pending_submissions_through(record.height)
+                    .map(|_| ())
+            });
+            continue;
+        }
+
+        sinks.fan_out(&payload, record.height, &domain.errors);
+        let started = std::time::Instant::now();
+        match sinks.primary.submit(&payload, record.height).await {
+            Ok(()) => {
+                drained += 1;
+                domain.relay_latency.record(started.elapsed());
+                domain.latency.observe(Latency::Registry, started.elapsed());
+                info!(
+                    "✅ [{}] Sent queued payload for height {} to registry",
+                    name, record.height
+                );
+                *last_height = Some(record.height);
+                let (name, events) = (name.clone(), events.clone());
+                domain.writer.submit("record relayed proof", move |db| {
+                    record_relayed(db, &name, &record, history_retention, &events)
+                });
+            }
+            Err(e) => {
+                error!(
+                    "❌ [{}] Failed to send queued payload for height {}: {}",
+                    name, record.height, e
+                );
+                domain.errors.record(Subsystem::Registry, &e);
+                publish_relay_failed(events, &name, record.height, &e);
+                return Err(e);
+            }
+        }
+    }
+    Ok(drained)
+}
+
+/// A registry cutover handed to a domain's relay loop, see `DomainLifecycle::cut_over`
+#[cfg(all(feature = "relayer", not(feature = "health-check")))]
+pub struct CutoverRequest {
+    pub target: RegistryTarget,
+    /// Sink for the new registry, replacing the primary one once the cutover succeeds
+    pub primary: Arc<dyn ProofSink>,
+    pub reply: oneshot::Sender<Result<CutoverRecord, anyhow::Error>>,
+}
+
+/// Moves the domain's submissions to another registry: drains the queue to the old one,
+/// replays the latest relayed proof to the new one and only then switches, so a new
+/// registry that can't accept proofs never replaces a working one. Records the cutover
+/// so restarts keep using the new registry.
+#[cfg(all(feature = "relayer", not(feature = "health-check")))]
+async fn cut_over(
+    domain: &Domain,
+    sinks: &mut Sinks,
+    target: RegistryTarget,
+    primary: Arc<dyn ProofSink>,
+    last_height: &mut Option<u64>,
+    history_retention: Option<u64>,
+    events: &EventStreams,
+) -> Result<CutoverRecord, anyhow::Error> {
+    let name = domain.name();
+    let from = domain.registry_endpoint();
+    info!(
+        "🔀 [{}] Cutting over from registry {} to {}",
+        name, from, target.endpoint
+    );
+
+    let pending = domain.read(|db| db.get_pending_submissions()).await?;
+    let drained = drain_queued(
+        domain,
+        sinks,
+        pending,
+        last_height,
+        history_retention,
+        events,
+    )
+    .await
+    .map_err(|e| anyhow::anyhow!("Failed to drain queued submissions to {}: {}", from, e))?;
+    // The drained proofs' history records must land before the latest one is read
+    domain
+        .writer
+        .write("flush before cutover", |_| Ok(()))
+        .await?;
+
+    let replayed_height = match domain.read(|db| db.get_latest_proof()).await? {
+        Some(record) => {
+            let payload = with_metadata(
+                build_payload(
+                    &record.proof,
+                    &record.public_values,
+                    domain.vk_monitor.configured(),
+                    &record.proof_id,
+                ),
+                &domain.config,
+                record.height,
+                &record.root,
+                record.timestamp,
+            );
+            primary.submit(&payload, record.height).await.map_err(|e| {
+                anyhow::anyhow!(
+                    "{} didn't accept the latest proof at height {}: {}",
+                    target.endpoint,
+                    record.height,
+                    e
+                )
+            })?;
+            Some(record.height)
+        }
+        None => None,
+    };
+
+    sinks.primary = primary;
+    *domain.registry.write().unwrap() = target.clone();
+    let mut cutover = CutoverRecord {
+        id: 0,
+        from_endpoint: from,
+        to_endpoint: target.endpoint,
+        latest_only: target.latest_only,
+        validate_endpoint: target.validate_endpoint,
+        drained,
+        replayed_height,
+        cut_over_at: chrono::Utc::now(),
+    };
+    let (record, recorded) = (cutover.clone(), Arc::new(AtomicU64::new(0)));
+    let id = recorded.clone();
+    let result = domain
+        .writer
+        .write("record cutover", move |db| {
+            id.store(db.insert_cutover(&record)?, Ordering::Relaxed);
+            Ok(())
+        })
+        .await;
+    match result {
+        Ok(()) => cutover.id = recorded.load(Ordering::Relaxed),
+        Err(e) => {
+            // The switch already happened; only a restart would undo it
+            error!(
+                "❌ [{}] Failed to record cutover, set registry_endpoint to {} before restarting: {}",
+                name, cutover.to_endpoint, e
+            );
+            domain.errors.record(Subsystem::Database, &e);
+        }
+    }
+    Ok(cutover)
+}
+
+/// Waits out the relay interval (or until an operator asks for a relay), recording when the
+/// next cycle is due. `found_new` is whether this cycle fetched a proof above the last one.
+#[cfg(all(feature = "relayer", not(feature = "health-check")))]
+async fn wait_for_next_cycle(domain: &Domain, found_new: bool) {
+    let interval = domain.polling.next_interval(PollLoop::Relay, found_new);
+    domain.scheduler.waiting(interval);
+    domain.control.wait(interval).await;
+}
+
+/// Drops queued payloads below `height`, for primary targets that only keep the latest proof
+#[cfg(all(feature = "relayer", not(feature = "health-check")))]
+fn prune_superseded(domain: &Domain, height: u64) {
+    let Some(below) = height.checked_sub(1) else {
+        return;
+    };
+    let name = domain.name().to_string();
+    domain
+        .writer
+        .submit("prune superseded payloads", move |db| {
+            let removed = db.remove_pending_submissions_through(below)?;
+            if removed > 0 {
+                info!(
+                    "🧹 [{}] Dropped {} queued payloads superseded by height {}",
+                    name, removed, height
+                );
+            }
+            Ok(())
+        });
+}
+
+/// Keeps a payload that failed every submission attempt for an operator to resubmit
+#[cfg(all(feature = "relayer", not(feature = "health-check")))]
+fn dead_letter(
+    db: &dyn Storage,
+    name: &str,
+    record: &ProofRecord,
+    payload: &serde_json::Value,
+    error: &str,
+    attempts: u32,
+) -> Result<(), anyhow::Error> {
+    let failed = FailedSubmission {
+        id: 0,
+        height: record.height,
+        payload: payload.to_string(),
+        error: error.to_string(),
+        attempts,
+        failed_at: chrono::Utc::now(),
+    };
+    match db.record_failed_submission(&failed) {
+        Ok(id) => {
+            warn!(
+                "📮 [{}] Payload for height {} moved to the dead-letter queue as #{}",
+                name, record.height, id
+            );
+            Ok(())
+        }
+        Err(e) => {
+            error!(
+                "❌ [{}] Failed to dead-letter payload for height {}: {}",
+                name, record.height, e
+            );
+            Err(e)
+        }
+    }
+}
+
+/// Announces a submission that failed after its retries to `/ws` subscribers
+#[cfg(all(feature = "relayer", not(feature = "health-check")))]
+fn publish_relay_failed(events: &EventStreams, domain: &str, height: u64, error: &anyhow::Error) {
+    events.lifecycle.publish(StreamEvent::RelayFailed {
+        domain: domain.to_string(),
+        height,
+        error: error.to_string(),
+    });
+}
+
+/// Single-attempt sink for resubmitting a dead-lettered payload to the registry the domain
+/// submits to now, which a live cutover may have moved away from `domain.config`'s
+#[cfg(all(feature = "relayer", not(feature = "health-check")))]
+fn retry_sink(domain: &Domain, retry: &RetryPolicy) -> Result<Arc<dyn ProofSink>, anyhow::Error> {
+    let once = RetryPolicy {
+        max_attempts: 1,
+        ..retry.clone()
+    };
+    primary_sink(&domain.registry_config(), &once, None, Some(&domain.writer))
+}
+
+/// Resubmits a dead-lettered payload with a single attempt. If the registry accepts it,
+/// it is recorded like any relayed proof and leaves the dead-letter queue; otherwise its
+/// entry is updated with the new error. Returns `None` if there is no entry with that id.
+#[cfg(all(feature = "relayer", not(feature = "health-check")))]
+pub async fn retry_failed_submission(
+    domain: &Domain,
+    id: u64,
+    retry: &RetryPolicy,
+    history_retention: Option<u64>,
+    events: &EventStreams,
+) -> Result<Option<ProofRecord>, anyhow::Error> {
+    let Some(failed) = domain.read(move |db| db.get_failed_submission(id)).await? else {
+        return Ok(None);
+    };
+    let payload: serde_json::Value = serde_json::from_str(&failed.payload)?;
+    let record = payload_record(&payload, &domain.config)?;
+
+    let sink = retry_sink(domain, retry)?;
+    match sink.submit(&payload, record.height).await {
+        Ok(()) => {
+            info!(
+                "✅ [{}] Resubmitted dead-lettered payload #{} for height {}",
+                domain.name(),
+                id,
+                record.height
+            );
+            let (name, relayed, events) =
+                (domain.name().to_string(), record.clone(), events.clone());
+            domain.writer.submit("record relayed proof", move |db| {
+                record_relayed(db, &name, &relayed, history_retention, &events)
+            });
+            domain
+                .writer
+                .write("remove dead-lettered payload", move |db| {
+                    db.remove_failed_submission(id).map(|_| ())
+                })
+                .await?;
+            Ok(Some(record))
+        }
+        Err(e) => {
+            domain.errors.record(Subsystem::Registry, &e);
+            publish_relay_failed(events, domain.name(), record.height, &e);
+            let message = e.to_string();
+            let name = domain.name().to_string();
+            domain
+                .writer
+                .write("dead-letter payload", move |db| {
+                    dead_letter(db, &name, &record, &payload, &message, 1)
+                })
+                .await?;
+            Err(e)
+        }
+    }
+}
+
+/// Picks up where the domain left off before a restart: detects the provers' API
+/// versions, drains payloads left queued, and returns the highest height relayed
+#[cfg(all(feature = "relayer", not(feature = "health-check")))]
+async fn resume_relaying(
+    domain: &Arc<Domain>,
+    sinks: &Sinks,
+    history_retention: Option<u64>,
+    events: &EventStreams,
+) -> Option<u64> {
+    let name = domain.name();
+    domain.provers.detect_api_versions().await;
+
+    // Resume from the highest height relayed before the restart, if any
+    let mut last_height: Option<u64> = match domain.read(|db| db.get_latest_proof()).await {
+        Ok(record) => record.map(|record| record.height),
+        Err(e) => {
+            warn!(
+                "⚠️  [{}] Error getting last relayed height from database: {}",
+                name, e
+            );
+            domain.errors.record(Subsystem::Database, &e);
+            None
+        }
+    };
+
+    // Drain payloads left queued by a previous run before fetching anything new
+    let pending = match domain.read(|db| db.get_pending_submissions()).await {
+        Ok(pending) => pending,
+        Err(e) => {
+            error!("❌ [{}] Failed to load queued submissions: {}", name, e);
+            domain.errors.record(Subsystem::Database, &e);
+            Vec::new()
+        }
+    };
+    if !pending.is_empty() {
+        info!(
+            "📥 [{}] Draining {} queued submissions from a previous run",
+            name,
+            pending.len()
+        );
+    }
+    // A failed payload stays queued; the loop resends it or a newer proof supersedes it
+    let _ = drain_queued(
+        domain,
+        sinks,
+        pending,
+        &mut last_height,
+        history_retention,
+        events,
+    )
+    .await;
+
+    last_height
+}
+
+/// Fetches, verifies and submits new proofs for one domain until the process exits
+#[cfg(all(feature = "relayer", not(feature = "health-check")))]
+pub async fn run_relay_loop(
+    domain: std::sync::Arc<Domain>,
+    retry: RetryPolicy,
+    mut sinks: Sinks,
+    mut cutovers: mpsc::UnboundedReceiver<CutoverRequest>,
+    watchdog: std::sync::Arc<CycleWatchdog>,
+    alerts: std::sync::Arc<DomainAlerts>,
+    history_retention: Option<u64>,
+    events: EventStreams,
+) {
+    let name = domain.name().to_string();
+    let mut last_height = resume_relaying(&domain, &sinks, history_retention, &events).await;
+
+    info!("📡 [{}] Relay loop started", name);
+
+    loop {
+        if domain.control.is_stopped() {
+            info!("⏹️  [{}] Relay loop stopped", name);
+            return;
+        }
+        // Cutovers run even while paused, so operators can pause around them
+        while let Ok(CutoverRequest {
+            target,
+            primary,
+            reply,
+        }) = cutovers.try_recv()
+        {
+            let outcome = cut_over(
+                &domain,
+                &mut sinks,
+                target,
+                primary,
+                &mut last_height,
+                history_retention,
+                &events,
+            )
+            .await;
+            match &outcome {
+                Ok(cutover) => info!(
+                    "🔀 [{}] Now submitting to {} ({} queued sent to {}, replayed height {:?})",
+                    name,
+                    cutover.to_endpoint,
+                    cutover.drained,
+                    cutover.from_endpoint,
+                    cutover.replayed_height
+                ),
+                Err(e) => error!(
+                    "❌ [{}] Cutover abandoned, keeping the current registry: {}",
+                    name, e
+                ),
+            }
+            let _ = reply.send(outcome);
+        }
+        domain.scheduler.cycle_started();
+        if domain.control.is_paused() {
+            info!("⏸️  [{}] Relaying paused by operator", name);
+            wait_for_next_cycle(&domain, false).await;
+            continue;
+        }
+
+        let outcome = relay_cycle(
+            &domain,
+            &sinks,
+            &mut last_height,
+            &watchdog,
+            &alerts,
+            &retry,
+            history_retention,
+            &events,
+        )
+        .await;
+        wait_for_next_cycle(&domain, outcome.found_new()).await;
+    }
+}
+
+/// How one relay cycle ended
+#[cfg(all(feature = "relayer", not(feature = "health-check")))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CycleOutcome {
+    /// A new proof was submitted to the primary sink
+    Relayed,
+    /// There was no proof above the last relayed height
+    UpToDate,
+    /// A new proof was held back because relaying is paused
+    Held,
+    /// No prover answered with a proof
+    FetchFailed,
+    /// A check refused the proof before submission
+    Refused,
+    /// A new proof failed submission and was dead-lettered
+    SendFailed,
+}
+
+#[cfg(all(feature = "relayer", not(feature = "health-check")))]
+impl CycleOutcome {
+    /// Whether a new proof turned up, which keeps polling at its fast interval
+    fn found_new(&self) -> bool {
+        matches!(
+            self,
+            CycleOutcome::Relayed | CycleOutcome::Held | CycleOutcome::SendFailed
+        )
+    }
+
+    /// Exit status of `--once`, higher for worse outcomes:
+    ///
+    /// - 0: a proof was relayed or held, or there was nothing to relay
+    /// - 2: the proof couldn't be fetched
+    /// - 4: a check refused the proof
+    /// - 5: its submission failed and it was dead-lettered
+    ///
+    /// 1 is left for startup errors and 3 for [`crate::config::FAIL_FAST_EXIT_CODE`]
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            CycleOutcome::Relayed | CycleOutcome::UpToDate | CycleOutcome::Held => 0,
+            CycleOutcome::FetchFailed => 2,
+            CycleOutcome::Refused => 4,
+            CycleOutcome::SendFailed => 5,
+        }
+    }
+}
+
+/// Runs the rollback guard on a proof at or below the last relayed height. When the
+/// policy accepts the rollback, the abandoned fork is discarded from the history and
+/// `last_height` moved below the proof, so it is relayed like a new one.
+#[cfg(all(feature = "relayer", not(feature = "health-check")))]
+async fn accept_rollback(
+    domain: &Arc<Domain>,
+    record: &ProofRecord,
+    last_height: &mut Option<u64>,
+) -> bool {
+    let Ok(root) = <[u8; 32]>::try_from(record.root.as_slice()) else {
+        return false;
+    };
+    let (rollback, height) = (domain.rollback.clone(), record.height);
+    match domain
+        .read(move |db| rollback.check(db, height, &root))
+        .await
+    {
+        Ok(Verdict::AcceptRollback) => {}
+        Ok(Verdict::Accept | Verdict::Skip) => return false,
+        Err(e) => {
+            error!(
+                "❌ [{}] Failed to check proof height against history: {}",
+                domain.name(),
+                e
+            );
+            domain.errors.record(Subsystem::Database, &e);
+            return false;
+        }
+    }
+
+    let result = domain
+        .writer
+        .write("discard abandoned fork", move |db| {
+            let removed = db.delete_proofs_from_height(height)?;
+            warn!("⚠️  Discarded {} proofs from the abandoned fork", removed);
+            Ok(())
+        })
+        .await;
+    if let Err(e) = result {
+        error!(
+            "❌ [{}] Failed to discard proofs above rollback height: {}",
+            domain.name(),
+            e
+        );
+        domain.errors.record(Subsystem::Database, &e);
+        return false;
+    }
+    *last_height = height.checked_sub(1);
+    true
+}
+
+/// Fetches the latest proof, checks it and submits it if it is above `last_height`
+#[cfg(all(feature = "relayer", not(feature = "health-check")))]
+async fn relay_cycle(
+    domain: &Arc<Domain>,
+    sinks: &Sinks,
+    last_height: &mut Option<u64>,
+    watchdog: &CycleWatchdog,
+    alerts: &DomainAlerts,
+    retry: &RetryPolicy,
+    history_retention: Option<u64>,
+    events: &EventStreams,
+) -> CycleOutcome {
+    let name = domain.name().to_string();
+    let (created, head) = fetch_with_head(
+        domain.reference.as_deref(),
+        create_payload(
+            &domain.provers,
+            &domain.vk_monitor,
+            &domain.errors,
+            domain.envelope.as_deref(),
+            domain.config.proof_id_algorithm,
+            &domain.latency,
+        ),
+    )
+    .await;
+    match created {
+        Ok(payload) => {
+            domain.errors.record_poll(PollOutcome::Proof);
+            let record = match payload_record(&payload, &domain.config) {
+                Ok(record) => record,
+                Err(e) => {
+                    error!("❌ [{}] {}", name, e);
+                    alerts.record_decode_failure(&e);
+                    let public_values = payload["public_values"]
+                        .as_str()
+                        .and_then(|encoded| hex::decode(encoded).ok())
+                        .unwrap_or_default();
+                    let failure =
+                        DecodeFailure::new(&e, domain.provers.active_endpoint(), &public_values);
+                    domain.writer.submit("record decode failure", move |db| {
+                        db.record_decode_failure(&failure).map(|_| ())
+                    });
+                    domain.freshness.observe(None);
+                    watchdog.record_failure();
+                    return CycleOutcome::Refused;
+                }
+            };
+            let payload = with_metadata(
+                payload,
+                &domain.config,
+                record.height,
+                &record.root,
+                record.timestamp,
+            );
+            if let Err(e) = check_chain_id(
+                domain.config.mode,
+                domain.config.chain_id.as_deref(),
+                &record.public_values,
+            ) {
+                error!("🚨 [{}] Rejecting proof from the wrong chain: {}", name, e);
+                domain.freshness.observe(None);
+                watchdog.record_failure();
+                return CycleOutcome::Refused;
+            }
+            if let (Some(reference), Some(head)) = (&domain.reference, head)
+                && let Err(e) = reference.check(record.height, head)
+            {
+                error!("🚨 [{}] Rejecting proof: {}", name, e);
+                domain.freshness.observe(None);
+                watchdog.record_failure();
+                return CycleOutcome::Refused;
+            }
+            // Only new heights are looked up, once each
+            if let Some(check) = &domain.root_check
+                && last_height.is_none_or(|last| record.height > last)
+            {
+                let result = check.check(record.height, &record.root).await;
+                alerts.record_root_check(
+                    result
+                        .as_ref()
+                        .err()
+                        .and_then(|e| e.downcast_ref::<RootDivergence>()),
+                );
+                if let Err(e) = result {
+                    error!("🚨 [{}] Refusing to relay proof: {}", name, e);
+                    domain.errors.record(Subsystem::Prover, &e);
+                    domain.freshness.observe(None);
+                    watchdog.record_failure();
+                    return CycleOutcome::Refused;
+                }
+            }
+            domain.freshness.observe(Some(record.height));
+
+            // A second root for a stored height is critical even though it's never relayed
+            let (conflicts, height, root) =
+                (domain.conflicts.clone(), record.height, record.root.clone());
+            let prover = domain.provers.active_endpoint().map(str::to_string);
+            if let Err(e) = domain
+                .read(move |db| conflicts.check(db, height, &root, prover.as_deref()))
+                .await
+            {
+                warn!(
+                    "⚠️  [{}] Failed to compare root with proof history: {}",
+                    name, e
+                );
+                domain.errors.record(Subsystem::Database, &e);
+            }
+            alerts.record_root_conflict(domain.conflicts.current().as_ref());
+
+            // Only relay heights above the last one sent, however the proof is serialized,
+            // or a lower one within the reorg tolerance the rollback policy accepts
+            let should_send = last_height.is_none_or(|last| record.height > last)
+                || (!domain.control.is_paused()
+                    && domain
+                        .rollback
+                        .regression(record.height, *last_height)
+                        .is_none()
+                    && accept_rollback(domain, &record, last_height).await);
+            alerts.record_decoded(record.height, should_send);
+
+            if should_send && domain.control.is_paused() {
+                info!("⏸️  [{}] Paused before submission, holding new proof", name);
+                CycleOutcome::Held
+            } else if should_send {
+                // Heights skipped since the last relayed proof go first
+                if let Some(last) = *last_height
+                    && record.height > last + 1
+                    && !sinks.primary.latest_only()
+                {
+                    *last_height = close_gap(
+                        domain,
+                        sinks,
+                        last,
+                        record.height,
+                        history_retention,
+                        events,
+                    )
+                    .await
+                    .or(*last_height);
+                }
+
+                // Persist the payload first so a crash before the registry accepts it
+                // doesn't lose the proof
+                let submission = PendingSubmission {
+                    height: record.height,
+                    payload: payload.to_string(),
+                    created_at: chrono::Utc::now(),
+                };
+                if let Err(e) = domain
+                    .writer
+                    .write("queue payload", move |db| {
+                        db.enqueue_submission(&submission)
+                    })
+                    .await
+                {
+                    error!(
+                        "❌ [{}] Failed to queue payload before sending: {}",
+                        name, e
+                    );
+                }
+                if sinks.primary.latest_only() {
+                    prune_superseded(domain, record.height);
+                }
+                events.lifecycle.publish(StreamEvent::ProofAccepted {
+                    domain: name.clone(),
+                    height: record.height,
+                    root: hex::encode(&record.root),
+                    proof_id: record.proof_id.clone(),
+                });
+
+                sinks.fan_out(&payload, record.height, &domain.errors);
+                let started = std::time::Instant::now();
+                match sinks.primary.submit(&payload, record.height).await {
+                    Ok(()) => {
+                        domain.relay_latency.record(started.elapsed());
+                        domain.latency.observe(Latency::Registry, started.elapsed());
+                        info!("✅ [{}] Successfully sent payload to registry", name);
+                        watchdog.record_success();
+                        alerts.record_send_success();
+                        *last_height = Some(record.height);
+                        let (name, events) = (name.clone(), events.clone());
+                        domain.writer.submit("record relayed proof", move |db| {
+                            record_relayed(db, &name, &record, history_retention, &events)
+                        });
+                        CycleOutcome::Relayed
+                    }
+                    Err(e) => {
+                        error!("❌ [{}] Failed to send payload to registry: {}", name, e);
+                        alerts.record_send_failure(&e);
+                        domain.errors.record(Subsystem::Registry, &e);
+                        publish_relay_failed(events, &name, record.height, &e);
+                        let name = name.clone();
+                        let (message, attempts) = (e.to_string(), retry.max_attempts);
+                        domain.writer.submit("dead-letter payload", move |db| {
+                            dead_letter(db, &name, &record, &payload, &message, attempts)
+                        });
+                        watchdog.record_failure();
+                        CycleOutcome::SendFailed
+                    }
+                }
+            } else if let Some(depth) = domain.rollback.regression(record.height, *last_height) {
+                let last = last_height.unwrap_or_default();
+                error!(
+                    "🚨 [{}] Refusing proof at height {}, {} below last relayed height {} (reorg tolerance {})",
+                    name, record.height, depth, last, domain.config.reorg_tolerance
+                );
+                alerts.record_height_regression(record.height, last);
+                watchdog.record_success();
+                CycleOutcome::Refused
+            } else {
+                info!(
+                    "⏳ [{}] No new proof (height {}, last relayed {}), waiting for next check...",
+                    name,
+                    record.height,
+                    last_height.unwrap_or_default()
+                );
+                watchdog.record_success();
+                CycleOutcome::UpToDate
+            }
+        }
+        Err(e) if e.is::<NoNewProof>() => {
+            info!("⏳ [{}] {}, waiting for next check...", name, e);
+            domain.errors.record_poll(PollOutcome::NoNewProof);
+            domain.freshness.observe(None);
+            watchdog.record_success();
+            CycleOutcome::UpToDate
+        }
+        Err(e) => {
+            error!("❌ [{}] Failed to create payload: {}", name, e);
+            domain.errors.record_poll(PollOutcome::Error);
+            domain.freshness.observe(None);
+            watchdog.record_failure();
+            CycleOutcome::FetchFailed
+        }
+    }
+}
+
+/// Starts domains' relay loops, at startup and when `/admin/domains` adds one
+#[cfg(all(feature = "relayer", not(feature = "health-check")))]
+struct Pipelines {
+    config: Arc<Config>,
+    domains: Arc<DomainSet>,
+    journal: Option<Arc<SubmissionJournal>>,
+    alerter: Option<Arc<Alerter>>,
+    events: EventStreams,
+    metrics: Arc<Metrics>,
+    /// Hands cutovers to each running domain's relay loop
+    cutovers: Mutex<HashMap<String, mpsc::UnboundedSender<CutoverRequest>>>,
+    /// Reports relay loops that crashed, ending `Relayer::run`
+    crashes: mpsc::UnboundedSender<String>,
+}
+
+#[cfg(all(feature = "relayer", not(feature = "health-check")))]
+impl Pipelines {
+    /// Opens the domain's sinks and storage without starting anything. Blocks on storage,
+    /// so async callers run it on the blocking pool.
+    fn open(&self, config: DomainConfig) -> Result<(Arc<Domain>, Sinks), anyhow::Error> {
+        if config.dry_run {
+            info!(
+                "🧪 [{}] Dry run: proofs are recorded in {}_dry_run_{}.db but never submitted",
+                config.name,
+                self.config.db_prefix(),
+                config.name
+            );
+        }
+        let mut domain = Domain::open(config, &self.config.storage, &self.config.db_prefix())?
+            .with_metrics(&self.metrics);
+        resume_cutover(&mut domain)?;
+        let domain = Arc::new(domain);
+        let sinks = Sinks::from_config(
+            &domain.config,
+            &self.config.registry_retry,
+            self.journal.clone(),
+            Some(&domain.writer),
+        )?;
+        Ok((domain, sinks))
+    }
+
+    fn start(&self, domain: &Arc<Domain>, sinks: Sinks) {
+        let span = info_span!("domain", domain = %domain.name());
+        let (cutover_requests, cutovers) = mpsc::unbounded_channel();
+        self.cutovers
+            .lock()
+            .unwrap()
+            .insert(domain.name().to_string(), cutover_requests);
+        let handle = tokio::spawn(
+            run_relay_loop(
+                domain.clone(),
+                self.config.registry_retry.clone(),
+                sinks,
+                cutovers,
+                spawn_watchdog(domain, self.config.fail_fast),
+                spawn_alerts(domain, self.alerter.clone(), &self.config.alerting),
+                self.config.inline_history_retention(),
+                self.events.clone(),
+            )
+            .instrument(span),
+        );
+
+        let (name, crashes) = (domain.name().to_string(), self.crashes.clone());
+        tokio::spawn(async move {
+            if let Err(e) = handle.await {
+                let _ = crashes.send(format!("Relay loop for {} crashed: {}", name, e));
+            }
+        });
+    }
+}
+
+#[cfg(all(feature = "relayer", not(feature = "health-check")))]
+impl DomainLifecycle for Pipelines {
+    fn add(&self, config: DomainConfig) -> Result<Arc<Domain>, anyhow::Error> {
+        validate_name(&config.name)?;
+        if self.domains.get(&config.name).is_some() {
+            return Err(anyhow::anyhow!("Domain {} already exists", config.name));
+        }
+
+        let (domain, sinks) = self.open(config)?;
+        self.domains.insert(domain.clone())?;
+        self.start(&domain, sinks);
+        info!("➕ [{}] Domain added", domain.name());
+        Ok(domain)
+    }
+
+    fn remove(&self, name: &str) -> bool {
+        let Some(domain) = self.domains.remove(name) else {
+            return false;
+        };
+        self.cutovers.lock().unwrap().remove(name);
+        domain.control.stop();
+        info!(
+            "➖ [{}] Domain removed, its relay loop stops after the current cycle",
+            name
+        );
+        true
+    }
+
+    fn cut_over(
+        &self,
+        name: &str,
+        target: RegistryTarget,
+    ) -> Result<oneshot::Receiver<Result<CutoverRecord, anyhow::Error>>, anyhow::Error> {
+        let domain = self
+            .domains
+            .get(name)
+            .ok_or_else(|| anyhow::anyhow!("Unknown domain {}", name))?;
+        if domain.config.primary_sink.is_some() {
+            return Err(anyhow::anyhow!(
+                "Domain {} submits to a primary sink rather than a registry",
+                name
+            ));
+        }
+        if target.endpoint == domain.registry_endpoint() {
+            return Err(anyhow::anyhow!(
+                "Domain {} already submits to {}",
+                name,
+                target.endpoint
+            ));
+        }
+
+        let config = DomainConfig {
+            registry_endpoint: target.endpoint.clone(),
+            registry_latest_only: target.latest_only,
+            registry_validate_endpoint: target.validate_endpoint.clone(),
+            ..domain.config.clone()
+        };
+        let primary = primary_sink(
+            &config,
+            &self.config.registry_retry,
+            self.journal.clone(),
+            Some(&domain.writer),
+        )?;
+
+        let (reply, outcome) = oneshot::channel();
+        self.cutovers
+            .lock()
+            .unwrap()
+            .get(name)
+            .ok_or_else(|| anyhow::anyhow!("Relay loop of {} isn't running", name))?
+            .send(CutoverRequest {
+                target,
+                primary,
+                reply,
+            })
+            .map_err(|_| anyhow::anyhow!("Relay loop of {} has stopped", name))?;
+        domain.control.relay_now();
+        Ok(outcome)
+    }
+}
+
+/// Points a domain opened from the config file at the registry it was last cut over to,
+/// unless the config already names a registry the domain hasn't moved away from. Reads
+/// storage directly, like opening it does: `Pipelines::open` runs on the blocking pool.
+#[cfg(all(feature = "relayer", not(feature = "health-check")))]
+fn resume_cutover(domain: &mut Domain) -> Result<(), anyhow::Error> {
+    if domain.config.primary_sink.is_some() {
+        return Ok(());
+    }
+    let cutovers = domain.db.get_cutovers(1000)?;
+    let configured = &domain.config.registry_endpoint;
+    let Some(latest) = cutovers.first() else {
+        return Ok(());
+    };
+    if latest.to_endpoint == *configured
+        || !cutovers
+            .iter()
+            .any(|cutover| cutover.from_endpoint == *configured)
+    {
+        return Ok(());
+    }
+
+    warn!(
+        "🔀 [{}] Submitting to {}, where the registry was cut over to at {}, rather than the configured {}; update registry_endpoint to match",
+        domain.name(),
+        latest.to_endpoint,
+        latest.cut_over_at.to_rfc3339(),
+        configured
+    );
+    domain.config.registry_endpoint = latest.to_endpoint.clone();
+    domain.config.registry_latest_only = latest.latest_only;
+    domain.config.registry_validate_endpoint = latest.validate_endpoint.clone();
+    *domain.registry.get_mut().unwrap() = latest.to_endpoint.clone();
     Ok(())
 }
+
+/// Relays proofs for every configured domain; the entry point for embedding the relayer
+#[cfg(all(feature = "relayer", not(feature = "health-check")))]
+pub struct Relayer {
+    pipelines: Arc<Pipelines>,
+    /// The configured domains' sinks, in the same order as the domain set, until `run`
+    /// starts them
+    sinks: Vec<Sinks>,
+    crashes: mpsc::UnboundedReceiver<String>,
+}
+
+#[cfg(all(feature = "relayer", not(feature = "health-check")))]
+impl Relayer {
+    /// Opens every domain's storage and sinks, plus the submission journal and webhooks if
+    /// configured. Blocks on storage, so async code should call it through
+    /// `tokio::task::spawn_blocking`.
+    pub fn open(config: Arc<Config>) -> Result<Self, anyhow::Error> {
+        let journal = match &config.submission_journal {
+            Some(journal_config) => {
+                info!("📓 Journaling submissions to {}", journal_config.path);
+                Some(Arc::new(SubmissionJournal::open(journal_config)?))
+            }
+            None => None,
+        };
+        let (crashes, crash_reports) = mpsc::unbounded_channel();
+        let pipelines = Pipelines {
+            domains: Arc::new(DomainSet::default()),
+            journal,
+            alerter: Alerter::new(&config.alerting)?.map(Arc::new),
+            events: EventStreams::default(),
+            metrics: Arc::new(Metrics::new(&config.metrics)?),
+            cutovers: Mutex::new(HashMap::new()),
+            crashes,
+            config,
+        };
+
+        let mut sinks = Vec::new();
+        for domain_config in &pipelines.config.domains {
+            let (domain, domain_sinks) = pipelines.open(domain_config.clone())?;
+            pipelines.domains.insert(domain)?;
+            sinks.push(domain_sinks);
+        }
+
+        Ok(Relayer {
+            pipelines: Arc::new(pipelines),
+            sinks,
+            crashes: crash_reports,
+        })
+    }
+
+    /// The relayed domains, e.g. for serving them with `create_api_server`
+    pub fn domains(&self) -> Arc<DomainSet> {
+        self.pipelines.domains.clone()
+    }
+
+    /// Every newly relayed proof and relay lifecycle event, for the API's `/ws` and
+    /// `/events` streams
+    pub fn events(&self) -> EventStreams {
+        self.pipelines.events.clone()
+    }
+
+    /// Prometheus metrics for the API's `/metrics`; only the latency histograms are
+    /// recorded in this mode
+    pub fn metrics(&self) -> Arc<Metrics> {
+        self.pipelines.metrics.clone()
+    }
+
+    /// Adds and removes domains at runtime, for the API's `/admin/domains`
+    pub fn lifecycle(&self) -> Arc<dyn DomainLifecycle> {
+        self.pipelines.clone()
+    }
+
+    /// Runs a single relay cycle per domain, in parallel, and returns each domain's
+    /// outcome once its deliveries and writes have finished. Queued submissions from a
+    /// previous run are drained first, as on startup.
+    pub async fn run_once(self) -> Result<Vec<(String, CycleOutcome)>, anyhow::Error> {
+        let Relayer {
+            pipelines, sinks, ..
+        } = self;
+        let cycles = pipelines
+            .domains
+            .all()
+            .into_iter()
+            .zip(sinks)
+            .map(|(domain, sinks)| {
+                let pipelines = pipelines.clone();
+                let span = info_span!("domain", domain = %domain.name());
+                async move {
+                    let config = &pipelines.config;
+                    let watchdog = spawn_watchdog(&domain, config.fail_fast);
+                    let alerts = spawn_alerts(&domain, pipelines.alerter.clone(), &config.alerting);
+                    let mut last_height = resume_relaying(
+                        &domain,
+                        &sinks,
+                        config.inline_history_retention(),
+                        &pipelines.events,
+                    )
+                    .await;
+                    domain.scheduler.cycle_started();
+                    let outcome = relay_cycle(
+                        &domain,
+                        &sinks,
+                        &mut last_height,
+                        &watchdog,
+                        &alerts,
+                        &config.registry_retry,
+                        config.inline_history_retention(),
+                        &pipelines.events,
+                    )
+                    .await;
+                    sinks.settle().await;
+                    if let Err(e) = domain.writer.flush().await {
+                        error!("❌ [{}] Failed to flush writes: {}", domain.name(), e);
+                    }
+                    info!(
+                        "🏁 [{}] Single cycle finished: {:?}",
+                        domain.name(),
+                        outcome
+                    );
+                    (domain.name().to_string(), outcome)
+                }
+                .instrument(span)
+            });
+        Ok(futures::future::join_all(cycles).await)
+    }
+
+    /// Runs an independent relay loop per domain. Only returns if one of them crashes.
+    pub async fn run(self) -> Result<(), anyhow::Error> {
+        let Relayer {
+            pipelines,
+            sinks,
+            mut crashes,
+        } = self;
+        for (domain, sinks) in pipelines.domains.all().iter().zip(sinks) {
+            pipelines.start(domain, sinks);
+        }
+
+        match crashes.recv().await {
+            Some(crash) => {
+                error!("❌ {}", crash);
+                Err(anyhow::anyhow!(crash))
+            }
+            None => Ok(()),
+        }
+    }
+}
+
+#[cfg(all(test, feature = "relayer", not(feature = "health-check")))]
+mod tests {
+    use super::*;
+    use crate::config::{RollbackPolicy, StorageConfig};
+
+    fn record(height: u64, root: u8) -> ProofRecord {
+        ProofRecord {
+            seq: 0,
+            height,
+            proof: vec![height as u8, root],
+            public_values: vec![],
+            root: vec![root; 32],
+            proof_id: ProofId::of(HashAlgorithm::Sha256, &[height as u8, root]),
+            timestamp: chrono::Utc::now(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_approved_rollback_is_relayed() -> anyhow::Result<()> {
+        let config = DomainConfig {
+            name: "rollback".to_string(),
+            mode: MODE::TENDERMINT,
+            rollback_policy: RollbackPolicy::AcceptAfterAdminApproval,
+            reorg_tolerance: 10,
+            ..DomainConfig::default()
+        };
+        let domain = Arc::new(Domain::open(config, &StorageConfig::Memory, "relayer")?);
+        for height in 10..=20 {
+            domain.db.insert_proof(&record(height, 1))?;
+        }
+        let mut last_height = Some(20);
+
+        // Held for approval, then accepted once
+        let fork = record(15, 2);
+        assert!(!accept_rollback(&domain, &fork, &mut last_height).await);
+        assert_eq!(last_height, Some(20));
+        assert!(domain.rollback.approve().is_some());
+        assert!(accept_rollback(&domain, &fork, &mut last_height).await);
+
+        // The fork above the rollback height is gone and the proof counts as new
+        assert_eq!(last_height, Some(14));
+        assert_eq!(
+            domain.db.get_latest_proof()?.map(|latest| latest.height),
+            Some(14)
+        );
+
+        // Relaying it records it as the latest proof, so the next cycle is up to date
+        record_relayed(
+            domain.db.as_ref(),
+            "rollback",
+            &fork,
+            None,
+            &EventStreams::default(),
+        )?;
+        let latest = domain.db.get_latest_proof()?.unwrap();
+        assert_eq!((latest.height, latest.root), (15, vec![2; 32]));
+        assert!(!accept_rollback(&domain, &fork, &mut Some(15)).await);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_retry_after_cutover() -> anyhow::Result<()> {
+        let config = DomainConfig {
+            name: "cutover".to_string(),
+            registry_endpoint: "http://127.0.0.1:9/old".to_string(),
+            ..DomainConfig::default()
+        };
+        let domain = Domain::open(config, &StorageConfig::Memory, "relayer")?;
+        let sink = retry_sink(&domain, &RetryPolicy::default())?;
+        assert_eq!(sink.describe(), "registry http://127.0.0.1:9/old");
+        assert!(!sink.latest_only());
+
+        // As `cut_over` leaves it, with the configured registry unchanged until a restart
+        *domain.registry.write().unwrap() = RegistryTarget {
+            endpoint: "http://127.0.0.1:9/new".to_string(),
+            latest_only: true,
+            validate_endpoint: None,
+        };
+        let sink = retry_sink(&domain, &RetryPolicy::default())?;
+        assert_eq!(sink.describe(), "registry http://127.0.0.1:9/new");
+        assert!(sink.latest_only());
+        assert_eq!(domain.config.registry_endpoint, "http://127.0.0.1:9/old");
+        Ok(())
+    }
+}
@@ -0,0 +1,244 @@
+use anyhow::Result;
+use serde::Serialize;
+use std::sync::Mutex;
+use tracing::{error, info, warn};
+
+use crate::config::{MODE, RollbackPolicy};
+use crate::db::Storage;
+
+/// A proof below the high-water mark whose root disagrees with the history
+#[derive(Debug, Clone, Serialize)]
+pub struct RollbackCandidate {
+    pub height: u64,
+    pub root: String, // hex encoded
+    pub high_water_mark: u64,
+    pub detected_at: String,
+}
+
+impl RollbackCandidate {
+    fn matches(&self, other: &RollbackCandidate) -> bool {
+        self.height == other.height && self.root == other.root
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum Verdict {
+    /// Height is at or above the high-water mark
+    Accept,
+    /// Height is below the high-water mark and the rollback was allowed; history
+    /// from this height upwards should be discarded before accepting the proof
+    AcceptRollback,
+    /// Stale or rejected proof, nothing should be recorded
+    Skip,
+}
+
+/// Decides whether proofs below the highest recorded height are accepted
+pub struct RollbackGuard {
+    mode: MODE,
+    policy: RollbackPolicy,
+    reorg_tolerance: u64,
+    state: Mutex<GuardState>,
+}
+
+#[derive(Default)]
+struct GuardState {
+    pending: Option<RollbackCandidate>,
+    approved: Option<RollbackCandidate>,
+}
+
+impl RollbackGuard {
+    pub fn new(mode: MODE, policy: RollbackPolicy, reorg_tolerance: u64) -> Self {
+        RollbackGuard {
+            mode,
+            policy,
+            reorg_tolerance,
+            state: Mutex::new(GuardState::default()),
+        }
+    }
+
+    /// How far `height` is below `last_accepted`, if that's more than the reorg
+    /// tolerance. Such proofs are refused before the rollback policy is consulted.
+    pub fn regression(&self, height: u64, last_accepted: Option<u64>) -> Option<u64> {
+        let depth = last_accepted?.checked_sub(height)?;
+        (depth > self.reorg_tolerance).then_some(depth)
+    }
+
+    pub fn check(&self, db: &dyn Storage, height: u64, root: &[u8; 32]) -> Result<Verdict> {
+        let Some(latest) = db.get_latest_proof()? else {
+            return Ok(Verdict::Accept);
+        };
+        if height >= latest.height {
+            return Ok(Verdict::Accept);
+        }
+
+        if let Some(existing) = db.get_proof_by_height(height)? {
+            if existing.root == root {
+                warn!(
+                    "⚠️  Ignoring stale proof for height {} (high-water mark is {})",
+                    height, latest.height
+                );
+                return Ok(Verdict::Skip);
+            }
+        }
+
+        let candidate = RollbackCandidate {
+            height,
+            root: hex::encode(root),
+            high_water_mark: latest.height,
+            detected_at: chrono::Utc::now().to_rfc3339(),
+        };
+
+        let policy = match self.mode {
+            MODE::TENDERMINT => self.policy,
+            MODE::HELIOS => RollbackPolicy::Reject,
+        };
+
+        match policy {
+            RollbackPolicy::Reject => {
+                warn!(
+                    "⚠️  Rejecting proof for height {} below high-water mark {} with root {}",
+                    height, latest.height, candidate.root
+                );
+                Ok(Verdict::Skip)
+            }
+            RollbackPolicy::AcceptWithAlert => {
+                error!(
+                    "🚨 Chain rollback detected: accepting height {} (root {}) below high-water mark {}",
+                    height, candidate.root, latest.height
+                );
+                Ok(Verdict::AcceptRollback)
+            }
+            RollbackPolicy::AcceptAfterAdminApproval => {
+                let mut state = self.state.lock().unwrap();
+
+                if state
+                    .approved
+                    .as_ref()
+                    .is_some_and(|approved| approved.matches(&candidate))
+                {
+                    state.approved = None;
+                    info!(
+                        "✅ Accepting approved rollback to height {} (root {})",
+                        height, candidate.root
+                    );
+                    return Ok(Verdict::AcceptRollback);
+                }
+
+                if !state
+                    .pending
+                    .as_ref()
+                    .is_some_and(|pending| pending.matches(&candidate))
+                {
+                    error!(
+                        "🚨 Chain rollback detected: height {} (root {}) below high-water mark {}, awaiting approval via POST /admin/rollback/approve",
+                        height, candidate.root, latest.height
+                    );
+                    state.pending = Some(candidate);
+                }
+                Ok(Verdict::Skip)
+            }
+        }
+    }
+
+    pub fn pending(&self) -> Option<RollbackCandidate> {
+        self.state.lock().unwrap().pending.clone()
+    }
+
+    /// Approves the pending rollback; it is accepted the next time the prover serves it
+    pub fn approve(&self) -> Option<RollbackCandidate> {
+        let mut state = self.state.lock().unwrap();
+        let approved = state.pending.take()?;
+        state.approved = Some(approved.clone());
+        Some(approved)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::{Database, ProofRecord};
+    use crate::proof_id::{HashAlgorithm, ProofId};
+
+    /// A history of heights 10 to 20, all with root `[1; 32]`
+    fn history() -> Result<Database> {
+        let db = Database::in_memory()?;
+        for height in 10..=20u64 {
+            db.insert_proof(&ProofRecord {
+                seq: 0,
+                height,
+                proof: vec![height as u8],
+                public_values: vec![],
+                root: vec![1; 32],
+                proof_id: ProofId::of(HashAlgorithm::Sha256, &[height as u8]),
+                timestamp: chrono::Utc::now(),
+            })?;
+        }
+        Ok(db)
+    }
+
+    #[test]
+    fn test_check() -> Result<()> {
+        use MODE::{HELIOS, TENDERMINT};
+        use RollbackPolicy::{AcceptAfterAdminApproval, AcceptWithAlert, Reject};
+        use Verdict::{Accept, AcceptRollback, Skip};
+
+        let db = history()?;
+        let cases = [
+            // Proofs at or above the high-water mark are always accepted
+            (TENDERMINT, Reject, 21, 1, Accept),
+            (HELIOS, Reject, 20, 2, Accept),
+            // A root already recorded for that height is stale whatever the policy
+            (TENDERMINT, AcceptWithAlert, 15, 1, Skip),
+            // A different root below the high-water mark is up to the policy
+            (TENDERMINT, Reject, 15, 2, Skip),
+            (TENDERMINT, AcceptWithAlert, 15, 2, AcceptRollback),
+            (TENDERMINT, AcceptAfterAdminApproval, 15, 2, Skip),
+            // Helios chains never roll back
+            (HELIOS, AcceptWithAlert, 15, 2, Skip),
+            (HELIOS, AcceptAfterAdminApproval, 15, 2, Skip),
+        ];
+        for (mode, policy, height, root, expected) in cases {
+            let guard = RollbackGuard::new(mode, policy, 0);
+            assert_eq!(
+                guard.check(&db, height, &[root; 32])?,
+                expected,
+                "{:?} {:?} at height {}",
+                mode,
+                policy,
+                height
+            );
+        }
+
+        // Nothing is recorded yet, so there is nothing to roll back
+        let guard = RollbackGuard::new(TENDERMINT, Reject, 0);
+        assert_eq!(guard.check(&Database::in_memory()?, 5, &[2; 32])?, Accept);
+        Ok(())
+    }
+
+    #[test]
+    fn test_approval() -> Result<()> {
+        let db = history()?;
+        let guard = RollbackGuard::new(
+            MODE::TENDERMINT,
+            RollbackPolicy::AcceptAfterAdminApproval,
+            0,
+        );
+        assert!(guard.approve().is_none());
+
+        assert_eq!(guard.check(&db, 15, &[2; 32])?, Verdict::Skip);
+        let pending = guard.pending().unwrap();
+        assert_eq!((pending.height, pending.high_water_mark), (15, 20));
+        assert_eq!(pending.root, hex::encode([2; 32]));
+
+        let approved = guard.approve().unwrap();
+        assert_eq!(approved.height, 15);
+        assert!(guard.pending().is_none());
+
+        // Only the approved root is accepted, and only once
+        assert_eq!(guard.check(&db, 15, &[3; 32])?, Verdict::Skip);
+        assert_eq!(guard.check(&db, 15, &[2; 32])?, Verdict::AcceptRollback);
+        assert_eq!(guard.check(&db, 15, &[2; 32])?, Verdict::Skip);
+        assert_eq!(guard.pending().unwrap().root, hex::encode([2; 32]));
+        Ok(())
+    }
+}
@@ -0,0 +1,130 @@
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Timing of a loop's cycles against its intended cadence, for `/stats/scheduler`
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct SchedulerSnapshot {
+    pub cycles: u64,
+    /// When the latest cycle should have started, i.e. when its wait should have ended
+    pub last_intended_start: Option<DateTime<Utc>>,
+    pub last_actual_start: Option<DateTime<Utc>>,
+    /// How late the latest cycle started
+    pub last_drift_ms: u64,
+    /// Sum of every cycle's lateness since startup
+    pub accumulated_drift_ms: u64,
+    /// Longest time between two cycle starts beyond the loop's interval, covering both
+    /// slow cycles and late wake-ups
+    pub longest_stall_ms: u64,
+    pub longest_stall_at: Option<DateTime<Utc>>,
+    /// Cycles started before their wait ended, e.g. by `/admin/relay-now`
+    pub early_starts: u64,
+}
+
+/// Records when a loop meant to start each cycle and when it really did. Lateness points
+/// at runtime starvation (the timer fired but the task didn't run); long stalls with
+/// little drift point at blocking work inside the cycle.
+pub struct SchedulerStats {
+    interval: Mutex<Duration>,
+    state: Mutex<State>,
+}
+
+#[derive(Default)]
+struct State {
+    intended_start: Option<Instant>,
+    last_start: Option<Instant>,
+    snapshot: SchedulerSnapshot,
+}
+
+impl SchedulerStats {
+    pub fn new() -> Self {
+        SchedulerStats {
+            interval: Mutex::new(Duration::ZERO),
+            state: Mutex::new(State::default()),
+        }
+    }
+
+    /// Called right before the loop waits `interval` for its next cycle
+    pub fn waiting(&self, interval: Duration) {
+        *self.interval.lock().unwrap() = interval;
+        self.state.lock().unwrap().intended_start = Some(Instant::now() + interval);
+    }
+
+    /// Called at the top of every cycle
+    pub fn cycle_started(&self) {
+        let now = Instant::now();
+        let interval = *self.interval.lock().unwrap();
+        let mut state = self.state.lock().unwrap();
+        let wall_now = Utc::now();
+
+        let drift = match state.intended_start.take() {
+            Some(intended) if now < intended => {
+                state.snapshot.early_starts += 1;
+                state.snapshot.last_intended_start =
+                    Some(wall_now + chrono::Duration::from_std(intended - now).unwrap_or_default());
+                Duration::ZERO
+            }
+            Some(intended) => {
+                let drift = now - intended;
+                state.snapshot.last_intended_start =
+                    Some(wall_now - chrono::Duration::from_std(drift).unwrap_or_default());
+                drift
+            }
+            None => {
+                state.snapshot.last_intended_start = Some(wall_now);
+                Duration::ZERO
+            }
+        };
+        state.snapshot.last_drift_ms = drift.as_millis() as u64;
+        state.snapshot.accumulated_drift_ms += drift.as_millis() as u64;
+
+        if let Some(last_start) = state.last_start {
+            let stall = (now - last_start).saturating_sub(interval).as_millis() as u64;
+            if stall > state.snapshot.longest_stall_ms {
+                state.snapshot.longest_stall_ms = stall;
+                state.snapshot.longest_stall_at = Some(wall_now);
+            }
+        }
+        state.last_start = Some(now);
+        state.snapshot.last_actual_start = Some(wall_now);
+        state.snapshot.cycles += 1;
+    }
+
+    pub fn snapshot(&self) -> SchedulerSnapshot {
+        self.state.lock().unwrap().snapshot.clone()
+    }
+}
+
+impl Default for SchedulerStats {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_scheduler_drift_and_stalls() {
+        let stats = SchedulerStats::new();
+        stats.cycle_started();
+
+        stats.waiting(Duration::from_millis(10));
+        std::thread::sleep(Duration::from_millis(40));
+        stats.cycle_started();
+        let snapshot = stats.snapshot();
+        assert_eq!(snapshot.cycles, 2);
+        assert!(snapshot.last_drift_ms >= 30);
+        assert!(snapshot.longest_stall_ms >= 30);
+
+        // Woken before the wait ended
+        stats.waiting(Duration::from_secs(60));
+        stats.cycle_started();
+        let snapshot = stats.snapshot();
+        assert_eq!(snapshot.early_starts, 1);
+        assert_eq!(snapshot.last_drift_ms, 0);
+        assert!(snapshot.accumulated_drift_ms >= 30);
+    }
+}
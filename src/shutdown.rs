@@ -0,0 +1,200 @@
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::{info, warn};
+
+use crate::config::ShutdownReportConfig;
+use crate::db::{AlertState, Storage};
+use crate::domain::Domain;
+use crate::relayer::http_client;
+
+/// Alerts scanned for unresolved ones; older alerts are resolved long ago in practice
+const ALERT_SCAN: usize = 100;
+
+/// Waits for Ctrl-C, or SIGTERM on Unix
+pub async fn wait_for_signal() {
+    #[cfg(unix)]
+    {
+        use tokio::signal::unix::{SignalKind, signal};
+        match signal(SignalKind::terminate()) {
+            Ok(mut terminate) => {
+                tokio::select! {
+                    _ = tokio::signal::ctrl_c() => {}
+                    _ = terminate.recv() => {}
+                }
+                return;
+            }
+            Err(e) => warn!("⚠️  Failed to listen for SIGTERM: {}", e),
+        }
+    }
+    let _ = tokio::signal::ctrl_c().await;
+}
+
+/// The state the process leaves behind, logged (and optionally posted) when it shuts down
+#[derive(Debug, Serialize)]
+pub struct ShutdownReport {
+    pub started_at: String,
+    pub stopped_at: String,
+    pub uptime_secs: i64,
+    pub domains: Vec<DomainShutdownReport>,
+}
+
+/// Counts that couldn't be read from storage are `None`
+#[derive(Debug, Serialize)]
+pub struct DomainShutdownReport {
+    pub domain: String,
+    /// Highest height in the proof history, i.e. the last one relayed in relayer mode
+    pub last_height: Option<u64>,
+    pub last_seq: Option<u64>,
+    /// Payloads still in the outbox, to be drained on the next start
+    pub pending_submissions: Option<usize>,
+    pub failed_submissions: Option<usize>,
+    pub unresolved_alerts: Option<usize>,
+    /// Database writes still queued, which are lost
+    pub queued_writes: usize,
+    /// Loop cycles since startup
+    pub cycles: u64,
+    /// Failures of every subsystem since startup
+    pub failures: u64,
+}
+
+impl ShutdownReport {
+    pub async fn collect(domains: &[Arc<Domain>], started_at: DateTime<Utc>) -> Self {
+        let stopped_at = Utc::now();
+        let mut reports = Vec::new();
+        for domain in domains {
+            reports.push(domain_report(domain).await);
+        }
+        ShutdownReport {
+            started_at: started_at.to_rfc3339(),
+            stopped_at: stopped_at.to_rfc3339(),
+            uptime_secs: (stopped_at - started_at).num_seconds(),
+            domains: reports,
+        }
+    }
+
+    /// Logs the report, then posts it as JSON to the configured webhook, if any
+    pub async fn publish(&self, config: &ShutdownReportConfig) {
+        info!(
+            "🛑 Shutting down after {}s with {} domains",
+            self.uptime_secs,
+            self.domains.len()
+        );
+        for report in &self.domains {
+            info!(
+                domain = %report.domain,
+                last_height = ?report.last_height,
+                pending_submissions = ?report.pending_submissions,
+                failed_submissions = ?report.failed_submissions,
+                unresolved_alerts = ?report.unresolved_alerts,
+                queued_writes = report.queued_writes,
+                cycles = report.cycles,
+                failures = report.failures,
+                "🛑 [{}] {}",
+                report.domain,
+                report.summary(),
+            );
+        }
+
+        let Some(url) = &config.webhook_url else {
+            return;
+        };
+        let client = match http_client(None, Some(Duration::from_secs(config.timeout_secs))) {
+            Ok(client) => client,
+            Err(e) => {
+                warn!("⚠️  Failed to build shutdown report client: {}", e);
+                return;
+            }
+        };
+        match client.post(url).json(self).send().await {
+            Ok(response) if response.status().is_success() => {
+                info!("📤 Shutdown report delivered")
+            }
+            Ok(response) => warn!(
+                "⚠️  Shutdown report webhook answered with status {}",
+                response.status()
+            ),
+            Err(e) => warn!(
+                "⚠️  Failed to deliver shutdown report{}",
+                // The URL may embed a token
+                e.status()
+                    .map(|status| format!(": status {}", status))
+                    .unwrap_or_default()
+            ),
+        }
+    }
+}
+
+impl DomainShutdownReport {
+    pub fn summary(&self) -> String {
+        format!(
+            "Last height {}, {} queued and {} dead-lettered payloads, {} unresolved alerts",
+            describe(self.last_height),
+            describe(self.pending_submissions),
+            describe(self.failed_submissions),
+            describe(self.unresolved_alerts),
+        )
+    }
+}
+
+/// Reads a domain's counts from its storage, also used by the `report` job
+pub async fn domain_report(domain: &Domain) -> DomainShutdownReport {
+    let latest = read(domain, "the latest proof", |db| db.get_latest_proof())
+        .await
+        .flatten();
+    let errors = domain.errors.snapshot();
+    let failures = [
+        errors.prover,
+        errors.registry,
+        errors.database,
+        errors.verifier,
+    ]
+    .iter()
+    .flatten()
+    .map(|error| error.count)
+    .sum();
+
+    DomainShutdownReport {
+        domain: domain.name().to_string(),
+        last_height: latest.as_ref().map(|record| record.height),
+        last_seq: latest.as_ref().map(|record| record.seq),
+        pending_submissions: read(domain, "queued submissions", |db| {
+            db.get_pending_submissions()
+        })
+        .await
+        .map(|pending| pending.len()),
+        failed_submissions: read(domain, "dead-lettered payloads", |db| {
+            db.get_failed_submissions()
+        })
+        .await
+        .map(|failed| failed.len()),
+        unresolved_alerts: read(domain, "alerts", |db| db.get_alerts(ALERT_SCAN))
+            .await
+            .map(|alerts| {
+                alerts
+                    .iter()
+                    .filter(|alert| alert.state != AlertState::Resolved)
+                    .count()
+            }),
+        queued_writes: domain.writer.depth(),
+        cycles: domain.scheduler.snapshot().cycles,
+        failures,
+    }
+}
+
+async fn read<T: Send + 'static>(
+    domain: &Domain,
+    what: &str,
+    read: impl FnOnce(&dyn Storage) -> anyhow::Result<T> + Send + 'static,
+) -> Option<T> {
+    domain
+        .read(read)
+        .await
+        .inspect_err(|e| warn!("⚠️  [{}] Failed to read {}: {}", domain.name(), what, e))
+        .ok()
+}
+
+fn describe<T: std::fmt::Display>(value: Option<T>) -> String {
+    value.map_or_else(|| "unknown".to_string(), |value| value.to_string())
+}
@@ -0,0 +1,842 @@
+use anyhow::{Result, anyhow};
+use async_trait::async_trait;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Weak};
+use std::time::Duration;
+use tracing::{debug, error, info, warn};
+
+use crate::aws::{AwsCredentials, sign_v4};
+use crate::config::{
+    DomainConfig, EndpointHttp, MODE, PayloadWire, RegistryConfirmation, RetryPolicy, SinkConfig,
+};
+use crate::cosmwasm::{CosmwasmSink, CosmwasmSinkConfig, load_mnemonic};
+use crate::db::{ConfirmationState, FailedSubmission, RelayAttempt, RelayConfirmation};
+use crate::encryption::PayloadEncryptor;
+use crate::errors::{ErrorTracker, RelayerError, Subsystem};
+use crate::evm::{EvmSink, EvmSinkConfig};
+use crate::identity::PayloadSigner;
+use crate::journal::SubmissionJournal;
+use crate::registry::{RegistryRejection, ResponseClass, SubmissionStatus};
+use crate::relayer::{
+    await_response, decode_public_values, endpoint_client, http_client, post_payload, read_body,
+    send,
+};
+use crate::writer::DbWriter;
+
+/// Somewhere a relayed payload is delivered to
+#[async_trait]
+pub trait ProofSink: Send + Sync {
+    /// Identifies the sink in logs, without credentials
+    fn describe(&self) -> String;
+
+    async fn submit(&self, payload: &serde_json::Value, height: u64) -> Result<()>;
+
+    /// Waits for background work `submit` left running, such as confirmation polls
+    async fn settle(&self) {}
+
+    /// Whether the target only keeps the latest proof. As the primary sink, its queued
+    /// payloads are then pruned to the highest height rather than submitted in order.
+    fn latest_only(&self) -> bool {
+        false
+    }
+}
+
+/// Posts payloads to a registry, retrying per the domain's retry policy
+pub struct RegistrySink {
+    pub domain: String,
+    pub endpoint: String,
+    /// How the registry is reached; the validate-only endpoint is reached the same way
+    pub http: EndpointHttp,
+    pub retry: RetryPolicy,
+    pub journal: Option<Arc<SubmissionJournal>>,
+    /// Records every attempt and the registry's response in the domain's `relay_attempts`,
+    /// and confirmations in its `relay_confirmations`
+    pub attempts: Option<DbWriter>,
+    /// Encoding and compression of the payloads, also used for the validate-only endpoint
+    pub wire: PayloadWire,
+    /// Signs each payload as this relayer before it is encrypted
+    pub signer: Option<Arc<PayloadSigner>>,
+    /// Encrypts each payload to the registry's key before it is posted
+    pub encryptor: Option<PayloadEncryptor>,
+    pub latest_only: bool,
+    /// Validate-only endpoint the first payload is checked against before it is submitted
+    pub validate_endpoint: Option<String>,
+    /// Set once a payload has passed validation
+    pub validated: AtomicBool,
+    /// Status endpoint polled after each submission, for registries that process them
+    /// asynchronously, with how it is reached
+    pub confirmation: Option<(RegistryConfirmation, EndpointHttp)>,
+    /// Decodes the root the registry must confirm
+    pub mode: MODE,
+    /// Dead-letters payloads the registry never confirms; set for the primary target
+    pub dead_letters: bool,
+    /// Confirmation polls that may still be running
+    pub watchers: std::sync::Mutex<Vec<tokio::task::JoinHandle<()>>>,
+    /// The sink itself, which confirmation polls hold on to
+    this: Weak<RegistrySink>,
+}
+
+impl RegistrySink {
+    fn new(
+        config: &DomainConfig,
+        endpoint: &str,
+        latest_only: bool,
+        validate_endpoint: Option<&str>,
+        retry: &RetryPolicy,
+        journal: Option<Arc<SubmissionJournal>>,
+        attempts: Option<&DbWriter>,
+        primary: bool,
+    ) -> Result<Arc<Self>> {
+        let encryptor = config
+            .payload_encryption
+            .get(endpoint)
+            .map(PayloadEncryptor::new)
+            .transpose()?;
+        let signer = config
+            .payload_signing
+            .as_ref()
+            .map(|signing| PayloadSigner::new(signing).map(Arc::new))
+            .transpose()?;
+        if let Some(signer) = &signer {
+            info!(
+                "🔏 [{}] Signing payloads to {} as {}",
+                config.name,
+                endpoint,
+                signer.describe()
+            );
+        }
+        Ok(Arc::new_cyclic(|this| RegistrySink {
+            domain: config.name.clone(),
+            endpoint: endpoint.to_string(),
+            http: config.http_for(endpoint),
+            wire: config.wire_for(endpoint),
+            retry: retry.clone(),
+            journal,
+            attempts: attempts.cloned(),
+            signer,
+            encryptor,
+            latest_only,
+            validate_endpoint: validate_endpoint.map(str::to_string),
+            validated: AtomicBool::new(false),
+            confirmation: config
+                .registry_confirmations
+                .get(endpoint)
+                .map(|confirmation| {
+                    let http = config.http_for(&confirmation.status_endpoint);
+                    (confirmation.clone(), http)
+                }),
+            mode: config.mode,
+            dead_letters: primary,
+            watchers: Default::default(),
+            this: this.clone(),
+        }))
+    }
+
+    /// Signs, encrypts and posts the payload, retrying per the retry policy
+    async fn deliver(&self, payload: &serde_json::Value, height: u64) -> Result<()> {
+        // Signed at submission, so `signed_at` is fresh even for a payload that was queued
+        let signed = match &self.signer {
+            Some(signer) => Some(signer.sign(payload).await?),
+            None => None,
+        };
+        let payload = signed.as_ref().unwrap_or(payload);
+        let encrypted = match &self.encryptor {
+            Some(encryptor) => Some(encryptor.encrypt(payload)?),
+            None => None,
+        };
+        let payload = encrypted.as_ref().unwrap_or(payload);
+
+        if let Some(validate_endpoint) = &self.validate_endpoint
+            && !self.validated.load(Ordering::Relaxed)
+        {
+            self.preflight(validate_endpoint, payload, height).await?;
+        }
+
+        let journal = self
+            .journal
+            .as_deref()
+            .map(|journal| (journal, self.domain.as_str()));
+        send(
+            payload,
+            height,
+            &self.endpoint,
+            &self.http,
+            &self.wire,
+            &self.retry,
+            journal,
+            self.attempts.as_ref(),
+        )
+        .await
+    }
+
+    /// Polls the status endpoint until the registry confirms `root` at `height`, fails
+    /// the submission, or the timeout passes. Each poll gives up after the endpoint's
+    /// read timeout, so a hung status server can't hold the timeout off.
+    async fn await_confirmation(
+        &self,
+        confirmation: &RegistryConfirmation,
+        http: &EndpointHttp,
+        height: u64,
+        root: &str,
+    ) -> Result<()> {
+        let client = endpoint_client(http)?;
+        let timeout = Duration::from_secs(confirmation.timeout_secs);
+        let read_timeout = http.timeouts.read_timeout();
+
+        let polls = async {
+            loop {
+                tokio::time::sleep(Duration::from_secs(confirmation.interval_secs)).await;
+                let request = client
+                    .get(&confirmation.status_endpoint)
+                    .query(&[("height", height.to_string()), ("root", root.to_string())]);
+                let response = match await_response(request, read_timeout).await {
+                    Ok(response) => response,
+                    Err(e) => {
+                        debug!("Registry status check at height {} failed: {}", height, e);
+                        continue;
+                    }
+                };
+                let status = response.status();
+                let body = match read_body(response, read_timeout).await {
+                    Ok(body) => String::from_utf8_lossy(&body).into_owned(),
+                    Err(e) => {
+                        debug!("Registry status check at height {} failed: {}", height, e);
+                        continue;
+                    }
+                };
+                match SubmissionStatus::of(status, &body, root) {
+                    SubmissionStatus::Confirmed => return Ok(()),
+                    SubmissionStatus::Failed(reason) => {
+                        return Err(anyhow!(
+                            "Registry failed the proof at height {}: {}",
+                            height,
+                            reason
+                        ));
+                    }
+                    SubmissionStatus::Pending => {}
+                }
+            }
+        };
+
+        match tokio::time::timeout(timeout, polls).await {
+            Ok(result) => result,
+            Err(_) => Err(anyhow!(
+                "Registry did not confirm the proof at height {} within {:?}",
+                height,
+                timeout
+            )),
+        }
+    }
+
+    /// Waits for the registry to confirm a delivered payload, submitting it again after
+    /// a failed or missing confirmation. Once `max_resubmissions` runs out the proof is
+    /// recorded as failed and, for the primary target, dead-lettered.
+    async fn confirm(self: Arc<Self>, payload: serde_json::Value, mut record: RelayConfirmation) {
+        let Some((confirmation, http)) = &self.confirmation else {
+            return;
+        };
+        let height = record.height;
+
+        loop {
+            let result = self
+                .await_confirmation(confirmation, http, height, &record.root)
+                .await;
+            record.updated_at = chrono::Utc::now();
+            let error = match result {
+                Ok(()) => {
+                    info!(
+                        "✅ [{}] Registry confirmed the proof at height {}",
+                        self.domain, height
+                    );
+                    record.state = ConfirmationState::Confirmed;
+                    record.error = None;
+                    self.record_confirmation(record);
+                    return;
+                }
+                Err(e) if record.submissions <= confirmation.max_resubmissions => {
+                    warn!(
+                        "⚠️  [{}] {}, submitting again ({}/{})",
+                        self.domain, e, record.submissions, confirmation.max_resubmissions
+                    );
+                    record.error = Some(e.to_string());
+                    record.submissions += 1;
+                    match self.deliver(&payload, height).await {
+                        Ok(()) => {
+                            record.updated_at = chrono::Utc::now();
+                            self.record_confirmation(record.clone());
+                            continue;
+                        }
+                        Err(e) => e,
+                    }
+                }
+                Err(e) => e,
+            };
+
+            error!(
+                "❌ [{}] {}, giving up after {} submissions",
+                self.domain, error, record.submissions
+            );
+            record.state = ConfirmationState::Failed;
+            record.error = Some(error.to_string());
+            self.record_confirmation(record.clone());
+            self.dead_letter(&payload, &record);
+            return;
+        }
+    }
+
+    /// Moves a payload the registry never confirmed to the dead-letter queue, where an
+    /// operator can resubmit it
+    fn dead_letter(&self, payload: &serde_json::Value, record: &RelayConfirmation) {
+        let Some(attempts) = self.attempts.as_ref().filter(|_| self.dead_letters) else {
+            return;
+        };
+        let failed = FailedSubmission {
+            id: 0,
+            height: record.height,
+            payload: payload.to_string(),
+            error: record.error.clone().unwrap_or_default(),
+            attempts: record.submissions,
+            failed_at: chrono::Utc::now(),
+        };
+        let domain = self.domain.clone();
+        attempts.submit("dead-letter unconfirmed payload", move |db| {
+            let id = db.record_failed_submission(&failed)?;
+            warn!(
+                "📮 [{}] Unconfirmed payload for height {} moved to the dead-letter queue as #{}",
+                domain, failed.height, id
+            );
+            Ok(())
+        });
+    }
+
+    fn record_confirmation(&self, confirmation: RelayConfirmation) {
+        if let Some(attempts) = &self.attempts {
+            attempts.submit("record relay confirmation", move |db| {
+                db.record_relay_confirmation(&confirmation)
+            });
+        }
+    }
+
+    /// Posts the payload to the validate-only endpoint. A refusal fails the submission;
+    /// a validator that is down or rate limited only skips the check until next time.
+    async fn preflight(
+        &self,
+        validate_endpoint: &str,
+        payload: &serde_json::Value,
+        height: u64,
+    ) -> Result<()> {
+        let client = endpoint_client(&self.http)?;
+        let response = match post_payload(&client, validate_endpoint, payload, &self.wire).await {
+            Ok(response) => response,
+            Err(e) => {
+                warn!(
+                    "⚠️  [{}] Could not validate the payload at height {}, submitting anyway: {}",
+                    self.domain, height, e
+                );
+                return Ok(());
+            }
+        };
+
+        let class = ResponseClass::of(&response);
+        match class {
+            ResponseClass::Accepted | ResponseClass::Duplicate => {
+                info!(
+                    "✅ [{}] Registry validated the payload at height {}",
+                    self.domain, height
+                );
+                self.validated.store(true, Ordering::Relaxed);
+                Ok(())
+            }
+            ResponseClass::RateLimited | ResponseClass::ServerError => {
+                warn!(
+                    "⚠️  [{}] Registry validation unavailable (status {}), submitting anyway",
+                    self.domain, response.status
+                );
+                Ok(())
+            }
+            ResponseClass::InvalidVk | ResponseClass::Rejected => {
+                error!(
+                    "🚨 [{}] Registry validation refused the payload at height {}, not submitting",
+                    self.domain, height
+                );
+                Err(RelayerError::from(RegistryRejection {
+                    class,
+                    status: response.status,
+                    body: response.body,
+                })
+                .into())
+            }
+        }
+    }
+}
+
+#[async_trait]
+impl ProofSink for RegistrySink {
+    fn describe(&self) -> String {
+        format!("registry {}", self.endpoint)
+    }
+
+    fn latest_only(&self) -> bool {
+        self.latest_only
+    }
+
+    async fn submit(&self, payload: &serde_json::Value, height: u64) -> Result<()> {
+        if self.confirmation.is_none() {
+            return self.deliver(payload, height).await;
+        }
+
+        let public_values = payload["public_values"]
+            .as_str()
+            .ok_or_else(|| RelayerError::decode("payload", "no public_values field"))?;
+        let public_values =
+            hex::decode(public_values).map_err(|e| RelayerError::decode("payload", e))?;
+        let (_, root) = decode_public_values(self.mode, &public_values)?;
+
+        self.deliver(payload, height).await?;
+        let record = RelayConfirmation {
+            height,
+            endpoint: self.endpoint.clone(),
+            root: hex::encode(root),
+            state: ConfirmationState::Pending,
+            submissions: 1,
+            error: None,
+            submitted_at: chrono::Utc::now(),
+            updated_at: chrono::Utc::now(),
+        };
+        self.record_confirmation(record.clone());
+
+        // Confirmation can take minutes, so the relay loop moves on once it's delivered
+        if let Some(sink) = self.this.upgrade() {
+            let watcher = tokio::spawn(sink.confirm(payload.clone(), record));
+            let mut watchers = self.watchers.lock().unwrap();
+            watchers.retain(|watcher| !watcher.is_finished());
+            watchers.push(watcher);
+        }
+        Ok(())
+    }
+
+    async fn settle(&self) {
+        let watchers = std::mem::take(&mut *self.watchers.lock().unwrap());
+        futures::future::join_all(watchers).await;
+    }
+}
+
+/// Stands in for a dry run's primary sink: logs what would be submitted and records it as
+/// a relay attempt without a response, but sends nothing
+pub struct DryRunSink {
+    pub domain: String,
+    /// Description of the sink that would have been submitted to
+    pub target: String,
+    pub latest_only: bool,
+    pub attempts: Option<DbWriter>,
+}
+
+#[async_trait]
+impl ProofSink for DryRunSink {
+    fn describe(&self) -> String {
+        format!("dry run of {}", self.target)
+    }
+
+    fn latest_only(&self) -> bool {
+        self.latest_only
+    }
+
+    async fn submit(&self, payload: &serde_json::Value, height: u64) -> Result<()> {
+        let payload_hash = SubmissionJournal::payload_hash(payload);
+        info!(
+            "🧪 [{}] Dry run: would submit the proof at height {} to {} (payload {})",
+            self.domain, height, self.target, payload_hash
+        );
+        if let Some(attempts) = &self.attempts {
+            let record = RelayAttempt {
+                id: 0,
+                height,
+                endpoint: self.target.clone(),
+                attempt: 1,
+                payload_hash,
+                status: None,
+                response: "dry run, not sent".to_string(),
+                latency_ms: 0,
+                attempted_at: chrono::Utc::now(),
+            };
+            attempts.submit("record relay attempt", move |db| {
+                db.insert_relay_attempt(&record).map(|_| ())
+            });
+        }
+        Ok(())
+    }
+}
+
+/// Writes each payload to `<dir>/<domain>/<height>.json`, replacing an earlier copy
+pub struct FileSink {
+    pub domain: String,
+    pub dir: PathBuf,
+}
+
+#[async_trait]
+impl ProofSink for FileSink {
+    fn describe(&self) -> String {
+        format!("file archive {}", self.dir.display())
+    }
+
+    async fn submit(&self, payload: &serde_json::Value, height: u64) -> Result<()> {
+        let dir = self.dir.join(&self.domain);
+        tokio::fs::create_dir_all(&dir).await?;
+
+        // Write then rename so readers never see a partial file
+        let path = dir.join(format!("{}.json", height));
+        let partial = dir.join(format!("{}.json.partial", height));
+        tokio::fs::write(&partial, serde_json::to_vec(payload)?).await?;
+        tokio::fs::rename(&partial, &path).await?;
+        Ok(())
+    }
+}
+
+/// Uploads each payload to `s3://<bucket>/<prefix><domain>/<height>.json`. Requests are
+/// signed with AWS Signature Version 4, so any S3-compatible store works via `endpoint`.
+pub struct S3Sink {
+    pub domain: String,
+    pub bucket: String,
+    pub region: String,
+    pub prefix: String,
+    /// Path-style base URL, e.g. for MinIO; virtual-hosted AWS S3 when unset
+    pub endpoint: Option<String>,
+    pub credentials: AwsCredentials,
+    pub client: reqwest::Client,
+}
+
+#[async_trait]
+impl ProofSink for S3Sink {
+    fn describe(&self) -> String {
+        format!("s3://{}/{}{}", self.bucket, self.prefix, self.domain)
+    }
+
+    async fn submit(&self, payload: &serde_json::Value, height: u64) -> Result<()> {
+        let key = format!("{}{}/{}.json", self.prefix, self.domain, height);
+        let url = match &self.endpoint {
+            Some(endpoint) => format!("{}/{}/{}", endpoint.trim_end_matches('/'), self.bucket, key),
+            None => format!(
+                "https://{}.s3.{}.amazonaws.com/{}",
+                self.bucket, self.region, key
+            ),
+        };
+        let url = reqwest::Url::parse(&url)?;
+
+        let body = serde_json::to_vec(payload)?;
+        let headers = sign_v4(
+            "PUT",
+            &url,
+            "s3",
+            &self.region,
+            Vec::new(),
+            &body,
+            &self.credentials,
+        );
+        let mut request = self
+            .client
+            .put(url)
+            .header("content-type", "application/json");
+        for (name, value) in headers {
+            request = request.header(name, value);
+        }
+
+        let response = request.body(body).send().await?;
+        if !response.status().is_success() {
+            let status = response.status();
+            let text = response.text().await.unwrap_or_default();
+            return Err(anyhow!("S3 responded with status {}: {}", status, text));
+        }
+        Ok(())
+    }
+}
+
+/// Where a domain's relayed proofs go. The primary target, `primary_sink` or else the
+/// registry at `registry_endpoint`, decides: dedup, the submission queue and the
+/// dead-letter queue all follow its result. Every other sink receives the same payloads
+/// in the background, and its failures are only logged and recorded against the
+/// registry subsystem.
+pub struct Sinks {
+    pub primary: Arc<dyn ProofSink>,
+    pub secondary: Vec<Arc<dyn ProofSink>>,
+    /// Deliveries to the secondary sinks that may still be running
+    pub deliveries: std::sync::Mutex<Vec<tokio::task::JoinHandle<()>>>,
+}
+
+impl Sinks {
+    /// Registry sinks record their attempts through `attempts`, when given
+    pub fn from_config(
+        config: &DomainConfig,
+        retry: &RetryPolicy,
+        journal: Option<Arc<SubmissionJournal>>,
+        attempts: Option<&DbWriter>,
+    ) -> Result<Self> {
+        let primary = primary_sink(config, retry, journal.clone(), attempts)?;
+        info!(
+            "📤 [{}] Submitting proofs to {}",
+            config.name,
+            primary.describe()
+        );
+
+        let secondary = config
+            .sinks
+            .iter()
+            .map(|sink| build(config, sink, retry, journal.clone(), attempts, false))
+            .collect::<Result<Vec<_>>>()?;
+        if config.dry_run && !secondary.is_empty() {
+            info!(
+                "🧪 [{}] Dry run: not delivering proofs to {}",
+                config.name,
+                secondary
+                    .iter()
+                    .map(|sink| sink.describe())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            );
+            return Ok(Sinks {
+                primary,
+                secondary: Vec::new(),
+                deliveries: Default::default(),
+            });
+        }
+        for sink in &secondary {
+            info!(
+                "🗄️  [{}] Also delivering proofs to {}",
+                config.name,
+                sink.describe()
+            );
+        }
+
+        Ok(Sinks {
+            primary,
+            secondary,
+            deliveries: Default::default(),
+        })
+    }
+
+    /// Hands the payload to every secondary sink without waiting for them
+    pub fn fan_out(&self, payload: &serde_json::Value, height: u64, errors: &Arc<ErrorTracker>) {
+        let mut deliveries = self.deliveries.lock().unwrap();
+        deliveries.retain(|delivery| !delivery.is_finished());
+        for sink in &self.secondary {
+            let (sink, payload, errors) = (sink.clone(), payload.clone(), errors.clone());
+            deliveries.push(tokio::spawn(async move {
+                match sink.submit(&payload, height).await {
+                    Ok(()) => info!(
+                        "✅ Delivered proof at height {} to {}",
+                        height,
+                        sink.describe()
+                    ),
+                    Err(e) => {
+                        error!(
+                            "❌ Failed to deliver proof at height {} to {}: {}",
+                            height,
+                            sink.describe(),
+                            e
+                        );
+                        errors.record(Subsystem::Registry, &e);
+                    }
+                }
+            }));
+        }
+    }
+
+    /// Waits for every delivery `fan_out` has started to finish
+    pub async fn settle(&self) {
+        let deliveries = std::mem::take(&mut *self.deliveries.lock().unwrap());
+        futures::future::join_all(deliveries).await;
+        self.primary.settle().await;
+        for sink in &self.secondary {
+            sink.settle().await;
+        }
+    }
+}
+
+/// The sink that decides whether a domain's proofs count as relayed. In a dry run it is
+/// still built, so its settings are checked, but only stood in for by a `DryRunSink`.
+pub fn primary_sink(
+    config: &DomainConfig,
+    retry: &RetryPolicy,
+    journal: Option<Arc<SubmissionJournal>>,
+    attempts: Option<&DbWriter>,
+) -> Result<Arc<dyn ProofSink>> {
+    let sink: Arc<dyn ProofSink> = match &config.primary_sink {
+        Some(sink) => build(config, sink, retry, journal, attempts, true)?,
+        None => RegistrySink::new(
+            config,
+            &config.registry_endpoint,
+            config.registry_latest_only,
+            config.registry_validate_endpoint.as_deref(),
+            retry,
+            journal,
+            attempts,
+            true,
+        )?,
+    };
+    if !config.dry_run {
+        return Ok(sink);
+    }
+    Ok(Arc::new(DryRunSink {
+        domain: config.name.clone(),
+        target: sink.describe(),
+        latest_only: sink.latest_only(),
+        attempts: attempts.cloned(),
+    }))
+}
+
+fn build(
+    config: &DomainConfig,
+    sink: &SinkConfig,
+    retry: &RetryPolicy,
+    journal: Option<Arc<SubmissionJournal>>,
+    attempts: Option<&DbWriter>,
+    primary: bool,
+) -> Result<Arc<dyn ProofSink>> {
+    let from_env = |name: &str| std::env::var(name).ok();
+    Ok(match sink {
+        SinkConfig::Registry {
+            endpoint,
+            latest_only,
+            validate_endpoint,
+        } => RegistrySink::new(
+            config,
+            endpoint,
+            *latest_only,
+            validate_endpoint.as_deref(),
+            retry,
+            journal,
+            attempts,
+            primary,
+        )?,
+        SinkConfig::File { dir } => Arc::new(FileSink {
+            domain: config.name.clone(),
+            dir: PathBuf::from(dir),
+        }),
+        SinkConfig::S3 {
+            bucket,
+            region,
+            prefix,
+            endpoint,
+            access_key_id,
+            secret_access_key,
+        } => {
+            let credentials = AwsCredentials::resolve(
+                access_key_id.as_deref(),
+                secret_access_key.as_deref(),
+                &format!("S3 sink for bucket {}", bucket),
+            )?;
+            let proxy = endpoint
+                .as_ref()
+                .and_then(|endpoint| config.socks5_proxies.get(endpoint));
+            Arc::new(S3Sink {
+                domain: config.name.clone(),
+                bucket: bucket.clone(),
+                region: region.clone(),
+                prefix: prefix.clone(),
+                endpoint: endpoint.clone(),
+                credentials,
+                client: http_client(proxy, Some(Duration::from_secs(30)))?,
+            })
+        }
+        SinkConfig::Evm {
+            rpc_url,
+            contract,
+            latest_only,
+            private_key,
+            function,
+            confirmations,
+            receipt_timeout_secs,
+            gas_multiplier,
+        } => {
+            let private_key = private_key
+                .clone()
+                .or_else(|| from_env("EVM_PRIVATE_KEY"))
+                .ok_or_else(|| anyhow!("EVM sink for {} has no private key", contract))?;
+            Arc::new(EvmSink::new(EvmSinkConfig {
+                rpc_url,
+                contract,
+                private_key: &private_key,
+                function,
+                confirmations: *confirmations,
+                receipt_timeout_secs: *receipt_timeout_secs,
+                gas_multiplier: *gas_multiplier,
+                latest_only: *latest_only,
+            })?)
+        }
+        SinkConfig::Cosmwasm {
+            lcd_url,
+            chain_id,
+            contract,
+            latest_only,
+            account_prefix,
+            key,
+            derivation_path,
+            fee,
+            execute_msg,
+            confirm_timeout_secs,
+        } => {
+            let mnemonic = load_mnemonic(key.as_ref(), contract)?;
+            Arc::new(CosmwasmSink::new(CosmwasmSinkConfig {
+                lcd_url,
+                chain_id,
+                contract,
+                account_prefix,
+                mnemonic: &mnemonic,
+                derivation_path,
+                fee: fee.clone(),
+                execute_msg,
+                confirm_timeout_secs: *confirm_timeout_secs,
+                latest_only: *latest_only,
+                client: http_client(
+                    config.socks5_proxies.get(lcd_url),
+                    Some(Duration::from_secs(30)),
+                )?,
+            })?)
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[tokio::test]
+    async fn test_dry_run_sends_nothing() -> Result<()> {
+        let config = DomainConfig {
+            // Nothing listens here, so a real submission would fail
+            registry_endpoint: "http://127.0.0.1:9/registry".to_string(),
+            registry_latest_only: true,
+            dry_run: true,
+            ..DomainConfig::default()
+        };
+        let sinks = Sinks::from_config(&config, &RetryPolicy::default(), None, None)?;
+        assert_eq!(
+            sinks.primary.describe(),
+            "dry run of registry http://127.0.0.1:9/registry"
+        );
+        assert!(sinks.primary.latest_only());
+        sinks.primary.submit(&json!({ "proof": "01" }), 7).await?;
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_file_sink_writes_payload_by_height() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let sink = FileSink {
+            domain: "test".to_string(),
+            dir: dir.path().to_path_buf(),
+        };
+
+        sink.submit(&json!({ "proof": "01" }), 7).await?;
+        sink.submit(&json!({ "proof": "02" }), 7).await?;
+
+        let written = std::fs::read_to_string(dir.path().join("test").join("7.json"))?;
+        assert_eq!(
+            serde_json::from_str::<serde_json::Value>(&written)?,
+            json!({ "proof": "02" })
+        );
+        assert!(!dir.path().join("test").join("7.json.partial").exists());
+        Ok(())
+    }
+}
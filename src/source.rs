@@ -0,0 +1,416 @@
+use anyhow::{Result, anyhow};
+use async_trait::async_trait;
+use sp1_sdk::SP1ProofWithPublicValues;
+use std::time::Duration;
+use tracing::{debug, info, warn};
+
+use crate::config::{EndpointHttp, ProofSourceKind};
+use crate::relayer::{await_response, endpoint_client, read_body};
+
+/// Somewhere the latest proof can be fetched from. The relay and health-check loops
+/// only see this trait, so a prover deployment with a different interface needs a new
+/// implementation (and a `ProofSourceKind`) rather than changes to the loops.
+#[async_trait]
+pub trait ProofSource: Send + Sync {
+    async fn fetch(&self) -> Result<SP1ProofWithPublicValues>;
+
+    /// Whether `fetch_at` is implemented, so height gaps can be backfilled
+    fn serves_heights(&self) -> bool {
+        false
+    }
+
+    /// The proof at one height, or `None` if the prover never produced one there. Used
+    /// for backfill; sources that only serve the latest proof keep this default.
+    async fn fetch_at(&self, height: u64) -> Result<Option<SP1ProofWithPublicValues>> {
+        Err(anyhow!(
+            "Proof source can't fetch proofs by height (wanted {})",
+            height
+        ))
+    }
+}
+
+/// The prover answered, but has no proof yet, e.g. for an epoch that just began. Not a
+/// failure: the loops wait for the next poll without counting it against the prover.
+#[derive(Debug, Clone)]
+pub struct NoNewProof {
+    pub endpoint: String,
+}
+
+impl std::fmt::Display for NoNewProof {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Prover {} has no proof yet", self.endpoint)
+    }
+}
+
+impl std::error::Error for NoNewProof {}
+
+/// Prover API versions this relayer has a codec for, oldest first. Provers that
+/// predate `/version` speak the first one.
+const KNOWN_API_VERSIONS: &[(u64, ProofSourceKind)] = &[
+    (1, ProofSourceKind::HexHttp),
+    (2, ProofSourceKind::BinaryHttp),
+    (3, ProofSourceKind::BatchedHttp),
+];
+
+/// Builds the source for one configured prover endpoint. `auto` endpoints whose version
+/// hasn't been detected use the legacy hex codec.
+pub fn build(kind: ProofSourceKind, endpoint: &str, http: EndpointHttp) -> Box<dyn ProofSource> {
+    match kind {
+        ProofSourceKind::Auto | ProofSourceKind::HexHttp => Box::new(HexHttpSource {
+            endpoint: endpoint.to_string(),
+            http,
+        }),
+        ProofSourceKind::JsonHttp => Box::new(JsonHttpSource {
+            endpoint: endpoint.to_string(),
+            http,
+        }),
+        ProofSourceKind::BinaryHttp => Box::new(BinaryHttpSource {
+            endpoint: endpoint.to_string(),
+            http,
+        }),
+        ProofSourceKind::BatchedHttp => Box::new(BatchedHttpSource {
+            endpoint: endpoint.to_string(),
+            http,
+        }),
+        ProofSourceKind::File => Box::new(FileSource {
+            path: endpoint.to_string(),
+        }),
+    }
+}
+
+/// GET returning the hex encoding of the proof's JSON, as served by the lightwave provers
+pub struct HexHttpSource {
+    pub endpoint: String,
+    pub http: EndpointHttp,
+}
+
+#[async_trait]
+impl ProofSource for HexHttpSource {
+    async fn fetch(&self) -> Result<SP1ProofWithPublicValues> {
+        let response = get(&self.endpoint, &self.http).await?;
+        let body = text(response, &self.http).await?;
+        info!("📦 Received hex string of length: {}", body.len());
+        parse_hex(&body)
+    }
+
+    fn serves_heights(&self) -> bool {
+        true
+    }
+
+    async fn fetch_at(&self, height: u64) -> Result<Option<SP1ProofWithPublicValues>> {
+        match get_at(&self.endpoint, height, &self.http).await? {
+            Some(response) => parse_hex(&text(response, &self.http).await?).map(Some),
+            None => Ok(None),
+        }
+    }
+}
+
+/// GET returning the proof's JSON directly
+pub struct JsonHttpSource {
+    pub endpoint: String,
+    pub http: EndpointHttp,
+}
+
+#[async_trait]
+impl ProofSource for JsonHttpSource {
+    async fn fetch(&self) -> Result<SP1ProofWithPublicValues> {
+        let response = get(&self.endpoint, &self.http).await?;
+        let body = text(response, &self.http).await?;
+        info!("📦 Received proof JSON of length: {}", body.len());
+        parse_json(&body)
+    }
+
+    fn serves_heights(&self) -> bool {
+        true
+    }
+
+    async fn fetch_at(&self, height: u64) -> Result<Option<SP1ProofWithPublicValues>> {
+        match get_at(&self.endpoint, height, &self.http).await? {
+            Some(response) => parse_json(&text(response, &self.http).await?).map(Some),
+            None => Ok(None),
+        }
+    }
+}
+
+/// GET returning the bincode-encoded proof
+pub struct BinaryHttpSource {
+    pub endpoint: String,
+    pub http: EndpointHttp,
+}
+
+#[async_trait]
+impl ProofSource for BinaryHttpSource {
+    async fn fetch(&self) -> Result<SP1ProofWithPublicValues> {
+        let response = get(&self.endpoint, &self.http).await?;
+        let body = read_body(response, self.http.timeouts.read_timeout()).await?;
+        info!("📦 Received binary proof of length: {}", body.len());
+        let proof = bincode::deserialize(&body)?;
+        info!("✅ Successfully parsed proof");
+        Ok(proof)
+    }
+
+    fn serves_heights(&self) -> bool {
+        true
+    }
+
+    async fn fetch_at(&self, height: u64) -> Result<Option<SP1ProofWithPublicValues>> {
+        match get_at(&self.endpoint, height, &self.http).await? {
+            Some(response) => {
+                let body = read_body(response, self.http.timeouts.read_timeout()).await?;
+                Ok(Some(bincode::deserialize(&body)?))
+            }
+            None => Ok(None),
+        }
+    }
+}
+
+/// GET returning a JSON array of hex-encoded proofs, newest last. Only the newest is
+/// relayed; the loop never needs the older ones. A single height is served hex encoded.
+pub struct BatchedHttpSource {
+    pub endpoint: String,
+    pub http: EndpointHttp,
+}
+
+#[async_trait]
+impl ProofSource for BatchedHttpSource {
+    async fn fetch(&self) -> Result<SP1ProofWithPublicValues> {
+        let response = get(&self.endpoint, &self.http).await?;
+        let batch: Vec<String> =
+            serde_json::from_slice(&read_body(response, self.http.timeouts.read_timeout()).await?)?;
+        info!("📦 Received batch of {} proofs", batch.len());
+        let newest = batch.last().ok_or_else(|| NoNewProof {
+            endpoint: self.endpoint.clone(),
+        })?;
+        parse_hex(newest)
+    }
+
+    fn serves_heights(&self) -> bool {
+        true
+    }
+
+    async fn fetch_at(&self, height: u64) -> Result<Option<SP1ProofWithPublicValues>> {
+        match get_at(&self.endpoint, height, &self.http).await? {
+            Some(response) => parse_hex(&text(response, &self.http).await?).map(Some),
+            None => Ok(None),
+        }
+    }
+}
+
+/// A local file holding the proof as JSON or hex-encoded JSON, re-read on every fetch
+/// so an external process can replace it
+pub struct FileSource {
+    pub path: String,
+}
+
+#[async_trait]
+impl ProofSource for FileSource {
+    async fn fetch(&self) -> Result<SP1ProofWithPublicValues> {
+        info!("🔍 Reading proof from {}", self.path);
+        let body = tokio::fs::read_to_string(&self.path)
+            .await
+            .map_err(|e| anyhow!("Failed to read {}: {}", self.path, e))?;
+
+        let body = body.trim();
+        if body.starts_with('{') {
+            parse_json(body)
+        } else {
+            parse_hex(body)
+        }
+    }
+}
+
+async fn get(endpoint: &str, http: &EndpointHttp) -> Result<reqwest::Response> {
+    info!("🔍 Fetching proof from {}", endpoint);
+
+    let client = endpoint_client(http)?;
+    let response = await_response(client.get(endpoint), http.timeouts.read_timeout()).await?;
+
+    info!("📡 Received response with status: {}", response.status());
+
+    // Provers answer 404 or 204 until the first proof of a new epoch is ready
+    if matches!(
+        response.status(),
+        reqwest::StatusCode::NOT_FOUND | reqwest::StatusCode::NO_CONTENT
+    ) {
+        return Err(NoNewProof {
+            endpoint: endpoint.to_string(),
+        }
+        .into());
+    }
+    if !response.status().is_success() {
+        return Err(anyhow!(
+            "HTTP request failed with status: {}",
+            response.status()
+        ));
+    }
+
+    Ok(response)
+}
+
+/// The response body as text, read within the endpoint's read timeout
+async fn text(response: reqwest::Response, http: &EndpointHttp) -> Result<String> {
+    let body = read_body(response, http.timeouts.read_timeout()).await?;
+    Ok(String::from_utf8_lossy(&body).into_owned())
+}
+
+/// GET `/proof/{height}` on the prover behind `endpoint`, or `None` if it answers 404
+async fn get_at(
+    endpoint: &str,
+    height: u64,
+    http: &EndpointHttp,
+) -> Result<Option<reqwest::Response>> {
+    let url = reqwest::Url::parse(endpoint)?.join(&format!("/proof/{}", height))?;
+    debug!("🔍 Fetching proof at height {} from {}", height, url);
+
+    let client = endpoint_client(http)?;
+    let response = await_response(client.get(url.clone()), http.timeouts.read_timeout()).await?;
+    if response.status() == reqwest::StatusCode::NOT_FOUND {
+        return Ok(None);
+    }
+    if !response.status().is_success() {
+        return Err(anyhow!(
+            "{} responded with status {}",
+            url,
+            response.status()
+        ));
+    }
+
+    Ok(Some(response))
+}
+
+/// Asks a prover for its API version, from the `X-Prover-Api-Version` header or the
+/// body of `GET /version` (plain text, or JSON with a `version` field). Provers that
+/// predate the endpoint answer 404 and report `None`.
+pub async fn detect_api_version(endpoint: &str, http: &EndpointHttp) -> Result<Option<String>> {
+    let url = reqwest::Url::parse(endpoint)?.join("/version")?;
+    let client = endpoint_client(http)?;
+    let response = await_response(client.get(url.clone()), Duration::from_secs(5)).await?;
+
+    if let Some(version) = response
+        .headers()
+        .get("x-prover-api-version")
+        .and_then(|value| value.to_str().ok())
+    {
+        return Ok(Some(version.trim().to_string()));
+    }
+    if response.status() == reqwest::StatusCode::NOT_FOUND {
+        return Ok(None);
+    }
+    if !response.status().is_success() {
+        return Err(anyhow!(
+            "{} responded with status {}",
+            url,
+            response.status()
+        ));
+    }
+
+    let body = response.text().await?;
+    let version = match serde_json::from_str::<serde_json::Value>(&body) {
+        Ok(serde_json::Value::Object(fields)) => match fields.get("version") {
+            Some(serde_json::Value::String(version)) => version.clone(),
+            Some(version) => version.to_string(),
+            None => return Err(anyhow!("{} has no version field", url)),
+        },
+        Ok(serde_json::Value::Number(version)) => version.to_string(),
+        _ => body.trim().trim_matches('"').to_string(),
+    };
+    Ok(Some(version))
+}
+
+/// Picks the codec for a prover API version. Versions newer than any known codec get
+/// the newest one, with a warning.
+pub fn codec_for(endpoint: &str, version: Option<&str>) -> ProofSourceKind {
+    let (oldest, newest) = (
+        KNOWN_API_VERSIONS[0],
+        KNOWN_API_VERSIONS[KNOWN_API_VERSIONS.len() - 1],
+    );
+    let Some(version) = version else {
+        return oldest.1;
+    };
+
+    let major = version
+        .trim_start_matches('v')
+        .split('.')
+        .next()
+        .and_then(|major| major.parse::<u64>().ok());
+    let Some(major) = major else {
+        warn!(
+            "⚠️  Prover {} reported unrecognised API version {:?}, using the legacy codec",
+            endpoint, version
+        );
+        return oldest.1;
+    };
+
+    match KNOWN_API_VERSIONS.iter().find(|(known, _)| *known == major) {
+        Some((_, codec)) => *codec,
+        None if major > newest.0 => {
+            warn!(
+                "⚠️  Prover {} speaks API version {}, newer than any known codec; trying version {}",
+                endpoint, version, newest.0
+            );
+            newest.1
+        }
+        None => oldest.1,
+    }
+}
+
+/// Parses a proof in any encoding a source can return: JSON, bincode, or either one
+/// hex encoded
+pub fn parse_any(bytes: &[u8]) -> Result<SP1ProofWithPublicValues> {
+    if let Ok(text) = std::str::from_utf8(bytes) {
+        let text = text.trim();
+        if text.starts_with('{') {
+            return parse_json(text);
+        }
+        let hex = text.trim_start_matches("0x");
+        if !hex.is_empty() && hex.bytes().all(|byte| byte.is_ascii_hexdigit()) {
+            return parse_any(&hex::decode(hex)?);
+        }
+    }
+    let proof = bincode::deserialize(bytes)
+        .map_err(|e| anyhow!("Input is neither JSON, hex nor a bincode proof: {}", e))?;
+    info!("✅ Successfully parsed proof");
+    Ok(proof)
+}
+
+fn parse_hex(body: &str) -> Result<SP1ProofWithPublicValues> {
+    let bytes = hex::decode(body.trim())?;
+    let proof = serde_json::from_slice(&bytes)?;
+    info!("✅ Successfully parsed proof");
+    Ok(proof)
+}
+
+fn parse_json(body: &str) -> Result<SP1ProofWithPublicValues> {
+    let proof = serde_json::from_str(body)?;
+    info!("✅ Successfully parsed proof");
+    Ok(proof)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_codec_for_version() {
+        let endpoint = "http://prover";
+        assert_eq!(codec_for(endpoint, None), ProofSourceKind::HexHttp);
+        assert_eq!(codec_for(endpoint, Some("1")), ProofSourceKind::HexHttp);
+        assert_eq!(
+            codec_for(endpoint, Some("v2.3.0")),
+            ProofSourceKind::BinaryHttp
+        );
+        assert_eq!(
+            codec_for(endpoint, Some("3.0")),
+            ProofSourceKind::BatchedHttp
+        );
+        // Newer than anything known falls forward to the newest codec
+        assert_eq!(
+            codec_for(endpoint, Some("9.1")),
+            ProofSourceKind::BatchedHttp
+        );
+        assert_eq!(
+            codec_for(endpoint, Some("nightly")),
+            ProofSourceKind::HexHttp
+        );
+    }
+}
@@ -0,0 +1,259 @@
+use anyhow::{Result, anyhow};
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::sync::Mutex;
+use std::time::Duration;
+use tracing::{info, warn};
+
+use crate::db::MetricSample;
+use crate::domain::{Domain, DomainSet};
+use crate::relayer::decode_public_values;
+
+/// Stored proofs read per query by a history backfill
+const BACKFILL_BATCH: usize = 500;
+
+/// Metrics recorded in `metric_samples`, as named by `/stats/timeseries?metric=`
+pub const METRICS: [&str; 6] = [
+    "relay_latency",
+    "prover_latency",
+    "latest_height",
+    "proof_age",
+    "db_write_queue_depth",
+    "scheduler_drift",
+];
+
+/// Duration of a domain's latest submission to its primary target, including retries
+#[derive(Default)]
+pub struct LastLatency {
+    last: Mutex<Option<Duration>>,
+}
+
+impl LastLatency {
+    pub fn record(&self, elapsed: Duration) {
+        *self.last.lock().unwrap() = Some(elapsed);
+    }
+
+    pub fn get(&self) -> Option<Duration> {
+        *self.last.lock().unwrap()
+    }
+}
+
+/// Current values of a domain's `METRICS`, skipping those with nothing to report yet.
+/// Latencies are in milliseconds, ages in seconds.
+pub async fn sample(domain: &Domain) -> Vec<MetricSample> {
+    let sampled_at = chrono::Utc::now();
+    let latest = domain.read(|db| db.get_latest_proof()).await.ok().flatten();
+    let prover_latency = domain
+        .provers
+        .snapshot()
+        .into_iter()
+        .find(|health| domain.provers.active_endpoint() == Some(health.endpoint.as_str()))
+        .and_then(|health| health.last_latency_ms);
+
+    let values = [
+        (
+            "relay_latency",
+            domain
+                .relay_latency
+                .get()
+                .map(|latency| latency.as_millis() as f64),
+        ),
+        ("prover_latency", prover_latency.map(|ms| ms as f64)),
+        (
+            "latest_height",
+            latest.as_ref().map(|latest| latest.height as f64),
+        ),
+        (
+            "proof_age",
+            latest
+                .as_ref()
+                .map(|latest| (sampled_at - latest.timestamp).num_seconds() as f64),
+        ),
+        ("db_write_queue_depth", Some(domain.writer.depth() as f64)),
+        (
+            "scheduler_drift",
+            Some(domain.scheduler.snapshot().last_drift_ms as f64),
+        ),
+    ];
+    values
+        .into_iter()
+        .filter_map(|(metric, value)| {
+            value.map(|value| MetricSample {
+                metric: metric.to_string(),
+                value,
+                sampled_at,
+            })
+        })
+        .collect()
+}
+
+/// Samples every domain and drops samples older than `retention_hours`. Run by the
+/// `metrics_snapshot` job, so domains added at runtime are picked up on its next run.
+pub async fn sample_all(domains: &DomainSet, retention_hours: u64) {
+    let cutoff = chrono::Utc::now() - chrono::Duration::hours(retention_hours as i64);
+    for domain in domains.all() {
+        let samples = sample(&domain).await;
+        let name = domain.name().to_string();
+        domain.writer.submit("record metric samples", move |db| {
+            db.insert_metric_samples(&samples)?;
+            if let Err(e) = db.prune_metric_samples(cutoff) {
+                warn!("⚠️  [{}] Failed to prune metric samples: {}", name, e);
+            }
+            Ok(())
+        });
+    }
+}
+
+/// What a history backfill added to one domain
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct HistoryBackfill {
+    pub domain: String,
+    /// Stored proofs read
+    pub proofs_read: u64,
+    /// `latest_height` samples added
+    pub samples_added: u64,
+    /// Proofs left out because their public values didn't decode
+    pub undecodable: u64,
+}
+
+/// Fills in the `latest_height` history from the stored proofs recorded before its first
+/// sample, so charts of heights relayed before `metrics_history` was enabled don't start
+/// from zero. Each proof's height is decoded from its public values and sampled at the
+/// time it was stored; proofs older than `retention_hours` are left out, as the next
+/// snapshot would prune them. Once the history reaches back to the oldest proof,
+/// running it again adds nothing.
+pub async fn backfill_history(domain: &Domain, retention_hours: u64) -> Result<HistoryBackfill> {
+    let cutoff = Utc::now() - chrono::Duration::hours(retention_hours as i64);
+    let first_sample = domain
+        .read(move |db| db.get_metric_samples("latest_height", cutoff))
+        .await?
+        .first()
+        .map(|sample| sample.sampled_at);
+
+    let mut backfill = HistoryBackfill {
+        domain: domain.name().to_string(),
+        ..HistoryBackfill::default()
+    };
+    let mut last_seq = 0;
+    loop {
+        let records = domain
+            .read(move |db| db.get_proofs_since_seq(last_seq, BACKFILL_BATCH))
+            .await?;
+        let Some(last) = records.last() else {
+            break;
+        };
+        last_seq = last.seq;
+        backfill.proofs_read += records.len() as u64;
+
+        let mut samples = Vec::new();
+        for record in records
+            .iter()
+            .filter(|record| needs_sample(record.timestamp, cutoff, first_sample))
+        {
+            match decode_public_values(domain.config.mode, &record.public_values) {
+                Ok((height, _)) => samples.push(MetricSample {
+                    metric: "latest_height".to_string(),
+                    value: height as f64,
+                    sampled_at: record.timestamp,
+                }),
+                Err(e) => {
+                    warn!(
+                        "⚠️  [{}] Leaving stored proof at height {} out of the history backfill: {}",
+                        domain.name(),
+                        record.height,
+                        e
+                    );
+                    backfill.undecodable += 1;
+                }
+            }
+        }
+        if !samples.is_empty() {
+            backfill.samples_added += samples.len() as u64;
+            domain
+                .writer
+                .write("backfill metric samples", move |db| {
+                    db.insert_metric_samples(&samples)
+                })
+                .await?;
+        }
+        if records.len() < BACKFILL_BATCH {
+            break;
+        }
+    }
+
+    info!(
+        "📈 [{}] Backfilled {} history samples from {} stored proofs",
+        domain.name(),
+        backfill.samples_added,
+        backfill.proofs_read
+    );
+    Ok(backfill)
+}
+
+/// Whether a proof stored at `stored_at` falls within the retention and before the
+/// recorded history
+fn needs_sample(
+    stored_at: DateTime<Utc>,
+    cutoff: DateTime<Utc>,
+    first_sample: Option<DateTime<Utc>>,
+) -> bool {
+    stored_at >= cutoff && first_sample.is_none_or(|first| stored_at < first)
+}
+
+/// Reads a `/stats/timeseries` window such as `90s`, `30m`, `24h` or `7d`
+pub fn parse_window(window: &str) -> Result<chrono::Duration> {
+    let window = window.trim();
+    let split = window
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(window.len());
+    let (amount, unit) = window.split_at(split);
+    let amount: i64 = amount
+        .parse()
+        .map_err(|_| anyhow!("Invalid window {}", window))?;
+    let seconds = match unit {
+        "s" => 1,
+        "m" => 60,
+        "h" => 3600,
+        "d" => 86400,
+        _ => {
+            return Err(anyhow!(
+                "Invalid window {}, expected e.g. 30m, 24h or 7d",
+                window
+            ));
+        }
+    };
+    amount
+        .checked_mul(seconds)
+        .and_then(chrono::Duration::try_seconds)
+        .ok_or_else(|| anyhow!("Window {} is too long", window))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_window() {
+        assert_eq!(parse_window("24h").unwrap(), chrono::Duration::hours(24));
+        assert_eq!(parse_window("30m").unwrap(), chrono::Duration::minutes(30));
+        assert_eq!(parse_window("7d").unwrap(), chrono::Duration::days(7));
+        assert_eq!(parse_window("90s").unwrap(), chrono::Duration::seconds(90));
+        assert!(parse_window("24").is_err());
+        assert!(parse_window("h").is_err());
+        assert!(parse_window("1w").is_err());
+        assert!(parse_window(&format!("{}d", i64::MAX)).is_err());
+    }
+
+    #[test]
+    fn test_backfill_window() {
+        let now = Utc::now();
+        let cutoff = now - chrono::Duration::hours(24);
+        let hours_ago = |hours| now - chrono::Duration::hours(hours);
+
+        assert!(needs_sample(hours_ago(2), cutoff, None));
+        assert!(!needs_sample(hours_ago(25), cutoff, None));
+        assert!(needs_sample(hours_ago(5), cutoff, Some(hours_ago(4))));
+        assert!(!needs_sample(hours_ago(4), cutoff, Some(hours_ago(4))));
+        assert!(!needs_sample(hours_ago(3), cutoff, Some(hours_ago(4))));
+    }
+}
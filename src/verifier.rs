@@ -0,0 +1,42 @@
+use anyhow::Result;
+use serde::Deserialize;
+use sp1_verifier::{GROTH16_VK_BYTES, Groth16Verifier, PLONK_VK_BYTES, PlonkVerifier};
+
+use crate::errors::RelayerError;
+
+/// Proof system a wrapper proof was successfully verified with
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ProofSystem {
+    Groth16,
+    Plonk,
+}
+
+impl ProofSystem {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ProofSystem::Groth16 => "groth16",
+            ProofSystem::Plonk => "plonk",
+        }
+    }
+}
+
+/// Verifies the on-chain encoded proof bytes (as returned by
+/// `SP1ProofWithPublicValues::bytes`) against the program vkey hash.
+///
+/// The wrapper circuit is normally proven with Groth16; Plonk is tried as a fallback.
+pub fn verify_wrapper_proof(proof: &[u8], public_values: &[u8], vk: &str) -> Result<ProofSystem> {
+    match Groth16Verifier::verify(proof, public_values, vk, &GROTH16_VK_BYTES) {
+        Ok(()) => Ok(ProofSystem::Groth16),
+        Err(groth16_err) => {
+            match PlonkVerifier::verify(proof, public_values, vk, &PLONK_VK_BYTES) {
+                Ok(()) => Ok(ProofSystem::Plonk),
+                Err(_) => Err(RelayerError::ProofVerification {
+                    vk: vk.to_string(),
+                    reason: groth16_err.to_string(),
+                }
+                .into()),
+            }
+        }
+    }
+}
@@ -0,0 +1,136 @@
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use sp1_sdk::{SP1Proof, SP1ProofWithPublicValues};
+use std::sync::Mutex;
+use tracing::error;
+
+/// Program vkey hash a proof was produced for, as read from its public inputs
+#[derive(Debug, Clone, Serialize)]
+pub struct VkObservation {
+    pub vk: String,
+    pub fingerprint: String,
+    /// Unknown when the relay loop observes a proof before decoding it
+    pub height: Option<u64>,
+    pub matches_configured: bool,
+    pub observed_at: DateTime<Utc>,
+}
+
+/// Compares the VK embedded in each new proof with the configured one. After a
+/// circuit upgrade the two drift apart and every proof fails verification, which
+/// is easy to misread as a prover fault.
+pub struct VkMonitor {
+    configured: String,
+    fingerprint: String,
+    last: Mutex<Option<VkObservation>>,
+}
+
+impl VkMonitor {
+    pub fn new(configured: &str) -> Self {
+        VkMonitor {
+            configured: configured.to_string(),
+            fingerprint: fingerprint(configured),
+            last: Mutex::new(None),
+        }
+    }
+
+    pub fn configured(&self) -> &str {
+        &self.configured
+    }
+
+    pub fn configured_fingerprint(&self) -> &str {
+        &self.fingerprint
+    }
+
+    pub fn last_observation(&self) -> Option<VkObservation> {
+        self.last.lock().unwrap().clone()
+    }
+
+    /// True when the most recent proof was produced for a different VK
+    pub fn is_mismatched(&self) -> bool {
+        self.last_observation()
+            .is_some_and(|observation| !observation.matches_configured)
+    }
+
+    /// Records the VK of a freshly fetched proof. Core and compressed proofs
+    /// carry no vkey hash in their public inputs and are ignored.
+    pub fn observe(&self, proof: &SP1ProofWithPublicValues, height: Option<u64>) {
+        let Some(vk) = proof_vk(proof) else {
+            return;
+        };
+
+        let matches_configured = normalize(&vk) == normalize(&self.configured);
+        let observation = VkObservation {
+            fingerprint: fingerprint(&vk),
+            vk,
+            height,
+            matches_configured,
+            observed_at: Utc::now(),
+        };
+
+        if !matches_configured {
+            error!(
+                "🚨 VK MISMATCH: latest proof (height {}) was produced for vk {} (fingerprint {}), configured vk is {} (fingerprint {})",
+                height.map_or_else(|| "unknown".to_string(), |height| height.to_string()),
+                observation.vk,
+                observation.fingerprint,
+                self.configured,
+                self.fingerprint
+            );
+        }
+
+        *self.last.lock().unwrap() = Some(observation);
+    }
+}
+
+/// Short, stable identifier for a VK regardless of `0x` prefix or case
+pub fn fingerprint(vk: &str) -> String {
+    hex::encode(&Sha256::digest(normalize(vk).as_bytes())[..8])
+}
+
+fn normalize(vk: &str) -> String {
+    vk.trim_start_matches("0x").to_ascii_lowercase()
+}
+
+fn proof_vk(proof: &SP1ProofWithPublicValues) -> Option<String> {
+    let vkey_hash = match &proof.proof {
+        SP1Proof::Groth16(groth16) => groth16.public_inputs.first()?,
+        SP1Proof::Plonk(plonk) => plonk.public_inputs.first()?,
+        _ => return None,
+    };
+    decimal_to_bytes32(vkey_hash).map(|bytes| format!("0x{}", hex::encode(bytes)))
+}
+
+/// Wrapper proofs expose the vkey hash as a decimal field element
+fn decimal_to_bytes32(decimal: &str) -> Option<[u8; 32]> {
+    let mut bytes = [0u8; 32];
+    for digit in decimal.chars() {
+        let mut carry = digit.to_digit(10)?;
+        for byte in bytes.iter_mut().rev() {
+            let value = *byte as u32 * 10 + carry;
+            *byte = value as u8;
+            carry = value >> 8;
+        }
+        if carry != 0 {
+            return None;
+        }
+    }
+    Some(bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_vk_decoding_and_fingerprint() {
+        let bytes = decimal_to_bytes32("65536").unwrap();
+        assert_eq!(hex::encode(bytes), format!("{}010000", "0".repeat(58)));
+        assert!(decimal_to_bytes32("12a").is_none());
+        assert!(decimal_to_bytes32(&"9".repeat(78)).is_none());
+
+        assert_eq!(fingerprint("0xABCD"), fingerprint("abcd"));
+        assert_ne!(fingerprint("0xabcd"), fingerprint("0xabce"));
+        assert_eq!(fingerprint("0xabcd").len(), 16);
+    }
+}
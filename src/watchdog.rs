@@ -0,0 +1,163 @@
+use std::sync::Arc;
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::time::{Duration, Instant};
+use tracing::error;
+
+use crate::config::{FAIL_FAST_EXIT_CODE, FailFastPolicy};
+use crate::domain::Domain;
+
+/// Tracks one domain's loop outcomes and exits the process once the fail-fast
+/// policy is breached, leaving restarts and alerting to the orchestrator.
+pub struct CycleWatchdog {
+    domain: String,
+    policy: FailFastPolicy,
+    consecutive_failures: AtomicU32,
+    last_success: Mutex<Instant>,
+    /// Called with the reason once a limit is reached; exits the process by default
+    on_breach: Box<dyn Fn(&str) + Send + Sync>,
+}
+
+impl CycleWatchdog {
+    pub fn new(domain: &str, policy: FailFastPolicy) -> Self {
+        CycleWatchdog {
+            domain: domain.to_string(),
+            policy,
+            consecutive_failures: AtomicU32::new(0),
+            last_success: Mutex::new(Instant::now()),
+            on_breach: Box::new(|_| std::process::exit(FAIL_FAST_EXIT_CODE)),
+        }
+    }
+
+    /// Replaces exiting the process when a limit is reached, for embedders and tests
+    pub fn on_breach(mut self, hook: impl Fn(&str) + Send + Sync + 'static) -> Self {
+        self.on_breach = Box::new(hook);
+        self
+    }
+
+    pub fn record_success(&self) {
+        self.consecutive_failures.store(0, Ordering::SeqCst);
+        *self.last_success.lock().unwrap() = Instant::now();
+    }
+
+    pub fn record_failure(&self) {
+        let failures = self.consecutive_failures.fetch_add(1, Ordering::SeqCst) + 1;
+        if self
+            .policy
+            .max_consecutive_failures
+            .is_some_and(|max| failures >= max)
+        {
+            self.breach(&format!("{} consecutive failed cycles", failures));
+        }
+    }
+
+    fn check_stalled(&self) {
+        let Some(minutes) = self.policy.max_minutes_without_success else {
+            return;
+        };
+        let elapsed = self.last_success.lock().unwrap().elapsed();
+        if elapsed >= Duration::from_secs(minutes * 60) {
+            self.breach(&format!(
+                "no successful cycle for {} minutes",
+                elapsed.as_secs() / 60
+            ));
+        }
+    }
+
+    fn breach(&self, reason: &str) {
+        error!(
+            "💀 [{}] Fail-fast limit reached ({}), exiting with code {}",
+            self.domain, reason, FAIL_FAST_EXIT_CODE
+        );
+        (self.on_breach)(reason);
+    }
+}
+
+/// Enforces the time-based limit even when the loop itself is stuck. Time spent
+/// paused by an operator doesn't count.
+pub async fn run_watchdog(domain: Arc<Domain>, watchdog: Arc<CycleWatchdog>) {
+    loop {
+        tokio::time::sleep(Duration::from_secs(30)).await;
+        if domain.control.is_stopped() {
+            return;
+        }
+        if domain.control.is_paused() {
+            watchdog.record_success();
+        } else {
+            watchdog.check_stalled();
+        }
+    }
+}
+
+/// Creates a domain's fail-fast watchdog, starting its timer task when a limit is configured
+pub fn spawn_watchdog(domain: &Arc<Domain>, policy: FailFastPolicy) -> Arc<CycleWatchdog> {
+    let watchdog = Arc::new(CycleWatchdog::new(domain.name(), policy));
+    if policy.is_enabled() {
+        tokio::spawn(run_watchdog(domain.clone(), watchdog.clone()));
+    }
+    watchdog
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn watchdog(policy: FailFastPolicy) -> (CycleWatchdog, Arc<Mutex<Vec<String>>>) {
+        let breaches = Arc::new(Mutex::new(Vec::new()));
+        let recorded = breaches.clone();
+        let watchdog = CycleWatchdog::new("test", policy)
+            .on_breach(move |reason| recorded.lock().unwrap().push(reason.to_string()));
+        (watchdog, breaches)
+    }
+
+    #[test]
+    fn test_consecutive_failures() {
+        let (watchdog, breaches) = watchdog(FailFastPolicy {
+            max_consecutive_failures: Some(3),
+            ..FailFastPolicy::default()
+        });
+        watchdog.record_failure();
+        watchdog.record_failure();
+        // A success in between starts the count again
+        watchdog.record_success();
+        watchdog.record_failure();
+        watchdog.record_failure();
+        assert!(breaches.lock().unwrap().is_empty());
+
+        watchdog.record_failure();
+        assert_eq!(
+            *breaches.lock().unwrap(),
+            vec!["3 consecutive failed cycles".to_string()]
+        );
+    }
+
+    #[test]
+    fn test_stalled() {
+        let (watchdog, breaches) = watchdog(FailFastPolicy {
+            max_minutes_without_success: Some(10),
+            ..FailFastPolicy::default()
+        });
+        let ago = |minutes: u64| {
+            Instant::now()
+                .checked_sub(Duration::from_secs(minutes * 60))
+                .unwrap()
+        };
+
+        *watchdog.last_success.lock().unwrap() = ago(9);
+        watchdog.check_stalled();
+        assert!(breaches.lock().unwrap().is_empty());
+
+        *watchdog.last_success.lock().unwrap() = ago(11);
+        watchdog.check_stalled();
+        assert_eq!(
+            *breaches.lock().unwrap(),
+            vec!["no successful cycle for 11 minutes".to_string()]
+        );
+
+        // A success resets the timer; failures don't count without their own limit
+        watchdog.record_success();
+        watchdog.record_failure();
+        watchdog.check_stalled();
+        assert_eq!(breaches.lock().unwrap().len(), 1);
+    }
+}
@@ -0,0 +1,184 @@
+use anyhow::{Result, anyhow};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use tokio::sync::{mpsc, oneshot};
+use tracing::error;
+
+use crate::db::Storage;
+use crate::errors::{ErrorTracker, RelayerError, Subsystem};
+
+type WriteFn = Box<dyn FnOnce(&dyn Storage) -> Result<()> + Send>;
+
+struct Write {
+    description: &'static str,
+    apply: WriteFn,
+    /// Set for writes whose caller waits until they are applied
+    ack: Option<oneshot::Sender<Result<()>>>,
+}
+
+/// Applies a domain's database writes on a single task, in the order they were queued,
+/// so a slow database never holds up fetching or submitting proofs. Each write runs on
+/// tokio's blocking pool rather than on the runtime's threads. Reads go through
+/// `Domain::read`.
+#[derive(Clone)]
+pub struct DbWriter {
+    queue: mpsc::UnboundedSender<Write>,
+    depth: Arc<AtomicUsize>,
+}
+
+impl DbWriter {
+    /// Starts the writer task for a domain's storage. Failed writes are recorded in `errors`
+    /// whether or not a caller is waiting for them.
+    pub fn spawn(domain: &str, db: Arc<dyn Storage>, errors: Arc<ErrorTracker>) -> Self {
+        let (queue, mut writes) = mpsc::unbounded_channel::<Write>();
+        let depth = Arc::new(AtomicUsize::new(0));
+
+        let name = domain.to_string();
+        let pending = depth.clone();
+        tokio::spawn(async move {
+            while let Some(Write {
+                description,
+                apply,
+                ack,
+            }) = writes.recv().await
+            {
+                let db = db.clone();
+                let result = tokio::task::spawn_blocking(move || apply(db.as_ref()))
+                    .await
+                    .unwrap_or_else(|e| Err(anyhow!("the write did not complete: {}", e)))
+                    .map_err(|e| {
+                        RelayerError::Storage {
+                            operation: description,
+                            reason: e.to_string(),
+                        }
+                        .into()
+                    });
+                pending.fetch_sub(1, Ordering::Relaxed);
+                if let Err(e) = &result {
+                    errors.record(Subsystem::Database, e);
+                }
+
+                match ack {
+                    Some(ack) => {
+                        let _ = ack.send(result);
+                    }
+                    None => {
+                        if let Err(e) = result {
+                            error!("❌ [{}] Failed to {}: {}", name, description, e);
+                        }
+                    }
+                }
+            }
+        });
+
+        DbWriter { queue, depth }
+    }
+
+    /// Queues a write without waiting for it; failures are logged by the writer task
+    pub fn submit(
+        &self,
+        description: &'static str,
+        apply: impl FnOnce(&dyn Storage) -> Result<()> + Send + 'static,
+    ) {
+        self.push(Write {
+            description,
+            apply: Box::new(apply),
+            ack: None,
+        });
+    }
+
+    /// Queues a write and waits until it has been applied, for writes that must be
+    /// durable before the caller moves on
+    pub async fn write(
+        &self,
+        description: &'static str,
+        apply: impl FnOnce(&dyn Storage) -> Result<()> + Send + 'static,
+    ) -> Result<()> {
+        let (ack, applied) = oneshot::channel();
+        self.push(Write {
+            description,
+            apply: Box::new(apply),
+            ack: Some(ack),
+        });
+
+        applied
+            .await
+            .map_err(|_| anyhow!("Database writer stopped before it could {}", description))?
+    }
+
+    /// Waits until every write queued so far has been applied
+    pub async fn flush(&self) -> Result<()> {
+        self.write("flush queued writes", |_| Ok(())).await
+    }
+
+    /// Writes queued but not applied yet
+    pub fn depth(&self) -> usize {
+        self.depth.load(Ordering::Relaxed)
+    }
+
+    fn push(&self, write: Write) {
+        self.depth.fetch_add(1, Ordering::Relaxed);
+        if let Err(mpsc::error::SendError(write)) = self.queue.send(write) {
+            self.depth.fetch_sub(1, Ordering::Relaxed);
+            error!(
+                "❌ Database writer has stopped, dropping write: {}",
+                write.description
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::{Database, PendingSubmission};
+    use chrono::Utc;
+    use tempfile::NamedTempFile;
+
+    #[tokio::test]
+    async fn test_writes_apply_in_order() -> Result<()> {
+        let temp_file = NamedTempFile::new()?;
+        let db: Arc<dyn Storage> = Arc::new(Database::new(temp_file.path().to_str().unwrap())?);
+        let errors = Arc::new(ErrorTracker::default());
+        let writer = DbWriter::spawn("test", db.clone(), errors.clone());
+
+        for height in 1..=3 {
+            writer.submit("queue payload", move |db| {
+                db.enqueue_submission(&PendingSubmission {
+                    height,
+                    payload: "{}".to_string(),
+                    created_at: Utc::now(),
+                })
+            });
+        }
+        // Acknowledged writes come after everything queued before them
+        writer
+            .write("remove payloads", |db| {
+                db.remove_pending_submissions_through(2).map(|_| ())
+            })
+            .await?;
+
+        assert_eq!(writer.depth(), 0);
+        let pending = db.get_pending_submissions()?;
+        assert_eq!(
+            pending.iter().map(|s| s.height).collect::<Vec<_>>(),
+            vec![3]
+        );
+
+        // Failures of acknowledged writes are returned to the caller
+        let failed = writer
+            .write("fail", |_| Err(anyhow!("database unavailable")))
+            .await;
+        let failed = failed.unwrap_err();
+        assert!(matches!(
+            RelayerError::of(&failed),
+            Some(RelayerError::Storage {
+                operation: "fail",
+                ..
+            })
+        ));
+        assert_eq!(errors.snapshot().database.map(|last| last.count), Some(1));
+
+        Ok(())
+    }
+}
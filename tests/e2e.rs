@@ -0,0 +1,251 @@
+//! End-to-end harness: boots a mock prover and a mock registry on loopback ports and
+//! runs the real relay loop against them with a temporary database, driving it
+//! through several proof epochs with failures injected along the way.
+//!
+//! The relay loop verifies every proof before submitting it, so the epochs are real
+//! proofs: the prover responses (hex, as served by a prover) stored one per file in
+//! `E2E_PROOF_FIXTURES`, in file name order. Without fixtures a single proof is fetched
+//! from the default prover endpoint.
+//!
+//! ```bash
+//! cargo test --no-default-features --features relayer,e2e --test e2e
+//! ```
+#![cfg(all(feature = "e2e", feature = "relayer", not(feature = "health-check")))]
+
+use anyhow::{Result, anyhow};
+use axum::{Json, Router, extract::State, http::StatusCode, routing::get};
+use std::sync::atomic::{AtomicU32, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use helios_proof_relayer::alerting::DomainAlerts;
+use helios_proof_relayer::api::EventStreams;
+use helios_proof_relayer::config::{
+    AlertingConfig, DomainConfig, FailFastPolicy, LIGHT_CLIENT_MODE, LIGHT_CLIENT_PROVER_ENDPOINT,
+    LIGHT_CLIENT_VK, RetryPolicy, StorageConfig,
+};
+use helios_proof_relayer::domain::Domain;
+use helios_proof_relayer::relayer::{decode_public_values, run_relay_loop};
+use helios_proof_relayer::sink::Sinks;
+use helios_proof_relayer::watchdog::CycleWatchdog;
+
+/// Serves the current epoch's proof, failing the next `fail_next` requests with a 500
+#[derive(Default)]
+struct MockProver {
+    proof: Mutex<String>,
+    fail_next: AtomicU32,
+    failures_served: AtomicUsize,
+}
+
+/// Accepts payloads, failing the next `fail_next` submissions with a 503
+#[derive(Default)]
+struct MockRegistry {
+    received: Mutex<Vec<serde_json::Value>>,
+    fail_next: AtomicU32,
+    failures_served: AtomicUsize,
+}
+
+fn take_failure(fail_next: &AtomicU32) -> bool {
+    fail_next
+        .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |n| n.checked_sub(1))
+        .is_ok()
+}
+
+async fn serve_proof(State(prover): State<Arc<MockProver>>) -> (StatusCode, String) {
+    if take_failure(&prover.fail_next) {
+        prover.failures_served.fetch_add(1, Ordering::SeqCst);
+        return (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "injected failure".to_string(),
+        );
+    }
+    (StatusCode::OK, prover.proof.lock().unwrap().clone())
+}
+
+async fn accept_payload(
+    State(registry): State<Arc<MockRegistry>>,
+    Json(payload): Json<serde_json::Value>,
+) -> StatusCode {
+    if take_failure(&registry.fail_next) {
+        registry.failures_served.fetch_add(1, Ordering::SeqCst);
+        return StatusCode::SERVICE_UNAVAILABLE;
+    }
+    registry.received.lock().unwrap().push(payload);
+    StatusCode::OK
+}
+
+/// Binds a router to a free loopback port and returns its base URL
+async fn spawn_server(router: Router) -> Result<String> {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await?;
+    let addr = listener.local_addr()?;
+    tokio::spawn(async move {
+        let _ = axum::serve(listener, router).await;
+    });
+    Ok(format!("http://{}/", addr))
+}
+
+async fn load_fixtures() -> Result<Vec<String>> {
+    if let Ok(dir) = std::env::var("E2E_PROOF_FIXTURES") {
+        let mut paths: Vec<_> = std::fs::read_dir(&dir)?
+            .map(|entry| entry.map(|entry| entry.path()))
+            .collect::<Result<_, _>>()?;
+        paths.sort();
+        return paths
+            .iter()
+            .map(|path| Ok(std::fs::read_to_string(path)?.trim().to_string()))
+            .collect();
+    }
+
+    let response = reqwest::get(LIGHT_CLIENT_PROVER_ENDPOINT).await?;
+    Ok(vec![response.text().await?])
+}
+
+fn fixture_height(fixture: &str) -> Result<u64> {
+    let proof: sp1_sdk::SP1ProofWithPublicValues = serde_json::from_slice(&hex::decode(fixture)?)?;
+    Ok(decode_public_values(LIGHT_CLIENT_MODE, proof.public_values.as_slice())?.0)
+}
+
+/// Polls until `condition` holds, failing after 60 seconds
+async fn eventually(what: &str, condition: impl Fn() -> bool) -> Result<()> {
+    let deadline = tokio::time::Instant::now() + Duration::from_secs(60);
+    while !condition() {
+        if tokio::time::Instant::now() > deadline {
+            return Err(anyhow!("Timed out waiting for {}", what));
+        }
+        tokio::time::sleep(Duration::from_millis(50)).await;
+    }
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn e2e_relays_epochs_with_failures() -> Result<()> {
+    let fixtures = load_fixtures().await?;
+    let mut heights = fixtures
+        .iter()
+        .map(|fixture| fixture_height(fixture))
+        .collect::<Result<Vec<_>>>()?;
+    assert!(
+        heights.windows(2).all(|pair| pair[0] < pair[1]),
+        "fixtures must be in increasing height order"
+    );
+
+    let prover = Arc::new(MockProver::default());
+    *prover.proof.lock().unwrap() = fixtures[0].clone();
+    let registry = Arc::new(MockRegistry::default());
+    // The first epoch's submission fails every retry and is dead-lettered
+    registry.fail_next.store(2, Ordering::SeqCst);
+
+    let prover_url = spawn_server(
+        Router::new()
+            .route("/", get(serve_proof))
+            .with_state(prover.clone()),
+    )
+    .await?;
+    let registry_url = spawn_server(
+        Router::new()
+            .route("/", axum::routing::post(accept_payload))
+            .with_state(registry.clone()),
+    )
+    .await?;
+
+    let config = DomainConfig {
+        name: "e2e".to_string(),
+        prover_endpoints: vec![prover_url],
+        registry_endpoint: registry_url,
+        vk: std::env::var("E2E_VK").unwrap_or_else(|_| LIGHT_CLIENT_VK.to_string()),
+        ..DomainConfig::default()
+    };
+    let domain = Arc::new(Domain::open(config, &StorageConfig::Memory, "relayer")?);
+
+    let retry = RetryPolicy {
+        max_attempts: 2,
+        base_delay_ms: 10,
+        max_delay_ms: 10,
+        jitter: 0.0,
+    };
+    let sinks = Sinks::from_config(&domain.config, &retry, None, Some(&domain.writer))?;
+    let (_cutover_requests, cutovers) = tokio::sync::mpsc::unbounded_channel();
+    tokio::spawn(run_relay_loop(
+        domain.clone(),
+        retry,
+        sinks,
+        cutovers,
+        Arc::new(CycleWatchdog::new("e2e", FailFastPolicy::default())),
+        Arc::new(DomainAlerts::new("e2e", None, &AlertingConfig::default())),
+        Some(100),
+        EventStreams::new(16, 64),
+    ));
+    let db = domain.db.clone();
+
+    // Epoch 0: dead-lettered first, then relayed on the next cycle
+    eventually("the first payload to be dead-lettered", || {
+        db.get_failed_submissions()
+            .is_ok_and(|failed| failed.iter().any(|entry| entry.height == heights[0]))
+    })
+    .await?;
+    assert_eq!(registry.failures_served.load(Ordering::SeqCst), 2);
+    domain.control.relay_now();
+    eventually("the first payload to be relayed", || {
+        registry.received.lock().unwrap().len() == 1
+    })
+    .await?;
+    eventually("the dead-letter queue to clear", || {
+        db.get_failed_submissions()
+            .is_ok_and(|failed| failed.is_empty())
+    })
+    .await?;
+
+    // A prover outage and a repeat of the same proof submit nothing
+    prover.fail_next.store(1, Ordering::SeqCst);
+    domain.control.relay_now();
+    eventually("the prover failure to be served", || {
+        prover.failures_served.load(Ordering::SeqCst) == 1
+    })
+    .await?;
+    domain.control.relay_now();
+    tokio::time::sleep(Duration::from_secs(2)).await;
+    assert_eq!(registry.received.lock().unwrap().len(), 1);
+
+    // Every further epoch is relayed exactly once
+    for (epoch, fixture) in fixtures.iter().enumerate().skip(1) {
+        *prover.proof.lock().unwrap() = fixture.clone();
+        domain.control.relay_now();
+        eventually(&format!("epoch {} to be relayed", epoch), || {
+            registry.received.lock().unwrap().len() == epoch + 1
+        })
+        .await?;
+    }
+
+    // The registry got each epoch once, in order, and the database agrees
+    let received: Vec<u64> = registry
+        .received
+        .lock()
+        .unwrap()
+        .iter()
+        .map(|payload| {
+            let public_values = hex::decode(payload["public_values"].as_str().unwrap()).unwrap();
+            decode_public_values(LIGHT_CLIENT_MODE, &public_values)
+                .unwrap()
+                .0
+        })
+        .collect();
+    assert_eq!(received, heights);
+
+    let last = heights.pop().unwrap();
+    eventually("the history to catch up", || {
+        db.get_latest_proof()
+            .is_ok_and(|latest| latest.is_some_and(|record| record.height == last))
+    })
+    .await?;
+    eventually("the submission queue to drain", || {
+        db.get_pending_submissions()
+            .is_ok_and(|pending| pending.is_empty())
+    })
+    .await?;
+    assert_eq!(
+        db.get_proofs_in_range(0, i64::MAX as u64)?.len(),
+        fixtures.len()
+    );
+
+    Ok(())
+}